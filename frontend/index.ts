@@ -12,5 +12,6 @@ import './scripts/position-preview';
 import './scripts/form-variants';
 import './scripts/active-link';
 import './scripts/select-all-checkbox';
+import './scripts/selection-order';
 
 import "./styles/index.css";
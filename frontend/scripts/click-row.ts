@@ -2,8 +2,8 @@
 window.addEventListener("load", () => {
   document.querySelectorAll("tr.clickable").forEach((row) => {
     row.addEventListener("click", (event) => {
-      // skip if the click originated a cell with class drag-handle
-      if ((event?.target as HTMLElement).closest(".drag-handle")) {
+      // skip if the click originated a cell with class drag-handle or a checkbox
+      if ((event?.target as HTMLElement).closest(".drag-handle, .checkbox")) {
         return;
       }
 
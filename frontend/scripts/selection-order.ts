@@ -0,0 +1,46 @@
+// Submit checked checkboxes in the order in which they were checked, instead of the
+// order of the table. Used when the order of the selection matters, e.g. when adding
+// several candidates to a list at once.
+window.addEventListener("load", () => {
+  document
+    .querySelectorAll("form[data-selection-order]")
+    .forEach((element) => {
+      const form = element as HTMLFormElement;
+      const name = form.dataset.selectionOrder;
+      const checkboxes: NodeListOf<HTMLInputElement> = form.querySelectorAll(
+        `input[type=checkbox][name="${name}"]`,
+      );
+
+      // checkboxes that are checked on load keep their table order
+      let order: string[] = Array.from(checkboxes)
+        .filter((checkbox) => checkbox.checked)
+        .map((checkbox) => checkbox.value);
+
+      const update = () => {
+        const checked = Array.from(checkboxes)
+          .filter((checkbox) => checkbox.checked)
+          .map((checkbox) => checkbox.value);
+        order = order.filter((value) => checked.includes(value));
+        checked
+          .filter((value) => !order.includes(value))
+          .forEach((value) => order.push(value));
+      };
+
+      // the select-all checkbox sets checkboxes without firing their change event
+      form.addEventListener("change", update);
+
+      form.addEventListener("submit", () => {
+        update();
+        checkboxes.forEach((checkbox) => {
+          checkbox.disabled = true;
+        });
+        order.forEach((value) => {
+          const input = document.createElement("input");
+          input.type = "hidden";
+          input.name = name ?? "";
+          input.value = value;
+          form.appendChild(input);
+        });
+      });
+    });
+});
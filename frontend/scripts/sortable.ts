@@ -650,6 +650,20 @@ class SortableTable {
   }
 }
 
+/**
+ * Offer to undo the new order, using the banner the server renders hidden.
+ */
+const showUndoBanner = () => {
+  const banner = document.getElementById("undo-banner");
+  const message = banner?.querySelector(".undo-message");
+  if (!banner || !message) {
+    return;
+  }
+
+  message.textContent = banner.dataset.reorderedMessage ?? "";
+  banner.hidden = false;
+};
+
 window.addEventListener("load", () => {
   const table: HTMLTableElement | null =
     document.querySelector("table.sortable");
@@ -666,20 +680,42 @@ window.addEventListener("load", () => {
   sortable.attachGlobalEvents();
 
   if (updateUrl) {
-    sortable.setOnChange((order: string[]) => {
+    const saveOrder = (order: string[]) => {
       void fetch(updateUrl, {
         method: "POST",
         headers: { "Content-Type": "application/json" },
-        body: JSON.stringify({ person_ids: order }),
+        body: JSON.stringify({
+          person_ids: order,
+          version: table.dataset.version ?? "",
+        }),
       })
-        .then((response) => {
+        .then(async (response) => {
+          if (response.status === 409) {
+            // the list was changed by someone else, reapply or show their changes
+            const { version } = (await response.json()) as { version: string };
+            if (window.confirm(table.dataset.conflictMessage ?? "")) {
+              table.dataset.version = version;
+              saveOrder(order);
+            } else {
+              window.location.reload();
+            }
+            return;
+          }
+
           if (!response.ok) {
             console.error("Failed to update candidate order", response.status);
+            return;
           }
+
+          const { version } = (await response.json()) as { version: string };
+          table.dataset.version = version;
+          showUndoBanner();
         })
         .catch((error) => {
           console.error("Failed to update candidate order", error);
         });
-    });
+    };
+
+    sortable.setOnChange(saveOrder);
   }
 });
@@ -0,0 +1,22 @@
+CREATE TABLE persons_history (
+    id BIGSERIAL PRIMARY KEY,
+    person_id UUID NOT NULL REFERENCES persons(id) ON DELETE CASCADE,
+    changed_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    previous JSONB NOT NULL
+);
+
+CREATE INDEX persons_history_person_id_idx ON persons_history (person_id, changed_at);
+
+-- Keep the previous version of a person on every update that changes more than the timestamp
+CREATE FUNCTION record_person_history() RETURNS trigger AS $$
+BEGIN
+    IF (to_jsonb(OLD) - 'updated_at') IS DISTINCT FROM (to_jsonb(NEW) - 'updated_at') THEN
+        INSERT INTO persons_history (person_id, previous) VALUES (OLD.id, to_jsonb(OLD));
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER persons_history_on_update
+    AFTER UPDATE ON persons
+    FOR EACH ROW EXECUTE FUNCTION record_person_history();
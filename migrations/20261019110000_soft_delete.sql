@@ -0,0 +1,16 @@
+ALTER TABLE persons ADD COLUMN deleted_at timestamp with time zone;
+ALTER TABLE candidate_lists ADD COLUMN deleted_at timestamp with time zone;
+
+CREATE INDEX persons_deleted_at_idx ON persons (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX candidate_lists_deleted_at_idx ON candidate_lists (deleted_at) WHERE deleted_at IS NOT NULL;
+
+-- Deleting or restoring a person is not a change to their details
+CREATE OR REPLACE FUNCTION record_person_history() RETURNS trigger AS $$
+BEGIN
+    IF (to_jsonb(OLD) - 'updated_at' - 'deleted_at')
+        IS DISTINCT FROM (to_jsonb(NEW) - 'updated_at' - 'deleted_at') THEN
+        INSERT INTO persons_history (person_id, previous) VALUES (OLD.id, to_jsonb(OLD));
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,19 @@
+CREATE TABLE candidate_list_snapshots (
+    candidate_list_id UUID NOT NULL REFERENCES candidate_lists(id) ON DELETE CASCADE,
+    version INTEGER NOT NULL,
+    label VARCHAR,
+    created_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    snapshot JSONB NOT NULL,
+    PRIMARY KEY (candidate_list_id, version)
+);
+
+-- Snapshots record what a list looked like when it was printed or submitted, they never change
+CREATE FUNCTION reject_snapshot_update() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'candidate list snapshots are immutable';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER candidate_list_snapshots_immutable
+    BEFORE UPDATE ON candidate_list_snapshots
+    FOR EACH ROW EXECUTE FUNCTION reject_snapshot_update();
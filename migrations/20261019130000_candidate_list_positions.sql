@@ -0,0 +1,50 @@
+-- Memberships without a list or person, and a person on the same list more than once, can not
+-- be kept under the constraints below. Which of the duplicates is right is up to the operator,
+-- so refuse to migrate instead of dropping them.
+DO $$
+DECLARE
+    incomplete bigint;
+    duplicated bigint;
+BEGIN
+    SELECT COUNT(*) INTO incomplete
+    FROM candidate_lists_persons
+    WHERE candidate_list_id IS NULL OR person_id IS NULL;
+
+    SELECT COUNT(*) INTO duplicated
+    FROM (
+        SELECT 1
+        FROM candidate_lists_persons
+        WHERE candidate_list_id IS NOT NULL AND person_id IS NOT NULL
+        GROUP BY candidate_list_id, person_id
+        HAVING COUNT(*) > 1
+    ) duplicates;
+
+    IF incomplete > 0 OR duplicated > 0 THEN
+        RAISE EXCEPTION 'candidate_lists_persons has % memberships without a list or person and % '
+            'persons on the same list more than once, resolve these before migrating',
+            incomplete, duplicated;
+    END IF;
+END;
+$$;
+
+-- Number the candidates on each list 1..n, without gaps or duplicates
+UPDATE candidate_lists_persons clp
+SET position = ranked.n
+FROM (
+    SELECT candidate_list_id, person_id, ROW_NUMBER() OVER (
+        PARTITION BY candidate_list_id
+        ORDER BY position, created_at
+    ) AS n
+    FROM candidate_lists_persons
+) ranked
+WHERE clp.candidate_list_id = ranked.candidate_list_id
+    AND clp.person_id = ranked.person_id
+    AND clp.position <> ranked.n;
+
+-- The position constraint is deferrable, so that operations which temporarily place two
+-- candidates at the same position (such as a swap) can run in a single transaction
+ALTER TABLE candidate_lists_persons
+    ADD PRIMARY KEY (candidate_list_id, person_id),
+    ADD CONSTRAINT candidate_lists_persons_position_positive CHECK (position > 0),
+    ADD CONSTRAINT candidate_lists_persons_position_unique
+        UNIQUE (candidate_list_id, position) DEFERRABLE INITIALLY IMMEDIATE;
@@ -0,0 +1,31 @@
+CREATE TABLE users (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    issuer VARCHAR NOT NULL,
+    subject VARCHAR NOT NULL,
+    name VARCHAR NOT NULL,
+    email VARCHAR,
+    created_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    last_login_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    UNIQUE (issuer, subject)
+);
+
+-- Only a hash of the session token is stored, the token itself lives in a cookie
+CREATE TABLE sessions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    token_hash BYTEA NOT NULL UNIQUE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    id_token TEXT NOT NULL,
+    created_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    expires_at timestamp with time zone NOT NULL
+);
+
+CREATE INDEX sessions_user_id_idx ON sessions (user_id);
+
+-- Logins that were started but not yet completed at the identity provider
+CREATE TABLE login_attempts (
+    state VARCHAR PRIMARY KEY,
+    nonce VARCHAR NOT NULL,
+    pkce_verifier VARCHAR NOT NULL,
+    redirect_to VARCHAR NOT NULL,
+    expires_at timestamp with time zone NOT NULL
+);
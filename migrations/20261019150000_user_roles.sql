@@ -0,0 +1,8 @@
+CREATE TYPE user_role AS ENUM ('authorised_agent', 'list_submitter', 'csb_member', 'kiesraad_admin');
+
+CREATE TABLE user_roles (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role user_role NOT NULL,
+    created_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    PRIMARY KEY (user_id, role)
+);
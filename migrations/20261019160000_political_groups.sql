@@ -0,0 +1,123 @@
+CREATE TABLE political_groups (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR NOT NULL,
+    created_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+ALTER TABLE users ADD COLUMN political_group_id UUID REFERENCES political_groups(id) ON DELETE SET NULL;
+
+-- Connections acting on behalf of a user switch to this role, which is subject to row-level
+-- security; the role the application connects with owns the tables and is not. Roles are shared
+-- by all databases in the cluster, so it may already exist (or be created concurrently).
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT FROM pg_roles WHERE rolname = 'eks_tenant') THEN
+        CREATE ROLE eks_tenant NOLOGIN NOBYPASSRLS;
+    END IF;
+EXCEPTION WHEN duplicate_object OR unique_violation THEN
+    NULL;
+END;
+$$;
+
+DO $$
+BEGIN
+    IF NOT (SELECT rolsuper FROM pg_roles WHERE rolname = current_user) THEN
+        EXECUTE format('GRANT eks_tenant TO %I', current_user);
+    END IF;
+END;
+$$;
+
+GRANT SELECT, INSERT, UPDATE, DELETE ON ALL TABLES IN SCHEMA public TO eks_tenant;
+GRANT USAGE, SELECT ON ALL SEQUENCES IN SCHEMA public TO eks_tenant;
+ALTER DEFAULT PRIVILEGES IN SCHEMA public GRANT SELECT, INSERT, UPDATE, DELETE ON TABLES TO eks_tenant;
+ALTER DEFAULT PRIVILEGES IN SCHEMA public GRANT USAGE, SELECT ON SEQUENCES TO eks_tenant;
+
+-- The tenant of a connection is either the id of a political group, 'all' for read access to
+-- every political group, or empty
+CREATE FUNCTION current_political_group() RETURNS UUID AS $$
+    SELECT CASE WHEN setting ~* '^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$'
+        THEN setting::uuid
+    END
+    FROM (SELECT current_setting('eks.political_group_id', true) AS setting) AS tenant;
+$$ LANGUAGE sql STABLE;
+
+CREATE FUNCTION political_group_visible(political_group_id UUID) RETURNS BOOLEAN AS $$
+    SELECT political_group_id = current_political_group()
+        OR current_setting('eks.political_group_id', true) = 'all';
+$$ LANGUAGE sql STABLE;
+
+-- Rows are created in the political group of the connection. Rows created without one, e.g. by
+-- maintenance tasks, are only visible to connections with access to every political group.
+ALTER TABLE persons
+    ADD COLUMN political_group_id UUID REFERENCES political_groups(id) ON DELETE RESTRICT
+        DEFAULT current_political_group();
+ALTER TABLE candidate_lists
+    ADD COLUMN political_group_id UUID REFERENCES political_groups(id) ON DELETE RESTRICT
+        DEFAULT current_political_group();
+
+CREATE INDEX persons_political_group_id_idx ON persons (political_group_id);
+CREATE INDEX candidate_lists_political_group_id_idx ON candidate_lists (political_group_id);
+
+ALTER TABLE persons ENABLE ROW LEVEL SECURITY;
+ALTER TABLE candidate_lists ENABLE ROW LEVEL SECURITY;
+ALTER TABLE candidate_lists_persons ENABLE ROW LEVEL SECURITY;
+ALTER TABLE persons_history ENABLE ROW LEVEL SECURITY;
+ALTER TABLE candidate_list_snapshots ENABLE ROW LEVEL SECURITY;
+
+-- Read access may span every political group, changes are limited to the connection's own
+CREATE POLICY political_group_read ON persons FOR SELECT
+    USING (political_group_visible(political_group_id));
+CREATE POLICY political_group_write ON persons
+    USING (political_group_id = current_political_group())
+    WITH CHECK (political_group_id = current_political_group());
+
+CREATE POLICY political_group_read ON candidate_lists FOR SELECT
+    USING (political_group_visible(political_group_id));
+CREATE POLICY political_group_write ON candidate_lists
+    USING (political_group_id = current_political_group())
+    WITH CHECK (political_group_id = current_political_group());
+
+-- The remaining tables follow the rows they belong to, so a list can only hold candidates of
+-- its own political group
+CREATE POLICY political_group_read ON candidate_lists_persons FOR SELECT
+    USING (
+        EXISTS (SELECT FROM candidate_lists l WHERE l.id = candidate_list_id)
+        AND EXISTS (SELECT FROM persons p WHERE p.id = person_id)
+    );
+CREATE POLICY political_group_write ON candidate_lists_persons
+    USING (
+        EXISTS (
+            SELECT FROM candidate_lists l
+            WHERE l.id = candidate_list_id AND l.political_group_id = current_political_group()
+        )
+    )
+    WITH CHECK (
+        EXISTS (
+            SELECT FROM candidate_lists l
+            WHERE l.id = candidate_list_id AND l.political_group_id = current_political_group()
+        )
+        AND EXISTS (
+            SELECT FROM persons p
+            WHERE p.id = person_id AND p.political_group_id = current_political_group()
+        )
+    );
+
+CREATE POLICY political_group_read ON persons_history FOR SELECT
+    USING (EXISTS (SELECT FROM persons p WHERE p.id = person_id));
+CREATE POLICY political_group_write ON persons_history
+    USING (
+        EXISTS (
+            SELECT FROM persons p
+            WHERE p.id = person_id AND p.political_group_id = current_political_group()
+        )
+    );
+
+CREATE POLICY political_group_read ON candidate_list_snapshots FOR SELECT
+    USING (EXISTS (SELECT FROM candidate_lists l WHERE l.id = candidate_list_id));
+CREATE POLICY political_group_write ON candidate_list_snapshots
+    USING (
+        EXISTS (
+            SELECT FROM candidate_lists l
+            WHERE l.id = candidate_list_id AND l.political_group_id = current_political_group()
+        )
+    );
@@ -0,0 +1,13 @@
+-- Sessions started through SAML have no ID token; the role is the one the user chose to act
+-- in, when they have several
+ALTER TABLE sessions
+    ALTER COLUMN id_token DROP NOT NULL,
+    ADD COLUMN role user_role;
+
+-- Logins that were started but not yet completed at the SAML identity provider
+CREATE TABLE saml_login_attempts (
+    relay_state VARCHAR PRIMARY KEY,
+    request_id VARCHAR NOT NULL,
+    redirect_to VARCHAR NOT NULL,
+    expires_at timestamp with time zone NOT NULL
+);
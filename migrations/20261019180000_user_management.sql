@@ -0,0 +1,20 @@
+-- Deactivated users can no longer sign in
+ALTER TABLE users ADD COLUMN deactivated_at timestamp with time zone;
+
+-- Invitations to join a political group, or the central electoral committee (CSB) when there
+-- is no political group. Only a hash of the token in the one-time link is stored.
+CREATE TABLE user_invitations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    token_hash BYTEA NOT NULL UNIQUE,
+    name VARCHAR NOT NULL,
+    email VARCHAR,
+    political_group_id UUID REFERENCES political_groups(id) ON DELETE CASCADE,
+    role user_role NOT NULL,
+    invited_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    expires_at timestamp with time zone NOT NULL,
+    accepted_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    accepted_at timestamp with time zone
+);
+
+CREATE INDEX user_invitations_political_group_id_idx ON user_invitations (political_group_id);
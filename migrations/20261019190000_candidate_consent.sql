@@ -0,0 +1,39 @@
+-- Candidates accept or decline their position on a list through a personal invitation link
+CREATE TYPE candidate_consent AS ENUM ('accepted', 'declined');
+
+ALTER TABLE candidate_lists_persons
+    ADD COLUMN consent candidate_consent,
+    ADD COLUMN consent_at timestamp with time zone;
+
+-- A decision only applies to the position it was made for
+CREATE FUNCTION reset_candidate_consent() RETURNS trigger AS $$
+BEGIN
+    IF NEW.position <> OLD.position THEN
+        NEW.consent := NULL;
+        NEW.consent_at := NULL;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER candidate_lists_persons_reset_consent
+    BEFORE UPDATE OF position ON candidate_lists_persons
+    FOR EACH ROW EXECUTE FUNCTION reset_candidate_consent();
+
+-- Candidates are not users, the token in the link is looked up before the connection is scoped
+-- to the political group of the list, so this table has no row-level security. Invitations can
+-- only be created for candidates the connection can see. Only a hash of the token is stored.
+CREATE TABLE candidate_consent_invitations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    token_hash BYTEA NOT NULL UNIQUE,
+    candidate_list_id UUID NOT NULL,
+    person_id UUID NOT NULL,
+    political_group_id UUID REFERENCES political_groups(id) ON DELETE CASCADE,
+    created_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    expires_at timestamp with time zone NOT NULL,
+    FOREIGN KEY (candidate_list_id, person_id)
+        REFERENCES candidate_lists_persons (candidate_list_id, person_id) ON DELETE CASCADE
+);
+
+CREATE INDEX candidate_consent_invitations_candidate_idx
+    ON candidate_consent_invitations (candidate_list_id, person_id);
@@ -0,0 +1,24 @@
+-- Row-level security does not apply to the owner of a table unless it is forced. The application
+-- connects as the owner, so without it a connection that was not scoped to a tenant could read
+-- and change the rows of every political group. Forcing it makes such connections see nothing;
+-- tasks that act on every political group scope their connection to 'maintenance' instead.
+ALTER TABLE persons FORCE ROW LEVEL SECURITY;
+ALTER TABLE candidate_lists FORCE ROW LEVEL SECURITY;
+ALTER TABLE candidate_lists_persons FORCE ROW LEVEL SECURITY;
+ALTER TABLE persons_history FORCE ROW LEVEL SECURITY;
+ALTER TABLE candidate_list_snapshots FORCE ROW LEVEL SECURITY;
+
+CREATE FUNCTION maintenance_connection() RETURNS BOOLEAN AS $$
+    SELECT current_setting('eks.political_group_id', true) = 'maintenance';
+$$ LANGUAGE sql STABLE;
+
+CREATE POLICY maintenance ON persons
+    USING (maintenance_connection()) WITH CHECK (maintenance_connection());
+CREATE POLICY maintenance ON candidate_lists
+    USING (maintenance_connection()) WITH CHECK (maintenance_connection());
+CREATE POLICY maintenance ON candidate_lists_persons
+    USING (maintenance_connection()) WITH CHECK (maintenance_connection());
+CREATE POLICY maintenance ON persons_history
+    USING (maintenance_connection()) WITH CHECK (maintenance_connection());
+CREATE POLICY maintenance ON candidate_list_snapshots
+    USING (maintenance_connection()) WITH CHECK (maintenance_connection());
@@ -0,0 +1,9 @@
+-- Keyset pagination of persons compares (sort column, id) with the cursor as a row, one index
+-- per sort column. Soft deleted persons are never listed.
+CREATE INDEX persons_last_name_keyset_idx ON persons (last_name, id) WHERE deleted_at IS NULL;
+CREATE INDEX persons_first_name_keyset_idx ON persons (first_name, id) WHERE deleted_at IS NULL;
+CREATE INDEX persons_initials_keyset_idx ON persons (initials, id) WHERE deleted_at IS NULL;
+CREATE INDEX persons_gender_keyset_idx ON persons (gender, id) WHERE deleted_at IS NULL;
+CREATE INDEX persons_locality_keyset_idx ON persons (locality, id) WHERE deleted_at IS NULL;
+CREATE INDEX persons_created_at_keyset_idx ON persons (created_at, id) WHERE deleted_at IS NULL;
+CREATE INDEX persons_updated_at_keyset_idx ON persons (updated_at, id) WHERE deleted_at IS NULL;
@@ -4,3 +4,9 @@ test('homepage loads', async ({ page }) => {
   await page.goto('/');
   await expect(page).toHaveTitle('Kiesraad - Kandidaatstelling');
 });
+
+test('signs in through the identity provider', async ({ page }) => {
+  await page.goto('/persons');
+  await expect(page).toHaveURL(/\/persons$/);
+  await expect(page.getByText('Evert Klaas Smit')).toBeVisible();
+});
@@ -0,0 +1,294 @@
+//! Minimal OpenID Connect provider for development and tests. It signs in a fixed user
+//! without asking anything, so it must never be reachable outside a development machine.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    Form, Json, Router,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode, header::AUTHORIZATION},
+    response::{IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use base64::{
+    Engine,
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+};
+use chrono::{Duration, Utc};
+use openidconnect::{
+    AccessToken, Audience, AuthUrl, AuthorizationCode, EmptyAdditionalClaims,
+    EmptyAdditionalProviderMetadata, EmptyExtraTokenFields, EndSessionUrl, EndUserEmail,
+    EndUserName, IssuerUrl, JsonWebKeyId, JsonWebKeySetUrl, LogoutProviderMetadata, Nonce,
+    PrivateSigningKey, ProviderMetadataWithLogout, ResponseTypes, Scope, StandardClaims,
+    SubjectIdentifier, TokenUrl,
+    core::{
+        CoreIdToken, CoreIdTokenClaims, CoreIdTokenFields, CoreJsonWebKeySet,
+        CoreJwsSigningAlgorithm, CoreResponseType, CoreRsaPrivateSigningKey,
+        CoreSubjectIdentifierType, CoreTokenResponse, CoreTokenType,
+    },
+    url::Url,
+};
+use rand::{Rng, distr::Alphanumeric};
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::OidcConfig;
+
+/// Signing key of the mock provider, only ever used for development and tests.
+const SIGNING_KEY: &str = include_str!("mock_provider_key.pem");
+
+/// An authorization code handed out, waiting to be exchanged for tokens.
+struct PendingCode {
+    redirect_uri: String,
+    nonce: Option<String>,
+    code_challenge: String,
+}
+
+#[derive(Clone)]
+pub struct MockProvider {
+    issuer_url: String,
+    client_id: String,
+    client_secret: String,
+    signing_key: Arc<CoreRsaPrivateSigningKey>,
+    codes: Arc<Mutex<HashMap<String, PendingCode>>>,
+}
+
+#[derive(Deserialize)]
+struct AuthorizeQuery {
+    response_type: String,
+    client_id: String,
+    redirect_uri: String,
+    state: String,
+    nonce: Option<String>,
+    code_challenge: Option<String>,
+    code_challenge_method: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenForm {
+    grant_type: String,
+    code: String,
+    redirect_uri: String,
+    code_verifier: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LogoutQuery {
+    post_logout_redirect_uri: Option<String>,
+}
+
+fn error(error: &str) -> Response {
+    (StatusCode::BAD_REQUEST, Json(json!({ "error": error }))).into_response()
+}
+
+fn random_code() -> String {
+    rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect()
+}
+
+impl MockProvider {
+    pub const USER_SUBJECT: &str = "mock-user";
+    pub const USER_NAME: &str = "Evert Klaas Smit";
+    pub const USER_EMAIL: &str = "eks@example.com";
+
+    pub fn new(config: &OidcConfig) -> Self {
+        let signing_key =
+            CoreRsaPrivateSigningKey::from_pem(SIGNING_KEY, Some(JsonWebKeyId::new("mock".into())))
+                .expect("valid mock signing key");
+
+        Self {
+            issuer_url: config.issuer_url.trim_end_matches('/').to_string(),
+            client_id: config.client_id.clone(),
+            client_secret: config.client_secret.clone(),
+            signing_key: Arc::new(signing_key),
+            codes: Arc::default(),
+        }
+    }
+
+    pub fn router(self) -> Router {
+        Router::new()
+            .route("/.well-known/openid-configuration", get(discovery))
+            .route("/jwks", get(jwks))
+            .route("/authorize", get(authorize))
+            .route("/token", post(token))
+            .route("/logout", get(logout))
+            .with_state(self)
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}{path}", self.issuer_url)
+    }
+
+    /// Checks the client credentials, sent as basic authentication.
+    fn is_client(&self, headers: &HeaderMap) -> bool {
+        let expected = STANDARD.encode(format!("{}:{}", self.client_id, self.client_secret));
+
+        headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Basic "))
+            .is_some_and(|credentials| credentials == expected)
+    }
+}
+
+async fn discovery(State(provider): State<MockProvider>) -> Response {
+    let metadata = ProviderMetadataWithLogout::new(
+        IssuerUrl::new(provider.issuer_url.clone()).expect("valid issuer url"),
+        AuthUrl::new(provider.endpoint("/authorize")).expect("valid url"),
+        JsonWebKeySetUrl::new(provider.endpoint("/jwks")).expect("valid url"),
+        vec![ResponseTypes::new(vec![CoreResponseType::Code])],
+        vec![CoreSubjectIdentifierType::Public],
+        vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256],
+        LogoutProviderMetadata {
+            end_session_endpoint: Some(
+                EndSessionUrl::new(provider.endpoint("/logout")).expect("valid url"),
+            ),
+            additional_metadata: EmptyAdditionalProviderMetadata {},
+        },
+    )
+    .set_token_endpoint(Some(
+        TokenUrl::new(provider.endpoint("/token")).expect("valid url"),
+    ))
+    .set_scopes_supported(Some(
+        ["openid", "profile", "email"]
+            .into_iter()
+            .map(|scope| Scope::new(scope.to_string()))
+            .collect(),
+    ));
+
+    Json(metadata).into_response()
+}
+
+async fn jwks(State(provider): State<MockProvider>) -> Response {
+    Json(CoreJsonWebKeySet::new(vec![
+        provider.signing_key.as_verification_key(),
+    ]))
+    .into_response()
+}
+
+/// Signs in the mock user straight away, as if they filled in the login form.
+async fn authorize(
+    State(provider): State<MockProvider>,
+    Query(query): Query<AuthorizeQuery>,
+) -> Response {
+    if query.response_type != "code" || query.client_id != provider.client_id {
+        return error("unauthorized_client");
+    }
+
+    // only PKCE with S256 is accepted, like a strict production provider
+    let (Some(code_challenge), Some("S256")) =
+        (query.code_challenge, query.code_challenge_method.as_deref())
+    else {
+        return error("invalid_request");
+    };
+
+    let Ok(mut redirect_uri) = Url::parse(&query.redirect_uri) else {
+        return error("invalid_request");
+    };
+
+    let code = random_code();
+    provider.codes.lock().expect("codes poisoned").insert(
+        code.clone(),
+        PendingCode {
+            redirect_uri: query.redirect_uri,
+            nonce: query.nonce,
+            code_challenge,
+        },
+    );
+
+    redirect_uri
+        .query_pairs_mut()
+        .append_pair("code", &code)
+        .append_pair("state", &query.state);
+
+    Redirect::to(redirect_uri.as_str()).into_response()
+}
+
+async fn token(
+    State(provider): State<MockProvider>,
+    headers: HeaderMap,
+    Form(form): Form<TokenForm>,
+) -> Response {
+    if !provider.is_client(&headers) {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "error": "invalid_client" })),
+        )
+            .into_response();
+    }
+
+    if form.grant_type != "authorization_code" {
+        return error("unsupported_grant_type");
+    }
+
+    // codes can only be used once
+    let Some(pending) = provider
+        .codes
+        .lock()
+        .expect("codes poisoned")
+        .remove(&form.code)
+    else {
+        return error("invalid_grant");
+    };
+
+    let challenge = form
+        .code_verifier
+        .map(|verifier| URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes())));
+    if pending.redirect_uri != form.redirect_uri
+        || challenge.as_deref() != Some(&pending.code_challenge)
+    {
+        return error("invalid_grant");
+    }
+
+    let access_token = AccessToken::new(random_code());
+    let claims = CoreIdTokenClaims::new(
+        IssuerUrl::new(provider.issuer_url.clone()).expect("valid issuer url"),
+        vec![Audience::new(provider.client_id.clone())],
+        Utc::now() + Duration::minutes(5),
+        Utc::now(),
+        StandardClaims::new(SubjectIdentifier::new(
+            MockProvider::USER_SUBJECT.to_string(),
+        ))
+        .set_name(Some(
+            EndUserName::new(MockProvider::USER_NAME.to_string()).into(),
+        ))
+        .set_email(Some(EndUserEmail::new(
+            MockProvider::USER_EMAIL.to_string(),
+        ))),
+        EmptyAdditionalClaims {},
+    )
+    .set_nonce(pending.nonce.map(Nonce::new));
+
+    let Ok(id_token) = CoreIdToken::new(
+        claims,
+        provider.signing_key.as_ref(),
+        CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        Some(&access_token),
+        Some(&AuthorizationCode::new(form.code)),
+    ) else {
+        return error("server_error");
+    };
+
+    let mut response = CoreTokenResponse::new(
+        access_token,
+        CoreTokenType::Bearer,
+        CoreIdTokenFields::new(Some(id_token), EmptyExtraTokenFields {}),
+    );
+    response.set_expires_in(Some(&std::time::Duration::from_secs(300)));
+
+    Json(response).into_response()
+}
+
+async fn logout(Query(query): Query<LogoutQuery>) -> Response {
+    match query.post_logout_redirect_uri {
+        Some(uri) => Redirect::to(&uri).into_response(),
+        None => "Signed out".into_response(),
+    }
+}
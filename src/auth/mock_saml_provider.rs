@@ -0,0 +1,350 @@
+//! Minimal SAML identity provider standing in for DigiD and eHerkenning in development and
+//! tests. Anyone can sign in as any BSN or KvK number at any authentication level, so it must
+//! never be reachable outside a development machine.
+
+use std::{io::Read, sync::Arc};
+
+use axum::{
+    Form, Router,
+    extract::{Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+    routing::get,
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{Duration, SecondsFormat, Utc};
+use flate2::read::DeflateDecoder;
+use roxmltree::Document;
+use rsa::{RsaPrivateKey, pkcs1::DecodeRsaPrivateKey};
+use serde::Deserialize;
+
+use crate::auth::{
+    saml::{
+        ASSERTION_NS, AuthenticationLevel, BEARER, BSN_SECTOR_CODE, Identifier, KVK_QUALIFIER,
+        PROTOCOL_NS, STATUS_SUCCESS, escape_xml, now_timestamp, random_id,
+    },
+    xml_signature,
+};
+
+/// Signing key of the mock provider, only ever used for development and tests.
+const SIGNING_KEY: &str = include_str!("mock_saml_provider_key.pem");
+
+const STATUS_AUTHN_FAILED: &str = "urn:oasis:names:tc:SAML:2.0:status:AuthnFailed";
+
+/// Number of minutes an assertion of the mock provider can be used.
+const ASSERTION_TTL_MINUTES: i64 = 5;
+
+/// An authentication request as received from a service provider.
+#[derive(Debug)]
+pub struct ReceivedRequest {
+    pub id: String,
+    pub acs_url: String,
+    pub issuer: String,
+}
+
+impl ReceivedRequest {
+    /// Read a request sent with the HTTP-Redirect binding.
+    pub fn decode(saml_request: &str) -> Result<Self, String> {
+        let deflated = STANDARD
+            .decode(saml_request)
+            .map_err(|_| "invalid base64".to_string())?;
+        let mut xml = String::new();
+        DeflateDecoder::new(deflated.as_slice())
+            .read_to_string(&mut xml)
+            .map_err(|err| err.to_string())?;
+
+        let doc = Document::parse(&xml).map_err(|err| err.to_string())?;
+        let request = doc.root_element();
+        if !request.has_tag_name((PROTOCOL_NS, "AuthnRequest")) {
+            return Err("not an authentication request".to_string());
+        }
+
+        let attribute = |name: &str| {
+            request
+                .attribute(name)
+                .map(str::to_string)
+                .ok_or_else(|| format!("missing {name}"))
+        };
+        let issuer = request
+            .children()
+            .find(|child| child.has_tag_name((ASSERTION_NS, "Issuer")))
+            .and_then(|issuer| issuer.text())
+            .unwrap_or_default()
+            .to_string();
+
+        Ok(Self {
+            id: attribute("ID")?,
+            acs_url: attribute("AssertionConsumerServiceURL")?,
+            issuer,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct MockSamlProvider {
+    entity_id: String,
+    signing_key: Arc<RsaPrivateKey>,
+    bsn: String,
+    kvk: String,
+    level: AuthenticationLevel,
+}
+
+#[derive(Deserialize)]
+struct SsoQuery {
+    #[serde(rename = "SAMLRequest")]
+    saml_request: String,
+    #[serde(rename = "RelayState")]
+    relay_state: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SsoForm {
+    saml_request: String,
+    relay_state: String,
+    kind: String,
+    number: String,
+    level: String,
+    cancel: Option<String>,
+}
+
+fn bad_request(error: String) -> Response {
+    (StatusCode::BAD_REQUEST, error).into_response()
+}
+
+impl MockSamlProvider {
+    /// Certificate of the signing key, to configure as `SAML_IDP_CERTIFICATE`.
+    pub const CERTIFICATE: &str = include_str!("mock_saml_provider_cert.pem");
+    /// Test BSN, as issued for test purposes by the RvIG.
+    pub const DEFAULT_BSN: &str = "999990019";
+    pub const DEFAULT_KVK: &str = "90000001";
+
+    pub fn new(entity_id: &str) -> Self {
+        let signing_key =
+            RsaPrivateKey::from_pkcs1_pem(SIGNING_KEY).expect("valid mock signing key");
+
+        Self {
+            entity_id: entity_id.to_string(),
+            signing_key: Arc::new(signing_key),
+            bsn: Self::DEFAULT_BSN.to_string(),
+            kvk: Self::DEFAULT_KVK.to_string(),
+            level: AuthenticationLevel::Substantial,
+        }
+    }
+
+    /// Set the identifiers and authentication level the login form starts with.
+    pub fn with_defaults(self, bsn: String, kvk: String, level: AuthenticationLevel) -> Self {
+        Self {
+            bsn,
+            kvk,
+            level,
+            ..self
+        }
+    }
+
+    pub fn router(self) -> Router {
+        Router::new()
+            .route("/sso", get(sso).post(sso_submit))
+            .with_state(self)
+    }
+
+    /// A signed response signing in the given identity, as XML.
+    pub fn response_xml(
+        &self,
+        request: &ReceivedRequest,
+        identifier: &Identifier,
+        level: AuthenticationLevel,
+    ) -> String {
+        let now = Utc::now();
+        let not_after = (now + Duration::minutes(ASSERTION_TTL_MINUTES))
+            .to_rfc3339_opts(SecondsFormat::Secs, true);
+        let (name_id, class) = match identifier {
+            Identifier::Bsn(bsn) => (
+                format!(
+                    "<saml:NameID>{BSN_SECTOR_CODE}:{}</saml:NameID>",
+                    escape_xml(bsn)
+                ),
+                level.digid_class(),
+            ),
+            Identifier::Kvk(kvk) => (
+                format!(
+                    r#"<saml:NameID NameQualifier="{KVK_QUALIFIER}">{}</saml:NameID>"#,
+                    escape_xml(kvk)
+                ),
+                level.eherkenning_class(),
+            ),
+        };
+
+        let issuer = format!("<saml:Issuer>{}</saml:Issuer>", escape_xml(&self.entity_id));
+        let assertion_body = format!(
+            concat!(
+                r#"<saml:Subject>{name_id}<saml:SubjectConfirmation Method="{bearer}">"#,
+                r#"<saml:SubjectConfirmationData InResponseTo="{request_id}" "#,
+                r#"Recipient="{acs}" NotOnOrAfter="{not_after}"></saml:SubjectConfirmationData>"#,
+                r#"</saml:SubjectConfirmation></saml:Subject>"#,
+                r#"<saml:Conditions NotBefore="{now}" NotOnOrAfter="{not_after}">"#,
+                r#"<saml:AudienceRestriction><saml:Audience>{audience}</saml:Audience>"#,
+                r#"</saml:AudienceRestriction></saml:Conditions>"#,
+                r#"<saml:AuthnStatement AuthnInstant="{now}"><saml:AuthnContext>"#,
+                r#"<saml:AuthnContextClassRef>{class}</saml:AuthnContextClassRef>"#,
+                r#"</saml:AuthnContext></saml:AuthnStatement>"#,
+            ),
+            name_id = name_id,
+            bearer = BEARER,
+            request_id = escape_xml(&request.id),
+            acs = escape_xml(&request.acs_url),
+            not_after = not_after,
+            now = now_timestamp(),
+            audience = escape_xml(&request.issuer),
+            class = class,
+        );
+        let assertion_start = format!(
+            r#"<saml:Assertion ID="{}" Version="2.0" IssueInstant="{}">{issuer}"#,
+            random_id(),
+            now_timestamp(),
+        );
+
+        let unsigned = self.response_envelope(
+            request,
+            STATUS_SUCCESS,
+            &format!("{assertion_start}{assertion_body}</saml:Assertion>"),
+        );
+        let doc = Document::parse(&unsigned).expect("valid response");
+        let assertion = doc
+            .descendants()
+            .find(|node| node.has_tag_name((ASSERTION_NS, "Assertion")))
+            .expect("assertion");
+        let signature = xml_signature::sign_enveloped(assertion, &self.signing_key);
+
+        // the signature goes right after the issuer of the assertion
+        self.response_envelope(
+            request,
+            STATUS_SUCCESS,
+            &format!("{assertion_start}{signature}{assertion_body}</saml:Assertion>"),
+        )
+    }
+
+    /// A response telling the user did not sign in, as XML.
+    pub fn failed_response_xml(&self, request: &ReceivedRequest) -> String {
+        self.response_envelope(request, STATUS_AUTHN_FAILED, "")
+    }
+
+    fn response_envelope(
+        &self,
+        request: &ReceivedRequest,
+        status: &str,
+        assertion: &str,
+    ) -> String {
+        format!(
+            concat!(
+                r#"<samlp:Response xmlns:samlp="{protocol}" xmlns:saml="{assertion_ns}" "#,
+                r#"ID="{id}" Version="2.0" IssueInstant="{now}" Destination="{acs}" "#,
+                r#"InResponseTo="{request_id}"><saml:Issuer>{issuer}</saml:Issuer>"#,
+                r#"<samlp:Status><samlp:StatusCode Value="{status}"></samlp:StatusCode>"#,
+                r#"</samlp:Status>{assertion}</samlp:Response>"#,
+            ),
+            protocol = PROTOCOL_NS,
+            assertion_ns = ASSERTION_NS,
+            id = random_id(),
+            now = now_timestamp(),
+            acs = escape_xml(&request.acs_url),
+            request_id = escape_xml(&request.id),
+            issuer = escape_xml(&self.entity_id),
+            status = status,
+            assertion = assertion,
+        )
+    }
+}
+
+fn page(body: &str) -> Html<String> {
+    Html(format!(
+        concat!(
+            r#"<!doctype html><html lang="nl"><head><meta charset="utf-8">"#,
+            r#"<title>Mock DigiD / eHerkenning</title></head><body>{}</body></html>"#,
+        ),
+        body
+    ))
+}
+
+fn option(value: &str, label: &str, selected: bool) -> String {
+    let selected = if selected { " selected" } else { "" };
+    format!(r#"<option value="{value}"{selected}>{label}</option>"#)
+}
+
+/// Asks who to sign in as, instead of asking for credentials.
+async fn sso(State(provider): State<MockSamlProvider>, Query(query): Query<SsoQuery>) -> Response {
+    if let Err(err) = ReceivedRequest::decode(&query.saml_request) {
+        return bad_request(err);
+    }
+
+    let levels: String = AuthenticationLevel::ALL
+        .iter()
+        .map(|level| {
+            option(
+                &level.to_string(),
+                &level.to_string(),
+                *level == provider.level,
+            )
+        })
+        .collect();
+
+    page(&format!(
+        concat!(
+            r#"<h1>Mock DigiD / eHerkenning</h1><form method="post">"#,
+            r#"<input type="hidden" name="saml_request" value="{request}">"#,
+            r#"<input type="hidden" name="relay_state" value="{relay_state}">"#,
+            r#"<p><label><input type="radio" name="kind" value="bsn" checked> DigiD, BSN {bsn}</label></p>"#,
+            r#"<p><label><input type="radio" name="kind" value="kvk"> eHerkenning, KvK {kvk}</label></p>"#,
+            r#"<p><label>Other BSN or KvK number <input type="text" name="number"></label></p>"#,
+            r#"<p><label>Level <select name="level">{levels}</select></label></p>"#,
+            r#"<button type="submit">Log in</button> "#,
+            r#"<button type="submit" name="cancel" value="1">Cancel</button></form>"#,
+        ),
+        request = escape_xml(&query.saml_request),
+        relay_state = escape_xml(&query.relay_state.unwrap_or_default()),
+        bsn = escape_xml(&provider.bsn),
+        kvk = escape_xml(&provider.kvk),
+        levels = levels,
+    ))
+    .into_response()
+}
+
+/// Sends the user back to the service provider, posting the response.
+async fn sso_submit(
+    State(provider): State<MockSamlProvider>,
+    Form(form): Form<SsoForm>,
+) -> Response {
+    let request = match ReceivedRequest::decode(&form.saml_request) {
+        Ok(request) => request,
+        Err(err) => return bad_request(err),
+    };
+
+    let xml = if form.cancel.is_some() {
+        provider.failed_response_xml(&request)
+    } else {
+        let Ok(level) = form.level.parse() else {
+            return bad_request("unknown level".to_string());
+        };
+        let number = form.number.trim().to_string();
+        let identifier = match (form.kind.as_str(), number.is_empty()) {
+            ("kvk", true) => Identifier::Kvk(provider.kvk.clone()),
+            ("kvk", false) => Identifier::Kvk(number),
+            (_, true) => Identifier::Bsn(provider.bsn.clone()),
+            (_, false) => Identifier::Bsn(number),
+        };
+        provider.response_xml(&request, &identifier, level)
+    };
+
+    page(&format!(
+        concat!(
+            r#"<form method="post" action="{acs}">"#,
+            r#"<input type="hidden" name="SAMLResponse" value="{response}">"#,
+            r#"<input type="hidden" name="RelayState" value="{relay_state}">"#,
+            r#"<button type="submit">Continue</button></form>"#,
+            r#"<script>document.forms[0].submit()</script>"#,
+        ),
+        acs = escape_xml(&request.acs_url),
+        response = STANDARD.encode(xml),
+        relay_state = escape_xml(&form.relay_state),
+    ))
+    .into_response()
+}
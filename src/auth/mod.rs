@@ -0,0 +1,22 @@
+#[cfg(any(test, feature = "dev-features"))]
+mod mock_provider;
+#[cfg(any(test, feature = "dev-features"))]
+mod mock_saml_provider;
+mod oidc;
+mod pages;
+pub mod repository;
+mod role;
+mod saml;
+mod saml_pages;
+mod xml_signature;
+
+#[cfg(any(test, feature = "dev-features"))]
+pub use mock_provider::MockProvider;
+#[cfg(any(test, feature = "dev-features"))]
+pub use mock_saml_provider::MockSamlProvider;
+pub use oidc::{Identity, OidcClient};
+pub use pages::{
+    load_current_user, login_path, logout_path, require_login, router, select_role_path,
+};
+pub use role::{Permission, Role, RoleForm, require_permission};
+pub use saml::{AuthenticationLevel, SamlServiceProvider};
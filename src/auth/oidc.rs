@@ -0,0 +1,221 @@
+use std::{str::FromStr, sync::Arc};
+
+use openidconnect::{
+    AccessTokenHash, AuthorizationCode, ClientId, ClientSecret, CsrfToken, EndSessionUrl,
+    EndpointMaybeSet, EndpointNotSet, EndpointSet, IssuerUrl, LogoutRequest, Nonce,
+    OAuth2TokenResponse, PkceCodeChallenge, PkceCodeVerifier, PostLogoutRedirectUrl,
+    ProviderMetadataWithLogout, RedirectUrl, Scope, TokenResponse,
+    core::{CoreAuthenticationFlow, CoreClient, CoreIdToken},
+    reqwest,
+};
+use tokio::sync::OnceCell;
+
+use crate::{AppError, Config};
+
+/// Seconds to wait for the identity provider before giving up.
+const HTTP_TIMEOUT_SECONDS: u64 = 10;
+
+type DiscoveredClient = CoreClient<
+    EndpointSet,
+    EndpointNotSet,
+    EndpointNotSet,
+    EndpointNotSet,
+    EndpointMaybeSet,
+    EndpointMaybeSet,
+>;
+
+/// The identity provider as found through discovery.
+struct Provider {
+    client: DiscoveredClient,
+    end_session_url: Option<EndSessionUrl>,
+}
+
+/// A login started at the identity provider; everything but the url has to be kept
+/// until the user returns.
+pub struct AuthorizationRequest {
+    pub url: String,
+    pub state: String,
+    pub nonce: String,
+    pub pkce_verifier: String,
+}
+
+/// The user as identified by a verified ID token.
+#[derive(Debug)]
+pub struct Identity {
+    pub issuer: String,
+    pub subject: String,
+    pub name: String,
+    pub email: Option<String>,
+    pub id_token: String,
+}
+
+/// OpenID Connect relying party, using the authorization code flow with PKCE.
+/// The provider is discovered on first use, so the application starts without it.
+#[derive(Clone)]
+pub struct OidcClient {
+    http: reqwest::Client,
+    issuer_url: String,
+    client_id: String,
+    client_secret: String,
+    redirect_url: String,
+    post_logout_redirect_url: String,
+    provider: Arc<OnceCell<Provider>>,
+}
+
+impl OidcClient {
+    pub fn new(config: &Config) -> Result<Self, AppError> {
+        let http = reqwest::ClientBuilder::new()
+            // following redirects opens the client up to SSRF vulnerabilities
+            .redirect(reqwest::redirect::Policy::none())
+            .timeout(std::time::Duration::from_secs(HTTP_TIMEOUT_SECONDS))
+            .build()
+            .map_err(|err| AppError::ConfigLoadError(format!("invalid http client: {err}")))?;
+
+        Ok(Self {
+            http,
+            issuer_url: config.oidc.issuer_url.clone(),
+            client_id: config.oidc.client_id.clone(),
+            client_secret: config.oidc.client_secret.clone(),
+            redirect_url: format!("{}/login/callback", config.public_url),
+            post_logout_redirect_url: format!("{}/", config.public_url),
+            provider: Arc::default(),
+        })
+    }
+
+    async fn provider(&self) -> Result<&Provider, AppError> {
+        self.provider
+            .get_or_try_init(|| async {
+                let issuer_url = IssuerUrl::new(self.issuer_url.clone())
+                    .map_err(|err| AppError::IdentityProviderError(err.to_string()))?;
+                let redirect_url = RedirectUrl::new(self.redirect_url.clone())
+                    .map_err(|err| AppError::IdentityProviderError(err.to_string()))?;
+
+                let metadata = ProviderMetadataWithLogout::discover_async(issuer_url, &self.http)
+                    .await
+                    .map_err(|err| AppError::IdentityProviderError(err.to_string()))?;
+                let end_session_url = metadata.additional_metadata().end_session_endpoint.clone();
+
+                let client = CoreClient::from_provider_metadata(
+                    metadata,
+                    ClientId::new(self.client_id.clone()),
+                    Some(ClientSecret::new(self.client_secret.clone())),
+                )
+                .set_redirect_uri(redirect_url);
+
+                Ok(Provider {
+                    client,
+                    end_session_url,
+                })
+            })
+            .await
+    }
+
+    /// Start a login, returning where to send the user.
+    pub async fn authorize(&self) -> Result<AuthorizationRequest, AppError> {
+        let provider = self.provider().await?;
+        let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+
+        let (url, state, nonce) = provider
+            .client
+            .authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                CsrfToken::new_random,
+                Nonce::new_random,
+            )
+            .add_scope(Scope::new("profile".to_string()))
+            .add_scope(Scope::new("email".to_string()))
+            .set_pkce_challenge(pkce_challenge)
+            .url();
+
+        Ok(AuthorizationRequest {
+            url: url.to_string(),
+            state: state.into_secret(),
+            nonce: nonce.secret().clone(),
+            pkce_verifier: pkce_verifier.into_secret(),
+        })
+    }
+
+    /// Finish a login by exchanging the code the user returned with for a verified identity.
+    pub async fn exchange(
+        &self,
+        code: String,
+        pkce_verifier: String,
+        nonce: String,
+    ) -> Result<Identity, AppError> {
+        let provider = self.provider().await?;
+
+        let response = provider
+            .client
+            .exchange_code(AuthorizationCode::new(code))
+            .map_err(|err| AppError::IdentityProviderError(err.to_string()))?
+            .set_pkce_verifier(PkceCodeVerifier::new(pkce_verifier))
+            .request_async(&self.http)
+            .await
+            .map_err(|err| AppError::AuthenticationFailed(err.to_string()))?;
+
+        let id_token = response
+            .id_token()
+            .ok_or_else(|| AppError::AuthenticationFailed("no ID token returned".to_string()))?;
+        let verifier = provider.client.id_token_verifier();
+        let claims = id_token
+            .claims(&verifier, &Nonce::new(nonce))
+            .map_err(|err| AppError::AuthenticationFailed(err.to_string()))?;
+
+        // the access token is not used, but a mismatch means the response was tampered with
+        if let Some(expected_hash) = claims.access_token_hash() {
+            let signing_alg = id_token
+                .signing_alg()
+                .map_err(|err| AppError::AuthenticationFailed(err.to_string()))?;
+            let signing_key = id_token
+                .signing_key(&verifier)
+                .map_err(|err| AppError::AuthenticationFailed(err.to_string()))?;
+            let actual_hash =
+                AccessTokenHash::from_token(response.access_token(), signing_alg, signing_key)
+                    .map_err(|err| AppError::AuthenticationFailed(err.to_string()))?;
+
+            if actual_hash != *expected_hash {
+                return Err(AppError::AuthenticationFailed(
+                    "invalid access token".to_string(),
+                ));
+            }
+        }
+
+        let subject = claims.subject().to_string();
+        let email = claims.email().map(|email| email.to_string());
+        let name = claims
+            .name()
+            .and_then(|name| name.get(None))
+            .map(|name| name.to_string())
+            .or_else(|| {
+                claims
+                    .preferred_username()
+                    .map(|username| username.to_string())
+            })
+            .or_else(|| email.clone())
+            .unwrap_or_else(|| subject.clone());
+
+        Ok(Identity {
+            issuer: claims.issuer().to_string(),
+            subject,
+            name,
+            email,
+            id_token: id_token.to_string(),
+        })
+    }
+
+    /// Where to send the user to also sign out at the identity provider, if it supports that.
+    pub async fn logout_url(&self, id_token: &str) -> Option<String> {
+        let provider = self.provider().await.ok()?;
+        let end_session_url = provider.end_session_url.clone()?;
+        let post_logout_redirect_url =
+            PostLogoutRedirectUrl::new(self.post_logout_redirect_url.clone()).ok()?;
+
+        let mut request = LogoutRequest::from(end_session_url)
+            .set_post_logout_redirect_uri(post_logout_redirect_url);
+        if let Ok(id_token) = CoreIdToken::from_str(id_token) {
+            request = request.set_id_token_hint(&id_token);
+        }
+
+        Some(request.http_get_url().to_string())
+    }
+}
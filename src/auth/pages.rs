@@ -0,0 +1,776 @@
+use askama::Template;
+use axum::{
+    Router,
+    extract::{OriginalUri, Query, Request, State},
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::{
+    extract::{
+        Form,
+        cookie::{Cookie, CookieJar, SameSite},
+    },
+    routing::{RouterExt, TypedPath},
+};
+use chrono::{Duration, Utc};
+use serde::Deserialize;
+use sqlx::{Connection, PgConnection};
+
+use crate::{
+    AppError, AppState, Context, CsrfTokens, CurrentUser, DbConnection, HtmlTemplate, Role,
+    auth::{
+        Identity, RoleForm,
+        repository::{self, LoginAttempt},
+        saml_pages::{self, SamlLoginPath},
+    },
+    common::current_user::{SESSION_COOKIE, hash_token, random_token},
+    filters,
+    form::{FormData, Validate},
+    t,
+};
+
+/// Number of minutes a user has to complete a login at the identity provider.
+pub const LOGIN_TTL_MINUTES: i64 = 10;
+
+/// Number of hours a session lasts, after which the user has to sign in again.
+pub const SESSION_TTL_HOURS: i64 = 12;
+
+/// Ties a login attempt to the browser that started it.
+const LOGIN_COOKIE: &str = "eks_login";
+
+#[derive(TypedPath)]
+#[typed_path("/login", rejection(AppError))]
+pub struct LoginPath;
+
+#[derive(TypedPath)]
+#[typed_path("/login/oidc", rejection(AppError))]
+pub struct OidcLoginPath;
+
+#[derive(TypedPath)]
+#[typed_path("/login/callback", rejection(AppError))]
+pub struct LoginCallbackPath;
+
+#[derive(TypedPath)]
+#[typed_path("/login/role", rejection(AppError))]
+pub struct SelectRolePath;
+
+#[derive(TypedPath)]
+#[typed_path("/logout", rejection(AppError))]
+pub struct LogoutPath;
+
+pub fn login_path() -> String {
+    LoginPath {}.to_string()
+}
+
+pub fn logout_path() -> String {
+    LogoutPath {}.to_string()
+}
+
+/// Page where users holding more than one role choose the one to act in, before continuing
+/// to `redirect`.
+pub fn select_role_path(redirect: &str) -> String {
+    with_redirect(SelectRolePath, redirect)
+}
+
+/// Path of a page with the page to continue with after it as query.
+fn with_redirect(path: impl ToString, redirect: &str) -> String {
+    let query = serde_urlencoded::to_string([("redirect", redirect)]).unwrap_or_default();
+
+    format!("{}?{query}", path.to_string())
+}
+
+#[derive(Deserialize)]
+pub struct LoginQuery {
+    pub redirect: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+}
+
+/// Only redirect to paths within the application after signing in.
+pub(super) fn local_path(redirect: Option<String>) -> String {
+    match redirect {
+        Some(path) if path.starts_with('/') && !path.starts_with("//") && !path.contains('\\') => {
+            path
+        }
+        _ => "/".to_string(),
+    }
+}
+
+#[derive(Template)]
+#[template(path = "auth/login.html")]
+struct LoginTemplate {
+    oidc_login_path: String,
+    saml_login_path: String,
+}
+
+/// Let the user choose how to sign in when there is more than one identity provider, or go
+/// straight to the only one.
+async fn login(
+    _: LoginPath,
+    context: Context,
+    user: Option<CurrentUser>,
+    State(app_state): State<AppState>,
+    DbConnection(mut conn): DbConnection,
+    jar: CookieJar,
+    Query(query): Query<LoginQuery>,
+) -> Result<Response, AppError> {
+    let redirect_to = local_path(query.redirect);
+    if user.is_some() {
+        return Ok(Redirect::to(&redirect_to).into_response());
+    }
+
+    if app_state.saml().is_none() {
+        return start_oidc_login(&app_state, &mut conn, jar, redirect_to).await;
+    }
+
+    Ok(HtmlTemplate(
+        LoginTemplate {
+            oidc_login_path: with_redirect(OidcLoginPath, &redirect_to),
+            saml_login_path: with_redirect(SamlLoginPath, &redirect_to),
+        },
+        context,
+    )
+    .into_response())
+}
+
+/// Sign in with the OpenID Connect identity provider.
+async fn oidc_login(
+    _: OidcLoginPath,
+    user: Option<CurrentUser>,
+    State(app_state): State<AppState>,
+    DbConnection(mut conn): DbConnection,
+    jar: CookieJar,
+    Query(query): Query<LoginQuery>,
+) -> Result<Response, AppError> {
+    let redirect_to = local_path(query.redirect);
+    if user.is_some() {
+        return Ok(Redirect::to(&redirect_to).into_response());
+    }
+
+    start_oidc_login(&app_state, &mut conn, jar, redirect_to).await
+}
+
+/// Send the user to the identity provider to sign in.
+async fn start_oidc_login(
+    app_state: &AppState,
+    conn: &mut PgConnection,
+    jar: CookieJar,
+    redirect_to: String,
+) -> Result<Response, AppError> {
+    let request = app_state.oidc().authorize().await?;
+    repository::create_login_attempt(
+        conn,
+        &LoginAttempt {
+            state: request.state.clone(),
+            nonce: request.nonce,
+            pkce_verifier: request.pkce_verifier,
+            redirect_to,
+            expires_at: Utc::now() + Duration::minutes(LOGIN_TTL_MINUTES),
+        },
+    )
+    .await?;
+
+    // lax, as the user returns from the identity provider through a cross-site redirect
+    let cookie = Cookie::build((LOGIN_COOKIE, request.state))
+        .path(LoginPath::PATH)
+        .http_only(true)
+        .secure(app_state.config().is_https())
+        .same_site(SameSite::Lax);
+
+    Ok((jar.add(cookie), Redirect::to(&request.url)).into_response())
+}
+
+/// The identity provider sends the user back here after signing in.
+async fn login_callback(
+    _: LoginCallbackPath,
+    State(app_state): State<AppState>,
+    DbConnection(mut conn): DbConnection,
+    jar: CookieJar,
+    Query(query): Query<CallbackQuery>,
+) -> Result<Response, AppError> {
+    if let Some(error) = query.error {
+        return Err(AppError::AuthenticationFailed(error));
+    }
+
+    let (Some(code), Some(state)) = (query.code, query.state) else {
+        return Err(AppError::AuthenticationFailed(
+            "missing code or state".to_string(),
+        ));
+    };
+
+    // the login must have been started in this browser
+    if jar.get(LOGIN_COOKIE).map(|cookie| cookie.value()) != Some(state.as_str()) {
+        return Err(AppError::AuthenticationFailed("state mismatch".to_string()));
+    }
+
+    let attempt = repository::take_login_attempt(&mut conn, &state)
+        .await?
+        .ok_or_else(|| AppError::AuthenticationFailed("unknown or expired login".to_string()))?;
+
+    let identity = app_state
+        .oidc()
+        .exchange(code, attempt.pkce_verifier, attempt.nonce)
+        .await?;
+
+    let jar = jar.remove(Cookie::build(LOGIN_COOKIE).path(LoginPath::PATH));
+
+    sign_in(
+        &app_state,
+        &mut conn,
+        jar,
+        &identity,
+        Some(&identity.id_token),
+        &attempt.redirect_to,
+    )
+    .await
+}
+
+/// Start a session for the user with the given identity, unless an administrator deactivated
+/// them. Users holding more than one role first choose the role to act in.
+pub(super) async fn sign_in(
+    app_state: &AppState,
+    conn: &mut PgConnection,
+    jar: CookieJar,
+    identity: &Identity,
+    id_token: Option<&str>,
+    redirect_to: &str,
+) -> Result<Response, AppError> {
+    let token = random_token();
+    let mut tx = conn.begin().await?;
+    let user_id = repository::upsert_user(&mut tx, identity).await?;
+    if repository::is_user_deactivated(&mut tx, &user_id).await? {
+        return Err(AppError::AuthenticationFailed(
+            "user is deactivated".to_string(),
+        ));
+    }
+    repository::create_session(
+        &mut tx,
+        &user_id,
+        &hash_token(&token),
+        id_token,
+        Utc::now() + Duration::hours(SESSION_TTL_HOURS),
+    )
+    .await?;
+    let roles = repository::list_user_roles(&mut tx, &user_id).await?;
+    tx.commit().await?;
+
+    let session_cookie = Cookie::build((SESSION_COOKIE, token))
+        .path("/")
+        .http_only(true)
+        .secure(app_state.config().is_https())
+        .same_site(SameSite::Lax);
+
+    let redirect_to = if roles.len() > 1 {
+        select_role_path(redirect_to)
+    } else {
+        redirect_to.to_string()
+    };
+
+    Ok((jar.add(session_cookie), Redirect::to(&redirect_to)).into_response())
+}
+
+#[derive(Template)]
+#[template(path = "auth/select_role.html")]
+struct SelectRoleTemplate {
+    roles: Vec<Role>,
+    form: FormData<RoleForm>,
+    action: String,
+}
+
+/// Let a user holding more than one role, e.g. both authorised agent and list submitter,
+/// choose the role to act in for the rest of the session.
+async fn select_role(
+    _: SelectRolePath,
+    context: Context,
+    user: CurrentUser,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+    Query(query): Query<LoginQuery>,
+) -> Result<Response, AppError> {
+    let roles = repository::list_user_roles(&mut conn, &user.id).await?;
+    let mut form = FormData::<RoleForm>::new(&csrf_tokens);
+    if let [role] = user.roles.as_slice()
+        && roles.len() > 1
+    {
+        form.data.role = role.to_string();
+    }
+
+    Ok(HtmlTemplate(
+        SelectRoleTemplate {
+            roles,
+            form,
+            action: select_role_path(&local_path(query.redirect)),
+        },
+        context,
+    )
+    .into_response())
+}
+
+async fn update_role(
+    _: SelectRolePath,
+    context: Context,
+    user: CurrentUser,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+    Query(query): Query<LoginQuery>,
+    Form(form): Form<RoleForm>,
+) -> Result<Response, AppError> {
+    let redirect_to = local_path(query.redirect);
+    let roles = repository::list_user_roles(&mut conn, &user.id).await?;
+
+    match form.validate(None, &csrf_tokens) {
+        Err(form) => Ok(HtmlTemplate(
+            SelectRoleTemplate {
+                roles,
+                form,
+                action: select_role_path(&redirect_to),
+            },
+            context,
+        )
+        .into_response()),
+        Ok(role) => {
+            if !roles.contains(&role) {
+                return Err(AppError::Forbidden);
+            }
+
+            repository::set_session_role(&mut conn, &user.session_id, role).await?;
+
+            Ok(Redirect::to(&redirect_to).into_response())
+        }
+    }
+}
+
+/// Sign out, also at the identity provider when it supports that.
+///
+/// No CSRF token is needed: the session cookie is `SameSite=Lax`, so it is not sent along
+/// with cross-site posts.
+async fn logout(
+    _: LogoutPath,
+    user: Option<CurrentUser>,
+    State(app_state): State<AppState>,
+    DbConnection(mut conn): DbConnection,
+    jar: CookieJar,
+) -> Result<Response, AppError> {
+    let mut redirect_to = "/".to_string();
+
+    if let Some(user) = user
+        && let Some(id_token) = repository::delete_session(&mut conn, &user.session_id).await?
+        && let Some(logout_url) = app_state.oidc().logout_url(&id_token).await
+    {
+        redirect_to = logout_url;
+    }
+
+    let jar = jar.remove(Cookie::build(SESSION_COOKIE).path("/"));
+
+    Ok((jar, Redirect::to(&redirect_to)).into_response())
+}
+
+/// Middleware resolving the signed in user, if any, so pages and templates can use it.
+pub async fn load_current_user(
+    user: Option<CurrentUser>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if let Some(user) = user {
+        request.extensions_mut().insert(user);
+    }
+
+    next.run(request).await
+}
+
+/// Middleware only letting signed in users through; others are sent to the login page
+/// when they were navigating, and refused otherwise.
+pub async fn require_login(
+    OriginalUri(uri): OriginalUri,
+    request: Request,
+    next: Next,
+) -> Response {
+    if request.extensions().get::<CurrentUser>().is_some() {
+        return next.run(request).await;
+    }
+
+    if request.method() != Method::GET {
+        return AppError::Unauthorized.into_response();
+    }
+
+    let redirect = uri
+        .path_and_query()
+        .map(|path| path.to_string())
+        .unwrap_or_else(|| "/".to_string());
+
+    Redirect::to(&with_redirect(LoginPath, &redirect)).into_response()
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .typed_get(login)
+        .typed_get(oidc_login)
+        .typed_get(login_callback)
+        .typed_get(select_role)
+        .typed_post(update_role)
+        .typed_get(saml_pages::saml_login)
+        .typed_post(saml_pages::saml_acs)
+        .typed_post(logout)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{
+            Request, StatusCode,
+            header::{CONTENT_TYPE, COOKIE, LOCATION, SET_COOKIE},
+        },
+    };
+    use sqlx::PgPool;
+    use tokio::net::TcpListener;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::{
+        OidcConfig, SamlConfig,
+        auth::{AuthenticationLevel, MockProvider, MockSamlProvider, saml::Identifier},
+        router,
+        test_utils::response_body_string,
+    };
+
+    /// Serve a mock identity provider and return an application signing in with it.
+    async fn setup(pool: PgPool) -> (Router, MockProvider) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let oidc = OidcConfig {
+            issuer_url: format!("http://{}", listener.local_addr().unwrap()),
+            client_id: "eks".to_string(),
+            client_secret: "secret".to_string(),
+        };
+        let provider = MockProvider::new(&oidc);
+        let provider_router = provider.clone().router();
+        tokio::spawn(async move { axum::serve(listener, provider_router).await });
+
+        let state = AppState::new_for_tests(pool).with_oidc(oidc);
+        (router::create(&state).with_state(state), provider)
+    }
+
+    async fn send(app: &Router, method: Method, uri: &str, cookie: Option<&str>) -> Response {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(cookie) = cookie {
+            request = request.header(COOKIE, cookie);
+        }
+
+        app.clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn post_form(
+        app: &Router,
+        uri: &str,
+        cookie: Option<&str>,
+        form: &[(&str, &str)],
+    ) -> Response {
+        let mut request = Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded");
+        if let Some(cookie) = cookie {
+            request = request.header(COOKIE, cookie);
+        }
+
+        let body = serde_urlencoded::to_string(form).unwrap();
+        app.clone()
+            .oneshot(request.body(Body::from(body)).unwrap())
+            .await
+            .unwrap()
+    }
+
+    fn location(response: &Response) -> String {
+        response.headers()[LOCATION].to_str().unwrap().to_string()
+    }
+
+    fn cookie(response: &Response, name: &str) -> String {
+        response
+            .headers()
+            .get_all(SET_COOKIE)
+            .iter()
+            .map(|value| value.to_str().unwrap())
+            .find(|value| value.starts_with(&format!("{name}=")))
+            .and_then(|value| value.split(';').next())
+            .unwrap()
+            .to_string()
+    }
+
+    /// Follow the redirect of the identity provider, which signs in without asking.
+    async fn authorize(provider_url: &str) -> String {
+        let client = openidconnect::reqwest::ClientBuilder::new()
+            .redirect(openidconnect::reqwest::redirect::Policy::none())
+            .build()
+            .unwrap();
+        let response = client.get(provider_url).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+        let callback = response.headers()[LOCATION].to_str().unwrap();
+        let callback = callback.strip_prefix("http://localhost:3000").unwrap();
+        callback.to_string()
+    }
+
+    #[sqlx::test]
+    async fn logs_in_and_out_through_the_identity_provider(pool: PgPool) {
+        let (app, _provider) = setup(pool.clone()).await;
+
+        let response = send(&app, Method::GET, "/persons", None).await;
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(location(&response), "/login?redirect=%2Fpersons");
+
+        let response = send(&app, Method::GET, "/login?redirect=%2Fpersons", None).await;
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        let login_cookie = cookie(&response, LOGIN_COOKIE);
+        let callback = authorize(&location(&response)).await;
+
+        let response = send(&app, Method::GET, &callback, Some(&login_cookie)).await;
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(location(&response), "/persons");
+        let session_cookie = cookie(&response, SESSION_COOKIE);
+
+        // signed in, but not allowed to do anything yet
+        let response = send(&app, Method::GET, "/persons", Some(&session_cookie)).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let user_id = sqlx::query_scalar!("SELECT id FROM users")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        repository::add_user_role(&mut conn, &user_id, Role::CsbMember)
+            .await
+            .unwrap();
+
+        let response = send(&app, Method::GET, "/persons", Some(&session_cookie)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        assert!(body.contains(MockProvider::USER_NAME));
+        assert!(body.contains("Centraal stembureau"));
+
+        // read-only role
+        let response = send(&app, Method::GET, "/persons/new", Some(&session_cookie)).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = send(&app, Method::POST, "/logout", Some(&session_cookie)).await;
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert!(location(&response).contains("/logout?"));
+        assert!(cookie(&response, SESSION_COOKIE).ends_with('='));
+
+        let response = send(&app, Method::GET, "/persons", Some(&session_cookie)).await;
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    }
+
+    #[sqlx::test]
+    async fn callback_requires_the_browser_that_started_the_login(pool: PgPool) {
+        let (app, _provider) = setup(pool).await;
+
+        let response = send(&app, Method::GET, "/login", None).await;
+        let callback = authorize(&location(&response)).await;
+
+        let response = send(&app, Method::GET, &callback, None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = send(&app, Method::GET, &callback, Some("eks_login=other")).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[sqlx::test]
+    async fn callback_can_only_be_used_once(pool: PgPool) {
+        let (app, _provider) = setup(pool).await;
+
+        let response = send(&app, Method::GET, "/login", None).await;
+        let login_cookie = cookie(&response, LOGIN_COOKIE);
+        let callback = authorize(&location(&response)).await;
+
+        let response = send(&app, Method::GET, &callback, Some(&login_cookie)).await;
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+        let response = send(&app, Method::GET, &callback, Some(&login_cookie)).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    /// Sign in at the mock SAML identity provider with its default KvK number, returning the
+    /// form its page posts back to the application.
+    async fn sign_in_with_eherkenning(sso_url: &str) -> Vec<(String, String)> {
+        let (sso_url, query) = sso_url.split_once('?').unwrap();
+        let params: Vec<(String, String)> = serde_urlencoded::from_str(query).unwrap();
+        let form = serde_urlencoded::to_string([
+            ("saml_request", params[0].1.as_str()),
+            ("relay_state", params[1].1.as_str()),
+            ("kind", "kvk"),
+            ("number", ""),
+            ("level", "substantial"),
+        ])
+        .unwrap();
+
+        let page = openidconnect::reqwest::Client::new()
+            .post(sso_url)
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(form)
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+
+        ["SAMLResponse", "RelayState"]
+            .into_iter()
+            .map(|name| {
+                let (_, value) = page
+                    .split_once(&format!(r#"name="{name}" value=""#))
+                    .unwrap();
+                (
+                    name.to_string(),
+                    value.split('"').next().unwrap().to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[sqlx::test]
+    async fn logs_in_with_eherkenning_and_chooses_a_role(pool: PgPool) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let idp = format!("http://{}", listener.local_addr().unwrap());
+        let provider = MockSamlProvider::new(&idp).with_defaults(
+            MockSamlProvider::DEFAULT_BSN.to_string(),
+            MockSamlProvider::DEFAULT_KVK.to_string(),
+            AuthenticationLevel::Substantial,
+        );
+        tokio::spawn(async move { axum::serve(listener, provider.router()).await });
+
+        let state = AppState::new_for_tests(pool.clone()).with_saml(SamlConfig {
+            idp_entity_id: idp.clone(),
+            idp_sso_url: format!("{idp}/sso"),
+            idp_certificate: MockSamlProvider::CERTIFICATE.to_string(),
+            min_level: AuthenticationLevel::Substantial,
+        });
+        let csrf_tokens = state.csrf_tokens().clone();
+        let app = router::create(&state).with_state(state);
+
+        // a user acting both as authorised agent and list submitter
+        let mut conn = pool.acquire().await.unwrap();
+        let identifier = Identifier::Kvk(MockSamlProvider::DEFAULT_KVK.to_string());
+        let user_id = repository::upsert_user(
+            &mut conn,
+            &Identity {
+                issuer: idp.clone(),
+                subject: identifier.subject(),
+                name: identifier.display_name(),
+                email: None,
+                id_token: String::new(),
+            },
+        )
+        .await
+        .unwrap();
+        for role in [Role::AuthorisedAgent, Role::ListSubmitter] {
+            repository::add_user_role(&mut conn, &user_id, role)
+                .await
+                .unwrap();
+        }
+
+        let response = send(&app, Method::GET, "/login?redirect=%2Fpersons", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        assert!(body.contains("/login/saml?redirect=%2Fpersons"));
+        assert!(body.contains("/login/oidc?redirect=%2Fpersons"));
+
+        let response = send(&app, Method::GET, "/login/saml?redirect=%2Fpersons", None).await;
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        let sso_url = location(&response);
+        assert!(sso_url.starts_with(&format!("{idp}/sso?SAMLRequest=")));
+        let form = sign_in_with_eherkenning(&sso_url).await;
+        let form: Vec<(&str, &str)> = form
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+
+        let response = post_form(&app, "/login/saml/acs", None, &form).await;
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(location(&response), "/login/role?redirect=%2Fpersons");
+        let session_cookie = cookie(&response, SESSION_COOKIE);
+
+        // the response can only be used once
+        let response = post_form(&app, "/login/saml/acs", None, &form).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // holding two roles, nothing is allowed until one is chosen
+        let response = send(&app, Method::GET, "/persons", Some(&session_cookie)).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = send(
+            &app,
+            Method::GET,
+            "/login/role?redirect=%2Fpersons",
+            Some(&session_cookie),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        assert!(body.contains("Gemachtigde"));
+        assert!(body.contains("Lijstinleveraar"));
+
+        let response = post_form(
+            &app,
+            "/login/role?redirect=%2Fpersons",
+            Some(&session_cookie),
+            &[
+                ("role", "list_submitter"),
+                ("csrf_token", &csrf_tokens.issue().value.to_string()),
+            ],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(location(&response), "/persons");
+
+        let role = sqlx::query_scalar!(r#"SELECT role AS "role: Role" FROM sessions"#)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(role, Some(Role::ListSubmitter));
+        let response = send(&app, Method::GET, "/persons", Some(&session_cookie)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // only roles the user holds can be chosen
+        let response = post_form(
+            &app,
+            "/login/role",
+            Some(&session_cookie),
+            &[
+                ("role", "kiesraad_admin"),
+                ("csrf_token", &csrf_tokens.issue().value.to_string()),
+            ],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[sqlx::test]
+    async fn refuses_changes_without_session(pool: PgPool) {
+        let (app, _provider) = setup(pool).await;
+
+        let response = send(&app, Method::POST, "/persons/new", None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn only_redirects_within_the_application() {
+        assert_eq!(
+            local_path(Some("/persons?page=2".to_string())),
+            "/persons?page=2"
+        );
+        assert_eq!(local_path(Some("//evil.example".to_string())), "/");
+        assert_eq!(local_path(Some("https://evil.example".to_string())), "/");
+        assert_eq!(local_path(Some("/\\evil.example".to_string())), "/");
+        assert_eq!(local_path(None), "/");
+    }
+}
@@ -0,0 +1,442 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::{
+    CurrentUser,
+    auth::{Identity, Role},
+};
+
+/// A login that was started but not yet completed at the identity provider.
+#[derive(Debug)]
+pub struct LoginAttempt {
+    pub state: String,
+    pub nonce: String,
+    pub pkce_verifier: String,
+    pub redirect_to: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub async fn create_login_attempt(
+    conn: &mut PgConnection,
+    attempt: &LoginAttempt,
+) -> Result<(), sqlx::Error> {
+    // abandoned logins are cleaned up whenever a new one starts
+    sqlx::query!("DELETE FROM login_attempts WHERE expires_at < NOW()")
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO login_attempts (state, nonce, pkce_verifier, redirect_to, expires_at)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        attempt.state,
+        attempt.nonce,
+        attempt.pkce_verifier,
+        attempt.redirect_to,
+        attempt.expires_at,
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// A login that was started but not yet completed at the SAML identity provider.
+#[derive(Debug)]
+pub struct SamlLoginAttempt {
+    pub relay_state: String,
+    pub request_id: String,
+    pub redirect_to: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub async fn create_saml_login_attempt(
+    conn: &mut PgConnection,
+    attempt: &SamlLoginAttempt,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!("DELETE FROM saml_login_attempts WHERE expires_at < NOW()")
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO saml_login_attempts (relay_state, request_id, redirect_to, expires_at)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        attempt.relay_state,
+        attempt.request_id,
+        attempt.redirect_to,
+        attempt.expires_at,
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Like `take_login_attempt`, for logins at the SAML identity provider.
+pub async fn take_saml_login_attempt(
+    conn: &mut PgConnection,
+    relay_state: &str,
+) -> Result<Option<SamlLoginAttempt>, sqlx::Error> {
+    let attempt = sqlx::query_as!(
+        SamlLoginAttempt,
+        r#"
+        DELETE FROM saml_login_attempts
+        WHERE relay_state = $1
+        RETURNING relay_state, request_id, redirect_to, expires_at
+        "#,
+        relay_state,
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(attempt.filter(|attempt| attempt.expires_at > Utc::now()))
+}
+
+/// Remove a login attempt, returning it when it has not expired yet. Every attempt
+/// can only be completed once.
+pub async fn take_login_attempt(
+    conn: &mut PgConnection,
+    state: &str,
+) -> Result<Option<LoginAttempt>, sqlx::Error> {
+    let attempt = sqlx::query_as!(
+        LoginAttempt,
+        r#"
+        DELETE FROM login_attempts
+        WHERE state = $1
+        RETURNING state, nonce, pkce_verifier, redirect_to, expires_at
+        "#,
+        state,
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(attempt.filter(|attempt| attempt.expires_at > Utc::now()))
+}
+
+/// Create or update the user with the given identity, returning its id.
+pub async fn upsert_user(
+    conn: &mut PgConnection,
+    identity: &Identity,
+) -> Result<Uuid, sqlx::Error> {
+    let record = sqlx::query!(
+        r#"
+        INSERT INTO users (issuer, subject, name, email)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (issuer, subject) DO UPDATE SET
+            name = EXCLUDED.name,
+            email = EXCLUDED.email,
+            last_login_at = NOW()
+        RETURNING id
+        "#,
+        identity.issuer,
+        identity.subject,
+        identity.name,
+        identity.email,
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(record.id)
+}
+
+pub async fn create_session(
+    conn: &mut PgConnection,
+    user_id: &Uuid,
+    token_hash: &[u8],
+    id_token: Option<&str>,
+    expires_at: DateTime<Utc>,
+) -> Result<Uuid, sqlx::Error> {
+    sqlx::query!("DELETE FROM sessions WHERE expires_at < NOW()")
+        .execute(&mut *conn)
+        .await?;
+
+    let record = sqlx::query!(
+        r#"
+        INSERT INTO sessions (token_hash, user_id, id_token, expires_at)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+        "#,
+        token_hash,
+        user_id,
+        id_token,
+        expires_at,
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(record.id)
+}
+
+/// The user of a valid session, with the roles they act in: the role selected for the session,
+/// or their only role. Users holding more than one role act in none until they select one.
+pub async fn get_session_user(
+    conn: &mut PgConnection,
+    token_hash: &[u8],
+) -> Result<Option<CurrentUser>, sqlx::Error> {
+    sqlx::query_as!(
+        CurrentUser,
+        r#"
+        SELECT
+            u.id,
+            s.id AS session_id,
+            u.name,
+            u.email,
+            u.political_group_id,
+            ARRAY(
+                SELECT r.role FROM user_roles r
+                WHERE r.user_id = u.id AND (
+                    r.role = s.role
+                    OR s.role IS NULL
+                        AND NOT EXISTS(
+                            SELECT 1 FROM user_roles o WHERE o.user_id = u.id AND o.role <> r.role
+                        )
+                )
+                ORDER BY r.role
+            ) AS "roles!: Vec<Role>"
+        FROM sessions s
+        JOIN users u ON u.id = s.user_id
+        WHERE s.token_hash = $1 AND s.expires_at > NOW() AND u.deactivated_at IS NULL
+        "#,
+        token_hash,
+    )
+    .fetch_optional(conn)
+    .await
+}
+
+/// Whether the user was deactivated by an administrator, and may no longer sign in.
+pub async fn is_user_deactivated(
+    conn: &mut PgConnection,
+    user_id: &Uuid,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT deactivated_at IS NOT NULL AS "deactivated!" FROM users WHERE id = $1"#,
+        user_id,
+    )
+    .fetch_one(conn)
+    .await
+}
+
+/// All roles of a user, regardless of the role chosen for a session.
+pub async fn list_user_roles(
+    conn: &mut PgConnection,
+    user_id: &Uuid,
+) -> Result<Vec<Role>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT role AS "role: Role" FROM user_roles WHERE user_id = $1 ORDER BY role"#,
+        user_id,
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Act in a single role for the rest of the session.
+pub async fn set_session_role(
+    conn: &mut PgConnection,
+    session_id: &Uuid,
+    role: Role,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE sessions SET role = $2 WHERE id = $1",
+        session_id,
+        role as Role,
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn add_user_role(
+    conn: &mut PgConnection,
+    user_id: &Uuid,
+    role: Role,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO user_roles (user_id, role)
+        VALUES ($1, $2)
+        ON CONFLICT DO NOTHING
+        "#,
+        user_id,
+        role as Role,
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn remove_user_role(
+    conn: &mut PgConnection,
+    user_id: &Uuid,
+    role: Role,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "DELETE FROM user_roles WHERE user_id = $1 AND role = $2",
+        user_id,
+        role as Role,
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(any(test, feature = "fixtures"))]
+pub async fn create_political_group(
+    conn: &mut PgConnection,
+    name: &str,
+) -> Result<Uuid, sqlx::Error> {
+    let record = sqlx::query!(
+        "INSERT INTO political_groups (name) VALUES ($1) RETURNING id",
+        name,
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(record.id)
+}
+
+pub async fn set_user_political_group(
+    conn: &mut PgConnection,
+    user_id: &Uuid,
+    political_group_id: &Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE users SET political_group_id = $2 WHERE id = $1",
+        user_id,
+        political_group_id,
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Delete a session, returning the ID token it was created with, if any.
+pub async fn delete_session(
+    conn: &mut PgConnection,
+    session_id: &Uuid,
+) -> Result<Option<String>, sqlx::Error> {
+    let record = sqlx::query!(
+        "DELETE FROM sessions WHERE id = $1 RETURNING id_token",
+        session_id,
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(record.and_then(|record| record.id_token))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use sqlx::PgPool;
+
+    use super::*;
+
+    fn identity(subject: &str) -> Identity {
+        Identity {
+            issuer: "http://idp.test".to_string(),
+            subject: subject.to_string(),
+            name: "Evert Klaas Smit".to_string(),
+            email: Some("eks@example.com".to_string()),
+            id_token: "id-token".to_string(),
+        }
+    }
+
+    #[sqlx::test]
+    async fn sessions_resolve_to_their_user_until_they_expire(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let user_id = upsert_user(&mut conn, &identity("user-1")).await?;
+        assert_eq!(upsert_user(&mut conn, &identity("user-1")).await?, user_id);
+
+        let valid = create_session(
+            &mut conn,
+            &user_id,
+            b"valid",
+            Some("id-token"),
+            Utc::now() + Duration::hours(1),
+        )
+        .await?;
+        create_session(
+            &mut conn,
+            &user_id,
+            b"expired",
+            None,
+            Utc::now() - Duration::hours(1),
+        )
+        .await?;
+
+        // a single role is acted in without choosing it
+        add_user_role(&mut conn, &user_id, Role::ListSubmitter).await?;
+        let user = get_session_user(&mut conn, b"valid").await?.unwrap();
+        assert_eq!(user.roles, vec![Role::ListSubmitter]);
+
+        add_user_role(&mut conn, &user_id, Role::AuthorisedAgent).await?;
+        add_user_role(&mut conn, &user_id, Role::ListSubmitter).await?;
+
+        let political_group_id = create_political_group(&mut conn, "Lijst Smit").await?;
+        set_user_political_group(&mut conn, &user_id, &political_group_id).await?;
+
+        let user = get_session_user(&mut conn, b"valid").await?.unwrap();
+        assert_eq!(user.id, user_id);
+        assert_eq!(user.political_group_id, Some(political_group_id));
+        assert_eq!(user.session_id, valid);
+        assert_eq!(user.name, "Evert Klaas Smit");
+        // holding more than one role, the user acts in none until choosing one
+        assert!(user.roles.is_empty());
+        assert!(get_session_user(&mut conn, b"expired").await?.is_none());
+
+        // acting in a single role
+        set_session_role(&mut conn, &valid, Role::ListSubmitter).await?;
+        let user = get_session_user(&mut conn, b"valid").await?.unwrap();
+        assert_eq!(user.roles, vec![Role::ListSubmitter]);
+        assert_eq!(
+            list_user_roles(&mut conn, &user_id).await?,
+            vec![Role::AuthorisedAgent, Role::ListSubmitter]
+        );
+
+        assert_eq!(
+            delete_session(&mut conn, &valid).await?.as_deref(),
+            Some("id-token")
+        );
+        assert!(get_session_user(&mut conn, b"valid").await?.is_none());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn login_attempts_can_be_taken_once(pool: PgPool) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let attempt = LoginAttempt {
+            state: "state".to_string(),
+            nonce: "nonce".to_string(),
+            pkce_verifier: "verifier".to_string(),
+            redirect_to: "/persons".to_string(),
+            expires_at: Utc::now() + Duration::minutes(10),
+        };
+        create_login_attempt(&mut conn, &attempt).await?;
+
+        let taken = take_login_attempt(&mut conn, "state").await?.unwrap();
+        assert_eq!(taken.redirect_to, "/persons");
+        assert!(take_login_attempt(&mut conn, "state").await?.is_none());
+
+        let attempt = SamlLoginAttempt {
+            relay_state: "relay".to_string(),
+            request_id: "_request".to_string(),
+            redirect_to: "/persons".to_string(),
+            expires_at: Utc::now() - Duration::minutes(1),
+        };
+        create_saml_login_attempt(&mut conn, &attempt).await?;
+        assert!(take_saml_login_attempt(&mut conn, "relay").await?.is_none());
+
+        Ok(())
+    }
+}
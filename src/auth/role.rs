@@ -0,0 +1,217 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::{PgHasArrayType, PgTypeInfo};
+use std::str::FromStr;
+use strum::{Display, EnumString};
+use validate::Validate;
+
+use crate::{
+    AppError, CurrentUser,
+    form::{TokenValue, WithCsrfToken},
+    t,
+};
+
+/// Roles of the actors using the application, see `docs/use-cases/actoren-doelen.md`.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, Display, EnumString,
+)]
+#[sqlx(no_pg_array)]
+#[sqlx(type_name = "user_role", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Role {
+    /// Gemachtigde, acts on behalf of the political group.
+    AuthorisedAgent,
+    /// Lijstinleveraar, prepares and submits the candidate lists.
+    ListSubmitter,
+    /// Medewerker of the central electoral committee (CSB), checks what was submitted.
+    CsbMember,
+    KiesraadAdmin,
+}
+
+impl PgHasArrayType for Role {
+    fn array_type_info() -> PgTypeInfo {
+        PgTypeInfo::array_of("user_role")
+    }
+}
+
+/// What a user is allowed to do; pages check permissions, never roles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    ViewPersons,
+    EditPersons,
+    ViewCandidateLists,
+    /// Create, rename and duplicate lists.
+    EditCandidateLists,
+    DeleteCandidateLists,
+    /// Add, remove and reorder the candidates on a list.
+    EditCandidates,
+    RestoreDeleted,
+    /// Invite users, assign their roles and sign them out; gemachtigden manage their own
+    /// political group, administrators of the Electoral Council the CSB and every group.
+    ManageUsers,
+}
+
+impl Role {
+    pub fn permissions(&self) -> &'static [Permission] {
+        use Permission::*;
+
+        match self {
+            Role::AuthorisedAgent => &[
+                ViewPersons,
+                EditPersons,
+                ViewCandidateLists,
+                EditCandidateLists,
+                DeleteCandidateLists,
+                EditCandidates,
+                RestoreDeleted,
+                ManageUsers,
+            ],
+            Role::ListSubmitter => &[
+                ViewPersons,
+                EditPersons,
+                ViewCandidateLists,
+                EditCandidateLists,
+                EditCandidates,
+            ],
+            Role::CsbMember => &[ViewPersons, ViewCandidateLists],
+            Role::KiesraadAdmin => &[ViewPersons, ViewCandidateLists, ManageUsers],
+        }
+    }
+
+    /// Roles members of a political group can have, or otherwise the roles of the central
+    /// electoral committee and the Electoral Council.
+    pub fn assignable(political_group: bool) -> &'static [Role] {
+        if political_group {
+            &[Role::AuthorisedAgent, Role::ListSubmitter]
+        } else {
+            &[Role::CsbMember, Role::KiesraadAdmin]
+        }
+    }
+
+    pub fn label(&self) -> &[&'static str] {
+        match self {
+            Role::AuthorisedAgent => t!("role.authorised_agent"),
+            Role::ListSubmitter => t!("role.list_submitter"),
+            Role::CsbMember => t!("role.csb_member"),
+            Role::KiesraadAdmin => t!("role.kiesraad_admin"),
+        }
+    }
+}
+
+/// Choice of the role to act in, for users holding more than one.
+#[derive(Default, Serialize, Deserialize, Clone, Debug, Validate)]
+#[validate(target = "Role", build = "RoleForm::build")]
+#[serde(default)]
+pub struct RoleForm {
+    #[validate(parse = "Role")]
+    pub role: String,
+    #[validate(csrf)]
+    pub csrf_token: TokenValue,
+}
+
+impl WithCsrfToken for RoleForm {
+    fn with_csrf_token(self, csrf_token: crate::form::CsrfToken) -> Self {
+        RoleForm {
+            csrf_token: csrf_token.value,
+            ..self
+        }
+    }
+}
+
+impl RoleForm {
+    fn build(validated: RoleFormValidated, _current: Option<&Role>) -> Role {
+        validated.role
+    }
+}
+
+impl CurrentUser {
+    /// Whether the roles the user acts in grant the permission; users holding more than one role
+    /// act in the role they selected only, see `auth::repository::get_session_user`.
+    pub fn can(&self, permission: Permission) -> bool {
+        self.roles
+            .iter()
+            .any(|role| role.permissions().contains(&permission))
+    }
+}
+
+/// Middleware only letting users through that have the permission given as state, e.g.
+/// `middleware::from_fn_with_state(Permission::EditPersons, require_permission)`. Signed in users
+/// without it are forbidden, others unauthorized.
+pub async fn require_permission(
+    State(permission): State<Permission>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match request.extensions().get::<CurrentUser>() {
+        Some(user) if user.can(permission) => next.run(request).await,
+        Some(_) => AppError::Forbidden.into_response(),
+        None => AppError::Unauthorized.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, http::StatusCode, middleware, routing::get};
+    use tower::ServiceExt;
+
+    use crate::test_utils::sample_user as user;
+
+    #[test]
+    fn permissions_follow_roles() {
+        let agent = user(vec![Role::AuthorisedAgent]);
+        assert!(agent.can(Permission::DeleteCandidateLists));
+
+        let submitter = user(vec![Role::ListSubmitter]);
+        assert!(submitter.can(Permission::EditCandidates));
+        assert!(!submitter.can(Permission::DeleteCandidateLists));
+
+        let csb = user(vec![Role::CsbMember]);
+        assert!(csb.can(Permission::ViewCandidateLists));
+        assert!(!csb.can(Permission::EditPersons));
+        assert!(!csb.can(Permission::ManageUsers));
+        assert!(user(vec![Role::KiesraadAdmin]).can(Permission::ManageUsers));
+
+        assert!(!user(vec![]).can(Permission::ViewPersons));
+        assert!(user(vec![Role::CsbMember, Role::ListSubmitter]).can(Permission::EditPersons));
+    }
+
+    #[tokio::test]
+    async fn forbids_signed_in_users_without_permission() {
+        let status = |user: Option<CurrentUser>| async move {
+            Router::new()
+                .route("/", get(|| async { "ok" }))
+                .route_layer(middleware::from_fn_with_state(
+                    Permission::EditPersons,
+                    require_permission,
+                ))
+                .layer(middleware::from_fn(
+                    move |mut request: Request, next: Next| {
+                        if let Some(user) = user.clone() {
+                            request.extensions_mut().insert(user);
+                        }
+                        next.run(request)
+                    },
+                ))
+                .oneshot(Request::new(Body::empty()))
+                .await
+                .unwrap()
+                .status()
+        };
+
+        assert_eq!(status(None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            status(Some(user(vec![Role::CsbMember]))).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(Some(user(vec![Role::ListSubmitter]))).await,
+            StatusCode::OK
+        );
+    }
+}
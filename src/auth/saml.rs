@@ -0,0 +1,539 @@
+//! SAML 2.0 service provider for DigiD and eHerkenning, as offered through the
+//! Toegangsverleningservice (TVS) of Logius.
+//!
+//! Requests are sent with the HTTP-Redirect binding and responses are received with the
+//! HTTP-POST binding. Only signed, unencrypted assertions are accepted; TVS additionally
+//! requires signed requests and encrypted assertions, which still have to be added. Until then
+//! SAML can only be configured in development builds, see `Config`.
+
+use std::io::Write;
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use flate2::{Compression, write::DeflateEncoder};
+use rand::{Rng, distr::Alphanumeric};
+use roxmltree::{Document, Node};
+use rsa::{RsaPublicKey, pkcs8::DecodePublicKey};
+use strum::{Display, EnumString};
+use x509_cert::{
+    Certificate,
+    der::{DecodePem, Encode},
+};
+
+use crate::{AppError, SamlConfig, auth::xml_signature};
+
+pub const PROTOCOL_NS: &str = "urn:oasis:names:tc:SAML:2.0:protocol";
+pub const ASSERTION_NS: &str = "urn:oasis:names:tc:SAML:2.0:assertion";
+pub const STATUS_SUCCESS: &str = "urn:oasis:names:tc:SAML:2.0:status:Success";
+pub const BEARER: &str = "urn:oasis:names:tc:SAML:2.0:cm:bearer";
+const HTTP_POST_BINDING: &str = "urn:oasis:names:tc:SAML:2.0:bindings:HTTP-POST";
+
+/// Sector code DigiD prefixes the BSN of a citizen with.
+pub const BSN_SECTOR_CODE: &str = "s00000000";
+/// Qualifier eHerkenning identifies companies by their Chamber of Commerce number with.
+pub const KVK_QUALIFIER: &str = "urn:etoegang:1.9:EntityConcernedID:KvKnr";
+
+/// Tolerated difference between the clocks of the identity provider and ours.
+const CLOCK_SKEW_SECONDS: i64 = 60;
+
+/// Authentication levels ("betrouwbaarheidsniveaus") of DigiD, with the matching
+/// assurance levels of eHerkenning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum AuthenticationLevel {
+    /// Username and password.
+    Basic,
+    /// Username and password with an sms code.
+    Middle,
+    /// The DigiD app with an identity document.
+    Substantial,
+    /// An identity document with a chip.
+    High,
+}
+
+impl AuthenticationLevel {
+    pub const ALL: [AuthenticationLevel; 4] = [
+        AuthenticationLevel::Basic,
+        AuthenticationLevel::Middle,
+        AuthenticationLevel::Substantial,
+        AuthenticationLevel::High,
+    ];
+
+    /// Authentication context class DigiD reports this level with.
+    pub fn digid_class(&self) -> &'static str {
+        match self {
+            AuthenticationLevel::Basic => {
+                "urn:oasis:names:tc:SAML:2.0:ac:classes:PasswordProtectedTransport"
+            }
+            AuthenticationLevel::Middle => {
+                "urn:oasis:names:tc:SAML:2.0:ac:classes:MobileTwoFactorContract"
+            }
+            AuthenticationLevel::Substantial => "urn:oasis:names:tc:SAML:2.0:ac:classes:Smartcard",
+            AuthenticationLevel::High => "urn:oasis:names:tc:SAML:2.0:ac:classes:SmartcardPKI",
+        }
+    }
+
+    /// Assurance class eHerkenning reports this level with.
+    pub fn eherkenning_class(&self) -> &'static str {
+        match self {
+            AuthenticationLevel::Basic => "urn:etoegang:core:assurance-class:loa2",
+            AuthenticationLevel::Middle => "urn:etoegang:core:assurance-class:loa2plus",
+            AuthenticationLevel::Substantial => "urn:etoegang:core:assurance-class:loa3",
+            AuthenticationLevel::High => "urn:etoegang:core:assurance-class:loa4",
+        }
+    }
+
+    fn from_class(class: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|level| level.digid_class() == class || level.eherkenning_class() == class)
+    }
+}
+
+/// Who signed in: a citizen through DigiD or a company through eHerkenning.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Identifier {
+    Bsn(String),
+    Kvk(String),
+}
+
+impl Identifier {
+    /// Subject the user is stored with.
+    pub fn subject(&self) -> String {
+        match self {
+            Identifier::Bsn(bsn) => format!("bsn:{bsn}"),
+            Identifier::Kvk(kvk) => format!("kvk:{kvk}"),
+        }
+    }
+
+    /// Name to show for the user, as DigiD does not tell who signed in; only the last
+    /// digits of a BSN are shown.
+    pub fn display_name(&self) -> String {
+        match self {
+            Identifier::Bsn(bsn) => {
+                let visible = bsn.len().saturating_sub(3);
+                format!("BSN {}{}", "*".repeat(visible), &bsn[visible..])
+            }
+            Identifier::Kvk(kvk) => format!("KvK {kvk}"),
+        }
+    }
+
+    fn from_name_id(name_id: Node) -> Result<Self, String> {
+        let value = text(name_id)?;
+        let is_digits = |value: &str, len: usize| {
+            value.len() == len && value.chars().all(|c| c.is_ascii_digit())
+        };
+
+        if name_id.attribute("NameQualifier") == Some(KVK_QUALIFIER) {
+            return is_digits(value, 8)
+                .then(|| Identifier::Kvk(value.to_string()))
+                .ok_or_else(|| "invalid KvK number".to_string());
+        }
+
+        match value.split_once(':') {
+            Some((BSN_SECTOR_CODE, bsn)) if is_digits(bsn, 9) => {
+                Ok(Identifier::Bsn(bsn.to_string()))
+            }
+            _ => Err("unsupported name identifier".to_string()),
+        }
+    }
+}
+
+/// The user as identified by a verified assertion.
+#[derive(Debug)]
+pub struct SamlIdentity {
+    pub issuer: String,
+    pub identifier: Identifier,
+    pub level: AuthenticationLevel,
+}
+
+/// A login started at the identity provider; the request id has to be kept until the user
+/// returns with the relay state.
+pub struct AuthnRequest {
+    pub url: String,
+    pub request_id: String,
+    pub relay_state: String,
+}
+
+pub fn random_id() -> String {
+    let id: String = rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect();
+
+    // XML ids can not start with a digit
+    format!("_{id}")
+}
+
+pub fn now_timestamp() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+pub fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn child<'a, 'input>(
+    node: Node<'a, 'input>,
+    namespace: &str,
+    name: &str,
+) -> Result<Node<'a, 'input>, String> {
+    node.children()
+        .find(|child| child.has_tag_name((namespace, name)))
+        .ok_or_else(|| format!("missing {name}"))
+}
+
+/// Text of an element holding nothing but text. Comments are left out of the signature, so an
+/// element with more children could be read differently than it was signed.
+fn text<'a>(node: Node<'a, '_>) -> Result<&'a str, String> {
+    let mut children = node.children();
+    match (children.next(), children.next()) {
+        (Some(child), None) if child.is_text() => Ok(child.text().unwrap_or_default().trim()),
+        _ => Err(format!("unexpected content in {}", node.tag_name().name())),
+    }
+}
+
+fn timestamp(node: Node, attribute: &str) -> Result<Option<DateTime<Utc>>, String> {
+    node.attribute(attribute)
+        .map(|value| {
+            DateTime::parse_from_rfc3339(value)
+                .map(|time| time.with_timezone(&Utc))
+                .map_err(|_| format!("invalid {attribute}"))
+        })
+        .transpose()
+}
+
+#[derive(Clone)]
+pub struct SamlServiceProvider {
+    entity_id: String,
+    acs_url: String,
+    idp_entity_id: String,
+    idp_sso_url: String,
+    idp_key: RsaPublicKey,
+    min_level: AuthenticationLevel,
+}
+
+impl SamlServiceProvider {
+    pub fn new(public_url: &str, config: &SamlConfig) -> Result<Self, AppError> {
+        let invalid =
+            |err: String| AppError::ConfigLoadError(format!("invalid SAML_IDP_CERTIFICATE: {err}"));
+        let certificate = Certificate::from_pem(config.idp_certificate.trim())
+            .map_err(|err| invalid(err.to_string()))?;
+        let public_key = certificate
+            .tbs_certificate
+            .subject_public_key_info
+            .to_der()
+            .map_err(|err| invalid(err.to_string()))?;
+        let idp_key = RsaPublicKey::from_public_key_der(&public_key)
+            .map_err(|err| invalid(err.to_string()))?;
+
+        Ok(Self {
+            entity_id: public_url.to_string(),
+            acs_url: format!("{public_url}/login/saml/acs"),
+            idp_entity_id: config.idp_entity_id.clone(),
+            idp_sso_url: config.idp_sso_url.clone(),
+            idp_key,
+            min_level: config.min_level,
+        })
+    }
+
+    /// Start a login, asking for at least the configured authentication level.
+    pub fn authn_request(&self) -> Result<AuthnRequest, AppError> {
+        let request_id = random_id();
+        let relay_state = random_id();
+        let request = format!(
+            concat!(
+                r#"<samlp:AuthnRequest xmlns:samlp="{protocol}" xmlns:saml="{assertion}" "#,
+                r#"ID="{id}" Version="2.0" IssueInstant="{now}" Destination="{destination}" "#,
+                r#"AssertionConsumerServiceURL="{acs}" ProtocolBinding="{binding}">"#,
+                r#"<saml:Issuer>{issuer}</saml:Issuer>"#,
+                r#"<samlp:RequestedAuthnContext Comparison="minimum">"#,
+                r#"<saml:AuthnContextClassRef>{level}</saml:AuthnContextClassRef>"#,
+                r#"</samlp:RequestedAuthnContext></samlp:AuthnRequest>"#,
+            ),
+            protocol = PROTOCOL_NS,
+            assertion = ASSERTION_NS,
+            id = request_id,
+            now = now_timestamp(),
+            destination = escape_xml(&self.idp_sso_url),
+            acs = escape_xml(&self.acs_url),
+            binding = HTTP_POST_BINDING,
+            issuer = escape_xml(&self.entity_id),
+            level = self.min_level.digid_class(),
+        );
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(request.as_bytes())
+            .and_then(|_| encoder.finish())
+            .map(|deflated| {
+                let query = serde_urlencoded::to_string([
+                    ("SAMLRequest", STANDARD.encode(deflated)),
+                    ("RelayState", relay_state.clone()),
+                ])
+                .unwrap_or_default();
+
+                AuthnRequest {
+                    url: format!("{}?{query}", self.idp_sso_url),
+                    request_id,
+                    relay_state,
+                }
+            })
+            .map_err(|err| AppError::IdentityProviderError(err.to_string()))
+    }
+
+    /// Finish a login with the response the user returned with, which has to answer the
+    /// request with the given id.
+    pub fn validate_response(
+        &self,
+        saml_response: &str,
+        request_id: &str,
+    ) -> Result<SamlIdentity, AppError> {
+        let xml = STANDARD
+            .decode(saml_response.trim())
+            .ok()
+            .and_then(|xml| String::from_utf8(xml).ok())
+            .ok_or_else(|| AppError::AuthenticationFailed("invalid SAML response".to_string()))?;
+
+        self.validate_xml(&xml, request_id, Utc::now())
+            .map_err(AppError::AuthenticationFailed)
+    }
+
+    fn validate_xml(
+        &self,
+        xml: &str,
+        request_id: &str,
+        now: DateTime<Utc>,
+    ) -> Result<SamlIdentity, String> {
+        let doc = Document::parse(xml).map_err(|err| err.to_string())?;
+        let response = doc.root_element();
+        if !response.has_tag_name((PROTOCOL_NS, "Response")) {
+            return Err("not a SAML response".to_string());
+        }
+        if response
+            .attribute("Destination")
+            .is_some_and(|destination| destination != self.acs_url)
+        {
+            return Err("response is meant for another destination".to_string());
+        }
+
+        let status = child(
+            child(response, PROTOCOL_NS, "Status")?,
+            PROTOCOL_NS,
+            "StatusCode",
+        )?;
+        if status.attribute("Value") != Some(STATUS_SUCCESS) {
+            return Err(format!(
+                "identity provider returned {}",
+                status.attribute("Value").unwrap_or("no status")
+            ));
+        }
+
+        let mut assertions = response
+            .children()
+            .filter(|child| child.is_element() && child.tag_name().name().ends_with("Assertion"));
+        let (Some(assertion), None) = (assertions.next(), assertions.next()) else {
+            return Err("expected a single assertion".to_string());
+        };
+        if !assertion.has_tag_name((ASSERTION_NS, "Assertion")) {
+            return Err("encrypted assertions are not supported".to_string());
+        }
+
+        // everything below is read from the signed assertion only
+        xml_signature::verify_enveloped(assertion, &self.idp_key)?;
+
+        let issuer = text(child(assertion, ASSERTION_NS, "Issuer")?)?;
+        if issuer != self.idp_entity_id {
+            return Err("assertion from unknown issuer".to_string());
+        }
+
+        let skew = Duration::seconds(CLOCK_SKEW_SECONDS);
+        let conditions = child(assertion, ASSERTION_NS, "Conditions")?;
+        if timestamp(conditions, "NotBefore")?.is_some_and(|not_before| not_before > now + skew)
+            || timestamp(conditions, "NotOnOrAfter")?
+                .is_some_and(|not_after| not_after <= now - skew)
+        {
+            return Err("assertion is not valid at this time".to_string());
+        }
+        let mut audience = false;
+        for node in conditions
+            .children()
+            .filter(|child| child.has_tag_name((ASSERTION_NS, "AudienceRestriction")))
+            .flat_map(|restriction| restriction.children())
+            .filter(|child| child.has_tag_name((ASSERTION_NS, "Audience")))
+        {
+            audience |= text(node)? == self.entity_id;
+        }
+        if !audience {
+            return Err("assertion is meant for another audience".to_string());
+        }
+
+        let subject = child(assertion, ASSERTION_NS, "Subject")?;
+        let mut confirmed = false;
+        for confirmation in subject
+            .children()
+            .filter(|child| child.has_tag_name((ASSERTION_NS, "SubjectConfirmation")))
+            .filter(|confirmation| confirmation.attribute("Method") == Some(BEARER))
+        {
+            let Ok(data) = child(confirmation, ASSERTION_NS, "SubjectConfirmationData") else {
+                continue;
+            };
+            confirmed |= data.attribute("Recipient") == Some(&self.acs_url)
+                && data.attribute("InResponseTo") == Some(request_id)
+                && timestamp(data, "NotOnOrAfter")?.is_some_and(|not_after| not_after > now - skew);
+        }
+        if !confirmed {
+            return Err("assertion does not answer this login".to_string());
+        }
+
+        let identifier = Identifier::from_name_id(child(subject, ASSERTION_NS, "NameID")?)?;
+
+        let class = child(assertion, ASSERTION_NS, "AuthnStatement")
+            .and_then(|statement| child(statement, ASSERTION_NS, "AuthnContext"))
+            .and_then(|context| child(context, ASSERTION_NS, "AuthnContextClassRef"))?;
+        let level = AuthenticationLevel::from_class(text(class)?)
+            .ok_or_else(|| "unknown authentication level".to_string())?;
+        if level < self.min_level {
+            return Err(format!("authentication level {level} is too low"));
+        }
+
+        Ok(SamlIdentity {
+            issuer: issuer.to_string(),
+            identifier,
+            level,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{MockSamlProvider, mock_saml_provider::ReceivedRequest};
+
+    const IDP: &str = "http://localhost:8082";
+
+    fn setup(min_level: AuthenticationLevel) -> (SamlServiceProvider, MockSamlProvider) {
+        let config = SamlConfig {
+            idp_entity_id: IDP.to_string(),
+            idp_sso_url: format!("{IDP}/sso"),
+            idp_certificate: MockSamlProvider::CERTIFICATE.to_string(),
+            min_level,
+        };
+        let sp = SamlServiceProvider::new("http://localhost:3000", &config).unwrap();
+
+        (sp, MockSamlProvider::new(IDP))
+    }
+
+    /// Start a login and read the request the way the identity provider does.
+    fn start(sp: &SamlServiceProvider) -> (AuthnRequest, ReceivedRequest) {
+        let request = sp.authn_request().unwrap();
+        let (_, query) = request.url.split_once('?').unwrap();
+        let params: Vec<(String, String)> = serde_urlencoded::from_str(query).unwrap();
+        assert_eq!(
+            params[1],
+            ("RelayState".to_string(), request.relay_state.clone())
+        );
+
+        let received = ReceivedRequest::decode(&params[0].1).unwrap();
+        assert_eq!(received.id, request.request_id);
+        assert_eq!(received.acs_url, "http://localhost:3000/login/saml/acs");
+        assert_eq!(received.issuer, "http://localhost:3000");
+
+        (request, received)
+    }
+
+    #[test]
+    fn accepts_signed_assertions_of_the_identity_provider() {
+        let (sp, idp) = setup(AuthenticationLevel::Substantial);
+        let (request, received) = start(&sp);
+
+        let bsn = Identifier::Bsn(MockSamlProvider::DEFAULT_BSN.to_string());
+        let xml = idp.response_xml(&received, &bsn, AuthenticationLevel::Substantial);
+        let identity = sp
+            .validate_response(&STANDARD.encode(&xml), &request.request_id)
+            .unwrap();
+        assert_eq!(identity.issuer, IDP);
+        assert_eq!(identity.identifier, bsn);
+        assert_eq!(identity.identifier.subject(), "bsn:999990019");
+        assert_eq!(identity.identifier.display_name(), "BSN ******019");
+
+        let kvk = Identifier::Kvk(MockSamlProvider::DEFAULT_KVK.to_string());
+        let xml = idp.response_xml(&received, &kvk, AuthenticationLevel::High);
+        let identity = sp
+            .validate_xml(&xml, &request.request_id, Utc::now())
+            .unwrap();
+        assert_eq!(identity.identifier, kvk);
+        assert_eq!(identity.level, AuthenticationLevel::High);
+    }
+
+    #[test]
+    fn refuses_responses_not_answering_the_login() {
+        let (sp, idp) = setup(AuthenticationLevel::Substantial);
+        let (request, received) = start(&sp);
+        let bsn = Identifier::Bsn(MockSamlProvider::DEFAULT_BSN.to_string());
+        let xml = idp.response_xml(&received, &bsn, AuthenticationLevel::Substantial);
+
+        // another login
+        assert!(sp.validate_xml(&xml, "_other", Utc::now()).is_err());
+
+        // too late
+        let later = Utc::now() + Duration::hours(1);
+        assert!(sp.validate_xml(&xml, &request.request_id, later).is_err());
+
+        // insufficient level
+        let xml = idp.response_xml(&received, &bsn, AuthenticationLevel::Middle);
+        let err = sp
+            .validate_xml(&xml, &request.request_id, Utc::now())
+            .unwrap_err();
+        assert!(err.contains("too low"));
+
+        // cancelled
+        let xml = idp.failed_response_xml(&received);
+        let err = sp
+            .validate_xml(&xml, &request.request_id, Utc::now())
+            .unwrap_err();
+        assert!(err.contains("AuthnFailed"));
+    }
+
+    #[test]
+    fn refuses_tampered_or_foreign_assertions() {
+        let (sp, idp) = setup(AuthenticationLevel::Basic);
+        let (request, received) = start(&sp);
+        let bsn = Identifier::Bsn(MockSamlProvider::DEFAULT_BSN.to_string());
+        let xml = idp.response_xml(&received, &bsn, AuthenticationLevel::Substantial);
+
+        let tampered = xml.replace("999990019", "999990020");
+        assert_ne!(tampered, xml);
+        let err = sp
+            .validate_xml(&tampered, &request.request_id, Utc::now())
+            .unwrap_err();
+        assert!(err.contains("digest") || err.contains("signature"), "{err}");
+
+        // signed by the same key, but claiming another issuer
+        let other = MockSamlProvider::new("http://evil.example");
+        let xml = other.response_xml(&received, &bsn, AuthenticationLevel::Substantial);
+        assert!(
+            sp.validate_xml(&xml, &request.request_id, Utc::now())
+                .is_err()
+        );
+
+        // comments are not signed, so they could change how the identifier is read
+        let xml = idp.response_xml(&received, &bsn, AuthenticationLevel::Substantial);
+        let injected = xml.replace(":999990019<", ":999990<!---->019<");
+        assert_ne!(injected, xml);
+        let err = sp
+            .validate_xml(&injected, &request.request_id, Utc::now())
+            .unwrap_err();
+        assert!(err.contains("unexpected content in NameID"), "{err}");
+
+        let err = sp
+            .validate_response("not base64", &request.request_id)
+            .unwrap_err();
+        assert!(matches!(err, AppError::AuthenticationFailed(_)));
+    }
+}
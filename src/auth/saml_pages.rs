@@ -0,0 +1,114 @@
+//! Login with DigiD and eHerkenning, through a SAML identity provider.
+
+use axum::{
+    extract::{Query, State},
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::{
+    extract::{CookieJar, Form},
+    routing::TypedPath,
+};
+use chrono::{Duration, Utc};
+use serde::Deserialize;
+
+use crate::{
+    AppError, AppState, CurrentUser, DbConnection,
+    auth::{
+        Identity,
+        pages::{LOGIN_TTL_MINUTES, LoginQuery, local_path, sign_in},
+        repository::{self, SamlLoginAttempt},
+    },
+};
+
+#[derive(TypedPath)]
+#[typed_path("/login/saml", rejection(AppError))]
+pub struct SamlLoginPath;
+
+/// Where the identity provider posts its response to, the assertion consumer service.
+#[derive(TypedPath)]
+#[typed_path("/login/saml/acs", rejection(AppError))]
+pub struct SamlAcsPath;
+
+#[derive(Deserialize)]
+pub struct SamlResponseForm {
+    #[serde(rename = "SAMLResponse")]
+    saml_response: String,
+    #[serde(rename = "RelayState")]
+    relay_state: String,
+}
+
+/// Send the user to the SAML identity provider to sign in.
+pub async fn saml_login(
+    _: SamlLoginPath,
+    user: Option<CurrentUser>,
+    State(app_state): State<AppState>,
+    DbConnection(mut conn): DbConnection,
+    Query(query): Query<LoginQuery>,
+) -> Result<Response, AppError> {
+    let redirect_to = local_path(query.redirect);
+    if user.is_some() {
+        return Ok(Redirect::to(&redirect_to).into_response());
+    }
+
+    let Some(saml) = app_state.saml() else {
+        return Err(AppError::NotFound(
+            "SAML login is not configured".to_string(),
+        ));
+    };
+
+    let request = saml.authn_request()?;
+    repository::create_saml_login_attempt(
+        &mut conn,
+        &SamlLoginAttempt {
+            relay_state: request.relay_state,
+            request_id: request.request_id,
+            redirect_to,
+            expires_at: Utc::now() + Duration::minutes(LOGIN_TTL_MINUTES),
+        },
+    )
+    .await?;
+
+    Ok(Redirect::to(&request.url).into_response())
+}
+
+/// The identity provider posts the signed response here after signing in.
+///
+/// Browsers do not send lax cookies along with this cross-site post, so the login can not be
+/// tied to a cookie as with OpenID Connect. Instead, the response has to answer a request that
+/// is only accepted once and expires with the login attempt.
+pub async fn saml_acs(
+    _: SamlAcsPath,
+    State(app_state): State<AppState>,
+    DbConnection(mut conn): DbConnection,
+    jar: CookieJar,
+    Form(form): Form<SamlResponseForm>,
+) -> Result<Response, AppError> {
+    let Some(saml) = app_state.saml() else {
+        return Err(AppError::NotFound(
+            "SAML login is not configured".to_string(),
+        ));
+    };
+
+    let attempt = repository::take_saml_login_attempt(&mut conn, &form.relay_state)
+        .await?
+        .ok_or_else(|| AppError::AuthenticationFailed("unknown or expired login".to_string()))?;
+
+    let identity = saml.validate_response(&form.saml_response, &attempt.request_id)?;
+    let identity = Identity {
+        issuer: identity.issuer,
+        subject: identity.identifier.subject(),
+        name: identity.identifier.display_name(),
+        email: None,
+        id_token: String::new(),
+    };
+
+    sign_in(
+        &app_state,
+        &mut conn,
+        jar,
+        &identity,
+        None,
+        &attempt.redirect_to,
+    )
+    .await
+}
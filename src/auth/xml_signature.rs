@@ -0,0 +1,382 @@
+//! Enveloped XML signatures, as used on SAML assertions: exclusive canonicalization
+//! (without comments) and RSA-SHA256 signatures over SHA-256 digests.
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use roxmltree::{Node, NodeId};
+use rsa::{
+    RsaPublicKey,
+    pkcs1v15::{Signature, VerifyingKey},
+    signature::Verifier,
+};
+use sha2::{Digest, Sha256};
+
+pub const DSIG_NS: &str = "http://www.w3.org/2000/09/xmldsig#";
+const EXC_C14N: &str = "http://www.w3.org/2001/10/xml-exc-c14n#";
+const ENVELOPED_SIGNATURE: &str = "http://www.w3.org/2000/09/xmldsig#enveloped-signature";
+const RSA_SHA256: &str = "http://www.w3.org/2001/04/xmldsig-more#rsa-sha256";
+const SHA256: &str = "http://www.w3.org/2001/04/xmlenc#sha256";
+
+/// Qualified name of an element or attribute as written in the document.
+fn prefix_of(qname: &str) -> &str {
+    qname.split_once(':').map_or("", |(prefix, _)| prefix)
+}
+
+fn element_qname<'a>(node: Node<'a, '_>) -> &'a str {
+    let input = node.document().input_text();
+    input[node.range().start + 1..]
+        .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .next()
+        .unwrap_or_default()
+}
+
+fn escape_text(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '\r' => out.push_str("&#xD;"),
+            c => out.push(c),
+        }
+    }
+}
+
+fn escape_attribute(value: &str, out: &mut String) {
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '"' => out.push_str("&quot;"),
+            '\t' => out.push_str("&#x9;"),
+            '\n' => out.push_str("&#xA;"),
+            '\r' => out.push_str("&#xD;"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// Exclusive XML canonicalization of an element, leaving out the `excluded` element (the
+/// enveloped signature). Prefixes in `inclusive_prefixes` are treated as by inclusive
+/// canonicalization, as the `InclusiveNamespaces` of a transform ask for.
+pub fn canonicalize(
+    element: Node,
+    excluded: Option<NodeId>,
+    inclusive_prefixes: &[&str],
+) -> String {
+    let mut out = String::new();
+    write_element(
+        element,
+        excluded,
+        inclusive_prefixes,
+        &mut Vec::new(),
+        &mut out,
+    );
+    out
+}
+
+fn write_element(
+    node: Node,
+    excluded: Option<NodeId>,
+    inclusive_prefixes: &[&str],
+    rendered: &mut Vec<(String, String)>,
+    out: &mut String,
+) {
+    let input = node.document().input_text();
+    let qname = element_qname(node);
+
+    // namespaces are only rendered where they are used, and not already in scope
+    let mut prefixes = vec![prefix_of(qname)];
+    for attribute in node.attributes() {
+        let prefix = prefix_of(&input[attribute.range_qname()]);
+        if !prefix.is_empty() {
+            prefixes.push(prefix);
+        }
+    }
+    for prefix in inclusive_prefixes {
+        let prefix = if *prefix == "#default" { "" } else { prefix };
+        if node
+            .lookup_namespace_uri((!prefix.is_empty()).then_some(prefix))
+            .is_some()
+        {
+            prefixes.push(prefix);
+        }
+    }
+    prefixes.sort_unstable();
+    prefixes.dedup();
+
+    let depth = rendered.len();
+    let mut declarations = Vec::new();
+    for prefix in prefixes {
+        if prefix == "xml" {
+            continue;
+        }
+        let uri = node
+            .lookup_namespace_uri((!prefix.is_empty()).then_some(prefix))
+            .unwrap_or_default();
+        let in_scope = rendered
+            .iter()
+            .rev()
+            .find(|(rendered_prefix, _)| rendered_prefix == prefix)
+            .map_or("", |(_, uri)| uri.as_str());
+        if in_scope != uri {
+            declarations.push((prefix, uri));
+            rendered.push((prefix.to_string(), uri.to_string()));
+        }
+    }
+
+    let mut attributes: Vec<_> = node
+        .attributes()
+        .map(|attribute| {
+            (
+                attribute.namespace().unwrap_or_default(),
+                attribute.name(),
+                &input[attribute.range_qname()],
+                attribute.value(),
+            )
+        })
+        .collect();
+    attributes.sort_unstable_by_key(|(namespace, name, _, _)| (*namespace, *name));
+
+    out.push('<');
+    out.push_str(qname);
+    for (prefix, uri) in declarations {
+        out.push_str(" xmlns");
+        if !prefix.is_empty() {
+            out.push(':');
+            out.push_str(prefix);
+        }
+        out.push_str("=\"");
+        escape_attribute(uri, out);
+        out.push('"');
+    }
+    for (_, _, name, value) in attributes {
+        out.push(' ');
+        out.push_str(name);
+        out.push_str("=\"");
+        escape_attribute(value, out);
+        out.push('"');
+    }
+    out.push('>');
+
+    for child in node.children() {
+        if Some(child.id()) == excluded {
+            continue;
+        }
+        if child.is_element() {
+            write_element(child, excluded, inclusive_prefixes, rendered, out);
+        } else if child.is_text() {
+            escape_text(child.text().unwrap_or_default(), out);
+        } else if let Some(pi) = child.pi() {
+            out.push_str("<?");
+            out.push_str(pi.target);
+            if let Some(value) = pi.value {
+                out.push(' ');
+                out.push_str(value);
+            }
+            out.push_str("?>");
+        }
+    }
+
+    out.push_str("</");
+    out.push_str(qname);
+    out.push('>');
+    rendered.truncate(depth);
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Result<Node<'a, 'input>, String> {
+    node.children()
+        .find(|child| child.has_tag_name((DSIG_NS, name)))
+        .ok_or_else(|| format!("missing {name} in signature"))
+}
+
+fn algorithm<'a>(node: Node<'a, '_>) -> &'a str {
+    node.attribute("Algorithm").unwrap_or_default()
+}
+
+/// Prefixes of the `InclusiveNamespaces` of a canonicalization method or transform.
+fn inclusive_prefixes<'a>(node: Node<'a, '_>) -> Vec<&'a str> {
+    node.children()
+        .find(|child| child.has_tag_name((EXC_C14N, "InclusiveNamespaces")))
+        .and_then(|child| child.attribute("PrefixList"))
+        .map(|list| list.split_whitespace().collect())
+        .unwrap_or_default()
+}
+
+fn decode_base64(value: &str) -> Result<Vec<u8>, String> {
+    let value: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+    STANDARD
+        .decode(value)
+        .map_err(|_| "invalid base64 in signature".to_string())
+}
+
+/// Verify the enveloped signature of an element, which has to cover exactly that element.
+pub fn verify_enveloped(element: Node, key: &RsaPublicKey) -> Result<(), String> {
+    let signature = child(element, "Signature").map_err(|_| "element is not signed")?;
+    let signed_info = child(signature, "SignedInfo")?;
+
+    // the signed element has to be the only one with its id, or the signature could be
+    // moved to another element than the one being read
+    let id = element.attribute("ID").ok_or("signed element has no ID")?;
+    let same_id = element
+        .document()
+        .descendants()
+        .filter(|node| node.attribute("ID") == Some(id))
+        .count();
+    if same_id != 1 {
+        return Err("duplicate ID in document".to_string());
+    }
+
+    let canonicalization = child(signed_info, "CanonicalizationMethod")?;
+    if algorithm(canonicalization) != EXC_C14N {
+        return Err("unsupported canonicalization method".to_string());
+    }
+    if algorithm(child(signed_info, "SignatureMethod")?) != RSA_SHA256 {
+        return Err("unsupported signature method".to_string());
+    }
+
+    let mut references = signed_info
+        .children()
+        .filter(|child| child.has_tag_name((DSIG_NS, "Reference")));
+    let (Some(reference), None) = (references.next(), references.next()) else {
+        return Err("expected a single reference".to_string());
+    };
+    if reference.attribute("URI") != Some(&format!("#{id}")) {
+        return Err("signature does not reference the signed element".to_string());
+    }
+
+    let mut enveloped = false;
+    let mut prefixes = Vec::new();
+    for transform in child(reference, "Transforms")?
+        .children()
+        .filter(|child| child.has_tag_name((DSIG_NS, "Transform")))
+    {
+        match algorithm(transform) {
+            ENVELOPED_SIGNATURE => enveloped = true,
+            EXC_C14N => prefixes = inclusive_prefixes(transform),
+            other => return Err(format!("unsupported transform {other}")),
+        }
+    }
+    if !enveloped {
+        return Err("signature is not enveloped".to_string());
+    }
+    if algorithm(child(reference, "DigestMethod")?) != SHA256 {
+        return Err("unsupported digest method".to_string());
+    }
+
+    let digest = Sha256::digest(canonicalize(element, Some(signature.id()), &prefixes));
+    let expected = decode_base64(child(reference, "DigestValue")?.text().unwrap_or_default())?;
+    if digest.as_slice() != expected {
+        return Err("digest mismatch".to_string());
+    }
+
+    let signature_value = decode_base64(
+        child(signature, "SignatureValue")?
+            .text()
+            .unwrap_or_default(),
+    )?;
+    let signature_value = Signature::try_from(signature_value.as_slice())
+        .map_err(|_| "invalid signature value".to_string())?;
+    let signed = canonicalize(signed_info, None, &inclusive_prefixes(canonicalization));
+
+    VerifyingKey::<Sha256>::new(key.clone())
+        .verify(signed.as_bytes(), &signature_value)
+        .map_err(|_| "invalid signature".to_string())
+}
+
+/// Create the enveloped signature for an element that is not signed yet; it has to be
+/// inserted as a child of the element to be valid.
+#[cfg(any(test, feature = "dev-features"))]
+pub fn sign_enveloped(element: Node, key: &rsa::RsaPrivateKey) -> String {
+    use rsa::{
+        pkcs1v15::SigningKey,
+        signature::{SignatureEncoding, Signer},
+    };
+
+    let id = element.attribute("ID").unwrap_or_default();
+    let digest = STANDARD.encode(Sha256::digest(canonicalize(element, None, &[])));
+    let signed_info = format!(
+        concat!(
+            r#"<ds:SignedInfo xmlns:ds="{DSIG_NS}">"#,
+            r#"<ds:CanonicalizationMethod Algorithm="{EXC_C14N}"></ds:CanonicalizationMethod>"#,
+            r#"<ds:SignatureMethod Algorithm="{RSA_SHA256}"></ds:SignatureMethod>"#,
+            r##"<ds:Reference URI="#{id}"><ds:Transforms>"##,
+            r#"<ds:Transform Algorithm="{ENVELOPED_SIGNATURE}"></ds:Transform>"#,
+            r#"<ds:Transform Algorithm="{EXC_C14N}"></ds:Transform>"#,
+            r#"</ds:Transforms><ds:DigestMethod Algorithm="{SHA256}"></ds:DigestMethod>"#,
+            r#"<ds:DigestValue>{digest}</ds:DigestValue></ds:Reference></ds:SignedInfo>"#,
+        ),
+        DSIG_NS = DSIG_NS,
+        EXC_C14N = EXC_C14N,
+        RSA_SHA256 = RSA_SHA256,
+        ENVELOPED_SIGNATURE = ENVELOPED_SIGNATURE,
+        SHA256 = SHA256,
+        id = id,
+        digest = digest,
+    );
+
+    // written in canonical form, so it can be signed as is
+    let signature = SigningKey::<Sha256>::new(key.clone()).sign(signed_info.as_bytes());
+    let signed_info = signed_info.replacen(&format!(r#" xmlns:ds="{DSIG_NS}""#), "", 1);
+
+    format!(
+        r#"<ds:Signature xmlns:ds="{DSIG_NS}">{signed_info}<ds:SignatureValue>{}</ds:SignatureValue></ds:Signature>"#,
+        STANDARD.encode(signature.to_vec()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use roxmltree::Document;
+    use rsa::{RsaPrivateKey, pkcs1::DecodeRsaPrivateKey};
+
+    use super::*;
+
+    const KEY: &str = include_str!("mock_saml_provider_key.pem");
+
+    #[test]
+    fn canonicalizes_namespaces_attributes_and_text() {
+        let xml = r#"<a:root xmlns:a="urn:a" xmlns:b="urn:b" xmlns:unused="urn:unused">
+            <a:child z="1" b:y='2' a="&amp;&quot;"><![CDATA[<x>]]><!-- comment --></a:child>
+            <b:other/>
+        </a:root>"#;
+        let doc = Document::parse(xml).unwrap();
+        let child = doc.root_element().first_element_child().unwrap();
+
+        assert_eq!(
+            canonicalize(child, None, &[]),
+            r#"<a:child xmlns:a="urn:a" xmlns:b="urn:b" a="&amp;&quot;" z="1" b:y="2">&lt;x&gt;</a:child>"#
+        );
+        assert!(
+            canonicalize(doc.root_element(), None, &[])
+                .contains(r#"<b:other xmlns:b="urn:b"></b:other>"#)
+        );
+        assert!(canonicalize(child, None, &["unused"]).contains(r#"xmlns:unused="urn:unused""#));
+    }
+
+    #[test]
+    fn verifies_signatures_it_created() {
+        let key = RsaPrivateKey::from_pkcs1_pem(KEY).unwrap();
+        let xml = r#"<r:Root xmlns:r="urn:r"><r:Signed ID="_1"><r:Name>Jansen</r:Name></r:Signed></r:Root>"#;
+        let doc = Document::parse(xml).unwrap();
+        let signed = doc.root_element().first_element_child().unwrap();
+        let signature = sign_enveloped(signed, &key);
+
+        let xml = xml.replace(r#"ID="_1">"#, &format!(r#"ID="_1">{signature}"#));
+        let doc = Document::parse(&xml).unwrap();
+        let signed = doc.root_element().first_element_child().unwrap();
+        assert_eq!(verify_enveloped(signed, &key.to_public_key()), Ok(()));
+
+        let tampered = xml.replace("Jansen", "Bakker");
+        let doc = Document::parse(&tampered).unwrap();
+        let signed = doc.root_element().first_element_child().unwrap();
+        assert_eq!(
+            verify_enveloped(signed, &key.to_public_key()),
+            Err("digest mismatch".to_string())
+        );
+
+        let duplicated = xml.replace("</r:Root>", r#"<r:Signed ID="_1"></r:Signed></r:Root>"#);
+        let doc = Document::parse(&duplicated).unwrap();
+        let signed = doc.root_element().first_element_child().unwrap();
+        assert!(verify_enveloped(signed, &key.to_public_key()).is_err());
+    }
+}
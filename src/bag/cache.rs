@@ -0,0 +1,151 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// Cache holding at most `capacity` entries, evicting the least recently used entry first.
+/// Entries are kept in a doubly linked list by index, so looking up, inserting and evicting
+/// take constant time.
+pub struct LruCache<K, V> {
+    capacity: usize,
+    /// Index of the node of each key.
+    index: HashMap<K, usize>,
+    nodes: Vec<Node<K, V>>,
+    /// Least recently used node.
+    oldest: Option<usize>,
+    /// Most recently used node.
+    newest: Option<usize>,
+}
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    older: Option<usize>,
+    newer: Option<usize>,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> LruCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            index: HashMap::with_capacity(capacity),
+            nodes: Vec::with_capacity(capacity),
+            oldest: None,
+            newest: None,
+        }
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        let node = *self.index.get(key)?;
+        self.touch(node);
+
+        Some(self.nodes[node].value.clone())
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if let Some(&node) = self.index.get(&key) {
+            self.nodes[node].value = value;
+            self.touch(node);
+            return;
+        }
+
+        let node = if self.nodes.len() < self.capacity {
+            self.nodes.push(Node {
+                key: key.clone(),
+                value,
+                older: None,
+                newer: None,
+            });
+            self.nodes.len() - 1
+        } else {
+            // reuse the node of the least recently used entry
+            let Some(oldest) = self.oldest else {
+                return;
+            };
+            self.unlink(oldest);
+            let node = &mut self.nodes[oldest];
+            self.index.remove(&node.key);
+            node.key = key.clone();
+            node.value = value;
+            oldest
+        };
+        self.index.insert(key, node);
+        self.push_newest(node);
+    }
+
+    /// Mark the node as most recently used.
+    fn touch(&mut self, node: usize) {
+        if self.newest != Some(node) {
+            self.unlink(node);
+            self.push_newest(node);
+        }
+    }
+
+    fn unlink(&mut self, node: usize) {
+        let Node { older, newer, .. } = self.nodes[node];
+        match older {
+            Some(older) => self.nodes[older].newer = newer,
+            None => self.oldest = newer,
+        }
+        match newer {
+            Some(newer) => self.nodes[newer].older = older,
+            None => self.newest = older,
+        }
+    }
+
+    fn push_newest(&mut self, node: usize) {
+        self.nodes[node].older = self.newest;
+        self.nodes[node].newer = None;
+        match self.newest {
+            Some(newest) => self.nodes[newest].newer = Some(node),
+            None => self.oldest = Some(node),
+        }
+        self.newest = Some(node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_the_least_recently_used_entry() {
+        let mut cache = LruCache::new(2);
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        assert_eq!(cache.get(&"a"), Some(1));
+
+        cache.insert("c", 3);
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.get(&"a"), Some(1));
+        assert_eq!(cache.get(&"c"), Some(3));
+
+        cache.insert("c", 4);
+        cache.insert("d", 5);
+        assert_eq!(cache.get(&"a"), None);
+        assert_eq!(cache.get(&"c"), Some(4));
+    }
+
+    #[test]
+    fn keeps_at_most_capacity_entries() {
+        let mut cache = LruCache::new(3);
+        for i in 0..100 {
+            cache.insert(i, i * 2);
+            if i % 2 == 0 {
+                assert_eq!(cache.get(&i), Some(i * 2));
+            }
+        }
+
+        assert_eq!(cache.nodes.len(), 3);
+        assert_eq!(cache.index.len(), 3);
+        assert_eq!(cache.get(&96), None);
+        assert_eq!(cache.get(&97), Some(194));
+        assert_eq!(cache.get(&98), Some(196));
+        assert_eq!(cache.get(&99), Some(198));
+
+        let mut empty = LruCache::new(0);
+        empty.insert("a", 1);
+        assert_eq!(empty.get(&"a"), None);
+    }
+}
@@ -0,0 +1,255 @@
+//! Client for the BAG address service (Basisregistratie Adressen en Gebouwen), to look up
+//! and check Dutch addresses by postal code and house number.
+
+use std::{
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use axum::{
+    body::{Body, to_bytes},
+    http::{Request, StatusCode, header},
+};
+use hyper_util::{
+    client::legacy::{Client, connect::HttpConnector},
+    rt::TokioExecutor,
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::AppError;
+
+mod cache;
+mod pages;
+
+use cache::LruCache;
+pub use pages::router;
+
+/// Time a request to the BAG service may take, including connecting.
+const BAG_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Number of looked up addresses kept in memory.
+const BAG_CACHE_SIZE: usize = 4096;
+
+/// Largest response body accepted from the BAG service.
+const BAG_MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+
+/// An address as registered in the BAG. Serialized with the field names of the BAG service.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BagAddress {
+    #[serde(rename = "pr")]
+    pub street_name: String,
+    #[serde(rename = "wp")]
+    pub locality: String,
+}
+
+/// Looked up addresses by postal code and house number. Unknown addresses are not cached, as
+/// they may be registered at any moment.
+type AddressCache = LruCache<(String, String), BagAddress>;
+
+#[derive(Clone)]
+pub struct BagClient {
+    base_url: String,
+    client: Client<HttpConnector, Body>,
+    cache: Arc<Mutex<AddressCache>>,
+}
+
+/// Postal code as the BAG service expects it, e.g. "1234AB".
+fn compact_postal_code(postal_code: &str) -> String {
+    postal_code
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+impl BagClient {
+    pub fn new(base_url: &str) -> Self {
+        let mut connector = HttpConnector::new();
+        connector.set_connect_timeout(Some(BAG_TIMEOUT));
+
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client: Client::builder(TokioExecutor::new()).build(connector),
+            cache: Arc::new(Mutex::new(LruCache::new(BAG_CACHE_SIZE))),
+        }
+    }
+
+    /// Find the address with the given postal code and house number, `None` when the BAG does
+    /// not know it. Known addresses are cached.
+    pub async fn lookup(
+        &self,
+        postal_code: &str,
+        house_number: &str,
+    ) -> Result<Option<BagAddress>, AppError> {
+        let key = (
+            compact_postal_code(postal_code),
+            house_number.trim().to_string(),
+        );
+        if let Some(address) = self.cache_lock()?.get(&key) {
+            return Ok(Some(address));
+        }
+
+        let query = serde_urlencoded::to_string([("pc", &key.0), ("n", &key.1)])
+            .map_err(|err| AppError::UpstreamError(err.to_string()))?;
+        let address: Option<BagAddress> = self.get_json(&format!("/lookup?{query}")).await?;
+        if let Some(address) = &address {
+            self.cache_lock()?.insert(key, address.clone());
+        }
+
+        Ok(address)
+    }
+
+    /// Localities starting with the given text.
+    pub async fn suggest_localities(&self, text: &str) -> Result<Vec<String>, AppError> {
+        let query = serde_urlencoded::to_string([("wp", text)])
+            .map_err(|err| AppError::UpstreamError(err.to_string()))?;
+
+        Ok(self
+            .get_json(&format!("/suggest?{query}"))
+            .await?
+            .unwrap_or_default())
+    }
+
+    fn cache_lock(&self) -> Result<MutexGuard<'_, AddressCache>, AppError> {
+        self.cache
+            .lock()
+            .map_err(|_| AppError::UpstreamError("BAG cache is poisoned".to_string()))
+    }
+
+    /// Get and decode a JSON response, `None` when the BAG service responds with not found.
+    async fn get_json<T: DeserializeOwned>(&self, path_query: &str) -> Result<Option<T>, AppError> {
+        let request = Request::get(format!("{}{path_query}", self.base_url))
+            .header(header::ACCEPT, "application/json")
+            .body(Body::empty())
+            .map_err(|err| AppError::UpstreamError(err.to_string()))?;
+
+        let body = tokio::time::timeout(BAG_TIMEOUT, async {
+            let response = self.client.request(request).await.map_err(|err| {
+                AppError::UpstreamError(format!("BAG service unreachable: {err}"))
+            })?;
+
+            match response.status() {
+                StatusCode::NOT_FOUND => Ok(None),
+                status if status.is_success() => {
+                    to_bytes(Body::new(response.into_body()), BAG_MAX_RESPONSE_BYTES)
+                        .await
+                        .map(Some)
+                        .map_err(|err| AppError::UpstreamError(err.to_string()))
+                }
+                status => Err(AppError::UpstreamError(format!(
+                    "BAG service responded with {status}"
+                ))),
+            }
+        })
+        .await
+        .map_err(|_| AppError::UpstreamError("BAG service timed out".to_string()))??;
+
+        body.map(|body| {
+            serde_json::from_slice(&body)
+                .map_err(|err| AppError::UpstreamError(format!("invalid BAG response: {err}")))
+        })
+        .transpose()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use axum::{
+        Json, Router,
+        extract::Query,
+        http::StatusCode,
+        response::{IntoResponse, Response},
+        routing::get,
+    };
+    use std::{
+        collections::HashMap,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+    use tokio::net::TcpListener;
+
+    /// Serve a BAG service knowing only "1234AB 10" in Juinen, returning its base url and the
+    /// number of lookups it answered.
+    pub async fn start_bag_service() -> (String, Arc<AtomicUsize>) {
+        let lookups = Arc::new(AtomicUsize::new(0));
+        let counter = lookups.clone();
+        let router = Router::new()
+            .route(
+                "/lookup",
+                get(move |Query(query): Query<HashMap<String, String>>| {
+                    let counter = counter.clone();
+                    async move {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        if query.get("pc").map(String::as_str) == Some("1234AB")
+                            && query.get("n").map(String::as_str) == Some("10")
+                        {
+                            Json(BagAddress {
+                                street_name: "Stationsstraat".to_string(),
+                                locality: "Juinen".to_string(),
+                            })
+                            .into_response()
+                        } else {
+                            StatusCode::NOT_FOUND.into_response()
+                        }
+                    }
+                }),
+            )
+            .route(
+                "/suggest",
+                get(|| async { Json(vec!["Juinen".to_string()]) }),
+            )
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(BAG_TIMEOUT * 2).await;
+                    Response::new(Body::empty())
+                }),
+            );
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.unwrap();
+        });
+
+        (format!("http://{addr}"), lookups)
+    }
+
+    #[tokio::test]
+    async fn looks_up_addresses_once() {
+        let (url, lookups) = start_bag_service().await;
+        let bag = BagClient::new(&url);
+
+        let address = bag.lookup("1234 ab", "10").await.unwrap();
+        assert_eq!(
+            address,
+            Some(BagAddress {
+                street_name: "Stationsstraat".to_string(),
+                locality: "Juinen".to_string(),
+            })
+        );
+        assert_eq!(bag.lookup("1234AB", "10").await.unwrap(), address);
+        assert_eq!(lookups.load(Ordering::SeqCst), 1);
+
+        assert_eq!(bag.lookup("1234 AB", "11").await.unwrap(), None);
+        assert_eq!(bag.lookup("1234 AB", "11").await.unwrap(), None);
+        assert_eq!(lookups.load(Ordering::SeqCst), 3);
+        assert_eq!(
+            bag.suggest_localities("Jui").await.unwrap(),
+            vec!["Juinen".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn fails_on_unavailable_service() {
+        let (url, _) = start_bag_service().await;
+
+        let result = BagClient::new(&url).get_json::<BagAddress>("/slow").await;
+        assert!(matches!(result, Err(AppError::UpstreamError(_))));
+
+        let result = BagClient::new("http://127.0.0.1:0")
+            .lookup("1234 AB", "10")
+            .await;
+        assert!(matches!(result, Err(AppError::UpstreamError(_))));
+    }
+}
@@ -0,0 +1,105 @@
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+};
+use axum_extra::routing::{RouterExt, TypedPath};
+use serde::Deserialize;
+
+use crate::{AppError, AppState, Context, bag::BagAddress, t};
+
+#[derive(TypedPath)]
+#[typed_path("/lookup", rejection(AppError))]
+pub struct BagLookupPath;
+
+#[derive(TypedPath)]
+#[typed_path("/suggest", rejection(AppError))]
+pub struct BagSuggestPath;
+
+#[derive(Deserialize)]
+pub struct LookupQuery {
+    pc: String,
+    n: String,
+}
+
+#[derive(Deserialize)]
+pub struct SuggestQuery {
+    wp: String,
+}
+
+/// Street and locality of a postal code and house number, used to fill in address forms.
+pub async fn lookup_address(
+    _: BagLookupPath,
+    context: Context,
+    State(app_state): State<AppState>,
+    Query(query): Query<LookupQuery>,
+) -> Result<Json<BagAddress>, AppError> {
+    app_state
+        .bag()
+        .lookup(&query.pc, &query.n)
+        .await?
+        .map(Json)
+        .ok_or(AppError::NotFound(t!(
+            "validation.unknown_address",
+            &context.locale
+        )))
+}
+
+pub async fn suggest_localities(
+    _: BagSuggestPath,
+    State(app_state): State<AppState>,
+    Query(query): Query<SuggestQuery>,
+) -> Result<Json<Vec<String>>, AppError> {
+    Ok(Json(app_state.bag().suggest_localities(&query.wp).await?))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .typed_get(lookup_address)
+        .typed_get(suggest_localities)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use crate::{bag::tests::start_bag_service, test_utils::response_body_string};
+
+    #[sqlx::test]
+    async fn looks_up_addresses_through_the_bag_service(pool: PgPool) -> Result<(), sqlx::Error> {
+        let (url, _) = start_bag_service().await;
+        let app = router().with_state(AppState::new_for_tests(pool).with_bag_service(&url));
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/lookup?pc=1234AB&n=10")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        assert_eq!(body, r#"{"pr":"Stationsstraat","wp":"Juinen"}"#);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/lookup?pc=1234AB&n=11")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
+}
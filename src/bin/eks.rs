@@ -47,7 +47,7 @@ async fn run(listener: TcpListener) -> Result<(), AppError> {
     }
 
     // Start the server
-    server::serve(router::create(), state, listener).await?;
+    server::serve(router::create(&state), state, listener).await?;
 
     Ok(())
 }
@@ -0,0 +1,39 @@
+use eks::{Config, MockProvider, logging};
+use tokio::net::TcpListener;
+
+/// Serves the mock identity provider for the configured issuer.
+async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    // Initialize tracing subscriber (logging)
+    logging::init();
+
+    let config = Config::from_env()?;
+    let issuer_url = openidconnect::url::Url::parse(&config.oidc.issuer_url)?;
+
+    // first argument is the address to bind to, defaults to the address of the issuer
+    let addr = match std::env::args()
+        .nth(1)
+        .or(std::env::var("BIND_ADDRESS").ok())
+    {
+        Some(addr) => addr,
+        None => format!(
+            "{}:{}",
+            issuer_url.host_str().unwrap_or("127.0.0.1"),
+            issuer_url.port_or_known_default().unwrap_or(80)
+        ),
+    };
+
+    let listener = TcpListener::bind(&addr).await?;
+    tracing::info!("Starting mock OpenID Connect provider on {addr}");
+
+    axum::serve(listener, MockProvider::new(&config.oidc).router()).await?;
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(err) = run().await {
+        eprintln!("Error running mock OpenID Connect provider: {:?}", err);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,55 @@
+use eks::{AuthenticationLevel, Config, MockSamlProvider, logging};
+use tokio::net::TcpListener;
+
+/// Serves the mock SAML identity provider standing in for DigiD and eHerkenning.
+///
+/// The identifiers and authentication level it suggests can be changed with `MOCK_SAML_BSN`,
+/// `MOCK_SAML_KVK` and `MOCK_SAML_LEVEL`, and on its login page.
+async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    // Initialize tracing subscriber (logging)
+    logging::init();
+
+    let config = Config::from_env()?;
+    let saml = config
+        .saml
+        .ok_or("SAML_IDP_CERTIFICATE is not configured")?;
+    let sso_url = openidconnect::url::Url::parse(&saml.idp_sso_url)?;
+
+    let bsn = std::env::var("MOCK_SAML_BSN")
+        .unwrap_or_else(|_| MockSamlProvider::DEFAULT_BSN.to_string());
+    let kvk = std::env::var("MOCK_SAML_KVK")
+        .unwrap_or_else(|_| MockSamlProvider::DEFAULT_KVK.to_string());
+    let level = match std::env::var("MOCK_SAML_LEVEL") {
+        Ok(level) => level.parse()?,
+        Err(_) => AuthenticationLevel::Substantial,
+    };
+
+    // first argument is the address to bind to, defaults to the address of the login page
+    let addr = match std::env::args()
+        .nth(1)
+        .or(std::env::var("BIND_ADDRESS").ok())
+    {
+        Some(addr) => addr,
+        None => format!(
+            "{}:{}",
+            sso_url.host_str().unwrap_or("127.0.0.1"),
+            sso_url.port_or_known_default().unwrap_or(80)
+        ),
+    };
+
+    let listener = TcpListener::bind(&addr).await?;
+    tracing::info!("Starting mock SAML identity provider on {addr}");
+
+    let provider = MockSamlProvider::new(&saml.idp_entity_id).with_defaults(bsn, kvk, level);
+    axum::serve(listener, provider.router()).await?;
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(err) = run().await {
+        eprintln!("Error running mock SAML identity provider: {:?}", err);
+        std::process::exit(1);
+    }
+}
@@ -5,27 +5,32 @@ use serde::Deserialize;
 use uuid::Uuid;
 
 use crate::{
-    AppError, Context, DbConnection, HtmlTemplate,
+    AppError, Context, CsrfTokens, DbConnection, HtmlTemplate,
     candidate_lists::{
-        self, CandidateList, FullCandidateList, MAX_CANDIDATES,
+        self, BulkAddCandidatesForm, CandidateList, FullCandidateList, MAX_CANDIDATES,
+        PositionOperation,
         pages::{AddCandidatePath, load_candidate_list},
     },
     filters,
+    form::FormData,
     persons::{self, Person},
     t,
 };
 
 #[derive(Template)]
 #[template(path = "candidates/add_existing.html")]
-struct AddExistingPersonTemplate {
-    full_list: FullCandidateList,
-    persons: Vec<Person>,
-    max_candidates: usize,
+pub(super) struct AddExistingPersonTemplate {
+    pub(super) full_list: FullCandidateList,
+    pub(super) persons: Vec<Person>,
+    pub(super) max_candidates: usize,
+    /// Form to add several selected persons at once.
+    pub(super) form: FormData<BulkAddCandidatesForm>,
 }
 
 pub async fn add_existing_person(
     AddCandidatePath { id }: AddCandidatePath,
     context: Context,
+    csrf_tokens: CsrfTokens,
     DbConnection(mut conn): DbConnection,
 ) -> Result<impl IntoResponse, AppError> {
     let full_list: FullCandidateList = load_candidate_list(&mut conn, &id, context.locale).await?;
@@ -36,6 +41,7 @@ pub async fn add_existing_person(
             full_list,
             persons,
             max_candidates: MAX_CANDIDATES,
+            form: FormData::new(&csrf_tokens),
         },
         context,
     ))
@@ -59,9 +65,13 @@ pub async fn add_person_to_candidate_list(
         return Ok(redirect);
     }
 
-    let mut person_ids = full_list.get_ids();
-    person_ids.push(form.person_id);
-    candidate_lists::repository::update_candidate_list_order(&mut conn, &id, &person_ids).await?;
+    let operation = PositionOperation::InsertAt {
+        person_id: form.person_id,
+        position: full_list.candidates.len() + 1,
+    };
+    candidate_lists::repository::apply_position_operation(&mut conn, &id, operation)
+        .await
+        .map_err(|e| e.into_app_error(context.locale))?;
 
     Ok(redirect)
 }
@@ -78,7 +88,7 @@ mod tests {
     use uuid::Uuid;
 
     use crate::{
-        Context, DbConnection, Locale, candidate_lists, persons,
+        Context, CsrfTokens, DbConnection, Locale, candidate_lists, persons,
         test_utils::{
             response_body_string, sample_candidate_list, sample_person,
             sample_person_with_last_name,
@@ -98,6 +108,7 @@ mod tests {
         let response = add_existing_person(
             AddCandidatePath { id: list_id },
             Context::new(Locale::En),
+            CsrfTokens::default(),
             DbConnection(pool.acquire().await?),
         )
         .await
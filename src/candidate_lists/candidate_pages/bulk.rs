@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+
+use askama::Template;
+use axum::response::{IntoResponse, Response};
+use axum_extra::extract::Form;
+use sqlx::Connection;
+use uuid::Uuid;
+
+use crate::{
+    AppError, Context, CsrfTokens, DbConnection, HtmlTemplate, Locale,
+    candidate_lists::{
+        self, BulkAddCandidatesForm, CandidateBulkOutcome, CandidateList, CandidateListUndo,
+        FullCandidateList, MAX_CANDIDATES, UndoAction, UndoEntry,
+        candidate_pages::{
+            BulkAddCandidatesPath, BulkRemoveCandidatesPath, add::AddExistingPersonTemplate,
+        },
+        pages::load_candidate_list,
+    },
+    filters,
+    form::{BulkForm, BulkSummary, FormData, Validate},
+    pages::BulkSummaryTemplate,
+    persons, t,
+};
+
+#[derive(Template)]
+#[template(path = "candidates/bulk_remove.html")]
+struct BulkRemoveCandidatesTemplate {
+    full_list: FullCandidateList,
+    max_candidates: usize,
+    form: FormData<BulkForm>,
+}
+
+/// Summarize a bulk change of a list; names are looked up on the list before and after.
+fn candidate_summary(
+    outcomes: Vec<(Uuid, CandidateBulkOutcome)>,
+    lists: [&FullCandidateList; 2],
+    locale: &Locale,
+) -> BulkSummary {
+    let names: HashMap<Uuid, String> = lists
+        .iter()
+        .flat_map(|list| &list.candidates)
+        .map(|c| (c.person.id, c.person.display_name()))
+        .collect();
+
+    let mut summary = BulkSummary::default();
+    for (person_id, outcome) in outcomes {
+        let label = names
+            .get(&person_id)
+            .cloned()
+            .unwrap_or_else(|| person_id.to_string());
+        if outcome.succeeded() {
+            summary.succeeded(label, outcome.message(locale));
+        } else {
+            summary.skipped(label, outcome.message(locale));
+        }
+    }
+    summary
+}
+
+pub async fn add_candidates(
+    BulkAddCandidatesPath { candidate_list }: BulkAddCandidatesPath,
+    context: Context,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+    Form(form): Form<BulkAddCandidatesForm>,
+) -> Result<Response, AppError> {
+    let full_list = load_candidate_list(&mut conn, &candidate_list, context.locale).await?;
+
+    match form.validate(None, &csrf_tokens) {
+        Err(form_data) => {
+            let persons =
+                persons::repository::list_persons_not_on_candidate_list(&mut conn, &candidate_list)
+                    .await?;
+            Ok(HtmlTemplate(
+                AddExistingPersonTemplate {
+                    full_list,
+                    persons,
+                    max_candidates: MAX_CANDIDATES,
+                    form: form_data,
+                },
+                context,
+            )
+            .into_response())
+        }
+        Ok(selection) => {
+            let outcomes = candidate_lists::repository::add_candidates(
+                &mut conn,
+                &candidate_list,
+                &selection.person_ids,
+                selection.position,
+            )
+            .await?;
+            let updated = load_candidate_list(&mut conn, &candidate_list, context.locale).await?;
+
+            Ok(HtmlTemplate(
+                BulkSummaryTemplate {
+                    title: t!("bulk.add_title", &context.locale).to_string(),
+                    back_path: updated.list.view_path(),
+                    summary: candidate_summary(outcomes, [&full_list, &updated], &context.locale),
+                },
+                context,
+            )
+            .into_response())
+        }
+    }
+}
+
+pub async fn remove_candidates_form(
+    BulkRemoveCandidatesPath { candidate_list }: BulkRemoveCandidatesPath,
+    context: Context,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+) -> Result<impl IntoResponse, AppError> {
+    let full_list = load_candidate_list(&mut conn, &candidate_list, context.locale).await?;
+
+    Ok(HtmlTemplate(
+        BulkRemoveCandidatesTemplate {
+            full_list,
+            max_candidates: MAX_CANDIDATES,
+            form: FormData::new(&csrf_tokens),
+        },
+        context,
+    ))
+}
+
+pub async fn remove_candidates(
+    BulkRemoveCandidatesPath { candidate_list }: BulkRemoveCandidatesPath,
+    context: Context,
+    csrf_tokens: CsrfTokens,
+    undo: CandidateListUndo,
+    DbConnection(mut conn): DbConnection,
+    Form(form): Form<BulkForm>,
+) -> Result<Response, AppError> {
+    let full_list = load_candidate_list(&mut conn, &candidate_list, context.locale).await?;
+
+    let person_ids = match form.validate(None, &csrf_tokens) {
+        Ok(person_ids) => person_ids,
+        Err(form_data) => {
+            return Ok(HtmlTemplate(
+                BulkRemoveCandidatesTemplate {
+                    full_list,
+                    max_candidates: MAX_CANDIDATES,
+                    form: form_data,
+                },
+                context,
+            )
+            .into_response());
+        }
+    };
+
+    let mut tx = conn.begin().await?;
+    let previous =
+        candidate_lists::repository::lock_candidate_list_entries(&mut tx, &candidate_list).await?;
+    let outcomes =
+        candidate_lists::repository::remove_candidates(&mut tx, &candidate_list, &person_ids)
+            .await?;
+    let current =
+        candidate_lists::repository::lock_candidate_list_entries(&mut tx, &candidate_list).await?;
+    tx.commit().await?;
+
+    undo.push(
+        candidate_list,
+        UndoEntry::new(UndoAction::RemovedMany, previous, &current),
+    );
+    let updated = load_candidate_list(&mut conn, &candidate_list, context.locale).await?;
+
+    Ok(HtmlTemplate(
+        BulkSummaryTemplate {
+            title: t!("bulk.remove_title", &context.locale).to_string(),
+            back_path: updated.list.view_path(),
+            summary: candidate_summary(outcomes, [&full_list, &updated], &context.locale),
+        },
+        context,
+    )
+    .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::{
+        TokenValue,
+        test_utils::{response_body_string, sample_candidate_list, sample_person_with_last_name},
+    };
+
+    async fn setup(
+        pool: &PgPool,
+        on_list: &[&str],
+        off_list: &[&str],
+    ) -> Result<(Uuid, Vec<Uuid>, Vec<Uuid>), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let list = sample_candidate_list(Uuid::new_v4());
+        candidate_lists::repository::create_candidate_list(&mut conn, &list).await?;
+
+        let mut create = async |names: &[&str]| -> Result<Vec<Uuid>, sqlx::Error> {
+            let mut ids = Vec::new();
+            for name in names {
+                let person = sample_person_with_last_name(Uuid::new_v4(), name);
+                persons::repository::create_person(&mut conn, &person).await?;
+                ids.push(person.id);
+            }
+            Ok(ids)
+        };
+        let on_ids = create(on_list).await?;
+        let off_ids = create(off_list).await?;
+        candidate_lists::repository::update_candidate_list_order(&mut conn, &list.id, &on_ids)
+            .await?;
+
+        Ok((list.id, on_ids, off_ids))
+    }
+
+    fn order(full_list: &FullCandidateList) -> Vec<Uuid> {
+        full_list.candidates.iter().map(|c| c.person.id).collect()
+    }
+
+    #[sqlx::test]
+    async fn add_candidates_inserts_selection_in_order(pool: PgPool) -> Result<(), sqlx::Error> {
+        let (list_id, on_ids, off_ids) =
+            setup(&pool, &["Jansen", "Bakker"], &["Visser", "Smit"]).await?;
+        let csrf_tokens = CsrfTokens::default();
+        let unknown = Uuid::new_v4();
+        let form = BulkAddCandidatesForm {
+            person_ids: vec![off_ids[1], on_ids[0], off_ids[0], unknown],
+            position: "2".to_string(),
+            csrf_token: csrf_tokens.issue().value,
+        };
+
+        let response = add_candidates(
+            BulkAddCandidatesPath {
+                candidate_list: list_id,
+            },
+            Context::new(Locale::En),
+            csrf_tokens,
+            DbConnection(pool.acquire().await?),
+            Form(form),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        assert!(body.contains("2 processed, 2 skipped."));
+        assert!(body.contains("Added at position 2"));
+        assert!(body.contains("Added at position 3"));
+        assert!(body.contains("Already on this list"));
+        assert!(body.contains(&unknown.to_string()));
+
+        let mut conn = pool.acquire().await?;
+        let full_list = load_candidate_list(&mut conn, &list_id, Locale::En)
+            .await
+            .expect("candidate list");
+        assert_eq!(
+            order(&full_list),
+            vec![on_ids[0], off_ids[1], off_ids[0], on_ids[1]]
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn add_candidates_with_invalid_position_renders_form(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let (list_id, _, off_ids) = setup(&pool, &["Jansen"], &["Visser"]).await?;
+        let csrf_tokens = CsrfTokens::default();
+        let form = BulkAddCandidatesForm {
+            person_ids: off_ids.clone(),
+            position: "first".to_string(),
+            csrf_token: csrf_tokens.issue().value,
+        };
+
+        let response = add_candidates(
+            BulkAddCandidatesPath {
+                candidate_list: list_id,
+            },
+            Context::new(Locale::En),
+            csrf_tokens,
+            DbConnection(pool.acquire().await?),
+            Form(form),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        assert!(body.contains("Visser"));
+
+        let mut conn = pool.acquire().await?;
+        let full_list = load_candidate_list(&mut conn, &list_id, Locale::En)
+            .await
+            .expect("candidate list");
+        assert_eq!(full_list.candidates.len(), 1);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn remove_candidates_reports_each_person(pool: PgPool) -> Result<(), sqlx::Error> {
+        let (list_id, on_ids, off_ids) =
+            setup(&pool, &["Jansen", "Bakker", "Visser"], &["Smit"]).await?;
+        let csrf_tokens = CsrfTokens::default();
+        let form = BulkForm {
+            ids: vec![on_ids[0], off_ids[0], on_ids[2]],
+            csrf_token: csrf_tokens.issue().value,
+        };
+        let undo = CandidateListUndo::default();
+
+        let response = remove_candidates(
+            BulkRemoveCandidatesPath {
+                candidate_list: list_id,
+            },
+            Context::new(Locale::En),
+            csrf_tokens,
+            undo.clone(),
+            DbConnection(pool.acquire().await?),
+            Form(form),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        assert!(body.contains("2 processed, 1 skipped."));
+        assert!(body.contains("Not on this list"));
+
+        let mut conn = pool.acquire().await?;
+        let full_list = load_candidate_list(&mut conn, &list_id, Locale::En)
+            .await
+            .expect("candidate list");
+        assert_eq!(order(&full_list), vec![on_ids[1]]);
+        assert_eq!(full_list.candidates[0].position, 1);
+        assert_eq!(undo.latest(list_id), Some(UndoAction::RemovedMany));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn remove_candidates_with_invalid_csrf_changes_nothing(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let (list_id, on_ids, _) = setup(&pool, &["Jansen"], &[]).await?;
+        let form = BulkForm {
+            ids: on_ids,
+            csrf_token: TokenValue("invalid".to_string()),
+        };
+
+        let response = remove_candidates(
+            BulkRemoveCandidatesPath {
+                candidate_list: list_id,
+            },
+            Context::new(Locale::En),
+            CsrfTokens::default(),
+            CandidateListUndo::default(),
+            DbConnection(pool.acquire().await?),
+            Form(form),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let mut conn = pool.acquire().await?;
+        let full_list = load_candidate_list(&mut conn, &list_id, Locale::En)
+            .await
+            .expect("candidate list");
+        assert_eq!(full_list.candidates.len(), 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,335 @@
+use askama::Template;
+use axum::{
+    extract::State,
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::extract::Form;
+use chrono::{Duration, Utc};
+use sqlx::Connection;
+
+use crate::{
+    AppError, AppState, Context, CsrfTokens, DbConnection, ElectionConfig, HtmlTemplate, Locale,
+    candidate_lists::{
+        self, CONSENT_INVITATION_TTL_DAYS, Candidate, CandidateList, FullCandidateList,
+        MAX_CANDIDATES,
+        candidate_pages::{CandidateConsentPath, CandidateH9Path},
+        consent_pages::ConsentPath,
+        pages::load_candidate_list,
+    },
+    common::current_user::{hash_token, random_token},
+    filters,
+    form::{EmptyForm, FormData, Validate},
+    t,
+};
+
+#[derive(Template)]
+#[template(path = "candidates/consent.html")]
+struct CandidateConsentTemplate {
+    full_list: FullCandidateList,
+    candidate: Candidate,
+    form: FormData<EmptyForm>,
+    max_candidates: usize,
+    /// The personal link, right after it was created.
+    link: Option<String>,
+    ttl_days: i64,
+}
+
+#[derive(Template)]
+#[template(path = "candidates/h9.html")]
+struct H9Template {
+    full_list: FullCandidateList,
+    candidate: Candidate,
+    election: ElectionConfig,
+    locale: Locale,
+}
+
+pub async fn candidate_consent(
+    CandidateConsentPath {
+        candidate_list,
+        person,
+    }: CandidateConsentPath,
+    context: Context,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+) -> Result<impl IntoResponse, AppError> {
+    let full_list = load_candidate_list(&mut conn, &candidate_list, context.locale).await?;
+    let candidate = full_list.get_candidate(&person, context.locale)?;
+
+    Ok(HtmlTemplate(
+        CandidateConsentTemplate {
+            full_list,
+            candidate,
+            form: FormData::new(&csrf_tokens),
+            max_candidates: MAX_CANDIDATES,
+            link: None,
+            ttl_days: CONSENT_INVITATION_TTL_DAYS,
+        },
+        context,
+    ))
+}
+
+/// Create a personal link for the candidate to accept or decline their position, to be handed
+/// to the candidate by the list submitter. Only lists of a political group get links.
+pub async fn invite_candidate(
+    CandidateConsentPath {
+        candidate_list,
+        person,
+    }: CandidateConsentPath,
+    context: Context,
+    State(app_state): State<AppState>,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+    form: Form<EmptyForm>,
+) -> Result<Response, AppError> {
+    let path = CandidateConsentPath {
+        candidate_list,
+        person,
+    };
+    if form.validate(None, &csrf_tokens).is_err() {
+        return Ok(Redirect::to(&path.to_string()).into_response());
+    }
+
+    let full_list = load_candidate_list(&mut conn, &candidate_list, context.locale).await?;
+    let candidate = full_list.get_candidate(&person, context.locale)?;
+
+    let token = random_token();
+    candidate_lists::repository::create_consent_invitation(
+        &mut conn,
+        &candidate_list,
+        &person,
+        &hash_token(&token),
+        Utc::now() + Duration::days(CONSENT_INVITATION_TTL_DAYS),
+    )
+    .await?
+    .ok_or_else(|| AppError::BadRequest(t!("consent.no_political_group", &context.locale)))?;
+
+    Ok(HtmlTemplate(
+        CandidateConsentTemplate {
+            full_list,
+            candidate,
+            form: FormData::new(&csrf_tokens),
+            max_candidates: MAX_CANDIDATES,
+            link: Some(format!(
+                "{}{}",
+                app_state.config().public_url,
+                ConsentPath { token }
+            )),
+            ttl_days: CONSENT_INVITATION_TTL_DAYS,
+        },
+        context,
+    )
+    .into_response())
+}
+
+/// Declaration of consent (model H9) of a candidate, to print. Candidates who accepted their
+/// position through their personal link get a digital consent marker instead of a signature box.
+/// The list is snapshotted as the document was generated from it.
+pub async fn candidate_h9(
+    CandidateH9Path {
+        candidate_list,
+        person,
+    }: CandidateH9Path,
+    context: Context,
+    State(app_state): State<AppState>,
+    DbConnection(mut conn): DbConnection,
+) -> Result<impl IntoResponse, AppError> {
+    let mut tx = conn.begin().await?;
+    let full_list = load_candidate_list(&mut tx, &candidate_list, context.locale).await?;
+    let candidate = full_list.get_candidate(&person, context.locale)?;
+    candidate_lists::repository::create_snapshot(
+        &mut tx,
+        &candidate_list,
+        Some(&t!(
+            "candidate_list.snapshots.automatic.h9",
+            &context.locale,
+            candidate.person.display_name()
+        )),
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(HtmlTemplate(
+        H9Template {
+            full_list,
+            candidate,
+            election: app_state.config().election,
+            locale: context.locale,
+        },
+        context,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use crate::{
+        Tenant, auth,
+        candidate_lists::ConsentDecision,
+        persons,
+        test_utils::{response_body_string, sample_candidate_list, sample_person},
+    };
+
+    #[sqlx::test]
+    async fn h9_carries_a_consent_marker_once_accepted(pool: PgPool) -> Result<(), sqlx::Error> {
+        let list = sample_candidate_list(Uuid::new_v4());
+        let person = sample_person(Uuid::new_v4());
+
+        let mut conn = pool.acquire().await?;
+        candidate_lists::repository::create_candidate_list(&mut conn, &list).await?;
+        persons::repository::create_person(&mut conn, &person).await?;
+        candidate_lists::repository::update_candidate_list_order(&mut conn, &list.id, &[person.id])
+            .await?;
+
+        let state = AppState::new_for_tests(pool.clone());
+
+        let response = candidate_h9(
+            CandidateH9Path {
+                candidate_list: list.id,
+                person: person.id,
+            },
+            Context::new(Locale::En),
+            State(state.clone()),
+            DbConnection(pool.acquire().await?),
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        assert!(body.contains("Signature"));
+        assert!(!body.contains("Consent given digitally"));
+
+        candidate_lists::repository::set_candidate_consent(
+            &mut conn,
+            &list.id,
+            &person.id,
+            ConsentDecision::Accepted,
+        )
+        .await?;
+
+        let response = candidate_h9(
+            CandidateH9Path {
+                candidate_list: list.id,
+                person: person.id,
+            },
+            Context::new(Locale::En),
+            State(state),
+            DbConnection(pool.acquire().await?),
+        )
+        .await
+        .unwrap()
+        .into_response();
+        let body = response_body_string(response).await;
+        assert!(!body.contains("Signature"));
+        assert!(body.contains("Consent given digitally"));
+
+        // every generated H9 is backed by a snapshot of the list
+        let snapshots = candidate_lists::repository::list_snapshots(&mut conn, &list.id).await?;
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(
+            snapshots[0].label.as_deref(),
+            Some(
+                format!(
+                    "Declaration of consent (H9) of {} generated",
+                    person.display_name()
+                )
+                .as_str()
+            )
+        );
+        assert!(snapshots[0].full_list.candidates[0].consent.is_some());
+        assert!(snapshots[1].full_list.candidates[0].consent.is_none());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn invites_candidates_on_the_list_only(pool: PgPool) -> Result<(), sqlx::Error> {
+        let list = sample_candidate_list(Uuid::new_v4());
+        let person = sample_person(Uuid::new_v4());
+
+        let mut conn = pool.acquire().await?;
+        let group = auth::repository::create_political_group(&mut conn, "Lijst Smit").await?;
+        Tenant::PoliticalGroup(group).scope(&mut conn).await?;
+        candidate_lists::repository::create_candidate_list(&mut conn, &list).await?;
+        persons::repository::create_person(&mut conn, &person).await?;
+        candidate_lists::repository::update_candidate_list_order(&mut conn, &list.id, &[person.id])
+            .await?;
+
+        let csrf_tokens = CsrfTokens::default();
+        let response = invite_candidate(
+            CandidateConsentPath {
+                candidate_list: list.id,
+                person: person.id,
+            },
+            Context::new(Locale::En),
+            State(AppState::new_for_tests(pool.clone())),
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+            Form(EmptyForm::from(csrf_tokens.issue().value)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        let (_, link) = body.split_once("<code>").unwrap();
+        let (_, token) = link.split_once("/consent/").unwrap();
+        let invitation = candidate_lists::repository::get_consent_invitation(
+            &mut conn,
+            &hash_token(&token[..48]),
+        )
+        .await?
+        .expect("invitation");
+        assert_eq!(invitation.person_id, person.id);
+
+        let result = invite_candidate(
+            CandidateConsentPath {
+                candidate_list: list.id,
+                person: Uuid::new_v4(),
+            },
+            Context::new(Locale::En),
+            State(AppState::new_for_tests(pool.clone())),
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+            Form(EmptyForm::from(csrf_tokens.issue().value)),
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn refuses_links_for_lists_without_political_group(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let list = sample_candidate_list(Uuid::new_v4());
+        let person = sample_person(Uuid::new_v4());
+
+        let mut conn = pool.acquire().await?;
+        candidate_lists::repository::create_candidate_list(&mut conn, &list).await?;
+        persons::repository::create_person(&mut conn, &person).await?;
+        candidate_lists::repository::update_candidate_list_order(&mut conn, &list.id, &[person.id])
+            .await?;
+
+        let csrf_tokens = CsrfTokens::default();
+        let result = invite_candidate(
+            CandidateConsentPath {
+                candidate_list: list.id,
+                person: person.id,
+            },
+            Context::new(Locale::En),
+            State(AppState::new_for_tests(pool.clone())),
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+            Form(EmptyForm::from(csrf_tokens.issue().value)),
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+
+        Ok(())
+    }
+}
@@ -8,7 +8,7 @@ use axum_extra::extract::Form;
 use crate::{
     AppError, AppState, Context, CsrfTokens, DbConnection, HtmlTemplate,
     candidate_lists::{
-        self, CandidateList, FullCandidateList, MAX_CANDIDATES,
+        self, CandidateList, FullCandidateList, MAX_CANDIDATES, PositionOperation,
         pages::{CreateCandidatePath, load_candidate_list},
     },
     filters,
@@ -68,14 +68,17 @@ pub async fn create_person_candidate_list(
         Ok(person) => {
             let person = persons::repository::create_person(&mut conn, &person).await?;
 
-            let mut person_ids = full_list.get_ids();
-            person_ids.push(person.id);
-            candidate_lists::repository::update_candidate_list_order(
+            let operation = PositionOperation::InsertAt {
+                person_id: person.id,
+                position: full_list.candidates.len() + 1,
+            };
+            candidate_lists::repository::apply_position_operation(
                 &mut conn,
                 &candidate_list,
-                &person_ids,
+                operation,
             )
-            .await?;
+            .await
+            .map_err(|e| e.into_app_error(context.locale))?;
 
             let candidate =
                 candidate_lists::repository::get_candidate(&mut conn, &candidate_list, &person.id)
@@ -3,11 +3,12 @@ use axum::{
     response::{IntoResponse, Redirect, Response},
 };
 use axum_extra::extract::Form;
+use sqlx::Connection;
 
 use crate::{
     AppError, AppState, Context, CsrfTokens, DbConnection,
     candidate_lists::{
-        self, CandidateList,
+        self, CandidateListUndo, PositionOperation, UndoAction, UndoEntry,
         candidate_pages::{CandidateListDeletePersonPath, CandidateListEditPersonPath},
     },
     form::{EmptyForm, Validate},
@@ -22,6 +23,7 @@ pub async fn delete_person(
     context: Context,
     _: State<AppState>,
     csrf_tokens: CsrfTokens,
+    undo: CandidateListUndo,
     DbConnection(mut conn): DbConnection,
     form: Form<EmptyForm>,
 ) -> Result<Response, AppError> {
@@ -46,19 +48,40 @@ pub async fn delete_person(
             .await?;
             let candidate = full_list.get_candidate(&person, context.locale)?;
 
+            let mut tx = conn.begin().await?;
+            let previous =
+                candidate_lists::repository::lock_candidate_list_entries(&mut tx, &candidate_list)
+                    .await?;
+
             // remove person from list
-            let mut updates_ids = full_list.get_ids();
-            updates_ids.retain(|id| id != &candidate.person.id);
-            candidate_lists::repository::update_candidate_list_order(
-                &mut conn,
+            candidate_lists::repository::apply_position_operation(
+                &mut tx,
                 &candidate_list,
-                &updates_ids,
+                PositionOperation::Remove {
+                    person_id: candidate.person.id,
+                },
             )
-            .await?;
+            .await
+            .map_err(|e| e.into_app_error(context.locale))?;
+
+            // keep the person when they are still a candidate on another list
+            let mut deleted_persons = Vec::new();
+            if persons::repository::remove_person(&mut tx, &candidate.person.id).await? {
+                deleted_persons.push(candidate.person.id);
+            }
+
+            let current =
+                candidate_lists::repository::lock_candidate_list_entries(&mut tx, &candidate_list)
+                    .await?;
+            tx.commit().await?;
 
-            persons::repository::remove_person(&mut conn, &candidate.person.id).await?;
+            undo.push(
+                candidate_list,
+                UndoEntry::new(UndoAction::Removed, previous, &current)
+                    .with_deleted_persons(deleted_persons),
+            );
 
-            Ok(Redirect::to(&CandidateList::list_path()).into_response())
+            Ok(Redirect::to(&full_list.list.view_path()).into_response())
         }
     }
 }
@@ -1,6 +1,7 @@
 use askama::Template;
 use axum::{
     extract::State,
+    http::StatusCode,
     response::{IntoResponse, Redirect, Response},
 };
 use axum_extra::extract::Form;
@@ -12,8 +13,8 @@ use crate::{
         candidate_pages::CandidateListEditAddressPath, pages::load_candidate_list,
     },
     filters,
-    form::{FormData, Validate},
-    persons::{self, AddressForm},
+    form::{Conflict, FormData, Validate},
+    persons::{AddressForm, check_address, save_person},
     t,
 };
 
@@ -22,6 +23,7 @@ use crate::{
 struct PersonAddressUpdateTemplate {
     candidate: Candidate,
     form: FormData<AddressForm>,
+    conflict: Option<Conflict>,
     full_list: FullCandidateList,
     max_candidates: usize,
 }
@@ -46,6 +48,7 @@ pub async fn edit_person_address(
             candidate: candidate.clone(),
             full_list,
             max_candidates: MAX_CANDIDATES,
+            conflict: None,
         },
         context,
     ))
@@ -71,12 +74,46 @@ pub async fn update_person_address(
                 form: form_data,
                 full_list,
                 max_candidates: MAX_CANDIDATES,
+                conflict: None,
             },
             context,
         )
         .into_response()),
-        Ok(person) => {
-            persons::repository::update_person(&mut conn, &person).await?;
+        Ok(mut updated) => {
+            let errors = check_address(app_state.bag(), &mut updated).await;
+            if !errors.is_empty() {
+                return Ok(HtmlTemplate(
+                    PersonAddressUpdateTemplate {
+                        candidate,
+                        form: FormData::new_with_errors(form.0, app_state.csrf_tokens(), errors),
+                        full_list,
+                        max_candidates: MAX_CANDIDATES,
+                        conflict: None,
+                    },
+                    context,
+                )
+                .into_response());
+            }
+
+            if let Some(conflict) =
+                save_person(&mut conn, &updated, &form.version, context.locale).await?
+            {
+                let form = AddressForm {
+                    version: conflict.version.clone(),
+                    ..form.0
+                };
+                let page = HtmlTemplate(
+                    PersonAddressUpdateTemplate {
+                        candidate,
+                        full_list,
+                        form: FormData::new_with_data(form, app_state.csrf_tokens()),
+                        max_candidates: MAX_CANDIDATES,
+                        conflict: Some(conflict),
+                    },
+                    context,
+                );
+                return Ok((StatusCode::CONFLICT, page).into_response());
+            }
 
             Ok(Redirect::to(&full_list.list.view_path()).into_response())
         }
@@ -143,7 +180,7 @@ mod tests {
 
         let mut conn = pool.acquire().await?;
         candidate_lists::repository::create_candidate_list(&mut conn, &list).await?;
-        persons::repository::create_person(&mut conn, &person).await?;
+        let person = persons::repository::create_person(&mut conn, &person).await?;
         candidate_lists::repository::update_candidate_list_order(&mut conn, &list_id, &[person.id])
             .await?;
 
@@ -151,6 +188,7 @@ mod tests {
         let csrf_token = app_state.csrf_tokens().issue().value;
         let mut form = sample_address_form(&csrf_token);
         form.locality = "Rotterdam".to_string();
+        form.version = person.version();
 
         let response = update_person_address(
             CandidateListEditAddressPath {
@@ -218,7 +256,7 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::OK);
         let body = response_body_string(response).await;
-        assert!(body.contains("The value is too short"));
+        assert!(body.contains("Enter a postal code of four digits and two letters"));
 
         Ok(())
     }
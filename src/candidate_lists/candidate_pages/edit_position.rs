@@ -1,13 +1,15 @@
 use askama::Template;
 use axum::response::{IntoResponse, Redirect};
 use axum_extra::extract::Form;
+use sqlx::Connection;
 
 use crate::{
     AppError, Context, CsrfTokens, DbConnection, HtmlTemplate,
     candidate_lists::{
-        self, Candidate, CandidateList, CandidatePosition, CandidatePositionAction,
-        CandidatePositionForm, FullCandidateList, MAX_CANDIDATES,
-        candidate_pages::EditCandidatePositionPath, pages::load_candidate_list,
+        self, Candidate, CandidateList, CandidateListUndo, CandidatePosition,
+        CandidatePositionAction, CandidatePositionForm, FullCandidateList, MAX_CANDIDATES,
+        PositionOperation, UndoAction, UndoEntry, candidate_pages::EditCandidatePositionPath,
+        pages::load_candidate_list,
     },
     filters,
     form::{FormData, Validate},
@@ -65,12 +67,12 @@ pub async fn update_candidate_position(
     }: EditCandidatePositionPath,
     context: Context,
     csrf_tokens: CsrfTokens,
+    undo: CandidateListUndo,
     DbConnection(mut conn): DbConnection,
     Form(form): Form<CandidatePositionForm>,
 ) -> Result<impl IntoResponse, AppError> {
     let full_list: FullCandidateList =
         load_candidate_list(&mut conn, &candidate_list, context.locale).await?;
-    let mut person_ids = full_list.get_ids();
 
     let Some(current_index) = full_list.get_index(&person) else {
         return Err(AppError::NotFound(
@@ -97,30 +99,45 @@ pub async fn update_candidate_position(
         )
         .into_response()),
         Ok(position_form) => {
-            let moved = person_ids.remove(current_index);
+            let operation = match position_form.action {
+                CandidatePositionAction::Remove => Some((
+                    UndoAction::Removed,
+                    PositionOperation::Remove { person_id: person },
+                )),
+                CandidatePositionAction::Move => {
+                    let position = position_form.position.clamp(1, full_list.candidates.len());
+                    (position != current_index + 1).then_some((
+                        UndoAction::Reordered,
+                        PositionOperation::Move {
+                            person_id: person,
+                            position,
+                        },
+                    ))
+                }
+            };
 
-            if position_form.action == CandidatePositionAction::Remove {
-                candidate_lists::repository::update_candidate_list_order(
-                    &mut conn,
+            if let Some((action, operation)) = operation {
+                let mut tx = conn.begin().await?;
+                let previous = candidate_lists::repository::lock_candidate_list_entries(
+                    &mut tx,
                     &candidate_list,
-                    &person_ids,
                 )
                 .await?;
-            } else if position_form.action == CandidatePositionAction::Move {
-                let target_index = position_form
-                    .position
-                    .saturating_sub(1)
-                    .min(person_ids.len());
-
-                if current_index != target_index {
-                    person_ids.insert(target_index, moved);
-                    candidate_lists::repository::update_candidate_list_order(
-                        &mut conn,
-                        &candidate_list,
-                        &person_ids,
-                    )
-                    .await?;
-                }
+                candidate_lists::repository::apply_position_operation(
+                    &mut tx,
+                    &candidate_list,
+                    operation,
+                )
+                .await
+                .map_err(|e| e.into_app_error(context.locale))?;
+                let current = candidate_lists::repository::lock_candidate_list_entries(
+                    &mut tx,
+                    &candidate_list,
+                )
+                .await?;
+                tx.commit().await?;
+
+                undo.push(candidate_list, UndoEntry::new(action, previous, &current));
             }
 
             Ok(Redirect::to(&full_list.list.view_path()).into_response())
@@ -165,6 +182,7 @@ mod tests {
             person: person.clone(),
             position: 1,
             list_id,
+            consent: None,
         };
 
         let mut conn = pool.acquire().await?;
@@ -215,6 +233,7 @@ mod tests {
         let csrf_tokens = CsrfTokens::default();
         let csrf_token = csrf_tokens.issue().value;
         let form = sample_position_form(&csrf_token, 2, "move");
+        let undo = CandidateListUndo::default();
 
         let response = update_candidate_position(
             EditCandidatePositionPath {
@@ -223,6 +242,7 @@ mod tests {
             },
             Context::new(Locale::En),
             csrf_tokens,
+            undo.clone(),
             DbConnection(pool.acquire().await?),
             Form(form),
         )
@@ -239,6 +259,7 @@ mod tests {
         assert_eq!(full_list.candidates.len(), 2);
         assert_eq!(full_list.candidates[0].person.id, person_b.id);
         assert_eq!(full_list.candidates[1].person.id, person_a.id);
+        assert_eq!(undo.latest(list_id), Some(UndoAction::Reordered));
 
         Ok(())
     }
@@ -1,11 +1,17 @@
-use axum::Router;
+use axum::{Router, middleware};
 use axum_extra::routing::{RouterExt, TypedPath};
 use serde::Deserialize;
 use uuid::Uuid;
 
-use crate::{AppError, AppState, candidate_lists::Candidate};
+use crate::{
+    AppError, AppState, Permission,
+    auth::require_permission,
+    candidate_lists::{Candidate, CandidateList},
+};
 
 mod add;
+mod bulk;
+mod consent;
 mod create;
 mod delete;
 mod edit_address;
@@ -49,6 +55,51 @@ pub struct CandidateListDeletePersonPath {
     pub person: Uuid,
 }
 
+#[derive(TypedPath, Deserialize)]
+#[typed_path(
+    "/candidate-lists/{candidate_list}/consent/{person}",
+    rejection(AppError)
+)]
+pub struct CandidateConsentPath {
+    pub candidate_list: Uuid,
+    pub person: Uuid,
+}
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/candidate-lists/{candidate_list}/h9/{person}", rejection(AppError))]
+pub struct CandidateH9Path {
+    pub candidate_list: Uuid,
+    pub person: Uuid,
+}
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/candidate-lists/{candidate_list}/bulk-add", rejection(AppError))]
+pub struct BulkAddCandidatesPath {
+    pub candidate_list: Uuid,
+}
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/candidate-lists/{candidate_list}/bulk-remove", rejection(AppError))]
+pub struct BulkRemoveCandidatesPath {
+    pub candidate_list: Uuid,
+}
+
+impl CandidateList {
+    pub fn bulk_add_path(&self) -> String {
+        BulkAddCandidatesPath {
+            candidate_list: self.id,
+        }
+        .to_string()
+    }
+
+    pub fn bulk_remove_path(&self) -> String {
+        BulkRemoveCandidatesPath {
+            candidate_list: self.id,
+        }
+        .to_string()
+    }
+}
+
 impl Candidate {
     pub fn edit_position_path(&self) -> String {
         EditCandidatePositionPath {
@@ -81,6 +132,22 @@ impl Candidate {
         }
         .to_string()
     }
+
+    pub fn consent_path(&self) -> String {
+        CandidateConsentPath {
+            candidate_list: self.list_id,
+            person: self.person.id,
+        }
+        .to_string()
+    }
+
+    pub fn h9_path(&self) -> String {
+        CandidateH9Path {
+            candidate_list: self.list_id,
+            person: self.person.id,
+        }
+        .to_string()
+    }
 }
 
 pub fn candidate_router() -> Router<AppState> {
@@ -96,4 +163,14 @@ pub fn candidate_router() -> Router<AppState> {
         .typed_get(update::edit_person_form)
         .typed_post(update::update_person)
         .typed_post(delete::delete_person)
+        .typed_post(bulk::add_candidates)
+        .typed_get(bulk::remove_candidates_form)
+        .typed_post(bulk::remove_candidates)
+        .typed_get(consent::candidate_consent)
+        .typed_post(consent::invite_candidate)
+        .typed_get(consent::candidate_h9)
+        .route_layer(middleware::from_fn_with_state(
+            Permission::EditCandidates,
+            require_permission,
+        ))
 }
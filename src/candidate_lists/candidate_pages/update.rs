@@ -1,6 +1,7 @@
 use askama::Template;
 use axum::{
     extract::State,
+    http::StatusCode,
     response::{IntoResponse, Redirect, Response},
 };
 use axum_extra::extract::Form;
@@ -12,8 +13,8 @@ use crate::{
         candidate_pages::CandidateListEditPersonPath, pages::load_candidate_list,
     },
     filters,
-    form::{FormData, Validate},
-    persons::{self, PersonForm},
+    form::{Conflict, FormData, Validate},
+    persons::{PersonForm, save_person},
     t,
 };
 
@@ -23,6 +24,7 @@ struct PersonUpdateTemplate {
     full_list: FullCandidateList,
     candidate: Candidate,
     form: FormData<PersonForm>,
+    conflict: Option<Conflict>,
     max_candidates: usize,
 }
 
@@ -44,6 +46,7 @@ pub async fn edit_person_form(
             candidate,
             full_list,
             max_candidates: MAX_CANDIDATES,
+            conflict: None,
         },
         context,
     ))
@@ -69,12 +72,31 @@ pub async fn update_person(
                 full_list,
                 form: form_data,
                 max_candidates: MAX_CANDIDATES,
+                conflict: None,
             },
             context,
         )
         .into_response()),
-        Ok(person) => {
-            persons::repository::update_person(&mut conn, &person).await?;
+        Ok(updated) => {
+            if let Some(conflict) =
+                save_person(&mut conn, &updated, &form.version, context.locale).await?
+            {
+                let form = PersonForm {
+                    version: conflict.version.clone(),
+                    ..form.0
+                };
+                let page = HtmlTemplate(
+                    PersonUpdateTemplate {
+                        candidate,
+                        full_list,
+                        form: FormData::new_with_data(form, app_state.csrf_tokens()),
+                        max_candidates: MAX_CANDIDATES,
+                        conflict: Some(conflict),
+                    },
+                    context,
+                );
+                return Ok((StatusCode::CONFLICT, page).into_response());
+            }
 
             // Redirect to the address edit page
             Ok(Redirect::to(&candidate.edit_path()).into_response())
@@ -95,7 +117,7 @@ mod tests {
     use uuid::Uuid;
 
     use crate::{
-        AppState, Context, CsrfTokens, DbConnection, Locale, candidate_lists,
+        AppState, Context, CsrfTokens, DbConnection, Locale, candidate_lists, persons,
         test_utils::{
             response_body_string, sample_candidate_list, sample_person, sample_person_form,
         },
@@ -152,6 +174,7 @@ mod tests {
         let csrf_token = app_state.csrf_tokens().issue().value;
         let mut form = sample_person_form(&csrf_token);
         form.last_name = "Updated".to_string();
+        form.version = candidate.person.version();
 
         let response = update_person(
             CandidateListEditPersonPath {
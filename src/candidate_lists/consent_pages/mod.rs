@@ -0,0 +1,20 @@
+use axum::Router;
+use axum_extra::routing::{RouterExt, TypedPath};
+use serde::Deserialize;
+
+use crate::{AppError, AppState};
+
+mod respond;
+
+/// The personal link of a candidate to review their position, opened without signing in.
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/consent/{token}", rejection(AppError))]
+pub struct ConsentPath {
+    pub token: String,
+}
+
+pub fn consent_router() -> Router<AppState> {
+    Router::new()
+        .typed_get(respond::view_consent)
+        .typed_post(respond::respond_consent)
+}
@@ -0,0 +1,415 @@
+use askama::Template;
+use axum::{
+    extract::State,
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::extract::Form;
+use sqlx::{Connection, PgConnection};
+
+use crate::{
+    AppError, AppState, Context, CsrfTokens, DbConnection, ElectionConfig, HtmlTemplate, Locale,
+    Tenant,
+    candidate_lists::{
+        self, Candidate, ConsentDecision, ConsentForm, ConsentInvitation, FullCandidateList,
+        consent_pages::ConsentPath,
+    },
+    common::current_user::hash_token,
+    filters,
+    form::{FormData, Validate},
+    t,
+};
+
+#[derive(Template)]
+#[template(path = "consent/view.html")]
+struct ConsentTemplate {
+    full_list: FullCandidateList,
+    candidate: Candidate,
+    invitation: ConsentInvitation,
+    path: String,
+    form: FormData<ConsentForm>,
+    election: ElectionConfig,
+    locale: Locale,
+}
+
+fn consent_not_found(locale: Locale) -> AppError {
+    AppError::NotFound(t!("consent.not_found", &locale))
+}
+
+/// Look up the invitation of the token and the candidate it is for. The connection is scoped to
+/// the political group of the list, as the candidate is not signed in. Lists created before
+/// political groups existed have none; their links are not found.
+async fn load_consent(
+    conn: &mut PgConnection,
+    token: &str,
+    locale: Locale,
+) -> Result<(ConsentInvitation, FullCandidateList, Candidate), AppError> {
+    let invitation = candidate_lists::repository::get_consent_invitation(conn, &hash_token(token))
+        .await?
+        .ok_or(consent_not_found(locale))?;
+
+    let political_group_id = invitation
+        .political_group_id
+        .ok_or(consent_not_found(locale))?;
+    Tenant::PoliticalGroup(political_group_id)
+        .scope(conn)
+        .await?;
+
+    let full_list =
+        candidate_lists::repository::get_full_candidate_list(conn, &invitation.candidate_list_id)
+            .await?
+            .ok_or(consent_not_found(locale))?;
+    let candidate = full_list
+        .get_candidate(&invitation.person_id, locale)
+        .map_err(|_| consent_not_found(locale))?;
+
+    Ok((invitation, full_list, candidate))
+}
+
+/// Read-only view of the personal details of the candidate and the list they are on, where
+/// they accept or decline their position.
+pub async fn view_consent(
+    ConsentPath { token }: ConsentPath,
+    context: Context,
+    State(app_state): State<AppState>,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+) -> Result<impl IntoResponse, AppError> {
+    let (invitation, full_list, candidate) =
+        load_consent(&mut conn, &token, context.locale).await?;
+
+    Ok(HtmlTemplate(
+        ConsentTemplate {
+            full_list,
+            candidate,
+            invitation,
+            path: ConsentPath { token }.to_string(),
+            form: FormData::new(&csrf_tokens),
+            election: app_state.config().election,
+            locale: context.locale,
+        },
+        context,
+    ))
+}
+
+/// Store the decision of the candidate; once made, it stands until their position changes.
+pub async fn respond_consent(
+    ConsentPath { token }: ConsentPath,
+    context: Context,
+    State(app_state): State<AppState>,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+    Form(form): Form<ConsentForm>,
+) -> Result<Response, AppError> {
+    let (invitation, full_list, candidate) =
+        load_consent(&mut conn, &token, context.locale).await?;
+    let path = ConsentPath { token }.to_string();
+
+    let decision = match form.validate(None, &csrf_tokens) {
+        Ok(decision) => decision,
+        Err(form) => {
+            return Ok(HtmlTemplate(
+                ConsentTemplate {
+                    full_list,
+                    candidate,
+                    invitation,
+                    path,
+                    form,
+                    election: app_state.config().election,
+                    locale: context.locale,
+                },
+                context,
+            )
+            .into_response());
+        }
+    };
+
+    let mut tx = conn.begin().await?;
+    let stored = candidate_lists::repository::set_candidate_consent(
+        &mut tx,
+        &invitation.candidate_list_id,
+        &invitation.person_id,
+        decision,
+    )
+    .await?;
+    if stored {
+        let name = candidate.person.display_name();
+        let label = match decision {
+            ConsentDecision::Accepted => {
+                t!(
+                    "candidate_list.snapshots.automatic.consent_accepted",
+                    &context.locale,
+                    name
+                )
+            }
+            ConsentDecision::Declined => {
+                t!(
+                    "candidate_list.snapshots.automatic.consent_declined",
+                    &context.locale,
+                    name
+                )
+            }
+        };
+        candidate_lists::repository::create_snapshot(
+            &mut tx,
+            &invitation.candidate_list_id,
+            Some(&label),
+        )
+        .await?;
+    }
+    tx.commit().await?;
+
+    Ok(Redirect::to(&path).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use crate::{
+        auth,
+        common::current_user::random_token,
+        persons,
+        test_utils::{
+            db_connection, response_body_string, sample_candidate_list, sample_person,
+            sample_person_with_last_name,
+        },
+    };
+
+    fn consent_form(csrf_tokens: &CsrfTokens, decision: &str) -> Form<ConsentForm> {
+        Form(ConsentForm {
+            decision: decision.to_string(),
+            csrf_token: csrf_tokens.issue().value,
+        })
+    }
+
+    #[sqlx::test]
+    async fn candidates_accept_their_position_once(pool: PgPool) -> Result<(), sqlx::Error> {
+        let list = sample_candidate_list(Uuid::new_v4());
+        let first = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        let second = sample_person_with_last_name(Uuid::new_v4(), "Bakker");
+
+        let mut conn = pool.acquire().await?;
+        let group = auth::repository::create_political_group(&mut conn, "Lijst Smit").await?;
+        Tenant::PoliticalGroup(group).scope(&mut conn).await?;
+        candidate_lists::repository::create_candidate_list(&mut conn, &list).await?;
+        persons::repository::create_person(&mut conn, &first).await?;
+        persons::repository::create_person(&mut conn, &second).await?;
+        candidate_lists::repository::update_candidate_list_order(
+            &mut conn,
+            &list.id,
+            &[first.id, second.id],
+        )
+        .await?;
+
+        let token = random_token();
+        candidate_lists::repository::create_consent_invitation(
+            &mut conn,
+            &list.id,
+            &second.id,
+            &hash_token(&token),
+            Utc::now() + Duration::days(1),
+        )
+        .await?
+        .expect("invitation");
+
+        let state = AppState::new_for_tests(pool.clone());
+        let csrf_tokens = CsrfTokens::default();
+        let response = view_consent(
+            ConsentPath {
+                token: token.clone(),
+            },
+            Context::new(Locale::En),
+            State(state.clone()),
+            csrf_tokens.clone(),
+            db_connection(&state, None).await,
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        // the candidate sees the whole list
+        assert!(body.contains("Jansen"));
+        assert!(body.contains("Bakker"));
+        assert!(body.contains("Accept my position"));
+
+        let response = respond_consent(
+            ConsentPath {
+                token: token.clone(),
+            },
+            Context::new(Locale::En),
+            State(state.clone()),
+            csrf_tokens.clone(),
+            db_connection(&state, None).await,
+            consent_form(&csrf_tokens, "accepted"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+        // a later decision does not overwrite the first
+        respond_consent(
+            ConsentPath {
+                token: token.clone(),
+            },
+            Context::new(Locale::En),
+            State(state.clone()),
+            csrf_tokens.clone(),
+            db_connection(&state, None).await,
+            consent_form(&csrf_tokens, "declined"),
+        )
+        .await
+        .unwrap();
+
+        let candidate =
+            candidate_lists::repository::get_candidate(&mut conn, &list.id, &second.id).await?;
+        let consent = candidate.consent.expect("consent");
+        assert_eq!(consent.decision, ConsentDecision::Accepted);
+
+        // only the stored decision is recorded as a snapshot
+        let snapshots = candidate_lists::repository::list_snapshots(&mut conn, &list.id).await?;
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(
+            snapshots[0].label.as_deref(),
+            Some(format!("{} accepted their position", second.display_name()).as_str())
+        );
+
+        // moving the candidate clears their decision
+        candidate_lists::repository::update_candidate_list_order(
+            &mut conn,
+            &list.id,
+            &[second.id, first.id],
+        )
+        .await?;
+        let candidate =
+            candidate_lists::repository::get_candidate(&mut conn, &list.id, &second.id).await?;
+        assert_eq!(candidate.consent, None);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn candidates_respond_within_the_political_group_of_the_list(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let list = sample_candidate_list(Uuid::new_v4());
+        let person = sample_person(Uuid::new_v4());
+
+        let mut conn = pool.acquire().await?;
+        let group = auth::repository::create_political_group(&mut conn, "Lijst Smit").await?;
+        Tenant::PoliticalGroup(group).scope(&mut conn).await?;
+        candidate_lists::repository::create_candidate_list(&mut conn, &list).await?;
+        persons::repository::create_person(&mut conn, &person).await?;
+        candidate_lists::repository::update_candidate_list_order(&mut conn, &list.id, &[person.id])
+            .await?;
+
+        let token = random_token();
+        candidate_lists::repository::create_consent_invitation(
+            &mut conn,
+            &list.id,
+            &person.id,
+            &hash_token(&token),
+            Utc::now() + Duration::days(1),
+        )
+        .await?
+        .expect("invitation");
+
+        let state = AppState::new_for_tests(pool.clone());
+        let csrf_tokens = CsrfTokens::default();
+        let response = respond_consent(
+            ConsentPath { token },
+            Context::new(Locale::En),
+            State(state.clone()),
+            csrf_tokens.clone(),
+            db_connection(&state, None).await,
+            consent_form(&csrf_tokens, "declined"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+        let candidate =
+            candidate_lists::repository::get_candidate(&mut conn, &list.id, &person.id).await?;
+        let consent = candidate.consent.expect("consent");
+        assert_eq!(consent.decision, ConsentDecision::Declined);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn lists_without_political_group_have_no_links(pool: PgPool) -> Result<(), sqlx::Error> {
+        let list = sample_candidate_list(Uuid::new_v4());
+        let person = sample_person(Uuid::new_v4());
+
+        let mut conn = pool.acquire().await?;
+        candidate_lists::repository::create_candidate_list(&mut conn, &list).await?;
+        persons::repository::create_person(&mut conn, &person).await?;
+        candidate_lists::repository::update_candidate_list_order(&mut conn, &list.id, &[person.id])
+            .await?;
+
+        let token = random_token();
+        let invitation = candidate_lists::repository::create_consent_invitation(
+            &mut conn,
+            &list.id,
+            &person.id,
+            &hash_token(&token),
+            Utc::now() + Duration::days(1),
+        )
+        .await?;
+        assert_eq!(invitation, None);
+
+        // links handed out before lists had to belong to a political group
+        sqlx::query(
+            "INSERT INTO candidate_consent_invitations
+                (token_hash, candidate_list_id, person_id, political_group_id, expires_at)
+            VALUES ($1, $2, $3, NULL, NOW() + INTERVAL '1 day')",
+        )
+        .bind(hash_token(&token))
+        .bind(list.id)
+        .bind(person.id)
+        .execute(&mut *conn)
+        .await?;
+
+        let state = AppState::new_for_tests(pool.clone());
+        let csrf_tokens = CsrfTokens::default();
+        let result = respond_consent(
+            ConsentPath { token },
+            Context::new(Locale::En),
+            State(state.clone()),
+            csrf_tokens.clone(),
+            db_connection(&state, None).await,
+            consent_form(&csrf_tokens, "accepted"),
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        let candidate =
+            candidate_lists::repository::get_candidate(&mut conn, &list.id, &person.id).await?;
+        assert_eq!(candidate.consent, None);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn unknown_links_are_not_found(pool: PgPool) -> Result<(), sqlx::Error> {
+        let state = AppState::new_for_tests(pool);
+        let result = view_consent(
+            ConsentPath {
+                token: random_token(),
+            },
+            Context::new(Locale::En),
+            State(state.clone()),
+            CsrfTokens::default(),
+            db_connection(&state, None).await,
+        )
+        .await;
+
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        Ok(())
+    }
+}
@@ -1,9 +1,11 @@
 mod candidate_pages;
+mod consent_pages;
 mod pages;
 mod structs;
 
 pub mod repository;
 
 pub use candidate_pages::candidate_router;
-pub use pages::router;
+pub use consent_pages::consent_router;
+pub use pages::{candidate_list_not_found, router};
 pub use structs::*;
@@ -0,0 +1,93 @@
+use askama::Template;
+use axum::{extract::State, response::IntoResponse};
+
+use crate::{
+    AppError, AppState, Context, DbConnection, HtmlTemplate,
+    candidate_lists::{self, CandidateList, DistrictCoverage, pages::CandidateListCoveragePath},
+    filters, t,
+};
+
+#[derive(Template)]
+#[template(path = "candidate_lists/coverage.html")]
+struct DistrictCoverageTemplate {
+    coverage: DistrictCoverage,
+}
+
+pub async fn view_district_coverage(
+    _: CandidateListCoveragePath,
+    context: Context,
+    State(app_state): State<AppState>,
+    DbConnection(mut conn): DbConnection,
+) -> Result<impl IntoResponse, AppError> {
+    let full_lists = candidate_lists::repository::list_full_candidate_lists(&mut conn).await?;
+    let coverage = DistrictCoverage::new(app_state.config().get_districts(), full_lists);
+
+    Ok(HtmlTemplate(DistrictCoverageTemplate { coverage }, context))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        ElectoralDistrict, Locale, persons,
+        test_utils::{response_body_string, sample_person_with_last_name},
+    };
+
+    #[sqlx::test]
+    async fn coverage_shows_uncovered_districts_and_groups(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let app_state = AppState::new_for_tests(pool.clone());
+        let mut conn = pool.acquire().await?;
+        let jansen = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        let bakker = sample_person_with_last_name(Uuid::new_v4(), "Bakker");
+        persons::repository::create_person(&mut conn, &jansen).await?;
+        persons::repository::create_person(&mut conn, &bakker).await?;
+
+        let covered = [ElectoralDistrict::DR, ElectoralDistrict::UT];
+        for (district, order) in covered
+            .iter()
+            .zip([[jansen.id, bakker.id], [bakker.id, jansen.id]])
+        {
+            let list = CandidateList {
+                id: Uuid::new_v4(),
+                electoral_districts: vec![*district],
+                created_at: chrono::Utc::now(),
+                updated_at: chrono::Utc::now(),
+            };
+            candidate_lists::repository::create_candidate_list_with_candidates(
+                &mut conn, &list, &order,
+            )
+            .await?;
+        }
+
+        let response = view_district_coverage(
+            CandidateListCoveragePath {},
+            Context::new(Locale::En),
+            State(app_state),
+            DbConnection(pool.acquire().await?),
+        )
+        .await
+        .unwrap()
+        .into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        assert!(body.contains("List group 1"));
+        assert!(body.contains("Jansen"));
+        for district in ElectoralDistrict::ek2027() {
+            let warning = format!("No list for {}", district.title());
+            assert_eq!(
+                body.contains(&warning),
+                !covered.contains(district),
+                "{district:?}"
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -9,10 +9,12 @@ use crate::{
     AppError, AppState, Context, CsrfTokens, DbConnection, ElectionConfig, ElectoralDistrict,
     HtmlTemplate, Locale,
     candidate_lists::{
-        self, CandidateList, CandidateListForm, CandidateListSummary, pages::CandidateListNewPath,
+        self, CandidateList, CandidateListForm, CandidateListSort, CandidateListSummary,
+        pages::CandidateListNewPath,
     },
     filters,
-    form::{FormData, Validate},
+    form::{FormData, ValidateWith},
+    pagination::SortDirection,
     persons::{self, Person},
     t,
 };
@@ -35,8 +37,14 @@ pub async fn new_candidate_list_form(
     DbConnection(mut conn): DbConnection,
     State(app_state): State<AppState>,
 ) -> Result<impl IntoResponse, AppError> {
-    let candidate_lists =
-        candidate_lists::repository::list_candidate_list_with_count(&mut conn).await?;
+    let candidate_lists = candidate_lists::repository::list_candidate_list_summaries(
+        &mut conn,
+        None,
+        0,
+        &CandidateListSort::CreatedAt,
+        &SortDirection::Asc,
+    )
+    .await?;
     let total_persons = persons::repository::count_persons(&mut conn).await?;
     let election = app_state.config().election;
 
@@ -49,6 +57,7 @@ pub async fn new_candidate_list_form(
     let form = FormData::new_with_data(
         CandidateListForm {
             electoral_districts: available_districts,
+            version: String::new(),
             csrf_token: csrf_tokens.issue().value,
         },
         &csrf_tokens,
@@ -68,7 +77,7 @@ pub async fn new_candidate_list_form(
     .into_response())
 }
 
-fn determine_available_districts(
+pub(super) fn determine_available_districts(
     electoral_districts: &[ElectoralDistrict],
     used_districts: Vec<ElectoralDistrict>,
 ) -> Vec<ElectoralDistrict> {
@@ -89,10 +98,16 @@ pub async fn create_candidate_list(
 ) -> Result<Response, AppError> {
     let electoral_districts = app_state.config().election.electoral_districts();
 
-    match form.validate(None, &csrf_tokens) {
+    match form.validate_with(&app_state.config().election, None, &csrf_tokens) {
         Err(form_data) => {
-            let candidate_lists =
-                candidate_lists::repository::list_candidate_list_with_count(&mut conn).await?;
+            let candidate_lists = candidate_lists::repository::list_candidate_list_summaries(
+                &mut conn,
+                None,
+                0,
+                &CandidateListSort::CreatedAt,
+                &SortDirection::Asc,
+            )
+            .await?;
             let total_persons = persons::repository::count_persons(&mut conn).await?;
             let election = app_state.config().election;
 
@@ -166,6 +181,7 @@ mod test {
         let csrf_token = csrf_tokens.issue().value;
         let form = CandidateListForm {
             electoral_districts: vec![ElectoralDistrict::UT],
+            version: String::new(),
             csrf_token,
         };
 
@@ -189,7 +205,14 @@ mod test {
             .expect("location header value");
 
         let mut conn = pool.acquire().await?;
-        let lists = candidate_lists::repository::list_candidate_list_with_count(&mut conn).await?;
+        let lists = candidate_lists::repository::list_candidate_list_summaries(
+            &mut conn,
+            None,
+            0,
+            &CandidateListSort::CreatedAt,
+            &SortDirection::Asc,
+        )
+        .await?;
         assert_eq!(lists.len(), 1);
         assert_eq!(location, lists[0].list.view_path());
 
@@ -204,6 +227,7 @@ mod test {
         let csrf_tokens = CsrfTokens::default();
         let form = CandidateListForm {
             electoral_districts: vec![ElectoralDistrict::UT],
+            version: String::new(),
             csrf_token: TokenValue("invalid".to_string()),
         };
 
@@ -225,6 +249,45 @@ mod test {
         Ok(())
     }
 
+    #[sqlx::test]
+    async fn create_candidate_list_requires_a_district(pool: PgPool) -> Result<(), sqlx::Error> {
+        let app_state = AppState::new_for_tests(pool.clone());
+        let csrf_tokens = CsrfTokens::default();
+        let form = CandidateListForm {
+            electoral_districts: Vec::new(),
+            version: String::new(),
+            csrf_token: csrf_tokens.issue().value,
+        };
+
+        let response = create_candidate_list(
+            CandidateListNewPath {},
+            Context::new(Locale::En),
+            State(app_state),
+            csrf_tokens,
+            DbConnection(pool.acquire().await?),
+            Form(form),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(StatusCode::OK, response.status());
+        let body = response_body_string(response).await;
+        assert!(body.contains("Select at least 1 option(s)."));
+
+        let mut conn = pool.acquire().await?;
+        let lists = candidate_lists::repository::list_candidate_list_summaries(
+            &mut conn,
+            None,
+            0,
+            &CandidateListSort::CreatedAt,
+            &SortDirection::Asc,
+        )
+        .await?;
+        assert!(lists.is_empty());
+
+        Ok(())
+    }
+
     #[test]
     fn test_determine_available_districts() {
         // setup
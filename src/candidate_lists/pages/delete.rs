@@ -3,6 +3,7 @@ use axum::{
     response::{IntoResponse, Redirect, Response},
 };
 use axum_extra::extract::Form;
+use sqlx::Connection;
 
 use crate::{
     AppError, AppState, Context, CsrfTokens, DbConnection,
@@ -11,6 +12,7 @@ use crate::{
         pages::{CandidateListsDeletePath, candidate_list_not_found},
     },
     form::{EmptyForm, Validate},
+    t,
 };
 
 pub async fn delete_candidate_list(
@@ -30,7 +32,24 @@ pub async fn delete_candidate_list(
             Ok(Redirect::to(&candidate_list.update_path()).into_response())
         }
         Ok(_) => {
-            candidate_lists::repository::remove_candidate_list(&mut conn, id).await?;
+            // the list as it was deleted, while it can still be read
+            let mut tx = conn.begin().await?;
+            candidate_lists::repository::create_snapshot(
+                &mut tx,
+                &id,
+                Some(&t!(
+                    "candidate_list.snapshots.automatic.deleted",
+                    &context.locale
+                )),
+            )
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => candidate_list_not_found(id, context.locale),
+                e => e.into(),
+            })?;
+            candidate_lists::repository::remove_candidate_list(&mut tx, id).await?;
+            tx.commit().await?;
+
             Ok(Redirect::to(&CandidateList::list_path()).into_response())
         }
     }
@@ -50,7 +69,8 @@ mod tests {
 
     use crate::{
         AppState, Context, CsrfTokens, DbConnection, ElectoralDistrict, Locale, TokenValue,
-        candidate_lists,
+        candidate_lists::{self, CandidateListSort},
+        pagination::SortDirection,
     };
 
     #[sqlx::test]
@@ -93,9 +113,22 @@ mod tests {
 
         // verify deletion (i.e. no lists in database left)
         let mut conn = pool.acquire().await?;
-        let lists = candidate_lists::repository::list_candidate_list_with_count(&mut conn).await?;
+        let lists = candidate_lists::repository::list_candidate_list_summaries(
+            &mut conn,
+            None,
+            0,
+            &CandidateListSort::CreatedAt,
+            &SortDirection::Asc,
+        )
+        .await?;
         assert_eq!(lists.len(), 0);
 
+        // the list as it was deleted is kept as a snapshot
+        let snapshots =
+            candidate_lists::repository::list_snapshots(&mut conn, &candidate_list.id).await?;
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].label.as_deref(), Some("List deleted"));
+
         Ok(())
     }
 
@@ -141,7 +174,14 @@ mod tests {
 
         // verify deletion didn't go through (i.e. still 1 list in database left)
         let mut conn = pool.acquire().await?;
-        let lists = candidate_lists::repository::list_candidate_list_with_count(&mut conn).await?;
+        let lists = candidate_lists::repository::list_candidate_list_summaries(
+            &mut conn,
+            None,
+            0,
+            &CandidateListSort::CreatedAt,
+            &SortDirection::Asc,
+        )
+        .await?;
         assert_eq!(lists.len(), 1);
 
         Ok(())
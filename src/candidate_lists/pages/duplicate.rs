@@ -0,0 +1,290 @@
+use askama::Template;
+use axum::{
+    extract::State,
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::extract::Form;
+
+use crate::{
+    AppError, AppState, Context, CsrfTokens, DbConnection, ElectionConfig, ElectoralDistrict,
+    HtmlTemplate, Locale,
+    candidate_lists::{
+        self, CandidateList, CandidateListSort, CandidateListSummary, DuplicateCandidateListForm,
+        FullCandidateList,
+        pages::{
+            CandidateListDuplicatePath, create::determine_available_districts, load_candidate_list,
+        },
+    },
+    filters,
+    form::{FormData, ValidateWith},
+    pagination::SortDirection,
+    persons::{self, Person},
+    t,
+};
+
+#[derive(Template)]
+#[template(path = "candidate_lists/duplicate.html")]
+struct CandidateListDuplicateTemplate {
+    candidate_lists: Vec<CandidateListSummary>,
+    election: ElectionConfig,
+    total_persons: i64,
+    locale: Locale,
+    form: FormData<DuplicateCandidateListForm>,
+    full_list: FullCandidateList,
+    electoral_districts: &'static [ElectoralDistrict],
+}
+
+pub async fn duplicate_candidate_list_form(
+    CandidateListDuplicatePath { id }: CandidateListDuplicatePath,
+    context: Context,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+    State(app_state): State<AppState>,
+) -> Result<Response, AppError> {
+    let full_list = load_candidate_list(&mut conn, &id, context.locale).await?;
+    let candidate_lists = candidate_lists::repository::list_candidate_list_summaries(
+        &mut conn,
+        None,
+        0,
+        &CandidateListSort::CreatedAt,
+        &SortDirection::Asc,
+    )
+    .await?;
+    let total_persons = persons::repository::count_persons(&mut conn).await?;
+    let election = app_state.config().election;
+    let electoral_districts = app_state.config().get_districts();
+
+    // the template is usually submitted in the districts that do not have a list yet
+    let used_districts = candidate_lists::repository::get_used_districts(&mut conn).await?;
+    let form = FormData::new_with_data(
+        DuplicateCandidateListForm {
+            electoral_districts: determine_available_districts(electoral_districts, used_districts),
+            excluded_candidates: Vec::new(),
+            csrf_token: csrf_tokens.issue().value,
+        },
+        &csrf_tokens,
+    );
+
+    Ok(HtmlTemplate(
+        CandidateListDuplicateTemplate {
+            candidate_lists,
+            election,
+            total_persons,
+            locale: context.locale,
+            form,
+            full_list,
+            electoral_districts,
+        },
+        context,
+    )
+    .into_response())
+}
+
+pub async fn duplicate_candidate_list(
+    CandidateListDuplicatePath { id }: CandidateListDuplicatePath,
+    context: Context,
+    State(app_state): State<AppState>,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+    Form(form): Form<DuplicateCandidateListForm>,
+) -> Result<Response, AppError> {
+    let full_list = load_candidate_list(&mut conn, &id, context.locale).await?;
+    let excluded_candidates = form.excluded_candidates.clone();
+
+    match form.validate_with(&app_state.config().election, None, &csrf_tokens) {
+        Err(form_data) => {
+            let candidate_lists = candidate_lists::repository::list_candidate_list_summaries(
+                &mut conn,
+                None,
+                0,
+                &CandidateListSort::CreatedAt,
+                &SortDirection::Asc,
+            )
+            .await?;
+            let total_persons = persons::repository::count_persons(&mut conn).await?;
+
+            Ok(HtmlTemplate(
+                CandidateListDuplicateTemplate {
+                    candidate_lists,
+                    election: app_state.config().election,
+                    total_persons,
+                    locale: context.locale,
+                    form: form_data,
+                    full_list,
+                    electoral_districts: app_state.config().get_districts(),
+                },
+                context,
+            )
+            .into_response())
+        }
+        Ok(candidate_list) => {
+            let person_ids: Vec<_> = full_list
+                .candidates
+                .iter()
+                .map(|c| c.person.id)
+                .filter(|id| !excluded_candidates.contains(id))
+                .collect();
+            let created = candidate_lists::repository::create_candidate_list_with_candidates(
+                &mut conn,
+                &candidate_list,
+                &person_ids,
+            )
+            .await?;
+
+            Ok(Redirect::to(&created.list.view_path()).into_response())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{StatusCode, header};
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        TokenValue, candidate_lists,
+        test_utils::{response_body_string, sample_candidate_list, sample_person_with_last_name},
+    };
+
+    async fn insert_template(
+        pool: &PgPool,
+        last_names: &[&str],
+    ) -> Result<(CandidateList, Vec<Uuid>), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let list = sample_candidate_list(Uuid::new_v4());
+        candidate_lists::repository::create_candidate_list(&mut conn, &list).await?;
+
+        let mut person_ids = Vec::new();
+        for last_name in last_names {
+            let person = sample_person_with_last_name(Uuid::new_v4(), last_name);
+            persons::repository::create_person(&mut conn, &person).await?;
+            person_ids.push(person.id);
+        }
+        candidate_lists::repository::update_candidate_list_order(&mut conn, &list.id, &person_ids)
+            .await?;
+
+        Ok((list, person_ids))
+    }
+
+    #[sqlx::test]
+    async fn duplicate_form_prefills_unused_districts(pool: PgPool) -> Result<(), sqlx::Error> {
+        let app_state = AppState::new_for_tests(pool.clone());
+        let (list, _) = insert_template(&pool, &["Jansen", "Bakker"]).await?;
+
+        let response = duplicate_candidate_list_form(
+            CandidateListDuplicatePath { id: list.id },
+            Context::new(Locale::En),
+            CsrfTokens::default(),
+            DbConnection(pool.acquire().await?),
+            State(app_state),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        assert!(body.contains(&format!("action=\"{}\"", list.duplicate_path())));
+        assert!(body.contains("Jansen"));
+        assert!(body.contains("Bakker"));
+        for district in ElectoralDistrict::ek2027() {
+            let checkbox = format!(
+                "id=\"electoral_district_{}\" value=\"{}\"\n        checked",
+                district.code(),
+                district.code()
+            );
+            assert_eq!(
+                body.contains(&checkbox),
+                !list.electoral_districts.contains(district),
+                "{district:?}"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn duplicate_copies_candidates_without_excluded(pool: PgPool) -> Result<(), sqlx::Error> {
+        let app_state = AppState::new_for_tests(pool.clone());
+        let (list, person_ids) = insert_template(&pool, &["Jansen", "Bakker", "Visser"]).await?;
+        let csrf_tokens = CsrfTokens::default();
+        let form = DuplicateCandidateListForm {
+            electoral_districts: vec![ElectoralDistrict::DR],
+            excluded_candidates: vec![person_ids[1]],
+            csrf_token: csrf_tokens.issue().value,
+        };
+
+        let response = duplicate_candidate_list(
+            CandidateListDuplicatePath { id: list.id },
+            Context::new(Locale::En),
+            State(app_state),
+            csrf_tokens,
+            DbConnection(pool.acquire().await?),
+            Form(form),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        let location = response
+            .headers()
+            .get(header::LOCATION)
+            .expect("location header")
+            .to_str()
+            .expect("location header value")
+            .to_string();
+
+        let mut conn = pool.acquire().await?;
+        let lists = candidate_lists::repository::list_candidate_list(&mut conn).await?;
+        let copy = lists
+            .into_iter()
+            .find(|l| l.id != list.id)
+            .expect("duplicated list");
+        assert_eq!(location, copy.view_path());
+        assert_eq!(copy.electoral_districts, vec![ElectoralDistrict::DR]);
+
+        let copy = load_candidate_list(&mut conn, &copy.id, Locale::En)
+            .await
+            .expect("candidate list");
+        let order: Vec<Uuid> = copy.candidates.iter().map(|c| c.person.id).collect();
+        assert_eq!(order, vec![person_ids[0], person_ids[2]]);
+
+        // the template itself is unchanged
+        let template = load_candidate_list(&mut conn, &list.id, Locale::En)
+            .await
+            .expect("candidate list");
+        assert_eq!(template.candidates.len(), 3);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn duplicate_with_invalid_csrf_renders_form(pool: PgPool) -> Result<(), sqlx::Error> {
+        let app_state = AppState::new_for_tests(pool.clone());
+        let (list, _) = insert_template(&pool, &["Jansen"]).await?;
+        let form = DuplicateCandidateListForm {
+            electoral_districts: vec![ElectoralDistrict::DR],
+            excluded_candidates: Vec::new(),
+            csrf_token: TokenValue("invalid".to_string()),
+        };
+
+        let response = duplicate_candidate_list(
+            CandidateListDuplicatePath { id: list.id },
+            Context::new(Locale::En),
+            State(app_state),
+            CsrfTokens::default(),
+            DbConnection(pool.acquire().await?),
+            Form(form),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let mut conn = pool.acquire().await?;
+        let lists = candidate_lists::repository::list_candidate_list(&mut conn).await?;
+        assert_eq!(lists.len(), 1);
+
+        Ok(())
+    }
+}
@@ -3,8 +3,11 @@ use axum::{extract::State, response::IntoResponse};
 
 use crate::{
     AppError, AppState, Context, DbConnection, ElectionConfig, HtmlTemplate, Locale,
-    candidate_lists::{self, CandidateList, CandidateListSummary, pages::CandidateListsPath},
+    candidate_lists::{
+        self, CandidateList, CandidateListSort, CandidateListSummary, pages::CandidateListsPath,
+    },
     filters,
+    pagination::{Pagination, PaginationInfo},
     persons::{self, Person},
     t,
 };
@@ -13,6 +16,7 @@ use crate::{
 #[template(path = "candidate_lists/list.html")]
 struct CandidateListIndexTemplate {
     candidate_lists: Vec<CandidateListSummary>,
+    pagination: PaginationInfo<CandidateListSort>,
     election: ElectionConfig,
     total_persons: i64,
     locale: Locale,
@@ -22,16 +26,29 @@ pub async fn list_candidate_lists(
     _: CandidateListsPath,
     context: Context,
     State(app_state): State<AppState>,
+    pagination: Pagination<CandidateListSort>,
     DbConnection(mut conn): DbConnection,
 ) -> Result<impl IntoResponse, AppError> {
-    let candidate_lists =
-        candidate_lists::repository::list_candidate_list_with_count(&mut conn).await?;
+    let total_items = candidate_lists::repository::count_candidate_lists(&mut conn)
+        .await?
+        .max(0) as u64;
+    let pagination = pagination.set_total(total_items);
+
+    let candidate_lists = candidate_lists::repository::list_candidate_list_summaries(
+        &mut conn,
+        Some(pagination.limit()),
+        pagination.offset(),
+        pagination.sort(),
+        pagination.direction(),
+    )
+    .await?;
     let total_persons = persons::repository::count_persons(&mut conn).await?;
     let election = app_state.config().election;
 
     Ok(HtmlTemplate(
         CandidateListIndexTemplate {
             candidate_lists,
+            pagination,
             election,
             locale: context.locale,
             total_persons,
@@ -62,6 +79,7 @@ mod tests {
             CandidateListsPath {},
             Context::new(Locale::En),
             State(AppState::new_for_tests(pool.clone())),
+            Pagination::default(),
             DbConnection(pool.acquire().await?),
         )
         .await
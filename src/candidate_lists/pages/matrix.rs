@@ -0,0 +1,202 @@
+use askama::Template;
+use axum::{
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use sqlx::PgConnection;
+
+use crate::{
+    AppError, AppState, Context, DbConnection, ElectionConfig, HtmlTemplate, Locale,
+    candidate_lists::{
+        self, CandidateList, CandidateMatrix, CandidateMatrixQuery,
+        pages::{CandidateMatrixExportPath, CandidateMatrixPath},
+    },
+    filters, t,
+};
+
+#[derive(Template)]
+#[template(path = "candidate_lists/matrix.html")]
+struct CandidateMatrixTemplate {
+    matrix: CandidateMatrix,
+    query: CandidateMatrixQuery,
+    election: ElectionConfig,
+    locale: Locale,
+}
+
+async fn load_matrix(
+    conn: &mut PgConnection,
+    query: &CandidateMatrixQuery,
+) -> Result<CandidateMatrix, AppError> {
+    let lists = candidate_lists::repository::list_candidate_list(conn).await?;
+    let entries = candidate_lists::repository::list_candidate_matrix(conn).await?;
+
+    Ok(CandidateMatrix::new(lists, entries).apply(query))
+}
+
+pub async fn view_candidate_matrix(
+    _: CandidateMatrixPath,
+    context: Context,
+    State(app_state): State<AppState>,
+    Query(query): Query<CandidateMatrixQuery>,
+    DbConnection(mut conn): DbConnection,
+) -> Result<impl IntoResponse, AppError> {
+    let matrix = load_matrix(&mut conn, &query).await?;
+
+    Ok(HtmlTemplate(
+        CandidateMatrixTemplate {
+            matrix,
+            query,
+            election: app_state.config().election,
+            locale: context.locale,
+        },
+        context,
+    ))
+}
+
+pub async fn export_candidate_matrix(
+    _: CandidateMatrixExportPath,
+    context: Context,
+    State(app_state): State<AppState>,
+    Query(query): Query<CandidateMatrixQuery>,
+    DbConnection(mut conn): DbConnection,
+) -> Result<Response, AppError> {
+    let matrix = load_matrix(&mut conn, &query).await?;
+    let csv = matrix.to_csv(&app_state.config().election, &context.locale)?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"candidates.csv\"",
+            ),
+        ],
+        csv,
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        ElectoralDistrict, persons,
+        test_utils::{response_body_string, sample_person_with_last_name},
+    };
+
+    async fn insert_lists(pool: &PgPool) -> Result<Vec<CandidateList>, sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let jansen = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        let bakker = sample_person_with_last_name(Uuid::new_v4(), "Bakker");
+        let visser = sample_person_with_last_name(Uuid::new_v4(), "Visser");
+        for person in [&jansen, &bakker, &visser] {
+            persons::repository::create_person(&mut conn, person).await?;
+        }
+
+        let mut lists = Vec::new();
+        for (district, order) in [
+            (ElectoralDistrict::DR, vec![jansen.id, bakker.id]),
+            (ElectoralDistrict::UT, vec![bakker.id, jansen.id]),
+        ] {
+            let list = CandidateList {
+                id: Uuid::new_v4(),
+                electoral_districts: vec![district],
+                created_at: chrono::Utc::now(),
+                updated_at: chrono::Utc::now(),
+            };
+            let full_list = candidate_lists::repository::create_candidate_list_with_candidates(
+                &mut conn, &list, &order,
+            )
+            .await?;
+            lists.push(full_list.list);
+        }
+
+        Ok(lists)
+    }
+
+    #[sqlx::test]
+    async fn matrix_aggregates_positions_per_person(pool: PgPool) -> Result<(), sqlx::Error> {
+        let lists = insert_lists(&pool).await?;
+        let mut conn = pool.acquire().await?;
+
+        let entries = candidate_lists::repository::list_candidate_matrix(&mut conn).await?;
+
+        // Visser is on no list
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].person.last_name, "Bakker");
+        assert_eq!(
+            entries[0].positions,
+            vec![(lists[0].id, 2), (lists[1].id, 1)]
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn view_candidate_matrix_renders_positions(pool: PgPool) -> Result<(), sqlx::Error> {
+        let app_state = AppState::new_for_tests(pool.clone());
+        let lists = insert_lists(&pool).await?;
+
+        let response = view_candidate_matrix(
+            CandidateMatrixPath {},
+            Context::new(Locale::En),
+            State(app_state),
+            Query(CandidateMatrixQuery {
+                sort_list: Some(lists[1].id),
+                ..Default::default()
+            }),
+            DbConnection(pool.acquire().await?),
+        )
+        .await
+        .unwrap()
+        .into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        assert!(body.contains("List 2 (Utrecht)"));
+        let bakker = body.find("Bakker").expect("Bakker");
+        let jansen = body.find("Jansen").expect("Jansen");
+        assert!(bakker < jansen);
+        assert!(!body.contains("Visser"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn export_candidate_matrix_returns_csv(pool: PgPool) -> Result<(), sqlx::Error> {
+        let app_state = AppState::new_for_tests(pool.clone());
+        insert_lists(&pool).await?;
+
+        let response = export_candidate_matrix(
+            CandidateMatrixExportPath {},
+            Context::new(Locale::En),
+            State(app_state),
+            Query(CandidateMatrixQuery {
+                search: Some("jansen".to_string()),
+                ..Default::default()
+            }),
+            DbConnection(pool.acquire().await?),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/csv; charset=utf-8"
+        );
+        let body = response_body_string(response).await;
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(",List 1 (Drenthe),List 2 (Utrecht)"));
+        assert!(lines[1].starts_with("Jansen,"));
+        assert!(lines[1].ends_with(",1,2"));
+
+        Ok(())
+    }
+}
@@ -1,19 +1,25 @@
-use axum::Router;
+use axum::{Router, middleware};
 use axum_extra::routing::{RouterExt, TypedPath};
 use serde::Deserialize;
 use sqlx::PgConnection;
 use uuid::Uuid;
 
 use crate::{
-    AppError, AppState, Locale,
+    AppError, AppState, Locale, Permission,
+    auth::require_permission,
     candidate_lists::{self, CandidateList, FullCandidateList},
     t,
 };
 
+mod coverage;
 mod create;
 mod delete;
+mod duplicate;
 mod list;
+mod matrix;
 mod reorder;
+mod snapshots;
+mod undo;
 mod update;
 mod view;
 
@@ -25,6 +31,18 @@ pub struct CandidateListsPath;
 #[typed_path("/candidate-lists/new", rejection(AppError))]
 pub struct CandidateListNewPath;
 
+#[derive(TypedPath)]
+#[typed_path("/candidate-lists/coverage", rejection(AppError))]
+pub struct CandidateListCoveragePath;
+
+#[derive(TypedPath)]
+#[typed_path("/candidate-lists/matrix", rejection(AppError))]
+pub struct CandidateMatrixPath;
+
+#[derive(TypedPath)]
+#[typed_path("/candidate-lists/matrix/export", rejection(AppError))]
+pub struct CandidateMatrixExportPath;
+
 #[derive(TypedPath, Deserialize)]
 #[typed_path("/candidate-lists/{id}", rejection(AppError))]
 pub struct ViewCandidateListPath {
@@ -43,12 +61,42 @@ pub struct CandidateListsDeletePath {
     pub id: Uuid,
 }
 
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/candidate-lists/{id}/duplicate", rejection(AppError))]
+pub struct CandidateListDuplicatePath {
+    pub id: Uuid,
+}
+
 #[derive(TypedPath, Deserialize)]
 #[typed_path("/candidate-lists/{id}/reorder", rejection(AppError))]
 pub struct CandidateListReorderPath {
     pub id: Uuid,
 }
 
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/candidate-lists/{id}/positions", rejection(AppError))]
+pub struct CandidateListPositionsPath {
+    pub id: Uuid,
+}
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/candidate-lists/{id}/undo", rejection(AppError))]
+pub struct CandidateListUndoPath {
+    pub id: Uuid,
+}
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/candidate-lists/{id}/snapshots", rejection(AppError))]
+pub struct CandidateListSnapshotsPath {
+    pub id: Uuid,
+}
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/candidate-lists/{id}/snapshots/compare", rejection(AppError))]
+pub struct CompareSnapshotsPath {
+    pub id: Uuid,
+}
+
 #[derive(TypedPath, Deserialize)]
 #[typed_path("/candidate-lists/{id}/add", rejection(AppError))]
 pub struct AddCandidatePath {
@@ -70,6 +118,18 @@ impl CandidateList {
         CandidateListNewPath {}.to_string()
     }
 
+    pub fn coverage_path() -> String {
+        CandidateListCoveragePath {}.to_string()
+    }
+
+    pub fn matrix_path() -> String {
+        CandidateMatrixPath {}.to_string()
+    }
+
+    pub fn matrix_export_path() -> String {
+        CandidateMatrixExportPath {}.to_string()
+    }
+
     pub fn update_path(&self) -> String {
         CandidateListsEditPath { id: self.id }.to_string()
     }
@@ -82,10 +142,26 @@ impl CandidateList {
         ViewCandidateListPath { id: self.id }.to_string()
     }
 
+    pub fn duplicate_path(&self) -> String {
+        CandidateListDuplicatePath { id: self.id }.to_string()
+    }
+
     pub fn reorder_path(&self) -> String {
         CandidateListReorderPath { id: self.id }.to_string()
     }
 
+    pub fn undo_path(&self) -> String {
+        CandidateListUndoPath { id: self.id }.to_string()
+    }
+
+    pub fn snapshots_path(&self) -> String {
+        CandidateListSnapshotsPath { id: self.id }.to_string()
+    }
+
+    pub fn compare_snapshots_path(&self) -> String {
+        CompareSnapshotsPath { id: self.id }.to_string()
+    }
+
     pub fn add_candidate_path(&self) -> String {
         AddCandidatePath { id: self.id }.to_string()
     }
@@ -99,17 +175,49 @@ impl CandidateList {
 }
 
 pub fn router() -> Router<AppState> {
-    Router::new()
-        // manage lists
+    let view = Router::new()
         .typed_get(list::list_candidate_lists)
+        .typed_get(coverage::view_district_coverage)
+        .typed_get(matrix::view_candidate_matrix)
+        .typed_get(matrix::export_candidate_matrix)
+        .typed_get(view::view_candidate_list)
+        .typed_get(snapshots::list_snapshots)
+        .typed_get(snapshots::compare_snapshots)
+        .route_layer(middleware::from_fn_with_state(
+            Permission::ViewCandidateLists,
+            require_permission,
+        ));
+
+    let edit = Router::new()
         .typed_get(create::new_candidate_list_form)
         .typed_post(create::create_candidate_list)
-        // manage single list
-        .typed_get(view::view_candidate_list)
         .typed_get(update::edit_candidate_list)
         .typed_post(update::update_candidate_list)
-        .typed_post(delete::delete_candidate_list)
+        .typed_get(duplicate::duplicate_candidate_list_form)
+        .typed_post(duplicate::duplicate_candidate_list)
+        .typed_post(snapshots::create_snapshot)
+        .route_layer(middleware::from_fn_with_state(
+            Permission::EditCandidateLists,
+            require_permission,
+        ));
+
+    let edit_candidates = Router::new()
         .typed_post(reorder::reorder_candidate_list)
+        .typed_post(reorder::apply_position_operation)
+        .typed_post(undo::undo_candidate_list_change)
+        .route_layer(middleware::from_fn_with_state(
+            Permission::EditCandidates,
+            require_permission,
+        ));
+
+    let delete = Router::new()
+        .typed_post(delete::delete_candidate_list)
+        .route_layer(middleware::from_fn_with_state(
+            Permission::DeleteCandidateLists,
+            require_permission,
+        ));
+
+    view.merge(edit).merge(edit_candidates).merge(delete)
 }
 
 pub fn candidate_list_not_found(id: Uuid, locale: Locale) -> AppError {
@@ -1,31 +1,139 @@
-use axum::{Json, http::StatusCode, response::IntoResponse};
-use serde::Deserialize;
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Connection, PgConnection};
 use uuid::Uuid;
 
 use crate::{
-    AppError, Context, DbConnection,
+    AppError, Context, DbConnection, Locale,
     candidate_lists::{
-        self,
-        pages::{CandidateListReorderPath, load_candidate_list},
+        self, CandidateListUndo, PositionOperation, UndoAction, UndoEntry,
+        pages::{CandidateListPositionsPath, CandidateListReorderPath, load_candidate_list},
     },
+    form::parse_version,
 };
 
 #[derive(Deserialize)]
 pub struct CandidateListReorderPayload {
     pub person_ids: Vec<Uuid>,
+    /// Version of the list the new order is based on.
+    pub version: String,
+}
+
+#[derive(Deserialize)]
+pub struct CandidateListPositionPayload {
+    #[serde(flatten)]
+    pub operation: PositionOperation,
+    /// Version of the list the operation is based on.
+    pub version: String,
+}
+
+/// Version of the list after the reorder, or the stored version on a conflict.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CandidateListReorderResponse {
+    pub version: String,
+}
+
+/// Lock the list for the rest of the transaction, when it is still at the version the change is
+/// based on. Checking the version as part of the lock keeps concurrent changes based on the same
+/// version from overwriting each other.
+async fn lock_at_version(
+    conn: &mut PgConnection,
+    id: &Uuid,
+    version: &str,
+) -> Result<bool, AppError> {
+    let Some(version) = parse_version(version) else {
+        return Ok(false);
+    };
+
+    Ok(candidate_lists::repository::lock_candidate_list_version(conn, id, &version).await?)
+}
+
+/// The list changed since the page was loaded; let the user decide whether to reapply.
+async fn conflict(
+    conn: &mut PgConnection,
+    id: &Uuid,
+    locale: Locale,
+) -> Result<Response, AppError> {
+    let full_list = load_candidate_list(conn, id, locale).await?;
+    let current = CandidateListReorderResponse {
+        version: full_list.list.version(),
+    };
+
+    Ok((StatusCode::CONFLICT, Json(current)).into_response())
 }
 
 pub async fn reorder_candidate_list(
     CandidateListReorderPath { id }: CandidateListReorderPath,
     context: Context,
+    undo: CandidateListUndo,
     DbConnection(mut conn): DbConnection,
     Json(payload): Json<CandidateListReorderPayload>,
-) -> Result<impl IntoResponse, AppError> {
-    load_candidate_list(&mut conn, &id, context.locale).await?;
-    candidate_lists::repository::update_candidate_list_order(&mut conn, &id, &payload.person_ids)
-        .await?;
+) -> Result<Response, AppError> {
+    let mut tx = conn.begin().await?;
+    if !lock_at_version(&mut tx, &id, &payload.version).await? {
+        tx.rollback().await?;
+        return conflict(&mut conn, &id, context.locale).await;
+    }
+    let previous = candidate_lists::repository::lock_candidate_list_entries(&mut tx, &id).await?;
+    let full_list =
+        candidate_lists::repository::reorder_candidates(&mut tx, &id, &payload.person_ids)
+            .await
+            .map_err(|e| e.into_app_error(context.locale))?;
+    let current = candidate_lists::repository::lock_candidate_list_entries(&mut tx, &id).await?;
+    tx.commit().await?;
+
+    undo.push(
+        id,
+        UndoEntry::new(UndoAction::Reordered, previous, &current),
+    );
+
+    Ok(Json(CandidateListReorderResponse {
+        version: full_list.list.version(),
+    })
+    .into_response())
+}
+
+pub async fn apply_position_operation(
+    CandidateListPositionsPath { id }: CandidateListPositionsPath,
+    context: Context,
+    undo: CandidateListUndo,
+    DbConnection(mut conn): DbConnection,
+    Json(payload): Json<CandidateListPositionPayload>,
+) -> Result<Response, AppError> {
+    // adding a candidate is not destructive, so there is nothing to offer an undo for
+    let action = match payload.operation {
+        PositionOperation::InsertAt { .. } => None,
+        PositionOperation::Remove { .. } => Some(UndoAction::Removed),
+        PositionOperation::Move { .. } | PositionOperation::Swap { .. } => {
+            Some(UndoAction::Reordered)
+        }
+    };
+
+    let mut tx = conn.begin().await?;
+    if !lock_at_version(&mut tx, &id, &payload.version).await? {
+        tx.rollback().await?;
+        return conflict(&mut conn, &id, context.locale).await;
+    }
+    let previous = candidate_lists::repository::lock_candidate_list_entries(&mut tx, &id).await?;
+    let full_list =
+        candidate_lists::repository::apply_position_operation(&mut tx, &id, payload.operation)
+            .await
+            .map_err(|e| e.into_app_error(context.locale))?;
+    let current = candidate_lists::repository::lock_candidate_list_entries(&mut tx, &id).await?;
+    tx.commit().await?;
+
+    if let Some(action) = action {
+        undo.push(id, UndoEntry::new(action, previous, &current));
+    }
 
-    Ok(StatusCode::NO_CONTENT)
+    Ok(Json(CandidateListReorderResponse {
+        version: full_list.list.version(),
+    })
+    .into_response())
 }
 
 #[cfg(test)]
@@ -36,7 +144,7 @@ mod tests {
 
     use crate::{
         Context, DbConnection, Locale, candidate_lists, persons,
-        test_utils::{sample_candidate_list, sample_person_with_last_name},
+        test_utils::{response_body_string, sample_candidate_list, sample_person_with_last_name},
     };
 
     #[sqlx::test]
@@ -50,26 +158,30 @@ mod tests {
         candidate_lists::repository::create_candidate_list(&mut conn, &list).await?;
         persons::repository::create_person(&mut conn, &person_a).await?;
         persons::repository::create_person(&mut conn, &person_b).await?;
-        candidate_lists::repository::update_candidate_list_order(
+        let before = candidate_lists::repository::update_candidate_list_order(
             &mut conn,
             &list_id,
             &[person_a.id, person_b.id],
         )
         .await?;
 
+        let undo = CandidateListUndo::default();
         let response = reorder_candidate_list(
             CandidateListReorderPath { id: list_id },
             Context::new(Locale::En),
+            undo.clone(),
             DbConnection(pool.acquire().await?),
             Json(CandidateListReorderPayload {
                 person_ids: vec![person_b.id, person_a.id],
+                version: before.list.version(),
             }),
         )
         .await
-        .unwrap()
-        .into_response();
+        .unwrap();
 
-        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: CandidateListReorderResponse =
+            serde_json::from_str(&response_body_string(response).await).unwrap();
 
         let mut conn = pool.acquire().await?;
         let full_list = load_candidate_list(&mut conn, &list_id, Locale::En)
@@ -78,6 +190,218 @@ mod tests {
         assert_eq!(full_list.candidates.len(), 2);
         assert_eq!(full_list.candidates[0].person.id, person_b.id);
         assert_eq!(full_list.candidates[1].person.id, person_a.id);
+        assert_eq!(body.version, full_list.list.version());
+        assert_eq!(undo.latest(list_id), Some(UndoAction::Reordered));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn reorder_candidate_list_rejects_stale_version(pool: PgPool) -> Result<(), sqlx::Error> {
+        let list_id = Uuid::new_v4();
+        let list = sample_candidate_list(list_id);
+        let person_a = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        let person_b = sample_person_with_last_name(Uuid::new_v4(), "Bakker");
+
+        let mut conn = pool.acquire().await?;
+        candidate_lists::repository::create_candidate_list(&mut conn, &list).await?;
+        persons::repository::create_person(&mut conn, &person_a).await?;
+        persons::repository::create_person(&mut conn, &person_b).await?;
+        let loaded = candidate_lists::repository::update_candidate_list_order(
+            &mut conn,
+            &list_id,
+            &[person_a.id, person_b.id],
+        )
+        .await?;
+        // someone else changes the list after it was loaded
+        let current = candidate_lists::repository::update_candidate_list_order(
+            &mut conn,
+            &list_id,
+            &[person_a.id, person_b.id],
+        )
+        .await?;
+
+        let response = reorder_candidate_list(
+            CandidateListReorderPath { id: list_id },
+            Context::new(Locale::En),
+            CandidateListUndo::default(),
+            DbConnection(pool.acquire().await?),
+            Json(CandidateListReorderPayload {
+                person_ids: vec![person_b.id, person_a.id],
+                version: loaded.list.version(),
+            }),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body: CandidateListReorderResponse =
+            serde_json::from_str(&response_body_string(response).await).unwrap();
+        assert_eq!(body.version, current.list.version());
+
+        let full_list = load_candidate_list(&mut conn, &list_id, Locale::En)
+            .await
+            .expect("candidate list");
+        assert_eq!(full_list.candidates[0].person.id, person_a.id);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn concurrent_reorders_of_the_same_version_conflict(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let list_id = Uuid::new_v4();
+        let list = sample_candidate_list(list_id);
+        let person_a = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        let person_b = sample_person_with_last_name(Uuid::new_v4(), "Bakker");
+        let person_c = sample_person_with_last_name(Uuid::new_v4(), "Visser");
+
+        let mut conn = pool.acquire().await?;
+        candidate_lists::repository::create_candidate_list(&mut conn, &list).await?;
+        for person in [&person_a, &person_b, &person_c] {
+            persons::repository::create_person(&mut conn, person).await?;
+        }
+        let loaded = candidate_lists::repository::update_candidate_list_order(
+            &mut conn,
+            &list_id,
+            &[person_a.id, person_b.id, person_c.id],
+        )
+        .await?;
+
+        let reorder = |person_ids: Vec<Uuid>, conn| {
+            reorder_candidate_list(
+                CandidateListReorderPath { id: list_id },
+                Context::new(Locale::En),
+                CandidateListUndo::default(),
+                DbConnection(conn),
+                Json(CandidateListReorderPayload {
+                    person_ids,
+                    version: loaded.list.version(),
+                }),
+            )
+        };
+        let (first, second) = tokio::join!(
+            reorder(
+                vec![person_b.id, person_a.id, person_c.id],
+                pool.acquire().await?
+            ),
+            reorder(
+                vec![person_c.id, person_b.id, person_a.id],
+                pool.acquire().await?
+            ),
+        );
+
+        let mut statuses = vec![first.unwrap().status(), second.unwrap().status()];
+        statuses.sort();
+        assert_eq!(statuses, vec![StatusCode::OK, StatusCode::CONFLICT]);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn reorder_candidate_list_rejects_non_permutation(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let list_id = Uuid::new_v4();
+        let list = sample_candidate_list(list_id);
+        let person_a = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        let person_b = sample_person_with_last_name(Uuid::new_v4(), "Bakker");
+
+        let mut conn = pool.acquire().await?;
+        candidate_lists::repository::create_candidate_list(&mut conn, &list).await?;
+        persons::repository::create_person(&mut conn, &person_a).await?;
+        persons::repository::create_person(&mut conn, &person_b).await?;
+        let before = candidate_lists::repository::update_candidate_list_order(
+            &mut conn,
+            &list_id,
+            &[person_a.id, person_b.id],
+        )
+        .await?;
+
+        let result = reorder_candidate_list(
+            CandidateListReorderPath { id: list_id },
+            Context::new(Locale::En),
+            CandidateListUndo::default(),
+            DbConnection(pool.acquire().await?),
+            Json(CandidateListReorderPayload {
+                person_ids: vec![person_b.id, person_b.id],
+                version: before.list.version(),
+            }),
+        )
+        .await;
+
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+
+        let full_list = load_candidate_list(&mut conn, &list_id, Locale::En)
+            .await
+            .expect("candidate list");
+        assert_eq!(full_list.candidates[0].person.id, person_a.id);
+        assert_eq!(full_list.candidates[1].person.id, person_b.id);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn apply_position_operation_moves_candidate(pool: PgPool) -> Result<(), sqlx::Error> {
+        let list_id = Uuid::new_v4();
+        let list = sample_candidate_list(list_id);
+        let person_a = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        let person_b = sample_person_with_last_name(Uuid::new_v4(), "Bakker");
+        let person_c = sample_person_with_last_name(Uuid::new_v4(), "Visser");
+
+        let mut conn = pool.acquire().await?;
+        candidate_lists::repository::create_candidate_list(&mut conn, &list).await?;
+        persons::repository::create_person(&mut conn, &person_a).await?;
+        persons::repository::create_person(&mut conn, &person_b).await?;
+        persons::repository::create_person(&mut conn, &person_c).await?;
+        let before = candidate_lists::repository::update_candidate_list_order(
+            &mut conn,
+            &list_id,
+            &[person_a.id, person_b.id, person_c.id],
+        )
+        .await?;
+
+        let payload: CandidateListPositionPayload = serde_json::from_value(serde_json::json!({
+            "operation": "move",
+            "person_id": person_c.id,
+            "position": 1,
+            "version": before.list.version(),
+        }))
+        .unwrap();
+        let response = apply_position_operation(
+            CandidateListPositionsPath { id: list_id },
+            Context::new(Locale::En),
+            CandidateListUndo::default(),
+            DbConnection(pool.acquire().await?),
+            Json(payload),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let full_list = load_candidate_list(&mut conn, &list_id, Locale::En)
+            .await
+            .expect("candidate list");
+        let order: Vec<Uuid> = full_list.candidates.iter().map(|c| c.person.id).collect();
+        assert_eq!(order, vec![person_c.id, person_a.id, person_b.id]);
+
+        // the old version is now stale
+        let response = apply_position_operation(
+            CandidateListPositionsPath { id: list_id },
+            Context::new(Locale::En),
+            CandidateListUndo::default(),
+            DbConnection(pool.acquire().await?),
+            Json(CandidateListPositionPayload {
+                operation: PositionOperation::Remove {
+                    person_id: person_a.id,
+                },
+                version: before.list.version(),
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
 
         Ok(())
     }
@@ -0,0 +1,239 @@
+use askama::Template;
+use axum::{
+    extract::Query,
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::extract::Form;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    AppError, Context, CsrfTokens, DbConnection, HtmlTemplate, Locale,
+    candidate_lists::{
+        self, CandidateList, CandidateListDiff, CandidateListSnapshot, FullCandidateList,
+        SnapshotForm,
+        pages::{CandidateListSnapshotsPath, CompareSnapshotsPath, load_candidate_list},
+    },
+    filters,
+    form::{FormData, Validate},
+    pagination::empty_as_none,
+    t,
+};
+
+#[derive(Template)]
+#[template(path = "candidate_lists/snapshots.html")]
+struct CandidateListSnapshotsTemplate {
+    full_list: FullCandidateList,
+    snapshots: Vec<CandidateListSnapshot>,
+    form: FormData<SnapshotForm>,
+}
+
+#[derive(Template)]
+#[template(path = "candidate_lists/snapshot_diff.html")]
+struct SnapshotDiffTemplate {
+    list: CandidateList,
+    from: CandidateListSnapshot,
+    /// `None` when comparing with the current state of the list.
+    to: Option<CandidateListSnapshot>,
+    diff: CandidateListDiff,
+}
+
+#[derive(Deserialize)]
+pub struct CompareQuery {
+    pub from: i32,
+    /// Empty or missing compares with the current list.
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub to: Option<i32>,
+}
+
+fn snapshot_not_found(version: i32, locale: Locale) -> AppError {
+    AppError::NotFound(t!("candidate_list.snapshots.not_found", &locale, version))
+}
+
+pub async fn list_snapshots(
+    CandidateListSnapshotsPath { id }: CandidateListSnapshotsPath,
+    context: Context,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+) -> Result<impl IntoResponse, AppError> {
+    let full_list = load_candidate_list(&mut conn, &id, context.locale).await?;
+    let snapshots = candidate_lists::repository::list_snapshots(&mut conn, &id).await?;
+
+    Ok(HtmlTemplate(
+        CandidateListSnapshotsTemplate {
+            full_list,
+            snapshots,
+            form: FormData::new(&csrf_tokens),
+        },
+        context,
+    ))
+}
+
+pub async fn create_snapshot(
+    CandidateListSnapshotsPath { id }: CandidateListSnapshotsPath,
+    context: Context,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+    form: Form<SnapshotForm>,
+) -> Result<Response, AppError> {
+    let full_list = load_candidate_list(&mut conn, &id, context.locale).await?;
+
+    match form.validate(None, &csrf_tokens) {
+        Err(form_data) => {
+            let snapshots = candidate_lists::repository::list_snapshots(&mut conn, &id).await?;
+
+            Ok(HtmlTemplate(
+                CandidateListSnapshotsTemplate {
+                    full_list,
+                    snapshots,
+                    form: form_data,
+                },
+                context,
+            )
+            .into_response())
+        }
+        Ok(label) => {
+            candidate_lists::repository::create_snapshot(&mut conn, &id, label.as_deref()).await?;
+
+            Ok(Redirect::to(&full_list.list.snapshots_path()).into_response())
+        }
+    }
+}
+
+pub async fn compare_snapshots(
+    CompareSnapshotsPath { id }: CompareSnapshotsPath,
+    context: Context,
+    Query(query): Query<CompareQuery>,
+    DbConnection(mut conn): DbConnection,
+) -> Result<impl IntoResponse, AppError> {
+    let current = load_candidate_list(&mut conn, &id, context.locale).await?;
+
+    let from = get_snapshot(&mut conn, &id, query.from, context.locale).await?;
+    let to = match query.to {
+        Some(version) => Some(get_snapshot(&mut conn, &id, version, context.locale).await?),
+        None => None,
+    };
+
+    let new = to.as_ref().map_or(&current, |to| &to.full_list);
+    let diff = CandidateListDiff::between(&from.full_list, new, context.locale);
+
+    Ok(HtmlTemplate(
+        SnapshotDiffTemplate {
+            list: current.list,
+            from,
+            to,
+            diff,
+        },
+        context,
+    ))
+}
+
+async fn get_snapshot(
+    conn: &mut sqlx::PgConnection,
+    id: &Uuid,
+    version: i32,
+    locale: Locale,
+) -> Result<CandidateListSnapshot, AppError> {
+    candidate_lists::repository::get_snapshot(conn, id, version)
+        .await?
+        .ok_or_else(|| snapshot_not_found(version, locale))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{StatusCode, header};
+    use sqlx::PgPool;
+
+    use crate::{
+        persons,
+        test_utils::{response_body_string, sample_candidate_list, sample_person_with_last_name},
+    };
+
+    #[sqlx::test]
+    async fn create_snapshot_stores_label_and_redirects(pool: PgPool) -> Result<(), sqlx::Error> {
+        let list = sample_candidate_list(Uuid::new_v4());
+        let mut conn = pool.acquire().await?;
+        candidate_lists::repository::create_candidate_list(&mut conn, &list).await?;
+
+        let csrf_tokens = CsrfTokens::default();
+        let form = SnapshotForm {
+            label: "H1 printed".to_string(),
+            csrf_token: csrf_tokens.issue().value,
+        };
+        let response = create_snapshot(
+            CandidateListSnapshotsPath { id: list.id },
+            Context::new(Locale::En),
+            csrf_tokens,
+            DbConnection(pool.acquire().await?),
+            Form(form),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        let location = response
+            .headers()
+            .get(header::LOCATION)
+            .expect("location header")
+            .to_str()
+            .expect("location header value");
+        assert_eq!(location, list.snapshots_path());
+
+        let snapshots = candidate_lists::repository::list_snapshots(&mut conn, &list.id).await?;
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].label.as_deref(), Some("H1 printed"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn compare_snapshot_with_current_state(pool: PgPool) -> Result<(), sqlx::Error> {
+        let list = sample_candidate_list(Uuid::new_v4());
+        let first = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        let second = sample_person_with_last_name(Uuid::new_v4(), "Bakker");
+
+        let mut conn = pool.acquire().await?;
+        candidate_lists::repository::create_candidate_list(&mut conn, &list).await?;
+        persons::repository::create_person(&mut conn, &first).await?;
+        persons::repository::create_person(&mut conn, &second).await?;
+        candidate_lists::repository::update_candidate_list_order(&mut conn, &list.id, &[first.id])
+            .await?;
+        candidate_lists::repository::create_snapshot(&mut conn, &list.id, None).await?;
+        candidate_lists::repository::update_candidate_list_order(
+            &mut conn,
+            &list.id,
+            &[second.id, first.id],
+        )
+        .await?;
+
+        let response = compare_snapshots(
+            CompareSnapshotsPath { id: list.id },
+            Context::new(Locale::En),
+            Query(CompareQuery { from: 1, to: None }),
+            DbConnection(pool.acquire().await?),
+        )
+        .await
+        .unwrap()
+        .into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        assert!(body.contains("Bakker"));
+        assert!(body.contains("Position moves"));
+
+        let missing = compare_snapshots(
+            CompareSnapshotsPath { id: list.id },
+            Context::new(Locale::En),
+            Query(CompareQuery {
+                from: 1,
+                to: Some(7),
+            }),
+            DbConnection(pool.acquire().await?),
+        )
+        .await;
+        assert!(matches!(missing, Err(AppError::NotFound(_))));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+use axum::response::{IntoResponse, Redirect, Response};
+use axum_extra::extract::Form;
+use sqlx::Connection;
+
+use crate::{
+    AppError, Context, CsrfTokens, DbConnection,
+    candidate_lists::{
+        self, CandidateListUndo,
+        pages::{CandidateListUndoPath, load_candidate_list},
+    },
+    form::{EmptyForm, Validate},
+    persons, t,
+};
+
+/// Undo the most recent change the user made to the candidates of a list.
+pub async fn undo_candidate_list_change(
+    CandidateListUndoPath { id }: CandidateListUndoPath,
+    context: Context,
+    csrf_tokens: CsrfTokens,
+    undo: CandidateListUndo,
+    DbConnection(mut conn): DbConnection,
+    Form(form): Form<EmptyForm>,
+) -> Result<Response, AppError> {
+    let full_list = load_candidate_list(&mut conn, &id, context.locale).await?;
+    let redirect = Redirect::to(&full_list.list.view_path());
+
+    if form.validate(None, &csrf_tokens).is_err() {
+        return Ok(redirect.into_response());
+    }
+
+    // nothing left to undo, e.g. because it expired
+    let Some(entry) = undo.pop(id) else {
+        return Ok(redirect.into_response());
+    };
+
+    let mut tx = conn.begin().await?;
+    for person_id in &entry.deleted_persons {
+        persons::repository::restore_person(&mut tx, person_id).await?;
+    }
+    let restored = candidate_lists::repository::restore_candidate_list_entries(
+        &mut tx,
+        &id,
+        &entry.current,
+        &entry.previous,
+    )
+    .await?;
+
+    if !restored {
+        return Err(AppError::BadRequest(
+            t!("undo.list_changed", &context.locale).to_string(),
+        ));
+    }
+    tx.commit().await?;
+
+    Ok(redirect.into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        Locale,
+        candidate_lists::{UndoAction, UndoEntry},
+        test_utils::{sample_candidate_list, sample_person_with_last_name},
+    };
+
+    async fn setup(pool: &PgPool) -> Result<(Uuid, Vec<Uuid>), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let list = sample_candidate_list(Uuid::new_v4());
+        candidate_lists::repository::create_candidate_list(&mut conn, &list).await?;
+
+        let mut person_ids = Vec::new();
+        for name in ["Jansen", "Bakker", "Visser"] {
+            let person = sample_person_with_last_name(Uuid::new_v4(), name);
+            persons::repository::create_person(&mut conn, &person).await?;
+            person_ids.push(person.id);
+        }
+        candidate_lists::repository::update_candidate_list_order(&mut conn, &list.id, &person_ids)
+            .await?;
+
+        Ok((list.id, person_ids))
+    }
+
+    async fn undo_request(
+        pool: &PgPool,
+        list_id: Uuid,
+        undo: CandidateListUndo,
+    ) -> Result<Response, AppError> {
+        let csrf_tokens = CsrfTokens::default();
+        let form = EmptyForm::from(csrf_tokens.issue().value);
+
+        undo_candidate_list_change(
+            CandidateListUndoPath { id: list_id },
+            Context::new(Locale::En),
+            csrf_tokens,
+            undo,
+            DbConnection(pool.acquire().await?),
+            Form(form),
+        )
+        .await
+    }
+
+    #[sqlx::test]
+    async fn undo_restores_removed_candidate_exactly(pool: PgPool) -> Result<(), sqlx::Error> {
+        let (list_id, person_ids) = setup(&pool).await?;
+        let mut conn = pool.acquire().await?;
+        let previous =
+            candidate_lists::repository::lock_candidate_list_entries(&mut conn, &list_id).await?;
+
+        // remove the middle candidate and delete them, like removing them from the list does
+        candidate_lists::repository::update_candidate_list_order(
+            &mut conn,
+            &list_id,
+            &[person_ids[0], person_ids[2]],
+        )
+        .await?;
+        persons::repository::remove_person(&mut conn, &person_ids[1]).await?;
+        let current =
+            candidate_lists::repository::lock_candidate_list_entries(&mut conn, &list_id).await?;
+
+        let undo = CandidateListUndo::default();
+        undo.push(
+            list_id,
+            UndoEntry::new(UndoAction::Removed, previous.clone(), &current)
+                .with_deleted_persons(vec![person_ids[1]]),
+        );
+
+        let response = undo_request(&pool, list_id, undo.clone()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+        let restored =
+            candidate_lists::repository::lock_candidate_list_entries(&mut conn, &list_id).await?;
+        assert_eq!(restored, previous);
+        assert!(
+            persons::repository::get_person(&mut conn, &person_ids[1])
+                .await?
+                .is_some()
+        );
+        assert_eq!(undo.latest(list_id), None);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn undo_refuses_when_list_changed_since(pool: PgPool) -> Result<(), sqlx::Error> {
+        let (list_id, person_ids) = setup(&pool).await?;
+        let mut conn = pool.acquire().await?;
+        let previous =
+            candidate_lists::repository::lock_candidate_list_entries(&mut conn, &list_id).await?;
+        let reordered = [person_ids[2], person_ids[1], person_ids[0]];
+        candidate_lists::repository::update_candidate_list_order(&mut conn, &list_id, &reordered)
+            .await?;
+        let current =
+            candidate_lists::repository::lock_candidate_list_entries(&mut conn, &list_id).await?;
+
+        let undo = CandidateListUndo::default();
+        undo.push(
+            list_id,
+            UndoEntry::new(UndoAction::Reordered, previous, &current),
+        );
+
+        // someone else changes the list in the meantime
+        candidate_lists::repository::update_candidate_list_order(&mut conn, &list_id, &person_ids)
+            .await?;
+
+        let result = undo_request(&pool, list_id, undo).await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+
+        let stored =
+            candidate_lists::repository::lock_candidate_list_entries(&mut conn, &list_id).await?;
+        let stored: Vec<Uuid> = stored.iter().map(|entry| entry.person_id).collect();
+        assert_eq!(stored, person_ids);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn undo_without_changes_redirects(pool: PgPool) -> Result<(), sqlx::Error> {
+        let (list_id, _) = setup(&pool).await?;
+
+        let response = undo_request(&pool, list_id, CandidateListUndo::default())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 use askama::Template;
 use axum::{
     extract::State,
+    http::StatusCode,
     response::{IntoResponse, Redirect, Response},
 };
 use axum_extra::extract::Form;
@@ -9,12 +10,13 @@ use crate::{
     AppError, AppState, Context, CsrfTokens, DbConnection, ElectionConfig, ElectoralDistrict,
     HtmlTemplate, Locale,
     candidate_lists::{
-        self, CandidateList, CandidateListForm, CandidateListSummary,
+        self, CandidateList, CandidateListForm, CandidateListSort, CandidateListSummary,
         pages::{CandidateListsEditPath, candidate_list_not_found},
     },
     filters,
-    form::{FormData, Validate},
-    persons::{self, Person},
+    form::{Conflict, FormData, ValidateWith},
+    pagination::SortDirection,
+    persons::{self, FieldChange, Person},
     t,
 };
 
@@ -28,6 +30,7 @@ struct CandidateListUpdateTemplate {
     form: FormData<CandidateListForm>,
     candidate_list: CandidateList,
     electoral_districts: &'static [ElectoralDistrict],
+    conflict: Option<Conflict>,
 }
 
 pub async fn edit_candidate_list(
@@ -37,8 +40,14 @@ pub async fn edit_candidate_list(
     DbConnection(mut conn): DbConnection,
     State(app_state): State<AppState>,
 ) -> Result<Response, AppError> {
-    let candidate_lists =
-        candidate_lists::repository::list_candidate_list_with_count(&mut conn).await?;
+    let candidate_lists = candidate_lists::repository::list_candidate_list_summaries(
+        &mut conn,
+        None,
+        0,
+        &CandidateListSort::CreatedAt,
+        &SortDirection::Asc,
+    )
+    .await?;
     let total_persons = persons::repository::count_persons(&mut conn).await?;
     let election = app_state.config().election;
     let electoral_districts = election.electoral_districts();
@@ -59,6 +68,7 @@ pub async fn edit_candidate_list(
             locale: context.locale,
             candidate_list,
             electoral_districts,
+            conflict: None,
         },
         context,
     )
@@ -73,8 +83,14 @@ pub async fn update_candidate_list(
     DbConnection(mut conn): DbConnection,
     form: Form<CandidateListForm>,
 ) -> Result<Response, AppError> {
-    let candidate_lists =
-        candidate_lists::repository::list_candidate_list_with_count(&mut conn).await?;
+    let candidate_lists = candidate_lists::repository::list_candidate_list_summaries(
+        &mut conn,
+        None,
+        0,
+        &CandidateListSort::CreatedAt,
+        &SortDirection::Asc,
+    )
+    .await?;
     let total_persons = persons::repository::count_persons(&mut conn).await?;
     let election = app_state.config().election;
 
@@ -84,7 +100,7 @@ pub async fn update_candidate_list(
         .await?
         .ok_or(candidate_list_not_found(id, context.locale))?;
 
-    match form.validate(Some(&candidate_list), &csrf_tokens) {
+    match form.validate_with(&election, Some(&candidate_list), &csrf_tokens) {
         Err(form_data) => Ok(HtmlTemplate(
             CandidateListUpdateTemplate {
                 candidate_lists,
@@ -94,15 +110,52 @@ pub async fn update_candidate_list(
                 form: form_data,
                 candidate_list,
                 electoral_districts,
+                conflict: None,
             },
             context,
         )
         .into_response()),
-        Ok(candidate_list) => {
-            let candidate_list =
-                candidate_lists::repository::update_candidate_list(&mut conn, &candidate_list)
-                    .await?;
-            Ok(Redirect::to(&candidate_list.view_path()).into_response())
+        Ok(updated) => {
+            let saved = if form.version == updated.version() {
+                candidate_lists::repository::update_candidate_list(&mut conn, &updated).await?
+            } else {
+                None
+            };
+            if let Some(saved) = saved {
+                return Ok(Redirect::to(&saved.view_path()).into_response());
+            }
+
+            // Someone else changed the list; show the form again with the user's districts
+            let current = candidate_lists::repository::get_candidate_list(&mut conn, &id)
+                .await?
+                .ok_or(candidate_list_not_found(id, context.locale))?;
+            let mut fields = Vec::new();
+            if current.electoral_districts != updated.electoral_districts {
+                fields.push(FieldChange {
+                    field: t!("candidate_list.fields.electoral_districts"),
+                    old: current.display_districts(&election, &context.locale),
+                    new: updated.display_districts(&election, &context.locale),
+                });
+            }
+            let conflict = Conflict::new(current.updated_at, fields);
+            let form = CandidateListForm {
+                version: conflict.version.clone(),
+                ..form.0
+            };
+            let page = HtmlTemplate(
+                CandidateListUpdateTemplate {
+                    candidate_lists,
+                    election,
+                    total_persons,
+                    locale: context.locale,
+                    form: FormData::new_with_data(form, &csrf_tokens),
+                    candidate_list: current,
+                    electoral_districts,
+                    conflict: Some(conflict),
+                },
+                context,
+            );
+            Ok((StatusCode::CONFLICT, page).into_response())
         }
     }
 }
@@ -141,6 +194,7 @@ mod tests {
 
         let form = CandidateListForm {
             electoral_districts: vec![ElectoralDistrict::DR],
+            version: candidate_list.version(),
             csrf_token,
         };
         let response = update_candidate_list(
@@ -167,7 +221,14 @@ mod tests {
 
         // verify updated candidate list object in database
         let mut conn = pool.acquire().await?;
-        let lists = candidate_lists::repository::list_candidate_list_with_count(&mut conn).await?;
+        let lists = candidate_lists::repository::list_candidate_list_summaries(
+            &mut conn,
+            None,
+            0,
+            &CandidateListSort::CreatedAt,
+            &SortDirection::Asc,
+        )
+        .await?;
         assert_eq!(lists.len(), 1);
 
         let updated_list = &lists[0].list;
@@ -205,6 +266,7 @@ mod tests {
 
         let form = CandidateListForm {
             electoral_districts: vec![ElectoralDistrict::DR],
+            version: candidate_list.version(),
             csrf_token: TokenValue("invalid".to_string()),
         };
         let response = update_candidate_list(
@@ -225,7 +287,14 @@ mod tests {
         assert!(body.contains("Edit candidate list"));
 
         let mut conn = pool.acquire().await?;
-        let lists = candidate_lists::repository::list_candidate_list_with_count(&mut conn).await?;
+        let lists = candidate_lists::repository::list_candidate_list_summaries(
+            &mut conn,
+            None,
+            0,
+            &CandidateListSort::CreatedAt,
+            &SortDirection::Asc,
+        )
+        .await?;
         assert_eq!(lists.len(), 1);
 
         let updated_list = &lists[0].list;
@@ -235,4 +304,62 @@ mod tests {
 
         Ok(())
     }
+
+    #[sqlx::test]
+    async fn update_candidate_list_with_stale_version_shows_conflict(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let app_state = AppState::new_for_tests(pool.clone());
+        let mut conn = pool.acquire().await.unwrap();
+        let csrf_tokens = CsrfTokens::default();
+        let creation_date = DateTime::from_timestamp(0, 0).unwrap();
+        let candidate_list = CandidateList {
+            id: Uuid::new_v4(),
+            electoral_districts: vec![ElectoralDistrict::UT],
+            created_at: creation_date,
+            updated_at: creation_date,
+        };
+        candidate_lists::repository::create_candidate_list(&mut conn, &candidate_list).await?;
+
+        // someone else saves the list after our form was loaded
+        let other = CandidateList {
+            electoral_districts: vec![ElectoralDistrict::GR],
+            ..candidate_list.clone()
+        };
+        let other = candidate_lists::repository::update_candidate_list(&mut conn, &other)
+            .await?
+            .expect("saved");
+
+        let form = CandidateListForm {
+            electoral_districts: vec![ElectoralDistrict::DR],
+            version: candidate_list.version(),
+            csrf_token: csrf_tokens.issue().value,
+        };
+        let response = update_candidate_list(
+            CandidateListsEditPath {
+                id: candidate_list.id,
+            },
+            Context::new(Locale::En),
+            State(app_state),
+            csrf_tokens,
+            DbConnection(conn),
+            Form(form),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body = response_body_string(response).await;
+        assert!(body.contains("Changed by someone else"));
+        // the form is based on the stored version, so saving again reapplies the change
+        assert!(body.contains(&format!(r#"name="version" value="{}""#, other.version())));
+
+        let mut conn = pool.acquire().await?;
+        let stored = candidate_lists::repository::get_candidate_list(&mut conn, &candidate_list.id)
+            .await?
+            .expect("candidate list");
+        assert_eq!(stored.electoral_districts, vec![ElectoralDistrict::GR]);
+
+        Ok(())
+    }
 }
@@ -2,12 +2,14 @@ use askama::Template;
 use axum::response::IntoResponse;
 
 use crate::{
-    AppError, Context, DbConnection, HtmlTemplate,
+    AppError, Context, CsrfTokens, DbConnection, HtmlTemplate,
     candidate_lists::{
-        CandidateList, FullCandidateList, MAX_CANDIDATES,
+        CandidateList, CandidateListUndo, FullCandidateList, MAX_CANDIDATES, UndoAction,
         pages::{ViewCandidateListPath, load_candidate_list},
     },
-    filters, t,
+    filters,
+    form::{EmptyForm, FormData},
+    t,
 };
 
 #[derive(Template)]
@@ -15,11 +17,17 @@ use crate::{
 struct CandidateListViewTemplate {
     full_list: FullCandidateList,
     max_candidates: usize,
+    /// Most recent change of the list the user can undo; the banner is also shown after
+    /// reordering by dragging, without reloading the page.
+    undo_action: Option<UndoAction>,
+    undo_form: FormData<EmptyForm>,
 }
 
 pub async fn view_candidate_list(
     ViewCandidateListPath { id }: ViewCandidateListPath,
     context: Context,
+    csrf_tokens: CsrfTokens,
+    undo: CandidateListUndo,
     DbConnection(mut conn): DbConnection,
 ) -> Result<impl IntoResponse, AppError> {
     let full_list = load_candidate_list(&mut conn, &id, context.locale).await?;
@@ -28,6 +36,8 @@ pub async fn view_candidate_list(
         CandidateListViewTemplate {
             full_list,
             max_candidates: MAX_CANDIDATES,
+            undo_action: undo.latest(id),
+            undo_form: FormData::new(&csrf_tokens),
         },
         context,
     ))
@@ -41,8 +51,10 @@ mod tests {
     use uuid::Uuid;
 
     use crate::{
-        Context, DbConnection, Locale, candidate_lists, persons,
-        test_utils::{response_body_string, sample_candidate_list, sample_person},
+        DbConnection, Role, candidate_lists, persons,
+        test_utils::{
+            context_with_role, response_body_string, sample_candidate_list, sample_person,
+        },
     };
 
     #[sqlx::test]
@@ -59,7 +71,9 @@ mod tests {
 
         let response = view_candidate_list(
             ViewCandidateListPath { id: list_id },
-            Context::new(Locale::En),
+            context_with_role(Role::ListSubmitter),
+            CsrfTokens::default(),
+            CandidateListUndo::default(),
             DbConnection(pool.acquire().await?),
         )
         .await
@@ -72,4 +86,31 @@ mod tests {
 
         Ok(())
     }
+
+    #[sqlx::test]
+    async fn view_candidate_list_hides_changes_for_read_only_roles(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let list = sample_candidate_list(Uuid::new_v4());
+        let mut conn = pool.acquire().await?;
+        candidate_lists::repository::create_candidate_list(&mut conn, &list).await?;
+
+        let response = view_candidate_list(
+            ViewCandidateListPath { id: list.id },
+            context_with_role(Role::CsbMember),
+            CsrfTokens::default(),
+            CandidateListUndo::default(),
+            DbConnection(pool.acquire().await?),
+        )
+        .await
+        .unwrap()
+        .into_response();
+
+        let body = response_body_string(response).await;
+        assert!(body.contains(&list.snapshots_path()));
+        assert!(!body.contains(&list.add_candidate_path()));
+        assert!(!body.contains(&list.update_path()));
+
+        Ok(())
+    }
 }
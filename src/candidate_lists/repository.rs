@@ -1,49 +1,66 @@
-use sqlx::{Connection, PgConnection};
+use chrono::{DateTime, Utc};
+use sqlx::{Connection, PgConnection, types::Json};
 use uuid::Uuid;
 
 use crate::{
     ElectoralDistrict,
-    candidate_lists::{Candidate, CandidateList, CandidateListSummary, FullCandidateList},
+    candidate_lists::{
+        Candidate, CandidateBulkOutcome, CandidateConsent, CandidateList, CandidateListEntry,
+        CandidateListMembership, CandidateListSnapshot, CandidateListSort, CandidateListSummary,
+        CandidateMatrixEntry, ConsentDecision, ConsentInvitation, DeletedCandidateList,
+        FullCandidateList, PositionError, PositionOperation, check_permutation,
+    },
+    pagination::{SortDirection, order_by},
     persons::{Gender, Person},
 };
 
-pub struct ListIdAndCount {
-    pub id: Uuid,
-    pub person_count: i64,
-}
-
-pub async fn list_candidate_list_with_count(
-    conn: &mut PgConnection,
-) -> Result<Vec<CandidateListSummary>, sqlx::Error> {
-    let counts = sqlx::query_as!(
-        ListIdAndCount,
-        r#"
-            SELECT
-                cl.id AS "id!",
-                COUNT(clp.person_id)::bigint AS "person_count!"
-            FROM candidate_lists cl
-            LEFT JOIN candidate_lists_persons clp ON clp.candidate_list_id = cl.id
-            GROUP BY cl.id
-            ORDER BY cl.updated_at DESC, cl.created_at DESC
-            "#,
+pub async fn count_candidate_lists(conn: &mut PgConnection) -> Result<i64, sqlx::Error> {
+    let record = sqlx::query!(
+        r#"
+        SELECT COUNT(*) as "count!"
+        FROM candidate_lists
+        WHERE deleted_at IS NULL
+        "#
     )
-    .fetch_all(&mut *conn)
+    .fetch_one(conn)
     .await?;
 
-    let lists = list_candidate_list(conn).await?;
+    Ok(record.count)
+}
 
-    Ok(lists
-        .into_iter()
-        .map(|list| {
-            let person_count = counts
-                .iter()
-                .find(|c| c.id == list.id)
-                .map(|c| c.person_count)
-                .unwrap_or(0);
+/// Candidate lists with their number of candidates, a single page or all of them when `limit` is
+/// `None`.
+pub async fn list_candidate_list_summaries(
+    conn: &mut PgConnection,
+    limit: Option<i64>,
+    offset: i64,
+    sort_field: &CandidateListSort,
+    sort_direction: &SortDirection,
+) -> Result<Vec<CandidateListSummary>, sqlx::Error> {
+    let query = format!(
+        r#"
+        SELECT
+            cl.id,
+            cl.electoral_districts,
+            cl.created_at,
+            cl.updated_at,
+            COUNT(clp.person_id) AS person_count
+        FROM candidate_lists cl
+        LEFT JOIN candidate_lists_persons clp ON clp.candidate_list_id = cl.id
+        WHERE cl.deleted_at IS NULL
+        GROUP BY cl.id
+        {}
+        LIMIT $1
+        OFFSET $2
+        "#,
+        order_by(sort_field, sort_direction),
+    );
 
-            CandidateListSummary { list, person_count }
-        })
-        .collect::<Vec<_>>())
+    sqlx::query_as::<_, CandidateListSummary>(&query)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(conn)
+        .await
 }
 
 pub async fn list_candidate_list(
@@ -54,6 +71,7 @@ pub async fn list_candidate_list(
             r#"
             SELECT id, electoral_districts AS "electoral_districts: Vec<ElectoralDistrict>", created_at, updated_at
             FROM candidate_lists
+            WHERE deleted_at IS NULL
             ORDER BY created_at ASC
             "#,
         )
@@ -70,7 +88,7 @@ pub async fn get_candidate_list(
         r#"
         SELECT id, electoral_districts AS "electoral_districts: Vec<ElectoralDistrict>", created_at, updated_at
         FROM candidate_lists
-        WHERE id = $1
+        WHERE id = $1 AND deleted_at IS NULL
         "#,
         list_id,
     )
@@ -92,6 +110,8 @@ pub async fn get_full_candidate_list(
         r#"
         SELECT
             clp.position,
+            clp.consent as "consent: ConsentDecision",
+            clp.consent_at,
             p.id as "id!",
             p.gender as "gender?: Gender",
             p.last_name as "last_name!",
@@ -114,7 +134,7 @@ pub async fn get_full_candidate_list(
             p.updated_at as "updated_at!"
         FROM candidate_lists_persons clp
         JOIN persons p ON p.id = clp.person_id
-        WHERE clp.candidate_list_id = $1
+        WHERE clp.candidate_list_id = $1 AND p.deleted_at IS NULL
         ORDER BY clp.position ASC
         "#,
         list.id
@@ -125,6 +145,7 @@ pub async fn get_full_candidate_list(
     .map(|row| Candidate {
         list_id: list.id,
         position: row.position,
+        consent: CandidateConsent::from_row(row.consent, row.consent_at),
         person: Person {
             id: row.id,
             gender: row.gender,
@@ -153,6 +174,183 @@ pub async fn get_full_candidate_list(
     Ok(Some(FullCandidateList { list, candidates }))
 }
 
+/// All candidate lists with their candidates, ordered by list creation.
+pub async fn list_full_candidate_lists(
+    conn: &mut PgConnection,
+) -> Result<Vec<FullCandidateList>, sqlx::Error> {
+    let lists = list_candidate_list(conn).await?;
+
+    let mut full_lists = Vec::with_capacity(lists.len());
+    for list in lists {
+        if let Some(full_list) = get_full_candidate_list(conn, &list.id).await? {
+            full_lists.push(full_list);
+        }
+    }
+
+    Ok(full_lists)
+}
+
+/// Every person on at least one candidate list, with their position on each list, in a single
+/// query. Positions are ordered by list creation.
+pub async fn list_candidate_matrix(
+    conn: &mut PgConnection,
+) -> Result<Vec<CandidateMatrixEntry>, sqlx::Error> {
+    let entries = sqlx::query!(
+        r#"
+        SELECT
+            p.id,
+            p.gender as "gender?: Gender",
+            p.last_name,
+            p.last_name_prefix,
+            p.first_name,
+            p.initials,
+            p.date_of_birth,
+            p.bsn,
+            p.locality,
+            p.postal_code,
+            p.house_number,
+            p.house_number_addition,
+            p.street_name,
+            p.is_dutch,
+            p.custom_country,
+            p.custom_region,
+            p.address_line_1,
+            p.address_line_2,
+            p.created_at,
+            p.updated_at,
+            array_agg(clp.candidate_list_id ORDER BY cl.created_at) as "list_ids!",
+            array_agg(clp.position ORDER BY cl.created_at) as "positions!"
+        FROM persons p
+        JOIN candidate_lists_persons clp ON clp.person_id = p.id
+        JOIN candidate_lists cl ON cl.id = clp.candidate_list_id
+        WHERE p.deleted_at IS NULL AND cl.deleted_at IS NULL
+        GROUP BY p.id
+        ORDER BY p.last_name, p.initials, p.id
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .map(|row| CandidateMatrixEntry {
+        positions: row.list_ids.into_iter().zip(row.positions).collect(),
+        person: Person {
+            id: row.id,
+            gender: row.gender,
+            last_name: row.last_name,
+            last_name_prefix: row.last_name_prefix,
+            first_name: row.first_name,
+            initials: row.initials,
+            date_of_birth: row.date_of_birth,
+            bsn: row.bsn,
+            locality: row.locality,
+            postal_code: row.postal_code,
+            house_number: row.house_number,
+            house_number_addition: row.house_number_addition,
+            street_name: row.street_name,
+            is_dutch: row.is_dutch,
+            custom_country: row.custom_country,
+            custom_region: row.custom_region,
+            address_line_1: row.address_line_1,
+            address_line_2: row.address_line_2,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        },
+    })
+    .collect();
+
+    Ok(entries)
+}
+
+/// All candidate lists a person is on, with their position, ordered by list creation.
+pub async fn list_memberships_for_person(
+    conn: &mut PgConnection,
+    person_id: &Uuid,
+) -> Result<Vec<CandidateListMembership>, sqlx::Error> {
+    let memberships = sqlx::query!(
+        r#"
+        SELECT
+            cl.id,
+            cl.electoral_districts AS "electoral_districts: Vec<ElectoralDistrict>",
+            cl.created_at,
+            cl.updated_at,
+            clp.position
+        FROM candidate_lists_persons clp
+        JOIN candidate_lists cl ON cl.id = clp.candidate_list_id
+        WHERE clp.person_id = $1 AND cl.deleted_at IS NULL
+        ORDER BY cl.created_at ASC, cl.id ASC
+        "#,
+        person_id,
+    )
+    .fetch_all(conn)
+    .await?
+    .into_iter()
+    .map(|row| CandidateListMembership {
+        list: CandidateList {
+            id: row.id,
+            electoral_districts: row.electoral_districts,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        },
+        position: row.position,
+    })
+    .collect();
+
+    Ok(memberships)
+}
+
+/// Remove a person from every (not deleted) candidate list they are on, moving the candidates
+/// below them up one position. Returns the ids of the affected lists.
+pub async fn remove_person_from_all_lists(
+    conn: &mut PgConnection,
+    person_id: &Uuid,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+
+    let removed = sqlx::query!(
+        r#"
+        DELETE FROM candidate_lists_persons clp
+        USING candidate_lists cl
+        WHERE clp.person_id = $1 AND cl.id = clp.candidate_list_id AND cl.deleted_at IS NULL
+        RETURNING clp.candidate_list_id as "candidate_list_id!", clp.position
+        "#,
+        person_id,
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let list_ids: Vec<Uuid> = removed.iter().map(|r| r.candidate_list_id).collect();
+    let positions: Vec<i32> = removed.iter().map(|r| r.position).collect();
+
+    sqlx::query!(
+        r#"
+        UPDATE candidate_lists_persons clp
+        SET position = clp.position - 1, updated_at = NOW()
+        FROM UNNEST($1::uuid[], $2::int[]) AS removed(candidate_list_id, position)
+        WHERE clp.candidate_list_id = removed.candidate_list_id
+            AND clp.position > removed.position
+        "#,
+        &list_ids,
+        &positions,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE candidate_lists
+        SET updated_at = NOW()
+        WHERE id = ANY($1)
+        "#,
+        &list_ids,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(list_ids)
+}
+
 /// retrieves a vector of all the electoral districts that have been used in one or more candidate lists
 pub async fn get_used_districts(
     conn: &mut PgConnection,
@@ -161,7 +359,8 @@ pub async fn get_used_districts(
         r#"
         SELECT array_agg(DISTINCT e) AS "electoral_districts: Vec<ElectoralDistrict>"
         FROM candidate_lists cl 
-        CROSS JOIN LATERAL unnest(cl.electoral_districts ) AS e;
+        CROSS JOIN LATERAL unnest(cl.electoral_districts ) AS e
+        WHERE cl.deleted_at IS NULL;
         "#
     )
     .fetch_one(&mut *conn)
@@ -196,6 +395,9 @@ pub async fn create_candidate_list(
     .await
 }
 
+/// Replace the candidates on a list with `person_ids`, in that order. Used to seed lists;
+/// changes made by users go through [`apply_position_operation`] and [`reorder_candidates`].
+#[cfg(any(test, feature = "fixtures"))]
 pub async fn update_candidate_list_order(
     conn: &mut PgConnection,
     list_id: &Uuid,
@@ -203,44 +405,367 @@ pub async fn update_candidate_list_order(
 ) -> Result<FullCandidateList, sqlx::Error> {
     let mut tx = conn.begin().await?;
 
+    let current = lock_candidate_order(&mut tx, list_id).await?;
+    write_candidate_order(&mut tx, list_id, &current, person_ids).await?;
+
+    tx.commit().await?;
+
+    get_full_candidate_list(conn, list_id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)
+}
+
+/// Create a new list with the given candidates, in that order, in a single transaction.
+pub async fn create_candidate_list_with_candidates(
+    conn: &mut PgConnection,
+    candidate_list: &CandidateList,
+    person_ids: &[Uuid],
+) -> Result<FullCandidateList, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+
+    create_candidate_list(&mut tx, candidate_list).await?;
+    write_candidate_order(&mut tx, &candidate_list.id, &[], person_ids).await?;
+
+    tx.commit().await?;
+
+    get_full_candidate_list(conn, &candidate_list.id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)
+}
+
+/// Reorder the candidates on a list. `person_ids` must contain every candidate on the list
+/// exactly once.
+pub async fn reorder_candidates(
+    conn: &mut PgConnection,
+    list_id: &Uuid,
+    person_ids: &[Uuid],
+) -> Result<FullCandidateList, PositionError> {
+    let mut tx = conn.begin().await?;
+
+    let current = lock_candidate_order(&mut tx, list_id).await?;
+    check_permutation(&current, person_ids)?;
+    write_candidate_order(&mut tx, list_id, &current, person_ids).await?;
+
+    tx.commit().await?;
+
+    Ok(get_full_candidate_list(conn, list_id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?)
+}
+
+/// Apply a single position operation to a candidate list, atomically.
+pub async fn apply_position_operation(
+    conn: &mut PgConnection,
+    list_id: &Uuid,
+    operation: PositionOperation,
+) -> Result<FullCandidateList, PositionError> {
+    let mut tx = conn.begin().await?;
+
+    let current = lock_candidate_order(&mut tx, list_id).await?;
+    let reordered = operation.apply(&current)?;
+    write_candidate_order(&mut tx, list_id, &current, &reordered).await?;
+
+    tx.commit().await?;
+
+    Ok(get_full_candidate_list(conn, list_id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?)
+}
+
+/// Add persons to a list in one transaction, in the given order, starting at `position` or at
+/// the end of the list. Persons that are already on the list or do not exist are skipped.
+pub async fn add_candidates(
+    conn: &mut PgConnection,
+    list_id: &Uuid,
+    person_ids: &[Uuid],
+    position: Option<usize>,
+) -> Result<Vec<(Uuid, CandidateBulkOutcome)>, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+
+    let current = lock_candidate_order(&mut tx, list_id).await?;
+    let existing = sqlx::query_scalar!(
+        r#"
+        SELECT id
+        FROM persons
+        WHERE id = ANY($1) AND deleted_at IS NULL
+        "#,
+        person_ids,
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut added = Vec::new();
+    let outcomes = person_ids
+        .iter()
+        .map(|person_id| {
+            let outcome = if !existing.contains(person_id) {
+                CandidateBulkOutcome::PersonNotFound
+            } else if current.contains(person_id) || added.contains(person_id) {
+                CandidateBulkOutcome::AlreadyOnList
+            } else {
+                added.push(*person_id);
+                CandidateBulkOutcome::Added { position: 0 }
+            };
+            (*person_id, outcome)
+        })
+        .collect::<Vec<_>>();
+
+    let start = position.unwrap_or(usize::MAX).clamp(1, current.len() + 1) - 1;
+    let mut reordered = current.clone();
+    reordered.splice(start..start, added.iter().copied());
+    write_candidate_order(&mut tx, list_id, &current, &reordered).await?;
+
+    tx.commit().await?;
+
+    // report the final position of every added person
+    Ok(outcomes
+        .into_iter()
+        .map(|(person_id, outcome)| match outcome {
+            CandidateBulkOutcome::Added { .. } => {
+                let position = reordered
+                    .iter()
+                    .position(|id| id == &person_id)
+                    .unwrap_or(0)
+                    + 1;
+                (person_id, CandidateBulkOutcome::Added { position })
+            }
+            outcome => (person_id, outcome),
+        })
+        .collect())
+}
+
+/// Remove persons from a list in one transaction, moving the remaining candidates up.
+pub async fn remove_candidates(
+    conn: &mut PgConnection,
+    list_id: &Uuid,
+    person_ids: &[Uuid],
+) -> Result<Vec<(Uuid, CandidateBulkOutcome)>, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+
+    let current = lock_candidate_order(&mut tx, list_id).await?;
+    let mut removed = Vec::new();
+    let outcomes = person_ids
+        .iter()
+        .map(|person_id| {
+            let outcome = if current.contains(person_id) && !removed.contains(person_id) {
+                removed.push(*person_id);
+                CandidateBulkOutcome::Removed
+            } else {
+                CandidateBulkOutcome::NotOnList
+            };
+            (*person_id, outcome)
+        })
+        .collect();
+
+    let remaining: Vec<Uuid> = current
+        .iter()
+        .filter(|id| !removed.contains(id))
+        .copied()
+        .collect();
+    write_candidate_order(&mut tx, list_id, &current, &remaining).await?;
+
+    tx.commit().await?;
+
+    Ok(outcomes)
+}
+
+/// Lock a list against concurrent changes until the transaction ends, and return its
+/// candidates as stored, in order. Used to record how a change can be undone.
+pub async fn lock_candidate_list_entries(
+    conn: &mut PgConnection,
+    list_id: &Uuid,
+) -> Result<Vec<CandidateListEntry>, sqlx::Error> {
+    sqlx::query!(
+        r#"
+        SELECT id
+        FROM candidate_lists
+        WHERE id = $1 AND deleted_at IS NULL
+        FOR UPDATE
+        "#,
+        list_id,
+    )
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or(sqlx::Error::RowNotFound)?;
+
+    sqlx::query_as!(
+        CandidateListEntry,
+        r#"
+        SELECT person_id as "person_id!", created_at, updated_at
+        FROM candidate_lists_persons
+        WHERE candidate_list_id = $1
+        ORDER BY position
+        "#,
+        list_id,
+    )
+    .fetch_all(&mut *conn)
+    .await
+}
+
+/// Lock a list against concurrent changes until the transaction ends, provided it is still at
+/// `version`, the version a change is based on. Returns `false` when the list changed in the
+/// meantime or does not exist.
+pub async fn lock_candidate_list_version(
+    conn: &mut PgConnection,
+    list_id: &Uuid,
+    version: &DateTime<Utc>,
+) -> Result<bool, sqlx::Error> {
     let updated = sqlx::query!(
         r#"
         UPDATE candidate_lists
         SET updated_at = NOW()
-        WHERE id = $1
+        WHERE id = $1 AND updated_at = $2 AND deleted_at IS NULL
+        "#,
+        list_id,
+        version,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(updated.rows_affected() == 1)
+}
+
+/// Put the candidates of a list back as they were recorded in `previous`, provided the list
+/// still holds `current`. Returns `false`, without changing anything, when it does not.
+pub async fn restore_candidate_list_entries(
+    conn: &mut PgConnection,
+    list_id: &Uuid,
+    current: &[Uuid],
+    previous: &[CandidateListEntry],
+) -> Result<bool, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+
+    let stored = lock_candidate_order(&mut tx, list_id).await?;
+    if stored != current {
+        return Ok(false);
+    }
+
+    let person_ids: Vec<Uuid> = previous.iter().map(|entry| entry.person_id).collect();
+    let created_at: Vec<DateTime<Utc>> = previous.iter().map(|entry| entry.created_at).collect();
+    let updated_at: Vec<DateTime<Utc>> = previous.iter().map(|entry| entry.updated_at).collect();
+    write_candidate_order(&mut tx, list_id, &stored, &person_ids).await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE candidate_lists_persons clp
+        SET created_at = restored.created_at, updated_at = restored.updated_at
+        FROM UNNEST($2::uuid[], $3::timestamptz[], $4::timestamptz[])
+            AS restored(person_id, created_at, updated_at)
+        WHERE clp.candidate_list_id = $1 AND clp.person_id = restored.person_id
+        "#,
+        list_id,
+        &person_ids,
+        &created_at,
+        &updated_at,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(true)
+}
+
+/// Mark a list as updated, which locks it against concurrent position changes until the
+/// transaction ends, and return its candidates in order.
+async fn lock_candidate_order(
+    conn: &mut PgConnection,
+    list_id: &Uuid,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    let updated = sqlx::query!(
+        r#"
+        UPDATE candidate_lists
+        SET updated_at = NOW()
+        WHERE id = $1 AND deleted_at IS NULL
+        "#,
+        list_id,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    sqlx::query_scalar!(
+        r#"
+        SELECT person_id as "person_id!"
+        FROM candidate_lists_persons
+        WHERE candidate_list_id = $1
+        ORDER BY position
+        "#,
+        list_id,
+    )
+    .fetch_all(&mut *conn)
+    .await
+}
+
+/// Store a new order of the candidates on a list, only touching the candidates that were
+/// removed, moved or added.
+async fn write_candidate_order(
+    conn: &mut PgConnection,
+    list_id: &Uuid,
+    current: &[Uuid],
+    person_ids: &[Uuid],
+) -> Result<(), sqlx::Error> {
+    let positions: Vec<i32> = (1..=person_ids.len() as i32).collect();
+
+    // candidates may share a position halfway through, e.g. when two are swapped
+    sqlx::query!("SET CONSTRAINTS candidate_lists_persons_position_unique DEFERRED")
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM candidate_lists_persons
+        WHERE candidate_list_id = $1 AND NOT (person_id = ANY($2))
+        "#,
+        list_id,
+        person_ids,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE candidate_lists_persons clp
+        SET position = reordered.position, updated_at = NOW()
+        FROM UNNEST($2::uuid[], $3::int[]) AS reordered(person_id, position)
+        WHERE clp.candidate_list_id = $1
+            AND clp.person_id = reordered.person_id
+            AND clp.position <> reordered.position
         "#,
         list_id,
+        person_ids,
+        &positions,
     )
-    .execute(&mut *tx)
+    .execute(&mut *conn)
     .await?;
 
-    if updated.rows_affected() == 0 {
-        return Err(sqlx::Error::RowNotFound);
-    }
-
     sqlx::query!(
         r#"
-        DELETE FROM candidate_lists_persons
-        WHERE candidate_list_id = $1
+        INSERT INTO candidate_lists_persons (candidate_list_id, person_id, position)
+        SELECT $1, person_id, position
+        FROM UNNEST($2::uuid[], $3::int[]) AS reordered(person_id, position)
+        WHERE NOT (person_id = ANY($4))
         "#,
         list_id,
+        person_ids,
+        &positions,
+        current,
     )
-    .execute(&mut *tx)
+    .execute(&mut *conn)
     .await?;
 
-    insert_candidates(&mut tx, list_id, person_ids).await?;
-
-    tx.commit().await?;
-
-    get_full_candidate_list(conn, list_id)
-        .await?
-        .ok_or(sqlx::Error::RowNotFound)
+    Ok(())
 }
 
+/// Update a candidate list, provided `updated_candidate_list.updated_at` still matches the
+/// stored version. Returns `None` when the list was changed or deleted in the meantime.
 pub async fn update_candidate_list(
     conn: &mut PgConnection,
     updated_candidate_list: &CandidateList,
-) -> Result<CandidateList, sqlx::Error> {
+) -> Result<Option<CandidateList>, sqlx::Error> {
     sqlx::query_as!(
         CandidateList,
         r#"
@@ -248,7 +773,7 @@ pub async fn update_candidate_list(
         SET
             electoral_districts = $1,
             updated_at = NOW()
-        WHERE id = $2
+        WHERE id = $2 AND updated_at = $3 AND deleted_at IS NULL
         RETURNING
             id,
             electoral_districts AS "electoral_districts: Vec<ElectoralDistrict>",
@@ -256,62 +781,146 @@ pub async fn update_candidate_list(
             updated_at
         "#,
         &updated_candidate_list.electoral_districts as &[ElectoralDistrict],
-        updated_candidate_list.id
+        updated_candidate_list.id,
+        updated_candidate_list.updated_at,
     )
-    .fetch_one(conn)
+    .fetch_optional(conn)
     .await
 }
 
+/// Soft delete a candidate list. Its candidates and their positions are kept, so that
+/// [`restore_candidate_list`] can bring the list back as it was.
 pub async fn remove_candidate_list(
     conn: &mut PgConnection,
     list_id: Uuid,
 ) -> Result<(), sqlx::Error> {
-    // delete all the candidates first (otherwise we get a foreign key violation)
     sqlx::query!(
         r#"
-        DELETE FROM candidate_lists_persons
-        WHERE candidate_list_id = $1
+        UPDATE candidate_lists
+        SET deleted_at = NOW()
+        WHERE id = $1 AND deleted_at IS NULL
         "#,
         list_id
     )
-    .execute(&mut *conn)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Undo [`remove_candidate_list`], including restoring candidates on the list that were deleted
+/// since. Returns `false` when there is no deleted list with this id.
+pub async fn restore_candidate_list(
+    conn: &mut PgConnection,
+    list_id: &Uuid,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+
+    let restored = sqlx::query!(
+        r#"
+        UPDATE candidate_lists
+        SET deleted_at = NULL, updated_at = NOW()
+        WHERE id = $1 AND deleted_at IS NOT NULL
+        "#,
+        list_id,
+    )
+    .execute(&mut *tx)
     .await?;
 
-    // then, delete the list row itself
+    if restored.rows_affected() == 0 {
+        return Ok(false);
+    }
+
     sqlx::query!(
         r#"
-        DELETE FROM candidate_lists
-        WHERE id = $1
+        UPDATE persons
+        SET deleted_at = NULL
+        WHERE deleted_at IS NOT NULL AND id IN (
+            SELECT person_id
+            FROM candidate_lists_persons
+            WHERE candidate_list_id = $1
+        )
         "#,
-        list_id
+        list_id,
     )
-    .execute(&mut *conn)
+    .execute(&mut *tx)
     .await?;
 
-    Ok(())
+    tx.commit().await?;
+
+    Ok(true)
 }
 
-async fn insert_candidates(
-    executor: &mut PgConnection,
-    list_id: &Uuid,
-    person_ids: &[Uuid],
-) -> Result<(), sqlx::Error> {
-    let positions: Vec<i32> = (1..=person_ids.len() as i32).collect();
+/// Soft deleted candidate lists with their number of candidates, most recently deleted first.
+pub async fn list_deleted_candidate_lists(
+    conn: &mut PgConnection,
+) -> Result<Vec<DeletedCandidateList>, sqlx::Error> {
+    let lists = sqlx::query!(
+        r#"
+        SELECT
+            cl.id,
+            cl.electoral_districts AS "electoral_districts: Vec<ElectoralDistrict>",
+            cl.created_at,
+            cl.updated_at,
+            cl.deleted_at AS "deleted_at!",
+            COUNT(clp.person_id) AS "person_count!"
+        FROM candidate_lists cl
+        LEFT JOIN candidate_lists_persons clp ON clp.candidate_list_id = cl.id
+        WHERE cl.deleted_at IS NOT NULL
+        GROUP BY cl.id
+        ORDER BY cl.deleted_at DESC, cl.id ASC
+        "#,
+    )
+    .fetch_all(conn)
+    .await?
+    .into_iter()
+    .map(|row| DeletedCandidateList {
+        list: CandidateList {
+            id: row.id,
+            electoral_districts: row.electoral_districts,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        },
+        person_count: row.person_count,
+        deleted_at: row.deleted_at,
+    })
+    .collect();
+
+    Ok(lists)
+}
+
+/// Permanently delete candidate lists (and their candidate positions) that were soft deleted
+/// before `deleted_before`. Returns the number of purged lists.
+pub async fn purge_deleted_candidate_lists(
+    conn: &mut PgConnection,
+    deleted_before: DateTime<Utc>,
+) -> Result<u64, sqlx::Error> {
+    let mut tx = conn.begin().await?;
 
     sqlx::query!(
         r#"
-        INSERT INTO candidate_lists_persons (candidate_list_id, person_id, position)
-        SELECT $1, person_id, position
-        FROM UNNEST($2::uuid[], $3::int[]) AS t(person_id, position)
+        DELETE FROM candidate_lists_persons clp
+        USING candidate_lists cl
+        WHERE cl.id = clp.candidate_list_id AND cl.deleted_at < $1
         "#,
-        list_id,
-        &person_ids,
-        &positions,
+        deleted_before,
     )
-    .execute(&mut *executor)
+    .execute(&mut *tx)
     .await?;
 
-    Ok(())
+    let purged = sqlx::query!(
+        r#"
+        DELETE FROM candidate_lists
+        WHERE deleted_at < $1
+        "#,
+        deleted_before,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(purged.rows_affected())
 }
 
 pub async fn get_candidate(
@@ -325,7 +934,7 @@ pub async fn get_candidate(
 
     let record = sqlx::query!(
         r#"
-        SELECT position
+        SELECT position, consent as "consent: ConsentDecision", consent_at
         FROM candidate_lists_persons
         WHERE candidate_list_id = $1 AND person_id = $2
         "#,
@@ -339,7 +948,171 @@ pub async fn get_candidate(
         list_id: *list_id,
         position: record.position,
         person,
+        consent: CandidateConsent::from_row(record.consent, record.consent_at),
+    })
+}
+
+/// Create a personal link for a candidate to review their position. Returns `None` when the
+/// person is not on the list, the list is not visible to the connection, or the list belongs to no
+/// political group: the candidate's connection is scoped to the group of the list.
+pub async fn create_consent_invitation(
+    conn: &mut PgConnection,
+    list_id: &Uuid,
+    person_id: &Uuid,
+    token_hash: &[u8],
+    expires_at: DateTime<Utc>,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        INSERT INTO candidate_consent_invitations
+            (token_hash, candidate_list_id, person_id, political_group_id, expires_at)
+        SELECT $3, clp.candidate_list_id, clp.person_id, cl.political_group_id, $4
+        FROM candidate_lists_persons clp
+        JOIN candidate_lists cl ON cl.id = clp.candidate_list_id
+        WHERE clp.candidate_list_id = $1 AND clp.person_id = $2 AND cl.deleted_at IS NULL
+            AND cl.political_group_id IS NOT NULL
+        RETURNING id
+        "#,
+        list_id,
+        person_id,
+        token_hash,
+        expires_at,
+    )
+    .fetch_optional(conn)
+    .await
+}
+
+/// Get the invitation with the given token, while it has not expired.
+pub async fn get_consent_invitation(
+    conn: &mut PgConnection,
+    token_hash: &[u8],
+) -> Result<Option<ConsentInvitation>, sqlx::Error> {
+    sqlx::query_as!(
+        ConsentInvitation,
+        r#"
+        SELECT candidate_list_id, person_id, political_group_id, expires_at
+        FROM candidate_consent_invitations
+        WHERE token_hash = $1 AND expires_at > NOW()
+        "#,
+        token_hash,
+    )
+    .fetch_optional(conn)
+    .await
+}
+
+/// Store the decision of a candidate about their current position, unless they already made
+/// one. Returns whether it was stored.
+pub async fn set_candidate_consent(
+    conn: &mut PgConnection,
+    list_id: &Uuid,
+    person_id: &Uuid,
+    decision: ConsentDecision,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE candidate_lists_persons
+        SET consent = $3, consent_at = NOW()
+        WHERE candidate_list_id = $1 AND person_id = $2 AND consent IS NULL
+        "#,
+        list_id,
+        person_id,
+        decision as ConsentDecision,
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Store the current state of a candidate list as a new, immutable snapshot.
+pub async fn create_snapshot(
+    conn: &mut PgConnection,
+    list_id: &Uuid,
+    label: Option<&str>,
+) -> Result<CandidateListSnapshot, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+
+    let full_list = get_full_candidate_list(&mut tx, list_id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+
+    let record = sqlx::query!(
+        r#"
+        INSERT INTO candidate_list_snapshots (candidate_list_id, version, label, snapshot)
+        SELECT $1, COALESCE(MAX(version), 0) + 1, $2, $3
+        FROM candidate_list_snapshots
+        WHERE candidate_list_id = $1
+        RETURNING version, created_at
+        "#,
+        list_id,
+        label,
+        Json(&full_list) as _,
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(CandidateListSnapshot {
+        version: record.version,
+        label: label.map(str::to_string),
+        created_at: record.created_at,
+        full_list,
+    })
+}
+
+/// All snapshots of a candidate list, newest first.
+pub async fn list_snapshots(
+    conn: &mut PgConnection,
+    list_id: &Uuid,
+) -> Result<Vec<CandidateListSnapshot>, sqlx::Error> {
+    let snapshots = sqlx::query!(
+        r#"
+        SELECT version, label, created_at, snapshot as "snapshot: Json<FullCandidateList>"
+        FROM candidate_list_snapshots
+        WHERE candidate_list_id = $1
+        ORDER BY version DESC
+        "#,
+        list_id,
+    )
+    .fetch_all(conn)
+    .await?
+    .into_iter()
+    .map(|row| CandidateListSnapshot {
+        version: row.version,
+        label: row.label,
+        created_at: row.created_at,
+        full_list: row.snapshot.0,
     })
+    .collect();
+
+    Ok(snapshots)
+}
+
+pub async fn get_snapshot(
+    conn: &mut PgConnection,
+    list_id: &Uuid,
+    version: i32,
+) -> Result<Option<CandidateListSnapshot>, sqlx::Error> {
+    let snapshot = sqlx::query!(
+        r#"
+        SELECT version, label, created_at, snapshot as "snapshot: Json<FullCandidateList>"
+        FROM candidate_list_snapshots
+        WHERE candidate_list_id = $1 AND version = $2
+        "#,
+        list_id,
+        version,
+    )
+    .fetch_optional(conn)
+    .await?
+    .map(|row| CandidateListSnapshot {
+        version: row.version,
+        label: row.label,
+        created_at: row.created_at,
+        full_list: row.snapshot.0,
+    });
+
+    Ok(snapshot)
 }
 
 #[cfg(test)]
@@ -351,7 +1124,7 @@ mod tests {
     use sqlx::PgPool;
 
     use crate::{
-        persons,
+        Tenant, auth, persons,
         test_utils::{sample_candidate_list, sample_person_with_last_name},
     };
 
@@ -375,7 +1148,14 @@ mod tests {
         let mut conn = pool.acquire().await?;
         create_candidate_list(&mut conn, &list).await?;
 
-        let lists = list_candidate_list_with_count(&mut conn).await?;
+        let lists = list_candidate_list_summaries(
+            &mut conn,
+            None,
+            0,
+            &CandidateListSort::CreatedAt,
+            &SortDirection::Asc,
+        )
+        .await?;
         assert_eq!(1, lists.len());
         assert_eq!(list.id, lists[0].list.id);
         assert_eq!(0, lists[0].person_count);
@@ -383,6 +1163,51 @@ mod tests {
         Ok(())
     }
 
+    #[sqlx::test]
+    async fn list_candidate_list_summaries_sorts_and_pages(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let empty = insert_list(&mut conn, vec![ElectoralDistrict::UT]).await?;
+        let filled = insert_list(&mut conn, vec![ElectoralDistrict::DR]).await?;
+        let person = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        persons::repository::create_person(&mut conn, &person).await?;
+        update_candidate_list_order(&mut conn, &filled.id, &[person.id]).await?;
+
+        assert_eq!(count_candidate_lists(&mut conn).await?, 2);
+
+        let lists = list_candidate_list_summaries(
+            &mut conn,
+            Some(10),
+            0,
+            &CandidateListSort::PersonCount,
+            &SortDirection::Desc,
+        )
+        .await?;
+        assert_eq!(
+            lists.iter().map(|l| l.list.id).collect::<Vec<_>>(),
+            vec![filled.id, empty.id]
+        );
+        assert_eq!(lists[0].person_count, 1);
+        assert_eq!(
+            lists[0].list.electoral_districts,
+            vec![ElectoralDistrict::DR]
+        );
+
+        let page = list_candidate_list_summaries(
+            &mut conn,
+            Some(1),
+            1,
+            &CandidateListSort::PersonCount,
+            &SortDirection::Asc,
+        )
+        .await?;
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].list.id, filled.id);
+
+        Ok(())
+    }
+
     #[sqlx::test]
     async fn get_candidate_list_includes_candidates(pool: PgPool) -> Result<(), sqlx::Error> {
         let list_id = Uuid::new_v4();
@@ -504,7 +1329,14 @@ mod tests {
         remove_candidate_list(&mut conn, list_a.id).await?;
 
         // verify
-        let lists = list_candidate_list_with_count(&mut conn).await?;
+        let lists = list_candidate_list_summaries(
+            &mut conn,
+            None,
+            0,
+            &CandidateListSort::CreatedAt,
+            &SortDirection::Asc,
+        )
+        .await?;
         let list_b_from_db = get_full_candidate_list(&mut conn, &list_b.id)
             .await?
             .unwrap();
@@ -518,4 +1350,250 @@ mod tests {
 
         Ok(())
     }
+
+    #[sqlx::test]
+    async fn snapshots_are_versioned_and_immutable(pool: PgPool) -> Result<(), sqlx::Error> {
+        let list = sample_candidate_list(Uuid::new_v4());
+        let person = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+
+        let mut conn = pool.acquire().await?;
+        create_candidate_list(&mut conn, &list).await?;
+        persons::repository::create_person(&mut conn, &person).await?;
+
+        let first = create_snapshot(&mut conn, &list.id, Some("Printed")).await?;
+        update_candidate_list_order(&mut conn, &list.id, &[person.id]).await?;
+        let second = create_snapshot(&mut conn, &list.id, None).await?;
+        assert_eq!((first.version, second.version), (1, 2));
+
+        let snapshots = list_snapshots(&mut conn, &list.id).await?;
+        assert_eq!(
+            snapshots.iter().map(|s| s.version).collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+
+        let loaded = get_snapshot(&mut conn, &list.id, 1)
+            .await?
+            .expect("snapshot");
+        assert_eq!(loaded.label.as_deref(), Some("Printed"));
+        assert!(loaded.full_list.candidates.is_empty());
+        let loaded = get_snapshot(&mut conn, &list.id, 2)
+            .await?
+            .expect("snapshot");
+        assert_eq!(loaded.full_list.candidates[0].person.id, person.id);
+
+        let update = sqlx::query!("UPDATE candidate_list_snapshots SET label = 'changed'")
+            .execute(&mut *conn)
+            .await;
+        assert!(update.is_err());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn position_operations_keep_positions_consistent(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let list = insert_list(&mut conn, vec![ElectoralDistrict::UT]).await?;
+        let mut ids = Vec::new();
+        for name in ["Jansen", "Bakker", "Visser", "Smit"] {
+            let person = sample_person_with_last_name(Uuid::new_v4(), name);
+            persons::repository::create_person(&mut conn, &person).await?;
+            ids.push(person.id);
+        }
+        let [a, b, c, d] = ids[..] else {
+            unreachable!()
+        };
+        update_candidate_list_order(&mut conn, &list.id, &[a, b, c]).await?;
+
+        let order = |full_list: FullCandidateList| {
+            full_list
+                .candidates
+                .iter()
+                .map(|c| (c.position, c.person.id))
+                .collect::<Vec<_>>()
+        };
+
+        let operations = [
+            (
+                PositionOperation::Swap {
+                    person_id: a,
+                    other_person_id: c,
+                },
+                vec![(1, c), (2, b), (3, a)],
+            ),
+            (
+                PositionOperation::InsertAt {
+                    person_id: d,
+                    position: 2,
+                },
+                vec![(1, c), (2, d), (3, b), (4, a)],
+            ),
+            (
+                PositionOperation::Move {
+                    person_id: a,
+                    position: 1,
+                },
+                vec![(1, a), (2, c), (3, d), (4, b)],
+            ),
+            (
+                PositionOperation::Remove { person_id: c },
+                vec![(1, a), (2, d), (3, b)],
+            ),
+        ];
+        for (operation, expected) in operations {
+            let full_list = apply_position_operation(&mut conn, &list.id, operation)
+                .await
+                .expect("operation");
+            assert_eq!(order(full_list), expected, "{operation:?}");
+        }
+
+        let reordered = reorder_candidates(&mut conn, &list.id, &[b, a, d])
+            .await
+            .expect("reorder");
+        assert_eq!(order(reordered), vec![(1, b), (2, a), (3, d)]);
+
+        let dropped = reorder_candidates(&mut conn, &list.id, &[b, a]).await;
+        assert!(matches!(dropped, Err(PositionError::NotAPermutation)));
+        let duplicated = reorder_candidates(&mut conn, &list.id, &[b, a, a]).await;
+        assert!(matches!(duplicated, Err(PositionError::NotAPermutation)));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn candidate_positions_are_unique_per_list(pool: PgPool) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let list = insert_list(&mut conn, vec![ElectoralDistrict::UT]).await?;
+        let first = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        let second = sample_person_with_last_name(Uuid::new_v4(), "Bakker");
+        persons::repository::create_person(&mut conn, &first).await?;
+        persons::repository::create_person(&mut conn, &second).await?;
+        update_candidate_list_order(&mut conn, &list.id, &[first.id]).await?;
+
+        let same_position = sqlx::query!(
+            r#"
+            INSERT INTO candidate_lists_persons (candidate_list_id, person_id, position)
+            VALUES ($1, $2, 1)
+            "#,
+            list.id,
+            second.id,
+        )
+        .execute(&mut *conn)
+        .await;
+        assert!(same_position.is_err());
+
+        let same_person = sqlx::query!(
+            r#"
+            INSERT INTO candidate_lists_persons (candidate_list_id, person_id, position)
+            VALUES ($1, $2, 2)
+            "#,
+            list.id,
+            first.id,
+        )
+        .execute(&mut *conn)
+        .await;
+        assert!(same_person.is_err());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn create_candidate_list_with_candidates_keeps_order(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let first = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        let second = sample_person_with_last_name(Uuid::new_v4(), "Bakker");
+        persons::repository::create_person(&mut conn, &first).await?;
+        persons::repository::create_person(&mut conn, &second).await?;
+
+        let list = sample_candidate_list(Uuid::new_v4());
+        let full_list =
+            create_candidate_list_with_candidates(&mut conn, &list, &[second.id, first.id]).await?;
+
+        assert_eq!(full_list.list.id, list.id);
+        let order: Vec<(i32, Uuid)> = full_list
+            .candidates
+            .iter()
+            .map(|c| (c.position, c.person.id))
+            .collect();
+        assert_eq!(order, vec![(1, second.id), (2, first.id)]);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn candidate_lists_are_isolated_between_political_groups(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let group_a = auth::repository::create_political_group(&mut conn, "Lijst A").await?;
+        let group_b = auth::repository::create_political_group(&mut conn, "Lijst B").await?;
+
+        Tenant::PoliticalGroup(group_a).scope(&mut conn).await?;
+        let person = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        persons::repository::create_person(&mut conn, &person).await?;
+        let list = insert_list(&mut conn, vec![ElectoralDistrict::UT]).await?;
+        update_candidate_list_order(&mut conn, &list.id, &[person.id]).await?;
+        assert_eq!(
+            get_used_districts(&mut conn).await?,
+            vec![ElectoralDistrict::UT]
+        );
+
+        Tenant::PoliticalGroup(group_b).scope(&mut conn).await?;
+        assert!(get_used_districts(&mut conn).await?.is_empty());
+        assert_eq!(count_candidate_lists(&mut conn).await?, 0);
+        assert!(
+            list_candidate_list_summaries(
+                &mut conn,
+                None,
+                0,
+                &CandidateListSort::CreatedAt,
+                &SortDirection::Asc
+            )
+            .await?
+            .is_empty()
+        );
+        assert!(list_candidate_matrix(&mut conn).await?.is_empty());
+        assert!(
+            get_full_candidate_list(&mut conn, &list.id)
+                .await?
+                .is_none()
+        );
+        assert!(
+            list_memberships_for_person(&mut conn, &person.id)
+                .await?
+                .is_empty()
+        );
+
+        // a list can not be filled with, or emptied of, candidates of another group
+        let own_list = insert_list(&mut conn, vec![ElectoralDistrict::DR]).await?;
+        assert!(
+            update_candidate_list_order(&mut conn, &own_list.id, &[person.id])
+                .await
+                .is_err()
+        );
+        assert!(
+            remove_person_from_all_lists(&mut conn, &person.id)
+                .await?
+                .is_empty()
+        );
+
+        Tenant::PoliticalGroup(group_a).scope(&mut conn).await?;
+        assert_eq!(count_candidate_lists(&mut conn).await?, 1);
+        let full_list = get_full_candidate_list(&mut conn, &list.id)
+            .await?
+            .expect("list");
+        assert_eq!(full_list.candidates.len(), 1);
+
+        Tenant::AllPoliticalGroups.scope(&mut conn).await?;
+        assert_eq!(count_candidate_lists(&mut conn).await?, 2);
+        assert_eq!(
+            BTreeSet::from_iter(get_used_districts(&mut conn).await?),
+            BTreeSet::from([ElectoralDistrict::DR, ElectoralDistrict::UT])
+        );
+
+        Ok(())
+    }
 }
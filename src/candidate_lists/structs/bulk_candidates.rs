@@ -0,0 +1,117 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validate::Validate;
+
+use crate::{
+    Locale, TokenValue,
+    form::{CsrfToken, WithCsrfToken},
+    t,
+};
+
+/// Persons to add to a candidate list, in the order they were selected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkAddCandidates {
+    pub person_ids: Vec<Uuid>,
+    /// Position of the first added person; the end of the list when not set.
+    pub position: Option<usize>,
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Debug, Validate)]
+#[validate(
+    target = "BulkAddCandidates",
+    build = "BulkAddCandidatesForm::build_selection"
+)]
+#[serde(default)]
+pub struct BulkAddCandidatesForm {
+    pub person_ids: Vec<Uuid>,
+    #[validate(parse = "usize", optional)]
+    pub position: String,
+    #[validate(csrf)]
+    pub csrf_token: TokenValue,
+}
+
+impl BulkAddCandidatesForm {
+    fn build_selection(
+        validated: BulkAddCandidatesFormValidated,
+        _: Option<&BulkAddCandidates>,
+    ) -> BulkAddCandidates {
+        BulkAddCandidates {
+            person_ids: validated.person_ids,
+            position: validated.position,
+        }
+    }
+}
+
+impl WithCsrfToken for BulkAddCandidatesForm {
+    fn with_csrf_token(self, csrf_token: CsrfToken) -> Self {
+        BulkAddCandidatesForm {
+            csrf_token: csrf_token.value,
+            ..self
+        }
+    }
+}
+
+/// What happened to a single person in a bulk change of a candidate list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CandidateBulkOutcome {
+    Added { position: usize },
+    Removed,
+    AlreadyOnList,
+    NotOnList,
+    PersonNotFound,
+}
+
+impl CandidateBulkOutcome {
+    pub fn succeeded(&self) -> bool {
+        matches!(
+            self,
+            CandidateBulkOutcome::Added { .. } | CandidateBulkOutcome::Removed
+        )
+    }
+
+    pub fn message(&self, locale: &Locale) -> String {
+        match self {
+            CandidateBulkOutcome::Added { position } => t!("bulk.added", locale, position),
+            CandidateBulkOutcome::Removed => t!("bulk.removed", locale).to_string(),
+            CandidateBulkOutcome::AlreadyOnList => t!("bulk.already_on_list", locale).to_string(),
+            CandidateBulkOutcome::NotOnList => t!("bulk.not_on_list", locale).to_string(),
+            CandidateBulkOutcome::PersonNotFound => t!("bulk.not_found", locale).to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CsrfTokens, form::Validate};
+
+    #[test]
+    fn keeps_selection_order_and_optional_position() {
+        let tokens = CsrfTokens::default();
+        let person_ids = vec![Uuid::new_v4(), Uuid::new_v4()];
+        let form = BulkAddCandidatesForm {
+            person_ids: person_ids.clone(),
+            position: String::new(),
+            csrf_token: tokens.issue().value,
+        };
+        assert_eq!(
+            form.validate(None, &tokens).unwrap(),
+            BulkAddCandidates {
+                person_ids: person_ids.clone(),
+                position: None,
+            }
+        );
+
+        let form = BulkAddCandidatesForm {
+            person_ids,
+            position: "three".to_string(),
+            csrf_token: tokens.issue().value,
+        };
+        let Err(data) = form.validate(None, &tokens) else {
+            panic!("expected validation errors");
+        };
+        assert!(data.errors().iter().any(|(field, _)| field == "position"));
+    }
+}
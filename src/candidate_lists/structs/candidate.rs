@@ -1,11 +1,14 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::persons::Person;
+use crate::{candidate_lists::CandidateConsent, persons::Person};
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Candidate {
     pub list_id: Uuid,
     pub position: i32,
     pub person: Person,
+    /// Decision of the candidate about their position, missing in older snapshots.
+    #[serde(default)]
+    pub consent: Option<CandidateConsent>,
 }
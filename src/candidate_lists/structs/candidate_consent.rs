@@ -0,0 +1,105 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use strum::{Display, EnumString};
+use uuid::Uuid;
+use validate::Validate;
+
+use crate::{constants::DEFAULT_DATE_TIME_FORMAT, form::*, t};
+
+/// Number of days a candidate can use the link to review their position.
+pub const CONSENT_INVITATION_TTL_DAYS: i64 = 14;
+
+/// Whether a candidate agrees with their position on a list (instemming).
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, Display, EnumString,
+)]
+#[sqlx(type_name = "candidate_consent", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ConsentDecision {
+    Accepted,
+    Declined,
+}
+
+impl ConsentDecision {
+    pub fn label(&self) -> &[&'static str] {
+        match self {
+            ConsentDecision::Accepted => t!("consent.accepted"),
+            ConsentDecision::Declined => t!("consent.declined"),
+        }
+    }
+}
+
+/// Decision of a candidate, to accept or decline their position.
+#[derive(Default, Serialize, Deserialize, Clone, Debug, Validate)]
+#[validate(target = "ConsentDecision", build = "ConsentForm::build")]
+#[serde(default)]
+pub struct ConsentForm {
+    #[validate(parse = "ConsentDecision")]
+    pub decision: String,
+    #[validate(csrf)]
+    pub csrf_token: TokenValue,
+}
+
+impl WithCsrfToken for ConsentForm {
+    fn with_csrf_token(self, csrf_token: CsrfToken) -> Self {
+        ConsentForm {
+            csrf_token: csrf_token.value,
+            ..self
+        }
+    }
+}
+
+impl ConsentForm {
+    fn build(
+        validated: ConsentFormValidated,
+        _current: Option<&ConsentDecision>,
+    ) -> ConsentDecision {
+        validated.decision
+    }
+}
+
+/// The decision of a candidate about their current position; it is cleared when the position
+/// changes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CandidateConsent {
+    pub decision: ConsentDecision,
+    pub decided_at: DateTime<Utc>,
+}
+
+impl CandidateConsent {
+    pub fn from_row(
+        decision: Option<ConsentDecision>,
+        decided_at: Option<DateTime<Utc>>,
+    ) -> Option<Self> {
+        Some(CandidateConsent {
+            decision: decision?,
+            decided_at: decided_at?,
+        })
+    }
+
+    pub fn is_accepted(&self) -> bool {
+        self.decision == ConsentDecision::Accepted
+    }
+
+    pub fn decided(&self) -> String {
+        self.decided_at.format(DEFAULT_DATE_TIME_FORMAT).to_string()
+    }
+}
+
+/// A personal link for a candidate to review their position on a list.
+#[derive(Debug, Clone)]
+pub struct ConsentInvitation {
+    pub candidate_list_id: Uuid,
+    pub person_id: Uuid,
+    /// Political group of the list, the connection is scoped to it to show the list.
+    pub political_group_id: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl ConsentInvitation {
+    pub fn expires(&self) -> String {
+        self.expires_at.format(DEFAULT_DATE_TIME_FORMAT).to_string()
+    }
+}
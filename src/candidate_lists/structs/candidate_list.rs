@@ -3,12 +3,15 @@ use serde::{Deserialize, Serialize};
 use sqlx::types::chrono::Utc;
 use uuid::Uuid;
 
-use crate::{AppError, ElectionConfig, ElectoralDistrict, Locale, candidate_lists::Candidate, t};
+use crate::{
+    AppError, ElectionConfig, ElectoralDistrict, Locale, candidate_lists::Candidate,
+    constants::DEFAULT_DATE_TIME_FORMAT, form::version_of, t,
+};
 
 /// Maximum number of persons allowed on a candidate list.
 pub const MAX_CANDIDATES: usize = 50;
 
-#[derive(Debug, Clone, Deserialize, Serialize, sqlx::Type, PartialEq, Eq)]
+#[derive(Debug, Clone, Deserialize, Serialize, sqlx::Type, sqlx::FromRow, PartialEq, Eq)]
 pub struct CandidateList {
     pub id: Uuid,
     pub electoral_districts: Vec<ElectoralDistrict>,
@@ -16,19 +19,39 @@ pub struct CandidateList {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, sqlx::FromRow)]
 pub struct CandidateListSummary {
+    #[sqlx(flatten)]
     pub list: CandidateList,
     pub person_count: i64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// A soft deleted candidate list, waiting to be restored or purged.
+#[derive(Debug, Clone)]
+pub struct DeletedCandidateList {
+    pub list: CandidateList,
+    pub person_count: i64,
+    pub deleted_at: DateTime<Utc>,
+}
+
+impl DeletedCandidateList {
+    pub fn deleted(&self) -> String {
+        self.deleted_at.format(DEFAULT_DATE_TIME_FORMAT).to_string()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FullCandidateList {
     pub list: CandidateList,
     pub candidates: Vec<Candidate>,
 }
 
 impl CandidateList {
+    /// Version token used to reject updates based on an outdated copy of this list.
+    pub fn version(&self) -> String {
+        version_of(&self.updated_at)
+    }
+
     pub fn display_districts(&self, election: &ElectionConfig, locale: &Locale) -> String {
         if !self.electoral_districts.is_empty()
             && self.electoral_districts.len() == election.electoral_districts().len()
@@ -60,10 +83,6 @@ impl FullCandidateList {
                 AppError::NotFound(t!("person.not_found_in_candidate_list", &locale).to_string())
             })
     }
-
-    pub fn get_ids(&self) -> Vec<Uuid> {
-        self.candidates.iter().map(|c| c.person.id).collect()
-    }
 }
 
 #[cfg(test)]
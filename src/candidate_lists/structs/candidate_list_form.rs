@@ -1,17 +1,26 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{ElectoralDistrict, TokenValue, candidate_lists::CandidateList, form::WithCsrfToken};
+use crate::{
+    ElectionConfig, ElectoralDistrict, TokenValue, candidate_lists::CandidateList,
+    form::WithCsrfToken,
+};
 use validate::Validate as ValidateDerive;
 
 #[derive(Default, Serialize, Deserialize, Clone, Debug, ValidateDerive)]
 #[validate(
     target = "CandidateList",
-    build = "CandidateListForm::build_candidate_list"
+    build = "CandidateListForm::build_candidate_list",
+    context = "ElectionConfig"
 )]
 #[serde(default)]
 pub struct CandidateListForm {
+    /// Districts the list is submitted in, at least one of the election.
+    #[validate(min_items = 1, allowed = "context.electoral_districts()")]
     pub electoral_districts: Vec<ElectoralDistrict>,
+    /// Version of the list the form was loaded from, empty when creating a list.
+    #[validate(skip)]
+    pub version: String,
     #[validate(csrf)]
     pub csrf_token: TokenValue,
 }
@@ -26,7 +35,7 @@ impl CandidateListForm {
                 id: current.id,
                 electoral_districts: validated.electoral_districts,
                 created_at: current.created_at,
-                updated_at: chrono::Utc::now(),
+                updated_at: current.updated_at,
             }
         } else {
             CandidateList {
@@ -41,8 +50,10 @@ impl CandidateListForm {
 
 impl From<CandidateList> for CandidateListForm {
     fn from(value: CandidateList) -> Self {
+        let version = value.version();
         CandidateListForm {
             electoral_districts: value.electoral_districts,
+            version,
             csrf_token: TokenValue(String::new()),
         }
     }
@@ -57,12 +68,53 @@ impl WithCsrfToken for CandidateListForm {
     }
 }
 
+/// Form to create a new list from an existing list, which serves as a template.
+#[derive(Default, Serialize, Deserialize, Clone, Debug, ValidateDerive)]
+#[validate(
+    target = "CandidateList",
+    build = "DuplicateCandidateListForm::build_candidate_list",
+    context = "ElectionConfig"
+)]
+#[serde(default)]
+pub struct DuplicateCandidateListForm {
+    #[validate(min_items = 1, allowed = "context.electoral_districts()")]
+    pub electoral_districts: Vec<ElectoralDistrict>,
+    /// Candidates of the template that are left out of the new list.
+    #[validate(skip)]
+    pub excluded_candidates: Vec<Uuid>,
+    #[validate(csrf)]
+    pub csrf_token: TokenValue,
+}
+
+impl DuplicateCandidateListForm {
+    fn build_candidate_list(
+        validated: DuplicateCandidateListFormValidated,
+        _: Option<&CandidateList>,
+    ) -> CandidateList {
+        CandidateList {
+            id: Uuid::new_v4(),
+            electoral_districts: validated.electoral_districts,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+}
+
+impl WithCsrfToken for DuplicateCandidateListForm {
+    fn with_csrf_token(self, csrf_token: crate::form::CsrfToken) -> Self {
+        DuplicateCandidateListForm {
+            csrf_token: csrf_token.value,
+            ..self
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
         CsrfTokens, ElectoralDistrict,
-        form::{Validate, ValidationError},
+        form::{ValidateWith, ValidationError},
     };
 
     #[test]
@@ -71,22 +123,66 @@ mod tests {
         let csrf_token = tokens.issue().value;
         let form = CandidateListForm {
             electoral_districts: vec![ElectoralDistrict::UT],
+            version: String::new(),
             csrf_token,
         };
 
-        let list = form.validate(None, &tokens).unwrap();
+        let list = form
+            .validate_with(&ElectionConfig::EK2027, None, &tokens)
+            .unwrap();
         assert_eq!(list.electoral_districts, vec![ElectoralDistrict::UT]);
     }
 
+    #[test]
+    fn builds_new_list_from_duplicate_form() {
+        let tokens = CsrfTokens::default();
+        let form = DuplicateCandidateListForm {
+            electoral_districts: vec![ElectoralDistrict::DR, ElectoralDistrict::FR],
+            excluded_candidates: vec![Uuid::new_v4()],
+            csrf_token: tokens.issue().value,
+        };
+
+        let list = form
+            .validate_with(&ElectionConfig::EK2027, None, &tokens)
+            .unwrap();
+        assert_eq!(
+            list.electoral_districts,
+            vec![ElectoralDistrict::DR, ElectoralDistrict::FR]
+        );
+    }
+
+    #[test]
+    fn requires_a_district() {
+        let tokens = CsrfTokens::default();
+        let form = CandidateListForm {
+            electoral_districts: Vec::new(),
+            version: String::new(),
+            csrf_token: tokens.issue().value,
+        };
+
+        let Err(data) = form.validate_with(&ElectionConfig::EK2027, None, &tokens) else {
+            panic!("expected validation errors");
+        };
+
+        assert_eq!(
+            data.errors(),
+            &vec![(
+                "electoral_districts".to_string(),
+                ValidationError::TooFewItems(1)
+            )]
+        );
+    }
+
     #[test]
     fn rejects_invalid_csrf_token() {
         let tokens = CsrfTokens::default();
         let form = CandidateListForm {
             electoral_districts: vec![ElectoralDistrict::UT],
+            version: String::new(),
             csrf_token: TokenValue("invalid".to_string()),
         };
 
-        let Err(data) = form.validate(None, &tokens) else {
+        let Err(data) = form.validate_with(&ElectionConfig::EK2027, None, &tokens) else {
             panic!("expected validation errors");
         };
 
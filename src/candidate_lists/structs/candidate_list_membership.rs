@@ -0,0 +1,10 @@
+use serde::Serialize;
+
+use crate::candidate_lists::CandidateList;
+
+/// A candidate list a person is on, with their position on it.
+#[derive(Debug, Clone, Serialize)]
+pub struct CandidateListMembership {
+    pub list: CandidateList,
+    pub position: i32,
+}
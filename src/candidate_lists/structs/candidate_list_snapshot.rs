@@ -0,0 +1,199 @@
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::Utc;
+use validate::Validate;
+
+use crate::{
+    Locale, TokenValue,
+    candidate_lists::{Candidate, FullCandidateList},
+    constants::DEFAULT_DATE_TIME_FORMAT,
+    form::*,
+    persons::{FieldChange, Person},
+};
+
+/// Immutable copy of a candidate list with its candidates, as it was at a given moment.
+#[derive(Debug, Clone)]
+pub struct CandidateListSnapshot {
+    /// Sequence number of the snapshot within its list, starting at 1.
+    pub version: i32,
+    pub label: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub full_list: FullCandidateList,
+}
+
+impl CandidateListSnapshot {
+    pub fn created(&self) -> String {
+        self.created_at.format(DEFAULT_DATE_TIME_FORMAT).to_string()
+    }
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Debug, Validate)]
+#[validate(target = "Option<String>", build = "SnapshotForm::build_label")]
+#[serde(default)]
+pub struct SnapshotForm {
+    #[validate(with = "validate_length(1, 255)", optional)]
+    pub label: String,
+    #[validate(csrf)]
+    pub csrf_token: TokenValue,
+}
+
+impl SnapshotForm {
+    fn build_label(validated: SnapshotFormValidated, _: Option<&Option<String>>) -> Option<String> {
+        validated.label
+    }
+}
+
+impl WithCsrfToken for SnapshotForm {
+    fn with_csrf_token(self, csrf_token: CsrfToken) -> Self {
+        SnapshotForm {
+            csrf_token: csrf_token.value,
+            ..self
+        }
+    }
+}
+
+/// A candidate that is on both lists, but at a different position.
+#[derive(Debug, Clone)]
+pub struct PositionMove {
+    pub person: Person,
+    pub from: i32,
+    pub to: i32,
+}
+
+/// A candidate on both lists whose personal details changed.
+#[derive(Debug, Clone)]
+pub struct CandidateChange {
+    pub person: Person,
+    pub fields: Vec<FieldChange>,
+}
+
+/// Differences between an older and a newer version of a candidate list.
+#[derive(Debug, Clone, Default)]
+pub struct CandidateListDiff {
+    pub added: Vec<Candidate>,
+    pub removed: Vec<Candidate>,
+    pub moved: Vec<PositionMove>,
+    pub changed: Vec<CandidateChange>,
+}
+
+impl CandidateListDiff {
+    pub fn between(old: &FullCandidateList, new: &FullCandidateList, locale: Locale) -> Self {
+        let mut diff = CandidateListDiff::default();
+
+        for candidate in &new.candidates {
+            let Some(previous) = old
+                .candidates
+                .iter()
+                .find(|c| c.person.id == candidate.person.id)
+            else {
+                diff.added.push(candidate.clone());
+                continue;
+            };
+
+            if previous.position != candidate.position {
+                diff.moved.push(PositionMove {
+                    person: candidate.person.clone(),
+                    from: previous.position,
+                    to: candidate.position,
+                });
+            }
+
+            let fields = candidate.person.changes_since(&previous.person, locale);
+            if !fields.is_empty() {
+                diff.changed.push(CandidateChange {
+                    person: candidate.person.clone(),
+                    fields,
+                });
+            }
+        }
+
+        diff.removed = old
+            .candidates
+            .iter()
+            .filter(|c| new.get_index(&c.person.id).is_none())
+            .cloned()
+            .collect();
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.moved.is_empty()
+            && self.changed.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    use crate::{
+        t,
+        test_utils::{sample_candidate_list, sample_person_with_last_name},
+    };
+
+    fn full_list(list_id: Uuid, persons: &[&Person]) -> FullCandidateList {
+        FullCandidateList {
+            list: sample_candidate_list(list_id),
+            candidates: persons
+                .iter()
+                .enumerate()
+                .map(|(i, person)| Candidate {
+                    list_id,
+                    position: i as i32 + 1,
+                    person: (*person).clone(),
+                    consent: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn diff_lists_added_removed_moved_and_changed_candidates() {
+        let list_id = Uuid::new_v4();
+        let stays = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        let removed = sample_person_with_last_name(Uuid::new_v4(), "Bakker");
+        let moves = sample_person_with_last_name(Uuid::new_v4(), "de Vries");
+        let added = sample_person_with_last_name(Uuid::new_v4(), "Visser");
+        let renamed = Person {
+            last_name: "Jansen-Bakker".to_string(),
+            ..stays.clone()
+        };
+
+        let old = full_list(list_id, &[&stays, &removed, &moves]);
+        let new = full_list(list_id, &[&renamed, &moves, &added]);
+
+        let diff = CandidateListDiff::between(&old, &new, Locale::En);
+
+        assert_eq!(
+            diff.added.iter().map(|c| c.person.id).collect::<Vec<_>>(),
+            vec![added.id]
+        );
+        assert_eq!(
+            diff.removed.iter().map(|c| c.person.id).collect::<Vec<_>>(),
+            vec![removed.id]
+        );
+        assert_eq!(
+            diff.moved
+                .iter()
+                .map(|m| (m.person.id, m.from, m.to))
+                .collect::<Vec<_>>(),
+            vec![(moves.id, 3, 2)]
+        );
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].person.id, stays.id);
+        assert_eq!(
+            diff.changed[0].fields,
+            vec![FieldChange {
+                field: t!("person.fields.last_name"),
+                old: "Jansen".to_string(),
+                new: "Jansen-Bakker".to_string(),
+            }]
+        );
+        assert!(!diff.is_empty());
+        assert!(CandidateListDiff::between(&new, &new, Locale::En).is_empty());
+    }
+}
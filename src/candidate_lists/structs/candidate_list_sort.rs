@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+use strum::AsRefStr;
+
+use crate::pagination::{SortColumn, SortDirection};
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, AsRefStr, Serialize, Deserialize)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum CandidateListSort {
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+    PersonCount,
+}
+
+impl SortColumn for CandidateListSort {
+    fn order_by(&self, direction: SortDirection) -> &'static str {
+        match (self, direction) {
+            (CandidateListSort::CreatedAt, SortDirection::Asc) => "cl.created_at ASC, cl.id ASC",
+            (CandidateListSort::CreatedAt, SortDirection::Desc) => "cl.created_at DESC, cl.id ASC",
+            (CandidateListSort::UpdatedAt, SortDirection::Asc) => "cl.updated_at ASC, cl.id ASC",
+            (CandidateListSort::UpdatedAt, SortDirection::Desc) => "cl.updated_at DESC, cl.id ASC",
+            (CandidateListSort::PersonCount, SortDirection::Asc) => {
+                "COUNT(clp.person_id) ASC, cl.id ASC"
+            }
+            (CandidateListSort::PersonCount, SortDirection::Desc) => {
+                "COUNT(clp.person_id) DESC, cl.id ASC"
+            }
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::request::Parts,
+};
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::{AppError, AppState, CurrentUser, t};
+
+/// Number of minutes a change of a candidate list can be undone.
+pub const UNDO_TTL_MINUTES: i64 = 10;
+
+/// Number of changes per list and user that can be undone.
+pub const MAX_UNDO_DEPTH: usize = 10;
+
+/// A candidate on a list as stored, to put them back exactly as they were.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CandidateListEntry {
+    pub person_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UndoAction {
+    Removed,
+    RemovedMany,
+    Reordered,
+}
+
+impl UndoAction {
+    pub fn message_key(&self) -> &[&'static str] {
+        match self {
+            UndoAction::Removed => t!("undo.removed"),
+            UndoAction::RemovedMany => t!("undo.removed_many"),
+            UndoAction::Reordered => t!("undo.reordered"),
+        }
+    }
+}
+
+/// A change of the candidates on a list that can be undone.
+#[derive(Debug, Clone)]
+pub struct UndoEntry {
+    pub action: UndoAction,
+    /// Candidates on the list before the change, in order.
+    pub previous: Vec<CandidateListEntry>,
+    /// Candidates on the list after the change; undo is refused once the list no longer
+    /// matches, so later changes are never overwritten.
+    pub current: Vec<Uuid>,
+    /// Persons that were deleted along with their removal from the list.
+    pub deleted_persons: Vec<Uuid>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl UndoEntry {
+    pub fn new(
+        action: UndoAction,
+        previous: Vec<CandidateListEntry>,
+        current: &[CandidateListEntry],
+    ) -> Self {
+        Self {
+            action,
+            previous,
+            current: current.iter().map(|entry| entry.person_id).collect(),
+            deleted_persons: Vec::new(),
+            expires_at: Utc::now() + Duration::minutes(UNDO_TTL_MINUTES),
+        }
+    }
+
+    pub fn with_deleted_persons(self, deleted_persons: Vec<Uuid>) -> Self {
+        Self {
+            deleted_persons,
+            ..self
+        }
+    }
+
+    /// Whether the change actually did something.
+    fn is_change(&self) -> bool {
+        !self.deleted_persons.is_empty()
+            || self.current.len() != self.previous.len()
+            || self
+                .current
+                .iter()
+                .zip(&self.previous)
+                .any(|(current, previous)| current != &previous.person_id)
+    }
+}
+
+/// Undo stacks by candidate list and user id.
+type UndoStackMap = HashMap<(Uuid, Uuid), Vec<UndoEntry>>;
+
+/// Short-lived undo stacks of all users, per candidate list.
+#[derive(Default, Clone)]
+pub struct UndoStacks {
+    stacks: Arc<RwLock<UndoStackMap>>,
+}
+
+impl UndoStacks {
+    fn purge_locked(stacks: &mut UndoStackMap) {
+        let now = Utc::now();
+        stacks.retain(|_, entries| {
+            entries.retain(|entry| entry.expires_at > now);
+            !entries.is_empty()
+        });
+    }
+}
+
+/// The undo stacks of the signed in user making the request, shared by all their sessions.
+#[derive(Default, Clone)]
+pub struct CandidateListUndo {
+    stacks: UndoStacks,
+    user_id: Uuid,
+}
+
+impl CandidateListUndo {
+    /// Remember a change of a list, dropping the oldest change when the stack is full.
+    pub fn push(&self, list_id: Uuid, entry: UndoEntry) {
+        if !entry.is_change() {
+            return;
+        }
+
+        let mut stacks = self.stacks.stacks.write().expect("undo store poisoned");
+        UndoStacks::purge_locked(&mut stacks);
+
+        let entries = stacks.entry((list_id, self.user_id)).or_default();
+        entries.push(entry);
+        if entries.len() > MAX_UNDO_DEPTH {
+            entries.remove(0);
+        }
+    }
+
+    /// The most recent change of a list that can still be undone.
+    pub fn latest(&self, list_id: Uuid) -> Option<UndoAction> {
+        let stacks = self.stacks.stacks.read().expect("undo store poisoned");
+        let now = Utc::now();
+
+        stacks
+            .get(&(list_id, self.user_id))?
+            .iter()
+            .rev()
+            .find(|entry| entry.expires_at > now)
+            .map(|entry| entry.action)
+    }
+
+    /// Take the most recent change of a list off the stack.
+    pub fn pop(&self, list_id: Uuid) -> Option<UndoEntry> {
+        let mut stacks = self.stacks.stacks.write().expect("undo store poisoned");
+        UndoStacks::purge_locked(&mut stacks);
+
+        stacks.get_mut(&(list_id, self.user_id))?.pop()
+    }
+}
+
+impl<S> FromRequestParts<S> for CandidateListUndo
+where
+    S: Send + Sync,
+    AppState: FromRef<S>,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let user = parts
+            .extensions
+            .get::<CurrentUser>()
+            .ok_or(AppError::Unauthorized)?;
+
+        Ok(Self {
+            stacks: AppState::from_ref(state).undo_stacks().clone(),
+            user_id: user.id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Request;
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::{Role, test_utils::sample_user};
+
+    fn entry(person_id: Uuid) -> CandidateListEntry {
+        CandidateListEntry {
+            person_id,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn stacks_are_kept_per_list_and_user() {
+        let undo = CandidateListUndo::default();
+        let other_user = CandidateListUndo {
+            user_id: Uuid::new_v4(),
+            ..undo.clone()
+        };
+        let list_id = Uuid::new_v4();
+        let (a, b) = (entry(Uuid::new_v4()), entry(Uuid::new_v4()));
+
+        undo.push(
+            list_id,
+            UndoEntry::new(
+                UndoAction::Reordered,
+                vec![a.clone(), b.clone()],
+                &[b.clone(), a.clone()],
+            ),
+        );
+        undo.push(
+            list_id,
+            UndoEntry::new(
+                UndoAction::Removed,
+                vec![b.clone(), a],
+                std::slice::from_ref(&b),
+            ),
+        );
+
+        assert_eq!(other_user.latest(list_id), None);
+        assert_eq!(undo.latest(Uuid::new_v4()), None);
+        assert_eq!(undo.latest(list_id), Some(UndoAction::Removed));
+
+        assert_eq!(undo.pop(list_id).unwrap().current, vec![b.person_id]);
+        assert_eq!(undo.latest(list_id), Some(UndoAction::Reordered));
+        assert!(undo.pop(list_id).is_some());
+        assert!(undo.pop(list_id).is_none());
+    }
+
+    #[test]
+    fn ignores_changes_that_did_nothing() {
+        let undo = CandidateListUndo::default();
+        let list_id = Uuid::new_v4();
+        let a = entry(Uuid::new_v4());
+
+        undo.push(
+            list_id,
+            UndoEntry::new(UndoAction::Reordered, vec![a.clone()], &[a]),
+        );
+
+        assert_eq!(undo.latest(list_id), None);
+    }
+
+    #[test]
+    fn keeps_a_limited_number_of_changes() {
+        let undo = CandidateListUndo::default();
+        let list_id = Uuid::new_v4();
+
+        for _ in 0..MAX_UNDO_DEPTH + 5 {
+            undo.push(
+                list_id,
+                UndoEntry::new(UndoAction::Removed, vec![entry(Uuid::new_v4())], &[]),
+            );
+        }
+
+        let mut count = 0;
+        while undo.pop(list_id).is_some() {
+            count += 1;
+        }
+        assert_eq!(count, MAX_UNDO_DEPTH);
+    }
+
+    #[sqlx::test]
+    async fn stacks_belong_to_the_signed_in_user(pool: PgPool) {
+        let state = AppState::new_for_tests(pool);
+        let user = sample_user(vec![Role::ListSubmitter]);
+        let list_id = Uuid::new_v4();
+
+        let extract = |user: Option<CurrentUser>| {
+            let state = state.clone();
+            async move {
+                let (mut parts, ()) = Request::new(()).into_parts();
+                if let Some(user) = user {
+                    parts.extensions.insert(user);
+                }
+                CandidateListUndo::from_request_parts(&mut parts, &state).await
+            }
+        };
+
+        let undo = extract(Some(user.clone())).await.unwrap();
+        undo.push(
+            list_id,
+            UndoEntry::new(UndoAction::Removed, vec![entry(Uuid::new_v4())], &[]),
+        );
+
+        let other_session = CurrentUser {
+            session_id: Uuid::new_v4(),
+            ..user
+        };
+        let same_user = extract(Some(other_session)).await.unwrap();
+        assert_eq!(same_user.latest(list_id), Some(UndoAction::Removed));
+
+        let other_user = extract(Some(sample_user(vec![Role::ListSubmitter])))
+            .await
+            .unwrap();
+        assert_eq!(other_user.latest(list_id), None);
+
+        assert!(matches!(extract(None).await, Err(AppError::Unauthorized)));
+    }
+}
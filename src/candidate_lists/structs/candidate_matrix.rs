@@ -0,0 +1,390 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    AppError, ElectionConfig, Locale,
+    candidate_lists::CandidateList,
+    pagination::{SortDirection, empty_as_none},
+    persons::Person,
+    t,
+};
+
+/// A person with the candidate lists they are on, as returned by
+/// `candidate_lists::repository::list_candidate_matrix`.
+#[derive(Debug, Clone)]
+pub struct CandidateMatrixEntry {
+    pub person: Person,
+    /// Pairs of list id and position.
+    pub positions: Vec<(Uuid, i32)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CandidateMatrixRow {
+    pub person: Person,
+    /// Position of the person on each list of the matrix, `None` when not on that list.
+    pub positions: Vec<Option<i32>>,
+}
+
+impl CandidateMatrixRow {
+    pub fn list_count(&self) -> usize {
+        self.positions.iter().flatten().count()
+    }
+
+    /// Whether the person is on several lists, at different positions.
+    pub fn has_differing_positions(&self) -> bool {
+        self.positions
+            .iter()
+            .flatten()
+            .collect::<BTreeSet<_>>()
+            .len()
+            > 1
+    }
+}
+
+/// Persons as rows, candidate lists as columns and positions in the cells.
+#[derive(Debug, Clone)]
+pub struct CandidateMatrix {
+    pub lists: Vec<CandidateList>,
+    pub rows: Vec<CandidateMatrixRow>,
+}
+
+/// Query parameters of the candidate matrix, for filtering and sorting.
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CandidateMatrixQuery {
+    /// Only show persons whose name or locality contains this text.
+    #[serde(deserialize_with = "empty_as_none")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search: Option<String>,
+    /// Only show persons on this candidate list.
+    #[serde(deserialize_with = "empty_as_none")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub candidate_list: Option<Uuid>,
+    /// Only show persons that are at different positions on different lists.
+    #[serde(skip_serializing_if = "is_false")]
+    pub differing_positions: bool,
+    /// Sort by the position on this list; sorts by name when not set.
+    #[serde(deserialize_with = "empty_as_none")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_list: Option<Uuid>,
+    #[serde(skip_serializing_if = "is_default_order")]
+    pub order: SortDirection,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+fn is_default_order(order: &SortDirection) -> bool {
+    *order == SortDirection::default()
+}
+
+impl CandidateMatrixQuery {
+    /// Whether any filter is set.
+    pub fn is_filtered(&self) -> bool {
+        self.search.is_some() || self.candidate_list.is_some() || self.differing_positions
+    }
+
+    fn query_string(&self) -> String {
+        let query = serde_urlencoded::to_string(self).unwrap_or_default();
+        if query.is_empty() {
+            query
+        } else {
+            format!("?{query}")
+        }
+    }
+
+    /// Query string that sorts by `sort_list`, reversing the order when already sorted by it.
+    pub fn sort_link(&self, sort_list: Option<Uuid>) -> String {
+        CandidateMatrixQuery {
+            sort_list,
+            order: if sort_list == self.sort_list {
+                self.order.reverse()
+            } else {
+                SortDirection::Asc
+            },
+            ..self.clone()
+        }
+        .query_string()
+    }
+
+    pub fn dir_icon(&self, sort_list: Option<Uuid>) -> &'static str {
+        if sort_list == self.sort_list {
+            match self.order {
+                SortDirection::Asc => "▲",
+                SortDirection::Desc => "▼",
+            }
+        } else {
+            ""
+        }
+    }
+
+    /// The current query string, to export the matrix as shown.
+    pub fn export_query(&self) -> String {
+        self.query_string()
+    }
+}
+
+impl CandidateMatrix {
+    pub fn new(lists: Vec<CandidateList>, entries: Vec<CandidateMatrixEntry>) -> Self {
+        let rows = entries
+            .into_iter()
+            .map(|entry| CandidateMatrixRow {
+                positions: lists
+                    .iter()
+                    .map(|list| {
+                        entry
+                            .positions
+                            .iter()
+                            .find(|(list_id, _)| list_id == &list.id)
+                            .map(|(_, position)| *position)
+                    })
+                    .collect(),
+                person: entry.person,
+            })
+            .collect();
+
+        CandidateMatrix { lists, rows }
+    }
+
+    /// Keep the rows matching the query filters, sorted as requested.
+    pub fn apply(mut self, query: &CandidateMatrixQuery) -> Self {
+        let list_index =
+            |id: &Option<Uuid>| id.and_then(|id| self.lists.iter().position(|list| list.id == id));
+        let filter_index = list_index(&query.candidate_list);
+        let sort_index = list_index(&query.sort_list);
+        let search = query.search.as_ref().map(|s| s.to_lowercase());
+
+        self.rows.retain(|row| {
+            let on_list = match (query.candidate_list, filter_index) {
+                (None, _) => true,
+                (Some(_), Some(index)) => row.positions[index].is_some(),
+                // unknown list, nobody is on it
+                (Some(_), None) => false,
+            };
+            let matches_search = search.as_ref().is_none_or(|search| {
+                row.person.display_name().to_lowercase().contains(search)
+                    || row
+                        .person
+                        .locality
+                        .as_ref()
+                        .is_some_and(|l| l.to_lowercase().contains(search))
+            });
+
+            on_list
+                && matches_search
+                && (!query.differing_positions || row.has_differing_positions())
+        });
+
+        let by_name = |row: &CandidateMatrixRow| {
+            (
+                row.person.last_name.to_lowercase(),
+                row.person.initials.clone(),
+                row.person.id,
+            )
+        };
+        match sort_index {
+            // persons not on the list go last, whatever the order
+            Some(index) => self.rows.sort_by(|a, b| {
+                let ordering = match (a.positions[index], b.positions[index]) {
+                    (Some(a), Some(b)) if query.order == SortDirection::Desc => b.cmp(&a),
+                    (Some(a), Some(b)) => a.cmp(&b),
+                    (a, b) => b.is_some().cmp(&a.is_some()),
+                };
+                ordering.then_with(|| by_name(a).cmp(&by_name(b)))
+            }),
+            None => {
+                self.rows.sort_by_key(by_name);
+                if query.order == SortDirection::Desc {
+                    self.rows.reverse();
+                }
+            }
+        }
+
+        self
+    }
+
+    /// Column header for the list at `index`, e.g. "List 2 (Drenthe, Friesland)".
+    pub fn list_label(&self, index: usize, election: &ElectionConfig, locale: &Locale) -> String {
+        format!(
+            "{} ({})",
+            t!("candidate_list.coverage.list", locale, index + 1),
+            self.lists[index].display_districts(election, locale)
+        )
+    }
+
+    pub fn to_csv(&self, election: &ElectionConfig, locale: &Locale) -> Result<String, AppError> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+
+        let mut header = vec![
+            t!("person.fields.last_name", locale).to_string(),
+            t!("person.fields.initials", locale).to_string(),
+            t!("person.fields.first_name", locale).to_string(),
+            t!("person.fields.locality", locale).to_string(),
+        ];
+        header.extend((0..self.lists.len()).map(|index| self.list_label(index, election, locale)));
+        writer
+            .write_record(&header)
+            .map_err(|_| AppError::InternalServerError)?;
+
+        for row in &self.rows {
+            let mut record = vec![
+                row.person.last_name_with_prefix_appended(),
+                row.person.initials.clone(),
+                row.person.first_name_display(),
+                row.person.locality.clone().unwrap_or_default(),
+            ];
+            record.extend(
+                row.positions
+                    .iter()
+                    .map(|p| p.map(|p| p.to_string()).unwrap_or_default()),
+            );
+            writer
+                .write_record(&record)
+                .map_err(|_| AppError::InternalServerError)?;
+        }
+
+        let bytes = writer
+            .into_inner()
+            .map_err(|_| AppError::InternalServerError)?;
+        String::from_utf8(bytes).map_err(|_| AppError::InternalServerError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ElectoralDistrict,
+        test_utils::{sample_candidate_list, sample_person_with_last_name},
+    };
+
+    fn sample_matrix() -> (CandidateMatrix, Vec<Uuid>) {
+        let first = sample_candidate_list(Uuid::new_v4());
+        let second = CandidateList {
+            electoral_districts: vec![ElectoralDistrict::DR],
+            ..sample_candidate_list(Uuid::new_v4())
+        };
+        let jansen = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        let bakker = sample_person_with_last_name(Uuid::new_v4(), "Bakker");
+        let visser = sample_person_with_last_name(Uuid::new_v4(), "Visser");
+        let list_ids = vec![first.id, second.id];
+
+        let entries = vec![
+            CandidateMatrixEntry {
+                person: jansen,
+                positions: vec![(first.id, 1), (second.id, 2)],
+            },
+            CandidateMatrixEntry {
+                person: bakker,
+                positions: vec![(second.id, 1)],
+            },
+            CandidateMatrixEntry {
+                person: visser,
+                positions: vec![(first.id, 2), (second.id, 2)],
+            },
+        ];
+
+        (CandidateMatrix::new(vec![first, second], entries), list_ids)
+    }
+
+    fn last_names(matrix: &CandidateMatrix) -> Vec<&str> {
+        matrix
+            .rows
+            .iter()
+            .map(|row| row.person.last_name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn places_positions_in_list_columns() {
+        let (matrix, _) = sample_matrix();
+
+        assert_eq!(matrix.rows[0].positions, vec![Some(1), Some(2)]);
+        assert_eq!(matrix.rows[1].positions, vec![None, Some(1)]);
+        assert_eq!(matrix.rows[1].list_count(), 1);
+        assert!(matrix.rows[0].has_differing_positions());
+        assert!(!matrix.rows[2].has_differing_positions());
+    }
+
+    #[test]
+    fn sorts_by_name_or_list_position() {
+        let (matrix, list_ids) = sample_matrix();
+
+        let by_name = matrix.clone().apply(&CandidateMatrixQuery::default());
+        assert_eq!(last_names(&by_name), vec!["Bakker", "Jansen", "Visser"]);
+
+        let by_first_list = matrix.clone().apply(&CandidateMatrixQuery {
+            sort_list: Some(list_ids[0]),
+            ..Default::default()
+        });
+        assert_eq!(
+            last_names(&by_first_list),
+            vec!["Jansen", "Visser", "Bakker"]
+        );
+
+        let descending = matrix.apply(&CandidateMatrixQuery {
+            sort_list: Some(list_ids[0]),
+            order: SortDirection::Desc,
+            ..Default::default()
+        });
+        assert_eq!(last_names(&descending), vec!["Visser", "Jansen", "Bakker"]);
+    }
+
+    #[test]
+    fn filters_rows() {
+        let (matrix, list_ids) = sample_matrix();
+
+        let on_first_list = matrix.clone().apply(&CandidateMatrixQuery {
+            candidate_list: Some(list_ids[0]),
+            ..Default::default()
+        });
+        assert_eq!(last_names(&on_first_list), vec!["Jansen", "Visser"]);
+
+        let differing = matrix.clone().apply(&CandidateMatrixQuery {
+            differing_positions: true,
+            ..Default::default()
+        });
+        assert_eq!(last_names(&differing), vec!["Jansen"]);
+
+        let search = matrix.apply(&CandidateMatrixQuery {
+            search: Some("BAK".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(last_names(&search), vec!["Bakker"]);
+    }
+
+    #[test]
+    fn sort_link_toggles_order_and_keeps_filters() {
+        let list_id = Uuid::new_v4();
+        let query = CandidateMatrixQuery {
+            search: Some("Jansen".to_string()),
+            sort_list: Some(list_id),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            query.sort_link(Some(list_id)),
+            format!("?search=Jansen&sort_list={list_id}&order=desc")
+        );
+        assert_eq!(query.sort_link(None), "?search=Jansen");
+        assert_eq!(CandidateMatrixQuery::default().export_query(), "");
+    }
+
+    #[test]
+    fn exports_csv() {
+        let (matrix, _) = sample_matrix();
+        let matrix = matrix.apply(&CandidateMatrixQuery::default());
+
+        let csv = matrix.to_csv(&ElectionConfig::EK2027, &Locale::En).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].ends_with(",List 1 (Utrecht),List 2 (Drenthe)"));
+        assert!(lines[1].starts_with("Bakker,"));
+        assert!(lines[1].ends_with(",,1"));
+        assert!(lines[2].ends_with(",1,2"));
+    }
+}
@@ -1,8 +1,13 @@
 use serde::{Deserialize, Serialize};
-use std::str::FromStr;
+use std::{collections::HashSet, str::FromStr};
 use strum::{Display, EnumString};
+use uuid::Uuid;
 
-use crate::form::{TokenValue, WithCsrfToken};
+use crate::{
+    AppError, Locale,
+    form::{TokenValue, WithCsrfToken},
+    t,
+};
 use validate::Validate;
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
@@ -65,3 +70,234 @@ impl From<CandidatePosition> for CandidatePositionForm {
         }
     }
 }
+
+/// A single change to the order of the candidates on a list. Positions start at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(tag = "operation", rename_all = "snake_case")]
+pub enum PositionOperation {
+    /// Move a candidate to `position`, shifting the candidates in between.
+    Move { person_id: Uuid, position: usize },
+    /// Add a person at `position`, shifting the candidates below it down.
+    InsertAt { person_id: Uuid, position: usize },
+    /// Remove a candidate, moving the candidates below it up.
+    Remove { person_id: Uuid },
+    /// Exchange the positions of two candidates.
+    Swap {
+        person_id: Uuid,
+        other_person_id: Uuid,
+    },
+}
+
+#[derive(Debug)]
+pub enum PositionError {
+    NotOnList,
+    AlreadyOnList,
+    InvalidPosition(usize),
+    NotAPermutation,
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for PositionError {
+    fn from(err: sqlx::Error) -> Self {
+        PositionError::Database(err)
+    }
+}
+
+impl PositionError {
+    pub fn into_app_error(self, locale: Locale) -> AppError {
+        match self {
+            PositionError::Database(err) => AppError::DatabaseError(err),
+            PositionError::NotOnList => {
+                AppError::NotFound(t!("person.not_found_in_candidate_list", &locale).to_string())
+            }
+            PositionError::AlreadyOnList => {
+                AppError::BadRequest(t!("candidate_list.positions.already_on_list", &locale))
+            }
+            PositionError::InvalidPosition(position) => AppError::BadRequest(t!(
+                "candidate_list.positions.invalid_position",
+                &locale,
+                position
+            )),
+            PositionError::NotAPermutation => {
+                AppError::BadRequest(t!("candidate_list.positions.not_a_permutation", &locale))
+            }
+        }
+    }
+}
+
+impl PositionOperation {
+    /// Apply the operation to the candidates of a list, given in list order.
+    pub fn apply(&self, person_ids: &[Uuid]) -> Result<Vec<Uuid>, PositionError> {
+        let mut ids = person_ids.to_vec();
+        let index_of = |ids: &[Uuid], person_id: &Uuid| {
+            ids.iter()
+                .position(|id| id == person_id)
+                .ok_or(PositionError::NotOnList)
+        };
+
+        match *self {
+            PositionOperation::Move {
+                person_id,
+                position,
+            } => {
+                let from = index_of(&ids, &person_id)?;
+                if position == 0 || position > ids.len() {
+                    return Err(PositionError::InvalidPosition(position));
+                }
+                let moved = ids.remove(from);
+                ids.insert(position - 1, moved);
+            }
+            PositionOperation::InsertAt {
+                person_id,
+                position,
+            } => {
+                if ids.contains(&person_id) {
+                    return Err(PositionError::AlreadyOnList);
+                }
+                if position == 0 || position > ids.len() + 1 {
+                    return Err(PositionError::InvalidPosition(position));
+                }
+                ids.insert(position - 1, person_id);
+            }
+            PositionOperation::Remove { person_id } => {
+                let index = index_of(&ids, &person_id)?;
+                ids.remove(index);
+            }
+            PositionOperation::Swap {
+                person_id,
+                other_person_id,
+            } => {
+                let first = index_of(&ids, &person_id)?;
+                let second = index_of(&ids, &other_person_id)?;
+                ids.swap(first, second);
+            }
+        }
+
+        Ok(ids)
+    }
+}
+
+/// Check that `reordered` contains every candidate in `current` exactly once, and nothing else.
+pub fn check_permutation(current: &[Uuid], reordered: &[Uuid]) -> Result<(), PositionError> {
+    let current_ids: HashSet<&Uuid> = current.iter().collect();
+    let reordered_ids: HashSet<&Uuid> = reordered.iter().collect();
+
+    if current.len() != reordered.len()
+        || reordered_ids.len() != reordered.len()
+        || current_ids != reordered_ids
+    {
+        return Err(PositionError::NotAPermutation);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(n: usize) -> Vec<Uuid> {
+        (0..n).map(|_| Uuid::new_v4()).collect()
+    }
+
+    #[test]
+    fn move_insert_remove_and_swap() {
+        let [a, b, c] = ids(3).try_into().unwrap();
+        let d = Uuid::new_v4();
+        let list = [a, b, c];
+
+        let moved = PositionOperation::Move {
+            person_id: c,
+            position: 1,
+        };
+        assert_eq!(moved.apply(&list).unwrap(), vec![c, a, b]);
+
+        let inserted = PositionOperation::InsertAt {
+            person_id: d,
+            position: 2,
+        };
+        assert_eq!(inserted.apply(&list).unwrap(), vec![a, d, b, c]);
+
+        let appended = PositionOperation::InsertAt {
+            person_id: d,
+            position: 4,
+        };
+        assert_eq!(appended.apply(&list).unwrap(), vec![a, b, c, d]);
+
+        let removed = PositionOperation::Remove { person_id: b };
+        assert_eq!(removed.apply(&list).unwrap(), vec![a, c]);
+
+        let swapped = PositionOperation::Swap {
+            person_id: a,
+            other_person_id: c,
+        };
+        assert_eq!(swapped.apply(&list).unwrap(), vec![c, b, a]);
+    }
+
+    #[test]
+    fn rejects_invalid_operations() {
+        let [a, b] = ids(2).try_into().unwrap();
+        let stranger = Uuid::new_v4();
+        let list = [a, b];
+
+        let result = PositionOperation::Move {
+            person_id: a,
+            position: 3,
+        }
+        .apply(&list);
+        assert!(matches!(result, Err(PositionError::InvalidPosition(3))));
+
+        let result = PositionOperation::Move {
+            person_id: a,
+            position: 0,
+        }
+        .apply(&list);
+        assert!(matches!(result, Err(PositionError::InvalidPosition(0))));
+
+        let result = PositionOperation::Remove {
+            person_id: stranger,
+        }
+        .apply(&list);
+        assert!(matches!(result, Err(PositionError::NotOnList)));
+
+        let result = PositionOperation::InsertAt {
+            person_id: b,
+            position: 1,
+        }
+        .apply(&list);
+        assert!(matches!(result, Err(PositionError::AlreadyOnList)));
+    }
+
+    #[test]
+    fn reorders_must_be_exact_permutations() {
+        let [a, b, c] = ids(3).try_into().unwrap();
+        let current = [a, b, c];
+
+        assert!(check_permutation(&current, &[c, a, b]).is_ok());
+        // dropped candidate
+        assert!(check_permutation(&current, &[a, b]).is_err());
+        // duplicate instead of a candidate
+        assert!(check_permutation(&current, &[a, b, b]).is_err());
+        // person who is not on the list
+        assert!(check_permutation(&current, &[a, b, Uuid::new_v4()]).is_err());
+        // extra person
+        assert!(check_permutation(&current, &[a, b, c, Uuid::new_v4()]).is_err());
+    }
+
+    #[test]
+    fn deserializes_tagged_operations() {
+        let person_id = Uuid::new_v4();
+        let operation: PositionOperation = serde_json::from_str(&format!(
+            r#"{{"operation": "insert_at", "person_id": "{person_id}", "position": 2}}"#
+        ))
+        .unwrap();
+
+        assert_eq!(
+            operation,
+            PositionOperation::InsertAt {
+                person_id,
+                position: 2
+            }
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::{BTreeSet, HashMap};
+
+use uuid::Uuid;
+
+use crate::{ElectoralDistrict, candidate_lists::FullCandidateList, persons::Person};
+
+/// How the candidate lists of the political group cover the electoral districts.
+#[derive(Debug, Clone)]
+pub struct DistrictCoverage {
+    pub lists: Vec<CoverageList>,
+    pub districts: Vec<DistrictCoverageRow>,
+    /// Lists with identical candidate sets, which together form a lijstengroep.
+    pub list_groups: Vec<ListGroup>,
+    /// Persons that are on more than one list, at different positions.
+    pub position_differences: Vec<PositionDifference>,
+}
+
+/// A column of the coverage matrix.
+#[derive(Debug, Clone)]
+pub struct CoverageList {
+    /// Number of the list within the overview, starting at 1.
+    pub number: usize,
+    pub full_list: FullCandidateList,
+    /// Number of the lijstengroep this list belongs to, if any.
+    pub group: Option<usize>,
+}
+
+/// A row of the coverage matrix, with one entry per list.
+#[derive(Debug, Clone)]
+pub struct DistrictCoverageRow {
+    pub district: ElectoralDistrict,
+    pub lists: Vec<bool>,
+}
+
+impl DistrictCoverageRow {
+    pub fn is_covered(&self) -> bool {
+        self.lists.contains(&true)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ListGroup {
+    pub number: usize,
+    /// Numbers of the lists in this group.
+    pub lists: Vec<usize>,
+    pub candidate_count: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct PositionDifference {
+    pub person: Person,
+    /// Position of the person on each list, `None` when not on that list.
+    pub positions: Vec<Option<i32>>,
+}
+
+impl DistrictCoverage {
+    pub fn new(districts: &[ElectoralDistrict], full_lists: Vec<FullCandidateList>) -> Self {
+        let rows = districts
+            .iter()
+            .map(|district| DistrictCoverageRow {
+                district: *district,
+                lists: full_lists
+                    .iter()
+                    .map(|l| l.list.electoral_districts.contains(district))
+                    .collect(),
+            })
+            .collect();
+
+        // group lists by candidate set, in the order the sets first appear
+        let mut sets: Vec<(BTreeSet<Uuid>, Vec<usize>)> = Vec::new();
+        for (index, full_list) in full_lists.iter().enumerate() {
+            let set: BTreeSet<Uuid> = full_list.candidates.iter().map(|c| c.person.id).collect();
+            if set.is_empty() {
+                continue;
+            }
+            match sets.iter_mut().find(|(s, _)| s == &set) {
+                Some((_, lists)) => lists.push(index + 1),
+                None => sets.push((set, vec![index + 1])),
+            }
+        }
+        let list_groups: Vec<ListGroup> = sets
+            .into_iter()
+            .filter(|(_, lists)| lists.len() > 1)
+            .enumerate()
+            .map(|(index, (set, lists))| ListGroup {
+                number: index + 1,
+                lists,
+                candidate_count: set.len(),
+            })
+            .collect();
+
+        // positions of every person on every list, in order of first appearance
+        let mut persons: Vec<PositionDifference> = Vec::new();
+        let mut person_index: HashMap<Uuid, usize> = HashMap::new();
+        for (index, full_list) in full_lists.iter().enumerate() {
+            for candidate in &full_list.candidates {
+                let row = *person_index.entry(candidate.person.id).or_insert_with(|| {
+                    persons.push(PositionDifference {
+                        person: candidate.person.clone(),
+                        positions: vec![None; full_lists.len()],
+                    });
+                    persons.len() - 1
+                });
+                persons[row].positions[index] = Some(candidate.position);
+            }
+        }
+        let position_differences = persons
+            .into_iter()
+            .filter(|p| {
+                let positions: BTreeSet<i32> = p.positions.iter().flatten().copied().collect();
+                positions.len() > 1
+            })
+            .collect();
+
+        let lists = full_lists
+            .into_iter()
+            .enumerate()
+            .map(|(index, full_list)| CoverageList {
+                number: index + 1,
+                group: list_groups
+                    .iter()
+                    .find(|g| g.lists.contains(&(index + 1)))
+                    .map(|g| g.number),
+                full_list,
+            })
+            .collect();
+
+        DistrictCoverage {
+            lists,
+            districts: rows,
+            list_groups,
+            position_differences,
+        }
+    }
+
+    pub fn uncovered_districts(&self) -> Vec<ElectoralDistrict> {
+        self.districts
+            .iter()
+            .filter(|row| !row.is_covered())
+            .map(|row| row.district)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        candidate_lists::{Candidate, CandidateList},
+        test_utils::{sample_candidate_list, sample_person_with_last_name},
+    };
+
+    fn full_list(
+        electoral_districts: Vec<ElectoralDistrict>,
+        persons: &[&Person],
+    ) -> FullCandidateList {
+        let list = CandidateList {
+            electoral_districts,
+            ..sample_candidate_list(Uuid::new_v4())
+        };
+        let candidates = persons
+            .iter()
+            .enumerate()
+            .map(|(index, person)| Candidate {
+                list_id: list.id,
+                position: index as i32 + 1,
+                person: (*person).clone(),
+                consent: None,
+            })
+            .collect();
+        FullCandidateList { list, candidates }
+    }
+
+    #[test]
+    fn shows_uncovered_districts_groups_and_position_differences() {
+        let jansen = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        let bakker = sample_person_with_last_name(Uuid::new_v4(), "Bakker");
+        let visser = sample_person_with_last_name(Uuid::new_v4(), "Visser");
+        let districts = [
+            ElectoralDistrict::DR,
+            ElectoralDistrict::FR,
+            ElectoralDistrict::UT,
+            ElectoralDistrict::OV,
+        ];
+        let lists = vec![
+            full_list(vec![ElectoralDistrict::DR], &[&jansen, &bakker, &visser]),
+            full_list(vec![ElectoralDistrict::FR], &[&jansen, &visser, &bakker]),
+            full_list(vec![ElectoralDistrict::UT], &[&jansen]),
+            full_list(vec![], &[]),
+        ];
+
+        let coverage = DistrictCoverage::new(&districts, lists);
+
+        assert_eq!(coverage.uncovered_districts(), vec![ElectoralDistrict::OV]);
+        assert_eq!(coverage.districts[0].lists, vec![true, false, false, false]);
+
+        // the first two lists have the same candidates in a different order
+        assert_eq!(coverage.list_groups.len(), 1);
+        assert_eq!(coverage.list_groups[0].lists, vec![1, 2]);
+        assert_eq!(coverage.list_groups[0].candidate_count, 3);
+        let groups: Vec<Option<usize>> = coverage.lists.iter().map(|l| l.group).collect();
+        assert_eq!(groups, vec![Some(1), Some(1), None, None]);
+
+        // Jansen is first on every list
+        let differences: Vec<(Uuid, Vec<Option<i32>>)> = coverage
+            .position_differences
+            .iter()
+            .map(|d| (d.person.id, d.positions.clone()))
+            .collect();
+        assert_eq!(
+            differences,
+            vec![
+                (bakker.id, vec![Some(2), Some(3), None, None]),
+                (visser.id, vec![Some(3), Some(2), None, None]),
+            ]
+        );
+    }
+}
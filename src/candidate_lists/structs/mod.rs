@@ -1,9 +1,34 @@
+mod bulk_candidates;
 mod candidate;
+mod candidate_consent;
 mod candidate_list;
 mod candidate_list_form;
+mod candidate_list_membership;
+mod candidate_list_snapshot;
+mod candidate_list_sort;
+mod candidate_list_undo;
+mod candidate_matrix;
 mod candidate_position;
+mod district_coverage;
 
+pub use bulk_candidates::{BulkAddCandidatesForm, CandidateBulkOutcome};
 pub use candidate::Candidate;
-pub use candidate_list::{CandidateList, CandidateListSummary, FullCandidateList, MAX_CANDIDATES};
-pub use candidate_list_form::CandidateListForm;
-pub use candidate_position::{CandidatePosition, CandidatePositionAction, CandidatePositionForm};
+pub use candidate_consent::{
+    CONSENT_INVITATION_TTL_DAYS, CandidateConsent, ConsentDecision, ConsentForm, ConsentInvitation,
+};
+pub use candidate_list::{
+    CandidateList, CandidateListSummary, DeletedCandidateList, FullCandidateList, MAX_CANDIDATES,
+};
+pub use candidate_list_form::{CandidateListForm, DuplicateCandidateListForm};
+pub use candidate_list_membership::CandidateListMembership;
+pub use candidate_list_snapshot::{CandidateListDiff, CandidateListSnapshot, SnapshotForm};
+pub use candidate_list_sort::CandidateListSort;
+pub use candidate_list_undo::{
+    CandidateListEntry, CandidateListUndo, UndoAction, UndoEntry, UndoStacks,
+};
+pub use candidate_matrix::{CandidateMatrix, CandidateMatrixEntry, CandidateMatrixQuery};
+pub use candidate_position::{
+    CandidatePosition, CandidatePositionAction, CandidatePositionForm, PositionError,
+    PositionOperation, check_permutation,
+};
+pub use district_coverage::DistrictCoverage;
@@ -3,12 +3,62 @@
 
 use std::env;
 
-use crate::{AppError, ElectionConfig, ElectoralDistrict};
+use crate::{
+    AppError, ElectionConfig, ElectoralDistrict, auth::AuthenticationLevel,
+    population_register::PopulationRegisterKind,
+};
+
+/// Number of days soft deleted persons and candidate lists can be restored before they are purged.
+pub const DEFAULT_DELETED_RETENTION_DAYS: i64 = 30;
+
+/// Certificate of the mock SAML identity provider, trusted by default during development.
+#[cfg(feature = "dev-features")]
+const DEV_SAML_IDP_CERTIFICATE: &str = crate::auth::MockSamlProvider::CERTIFICATE;
+#[cfg(not(feature = "dev-features"))]
+const DEV_SAML_IDP_CERTIFICATE: &str = "";
+
+/// Signing in with DigiD and eHerkenning is limited to development builds, until requests are
+/// signed and encrypted assertions are supported as the TVS of Logius requires.
+const SAML_AVAILABLE: bool = cfg!(feature = "dev-features");
+
+/// The population register fixture holds fictional persons; checking real candidates against it
+/// is limited to development builds.
+const FIXTURE_REGISTER_AVAILABLE: bool = cfg!(feature = "dev-features");
 
 #[derive(Debug)]
 pub struct Config {
     pub database_url: String,
     pub election: ElectionConfig,
+    pub deleted_retention_days: i64,
+    /// Address users reach the application on, used for redirects from the identity provider.
+    pub public_url: String,
+    pub oidc: OidcConfig,
+    /// Signing in with DigiD and eHerkenning, only when an identity provider is configured.
+    pub saml: Option<SamlConfig>,
+    /// Address of the BAG service, to look up and check Dutch addresses.
+    pub bag_service_url: String,
+    /// Population register (BRP) personal details of candidates are checked against.
+    pub population_register: PopulationRegisterKind,
+}
+
+/// Registration of the application at the OpenID Connect identity provider.
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    pub issuer_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+/// Registration of the application at the SAML identity provider for DigiD and eHerkenning.
+/// The application itself is the service provider, identified by its public url.
+#[derive(Debug, Clone)]
+pub struct SamlConfig {
+    pub idp_entity_id: String,
+    pub idp_sso_url: String,
+    /// PEM encoded certificate the identity provider signs its assertions with.
+    pub idp_certificate: String,
+    /// Lowest authentication level users are accepted with.
+    pub min_level: AuthenticationLevel,
 }
 
 /// Helper function to get environment variable or return an error
@@ -34,12 +84,69 @@ impl Config {
         Ok(Self {
             database_url: get("DATABASE_URL", "postgres://eks@localhost/eks")?,
             election: ElectionConfig::EK2027,
+            deleted_retention_days: match get("DELETED_RETENTION_DAYS", "30") {
+                // purging rows right after, or even before, they were deleted would make them
+                // impossible to restore
+                Ok(days) => days.parse().ok().filter(|days| *days >= 1).ok_or_else(|| {
+                    AppError::ConfigLoadError(format!("invalid DELETED_RETENTION_DAYS: {days}"))
+                })?,
+                Err(_) => DEFAULT_DELETED_RETENTION_DAYS,
+            },
+            public_url: get("PUBLIC_URL", "http://localhost:3000")?
+                .trim_end_matches('/')
+                .to_string(),
+            oidc: OidcConfig {
+                issuer_url: get("OIDC_ISSUER_URL", "http://localhost:8081")?,
+                client_id: get("OIDC_CLIENT_ID", "eks")?,
+                client_secret: get("OIDC_CLIENT_SECRET", "eks-development-secret")?,
+            },
+            bag_service_url: get("BAG_SERVICE_URL", "http://localhost:8080")?
+                .trim_end_matches('/')
+                .to_string(),
+            population_register: match get("POPULATION_REGISTER", "fixture")? {
+                register if register == "fixture" && !FIXTURE_REGISTER_AVAILABLE => {
+                    return Err(AppError::ConfigLoadError(
+                        "POPULATION_REGISTER is set to the fixture of fictional persons, which is \
+                         only available in development builds"
+                            .to_string(),
+                    ));
+                }
+                register => register.parse().map_err(|_| {
+                    AppError::ConfigLoadError(format!("invalid POPULATION_REGISTER: {register}"))
+                })?,
+            },
+            saml: match get("SAML_IDP_CERTIFICATE", DEV_SAML_IDP_CERTIFICATE) {
+                Ok(idp_certificate) if !idp_certificate.trim().is_empty() && !SAML_AVAILABLE => {
+                    return Err(AppError::ConfigLoadError(
+                        "SAML_IDP_CERTIFICATE is set, but signing in with DigiD and eHerkenning \
+                         is only available in development builds"
+                            .to_string(),
+                    ));
+                }
+                Ok(idp_certificate) if !idp_certificate.trim().is_empty() => Some(SamlConfig {
+                    idp_entity_id: get("SAML_IDP_ENTITY_ID", "http://localhost:8082")?,
+                    idp_sso_url: get("SAML_IDP_SSO_URL", "http://localhost:8082/sso")?,
+                    idp_certificate,
+                    min_level: match get("SAML_MIN_LEVEL", "substantial") {
+                        Ok(level) => level.parse().map_err(|_| {
+                            AppError::ConfigLoadError(format!("invalid SAML_MIN_LEVEL: {level}"))
+                        })?,
+                        Err(_) => AuthenticationLevel::Substantial,
+                    },
+                }),
+                _ => None,
+            },
         })
     }
 
     pub fn get_districts(&self) -> &'static [ElectoralDistrict] {
         self.election.electoral_districts()
     }
+
+    /// Whether the application is served over https, so cookies can be marked secure.
+    pub fn is_https(&self) -> bool {
+        self.public_url.starts_with("https://")
+    }
 }
 
 #[cfg(test)]
@@ -48,13 +155,141 @@ mod tests {
 
     #[test]
     fn loads_database_url_from_provider() {
-        let config = Config::from_env_with(|key, _default| match key {
+        let config = Config::from_env_with(|key, default| match key {
             "DATABASE_URL" => Ok("postgres://example".to_string()),
-            _ => Err(AppError::MissingEnvVar(key)),
+            "DELETED_RETENTION_DAYS" => Err(AppError::MissingEnvVar(key)),
+            _ => Ok(default.to_string()),
         })
         .unwrap();
 
         assert_eq!(config.database_url, "postgres://example");
+        assert_eq!(
+            config.deleted_retention_days,
+            DEFAULT_DELETED_RETENTION_DAYS
+        );
+    }
+
+    #[test]
+    fn loads_deleted_retention_days() {
+        let config = Config::from_env_with(|key, default| match key {
+            "DATABASE_URL" => Ok("postgres://example".to_string()),
+            "DELETED_RETENTION_DAYS" => Ok("7".to_string()),
+            _ => Ok(default.to_string()),
+        })
+        .unwrap();
+        assert_eq!(config.deleted_retention_days, 7);
+
+        let err = Config::from_env_with(|key, default| match key {
+            "DELETED_RETENTION_DAYS" => Ok("a week".to_string()),
+            _ => Ok(default.to_string()),
+        })
+        .unwrap_err();
+        assert!(matches!(err, AppError::ConfigLoadError(_)));
+    }
+
+    #[test]
+    fn rejects_deleted_retention_below_a_day() {
+        for days in ["0", "-7"] {
+            let err = Config::from_env_with(|key, default| match key {
+                "DELETED_RETENTION_DAYS" => Ok(days.to_string()),
+                _ => Ok(default.to_string()),
+            })
+            .unwrap_err();
+            assert!(matches!(err, AppError::ConfigLoadError(_)), "{days}");
+        }
+
+        let config = Config::from_env_with(|key, default| match key {
+            "DELETED_RETENTION_DAYS" => Ok("1".to_string()),
+            _ => Ok(default.to_string()),
+        })
+        .unwrap();
+        assert_eq!(config.deleted_retention_days, 1);
+    }
+
+    #[test]
+    #[cfg(feature = "dev-features")]
+    fn loads_population_register() {
+        let config = Config::from_env_with(|key, default| match key {
+            "POPULATION_REGISTER" => Ok("fixture".to_string()),
+            _ => Ok(default.to_string()),
+        })
+        .unwrap();
+        assert_eq!(config.population_register, PopulationRegisterKind::Fixture);
+
+        let err = Config::from_env_with(|key, default| match key {
+            "POPULATION_REGISTER" => Ok("brp".to_string()),
+            _ => Ok(default.to_string()),
+        })
+        .unwrap_err();
+        assert!(matches!(err, AppError::ConfigLoadError(_)));
+    }
+
+    #[test]
+    #[cfg(not(feature = "dev-features"))]
+    fn refuses_population_register_fixture_outside_development() {
+        let err = Config::from_env_with(|key, default| match key {
+            "POPULATION_REGISTER" => Ok("fixture".to_string()),
+            _ => Ok(default.to_string()),
+        })
+        .unwrap_err();
+        assert!(matches!(err, AppError::ConfigLoadError(_)));
+
+        let err = Config::from_env_with(|key, default| match key {
+            "POPULATION_REGISTER" => Err(AppError::MissingEnvVar(key)),
+            _ => Ok(default.to_string()),
+        })
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            AppError::MissingEnvVar("POPULATION_REGISTER")
+        ));
+    }
+
+    #[test]
+    fn loads_oidc_registration() {
+        let config = Config::from_env_with(|key, default| match key {
+            "PUBLIC_URL" => Ok("https://eks.example/".to_string()),
+            "OIDC_ISSUER_URL" => Ok("https://idp.example".to_string()),
+            _ => Ok(default.to_string()),
+        })
+        .unwrap();
+
+        assert_eq!(config.public_url, "https://eks.example");
+        assert!(config.is_https());
+        assert_eq!(config.oidc.issuer_url, "https://idp.example");
+        assert_eq!(config.oidc.client_id, "eks");
+    }
+
+    #[test]
+    #[cfg(feature = "dev-features")]
+    fn loads_saml_registration_when_certificate_is_configured() {
+        let config = Config::from_env_with(|key, default| match key {
+            "SAML_IDP_CERTIFICATE" => Ok("-----BEGIN CERTIFICATE-----".to_string()),
+            "SAML_MIN_LEVEL" => Ok("high".to_string()),
+            _ => Ok(default.to_string()),
+        })
+        .unwrap();
+        let saml = config.saml.unwrap();
+        assert_eq!(saml.idp_sso_url, "http://localhost:8082/sso");
+        assert_eq!(saml.min_level, AuthenticationLevel::High);
+
+        let config = Config::from_env_with(|key, default| match key {
+            "SAML_IDP_CERTIFICATE" => Err(AppError::MissingEnvVar(key)),
+            _ => Ok(default.to_string()),
+        })
+        .unwrap();
+        assert!(config.saml.is_none());
+    }
+
+    #[test]
+    #[cfg(not(feature = "dev-features"))]
+    fn refuses_saml_registration_outside_development() {
+        let err = Config::from_env_with(|key, default| match key {
+            "SAML_IDP_CERTIFICATE" => Ok("-----BEGIN CERTIFICATE-----".to_string()),
+            _ => Ok(default.to_string()),
+        })
+        .unwrap_err();
+        assert!(matches!(err, AppError::ConfigLoadError(_)));
     }
 
     #[test]
@@ -1,20 +1,22 @@
-//! Request-scoped template context carrying locale and helpers.
+//! Request-scoped template context carrying locale, the signed in user and helpers.
 //! Extracted from requests and passed into Askama templates.
 
 use std::convert::Infallible;
 
 use axum::{extract::FromRequestParts, http::request::Parts};
 
-use crate::Locale;
+use crate::{CurrentUser, Locale};
 
-#[derive(Default, Clone, Copy, Debug)]
+#[derive(Default, Clone, Debug)]
 pub struct Context {
     pub locale: Locale,
+    /// Set when the user signed in, see `auth::load_current_user`.
+    pub user: Option<CurrentUser>,
 }
 
 impl Context {
     pub fn new(locale: Locale) -> Self {
-        Self { locale }
+        Self { locale, user: None }
     }
 
     pub fn livereload_enabled() -> bool {
@@ -26,6 +28,7 @@ impl askama::Values for Context {
     fn get_value<'a>(&'a self, key: &str) -> Option<&'a dyn std::any::Any> {
         match key {
             "locale" => Some(&self.locale as &dyn std::any::Any),
+            "user" => self.user.as_ref().map(|user| user as &dyn std::any::Any),
             _ => None,
         }
     }
@@ -39,7 +42,9 @@ where
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         let locale = Locale::from_request_parts(parts, state).await?;
-        Ok(Context::new(locale))
+        let user = parts.extensions.get::<CurrentUser>().cloned();
+
+        Ok(Context { locale, user })
     }
 }
 
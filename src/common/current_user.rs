@@ -0,0 +1,94 @@
+//! The signed in user, resolved from the session cookie.
+//! Extracted from requests by handlers that need to know who is acting.
+
+use axum::{
+    extract::{FromRef, FromRequestParts, OptionalFromRequestParts},
+    http::request::Parts,
+};
+use axum_extra::extract::cookie::CookieJar;
+use rand::{Rng, distr::Alphanumeric};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    AppError,
+    auth::{self, Role},
+};
+
+/// Name of the cookie holding the session token.
+pub const SESSION_COOKIE: &str = "eks_session";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurrentUser {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    pub name: String,
+    pub email: Option<String>,
+    pub political_group_id: Option<Uuid>,
+    pub roles: Vec<Role>,
+}
+
+/// Random token for session cookies and invitation links.
+pub fn random_token() -> String {
+    rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(48)
+        .map(char::from)
+        .collect()
+}
+
+/// Sessions and invitations are looked up by a hash of their token, so a leaked database
+/// does not hand out valid session cookies or invitation links.
+pub fn hash_token(token: &str) -> Vec<u8> {
+    Sha256::digest(token.as_bytes()).to_vec()
+}
+
+impl<S> FromRequestParts<S> for CurrentUser
+where
+    S: Send + Sync,
+    PgPool: FromRef<S>,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // already resolved earlier on in this request
+        if let Some(user) = parts.extensions.get::<CurrentUser>() {
+            return Ok(user.clone());
+        }
+
+        let jar = CookieJar::from_headers(&parts.headers);
+        let Some(cookie) = jar.get(SESSION_COOKIE) else {
+            return Err(AppError::Unauthorized);
+        };
+
+        // sessions are not scoped to a tenant, the tenant follows from the user
+        let mut conn = PgPool::from_ref(state).acquire().await?;
+        let user = auth::repository::get_session_user(&mut conn, &hash_token(cookie.value()))
+            .await?
+            .ok_or(AppError::Unauthorized)?;
+
+        parts.extensions.insert(user.clone());
+
+        Ok(user)
+    }
+}
+
+impl<S> OptionalFromRequestParts<S> for CurrentUser
+where
+    S: Send + Sync,
+    PgPool: FromRef<S>,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        match <Self as FromRequestParts<S>>::from_request_parts(parts, state).await {
+            Ok(user) => Ok(Some(user)),
+            Err(AppError::Unauthorized) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
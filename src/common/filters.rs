@@ -2,7 +2,7 @@
 //! Used keep formatting logic out of templates.
 
 use crate::{
-    Locale,
+    CurrentUser, Locale, Permission,
     form::{FormData, WithCsrfToken},
 };
 
@@ -38,6 +38,13 @@ pub fn error<T: WithCsrfToken>(
     Ok(form.error(name, locale))
 }
 
+/// Whether the signed in user has a permission, to hide what they cannot use, e.g.
+/// `{% if crate::Permission::EditPersons|can %}`.
+#[askama::filter_fn]
+pub fn can(permission: Permission, values: &dyn askama::Values) -> askama::Result<bool> {
+    Ok(askama::get_value::<CurrentUser>(values, "user").is_ok_and(|user| user.can(permission)))
+}
+
 /// Returns a cache buster string based on the current git commit hash (set during build on github).
 pub fn cache_buster() -> &'static str {
     option_env!("GITHUB_SHA").unwrap_or("development")
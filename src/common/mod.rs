@@ -1,6 +1,7 @@
 pub mod config;
 pub mod constants;
 pub mod context;
+pub mod current_user;
 pub mod election;
 pub mod filters;
 pub mod locale;
@@ -9,11 +10,12 @@ pub mod router;
 pub mod server;
 pub mod state;
 pub mod templates;
+pub mod tenant;
 pub mod translate;
 
 #[cfg(feature = "livereload")]
 pub mod livereload;
-#[cfg(any(feature = "dev-features", not(feature = "memory-serve")))]
+#[cfg(not(feature = "memory-serve"))]
 pub mod proxy;
 
 #[cfg(test)]
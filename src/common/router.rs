@@ -6,30 +6,28 @@ use axum::{Router, middleware, routing::get};
 #[cfg(feature = "http-logging")]
 use tower_http::trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer};
 
-use crate::{AppState, candidate_lists, pages, persons, render_error_pages};
+use crate::{
+    AppState, auth, bag, candidate_lists, pages, persons, recently_deleted, render_error_pages,
+    users,
+};
+
+pub fn create(state: &AppState) -> Router<AppState> {
+    // pages that are only available to signed in users
+    let protected = Router::new()
+        .merge(persons::router())
+        .merge(candidate_lists::router())
+        .merge(candidate_lists::candidate_router())
+        .merge(recently_deleted::router())
+        .merge(users::router())
+        .merge(bag::router())
+        .route_layer(middleware::from_fn(auth::require_login));
 
-pub fn create() -> Router<AppState> {
     let router = Router::new()
         .route("/", get(pages::index))
-        .merge(persons::router())
-        .merge(candidate_lists::router())
-        .merge(candidate_lists::candidate_router());
-
-    #[cfg(feature = "dev-features")]
-    let bag_service_url =
-        crate::common::config::get_env("BAG_SERVICE_URL", "http://localhost:8080")
-            .expect("BAG_SERVICE_URL must be set in dev-features mode");
-
-    #[cfg(feature = "dev-features")]
-    let router = router
-        .route(
-            "/lookup",
-            crate::common::proxy::proxy_handler(&bag_service_url),
-        )
-        .route(
-            "/suggest",
-            crate::common::proxy::proxy_handler(&bag_service_url),
-        );
+        .merge(auth::router())
+        // candidates open their personal link without signing in
+        .merge(candidate_lists::consent_router())
+        .merge(protected);
 
     #[cfg(feature = "http-logging")]
     let router = router.layer(
@@ -56,6 +54,10 @@ pub fn create() -> Router<AppState> {
     router
         .layer(middleware::from_fn(render_error_pages))
         .fallback(get(pages::not_found))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth::load_current_user,
+        ))
 }
 
 #[cfg(test)]
@@ -72,7 +74,8 @@ mod tests {
 
     #[sqlx::test]
     async fn index_route_renders_index(pool: PgPool) {
-        let app = create().with_state(AppState::new_for_tests(pool));
+        let state = AppState::new_for_tests(pool);
+        let app = create(&state).with_state(state);
 
         let response = app
             .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
@@ -86,7 +89,8 @@ mod tests {
 
     #[sqlx::test]
     async fn fallback_route_renders_not_found(pool: PgPool) {
-        let app = create().with_state(AppState::new_for_tests(pool));
+        let state = AppState::new_for_tests(pool);
+        let app = create(&state).with_state(state);
 
         let response = app
             .oneshot(
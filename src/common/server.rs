@@ -4,13 +4,15 @@
 use axum::Router;
 use tokio::{net::TcpListener, signal};
 
-use crate::{AppError, AppState};
+use crate::{AppError, AppState, recently_deleted};
 
 pub async fn serve(
     router: Router<AppState>,
     state: AppState,
     listener: TcpListener,
 ) -> Result<(), AppError> {
+    recently_deleted::spawn_purge_task(state.clone());
+
     let app = router.with_state(state);
     let addr = listener.local_addr().map_err(AppError::ServerError)?;
 
@@ -7,12 +7,19 @@ use axum::{
     extract::{FromRef, FromRequestParts},
     http::request::Parts,
 };
-use sqlx::{PgPool, Postgres, pool::PoolConnection};
-
+use sqlx::{PgPool, Postgres, pool::PoolConnection, postgres::PgPoolOptions};
+
+use crate::{
+    AppError, Config, CsrfTokens, CurrentUser, Tenant,
+    auth::{OidcClient, SamlServiceProvider},
+    bag::BagClient,
+    candidate_lists::UndoStacks,
+    population_register::PopulationRegister,
+};
 #[cfg(test)]
-use crate::ElectionConfig;
-use crate::{AppError, Config, CsrfTokens};
+use crate::{ElectionConfig, OidcConfig, SamlConfig, population_register::PopulationRegisterKind};
 
+/// A pooled connection, scoped to the tenant of the signed in user.
 pub struct DbConnection(pub PoolConnection<Postgres>);
 
 #[derive(FromRef, Clone)]
@@ -20,18 +27,44 @@ pub struct AppState {
     config: Arc<Config>,
     pool: sqlx::PgPool,
     csrf_tokens: CsrfTokens,
+    undo_stacks: UndoStacks,
+    oidc: OidcClient,
+    saml: Option<SamlServiceProvider>,
+    population_register: Arc<dyn PopulationRegister>,
+    bag: BagClient,
 }
 
 impl AppState {
     pub fn new() -> Result<Self, AppError> {
         let config = Config::from_env()?;
-        let pool = PgPool::connect_lazy(&config.database_url)?;
+        let pool = PgPoolOptions::new()
+            // connections scoped to a tenant must not leak into the next use of the pool
+            .after_release(|conn, _| {
+                Box::pin(async move {
+                    Tenant::reset(conn).await?;
+                    Ok(true)
+                })
+            })
+            .connect_lazy(&config.database_url)?;
         let csrf_tokens = CsrfTokens::default();
+        let oidc = OidcClient::new(&config)?;
+        let saml = config
+            .saml
+            .as_ref()
+            .map(|saml| SamlServiceProvider::new(&config.public_url, saml))
+            .transpose()?;
+        let bag = BagClient::new(&config.bag_service_url);
+        let population_register = config.population_register.connect()?;
 
         Ok(Self {
             config: Arc::new(config),
             pool,
             csrf_tokens,
+            undo_stacks: UndoStacks::default(),
+            oidc,
+            saml,
+            population_register,
+            bag,
         })
     }
 
@@ -43,21 +76,109 @@ impl AppState {
         &self.csrf_tokens
     }
 
+    pub fn undo_stacks(&self) -> &UndoStacks {
+        &self.undo_stacks
+    }
+
     pub fn config(&self) -> &Config {
         &self.config
     }
 
+    pub fn oidc(&self) -> &OidcClient {
+        &self.oidc
+    }
+
+    /// The SAML service provider, when signing in with DigiD and eHerkenning is configured.
+    pub fn saml(&self) -> Option<&SamlServiceProvider> {
+        self.saml.as_ref()
+    }
+
+    /// The population register (BRP) to check personal details against.
+    pub fn population_register(&self) -> &dyn PopulationRegister {
+        self.population_register.as_ref()
+    }
+
+    pub fn bag(&self) -> &BagClient {
+        &self.bag
+    }
+
     #[cfg(test)]
     pub fn new_for_tests(pool: PgPool) -> Self {
         let config = Config {
             database_url: "postgres://test".to_string(),
             election: ElectionConfig::EK2027,
+            deleted_retention_days: crate::common::config::DEFAULT_DELETED_RETENTION_DAYS,
+            public_url: "http://localhost:3000".to_string(),
+            oidc: OidcConfig {
+                issuer_url: "http://localhost:8081".to_string(),
+                client_id: "eks".to_string(),
+                client_secret: "secret".to_string(),
+            },
+            saml: None,
+            // nothing listens here, addresses are only checked when a test starts a BAG service
+            bag_service_url: "http://127.0.0.1:0".to_string(),
+            population_register: PopulationRegisterKind::Fixture,
         };
+        let oidc = OidcClient::new(&config).expect("valid oidc config");
+        let bag = BagClient::new(&config.bag_service_url);
+        let population_register = config
+            .population_register
+            .connect()
+            .expect("valid population register fixture");
 
         Self {
             config: Arc::new(config),
             pool,
             csrf_tokens: CsrfTokens::default(),
+            undo_stacks: UndoStacks::default(),
+            oidc,
+            saml: None,
+            population_register,
+            bag,
+        }
+    }
+
+    /// Sign in with the given identity provider, e.g. a `MockProvider` in tests.
+    #[cfg(test)]
+    pub fn with_oidc(self, oidc: OidcConfig) -> Self {
+        let config = Config {
+            oidc,
+            public_url: self.config.public_url.clone(),
+            database_url: self.config.database_url.clone(),
+            election: self.config.election,
+            deleted_retention_days: self.config.deleted_retention_days,
+            saml: self.config.saml.clone(),
+            bag_service_url: self.config.bag_service_url.clone(),
+            population_register: self.config.population_register,
+        };
+        let oidc = OidcClient::new(&config).expect("valid oidc config");
+
+        Self {
+            config: Arc::new(config),
+            oidc,
+            ..self
+        }
+    }
+
+    /// Check addresses with the BAG service at the given url.
+    #[cfg(test)]
+    pub fn with_bag_service(self, url: &str) -> Self {
+        Self {
+            bag: BagClient::new(url),
+            ..self
+        }
+    }
+
+    /// Sign in with DigiD and eHerkenning through the given identity provider, e.g. a
+    /// `MockSamlProvider` in tests.
+    #[cfg(test)]
+    pub fn with_saml(self, saml: SamlConfig) -> Self {
+        let provider =
+            SamlServiceProvider::new(&self.config.public_url, &saml).expect("valid saml config");
+
+        Self {
+            saml: Some(provider),
+            ..self
         }
     }
 }
@@ -69,8 +190,13 @@ where
 {
     type Rejection = AppError;
 
-    async fn from_request_parts(_: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
-        let conn = PgPool::from_ref(state).acquire().await?;
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let mut conn = PgPool::from_ref(state).acquire().await?;
+        let tenant = parts
+            .extensions
+            .get::<CurrentUser>()
+            .map_or(Tenant::None, CurrentUser::tenant);
+        tenant.scope(&mut conn).await?;
 
         Ok(DbConnection(conn))
     }
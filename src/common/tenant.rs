@@ -0,0 +1,147 @@
+//! Scoping of database connections to a political group.
+//! Persons and candidate lists are protected by row-level security in the database. Connections
+//! that are not scoped to a tenant cannot see any of them.
+
+use sqlx::{Executor, PgConnection};
+use uuid::Uuid;
+
+use crate::{CurrentUser, Role};
+
+/// Database role connections switch to when acting on behalf of a tenant.
+const TENANT_ROLE: &str = "eks_tenant";
+
+/// Whose persons and candidate lists a connection can see.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tenant {
+    /// Read and change the data of a single political group.
+    PoliticalGroup(Uuid),
+    /// Read the data of every political group, e.g. to check submitted lists.
+    AllPoliticalGroups,
+    /// No access to persons and candidate lists at all.
+    None,
+    /// Read and change the data of every political group, for background tasks such as
+    /// purging deleted rows. Never used on behalf of a signed in user.
+    Maintenance,
+}
+
+impl Tenant {
+    fn setting(&self) -> String {
+        match self {
+            Tenant::PoliticalGroup(id) => id.to_string(),
+            Tenant::AllPoliticalGroups => "all".to_string(),
+            Tenant::None => String::new(),
+            Tenant::Maintenance => "maintenance".to_string(),
+        }
+    }
+
+    /// Scope the connection to this tenant, until it is reset.
+    pub async fn scope(&self, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            SELECT
+                set_config('role', $1, false) AS "role!",
+                set_config('eks.political_group_id', $2, false) AS "political_group_id!"
+            "#,
+            TENANT_ROLE,
+            self.setting(),
+        )
+        .fetch_one(conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Give the connection its unrestricted access back, before it is returned to the pool.
+    pub async fn reset(conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+        conn.execute("RESET ROLE; RESET eks.political_group_id")
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl CurrentUser {
+    /// Users act for their own political group, members of the central polling station and
+    /// the Electoral Council without one can look at those of all political groups.
+    pub fn tenant(&self) -> Tenant {
+        match self.political_group_id {
+            Some(id) => Tenant::PoliticalGroup(id),
+            None if self
+                .roles
+                .iter()
+                .any(|role| matches!(role, Role::CsbMember | Role::KiesraadAdmin)) =>
+            {
+                Tenant::AllPoliticalGroups
+            }
+            None => Tenant::None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::test_utils::sample_user;
+
+    #[test]
+    fn users_act_for_their_political_group() {
+        let group = Uuid::new_v4();
+        let agent = CurrentUser {
+            political_group_id: Some(group),
+            ..sample_user(vec![Role::AuthorisedAgent])
+        };
+        assert_eq!(agent.tenant(), Tenant::PoliticalGroup(group));
+
+        assert_eq!(
+            sample_user(vec![Role::CsbMember]).tenant(),
+            Tenant::AllPoliticalGroups
+        );
+        assert_eq!(
+            sample_user(vec![Role::ListSubmitter]).tenant(),
+            Tenant::None
+        );
+    }
+
+    #[sqlx::test]
+    async fn scope_switches_role_until_reset(pool: PgPool) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+
+        Tenant::AllPoliticalGroups.scope(&mut conn).await?;
+        let tenant = sqlx::query!(
+            r#"SELECT current_user AS "role!", current_setting('eks.political_group_id') AS "setting!""#
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+        assert_eq!(tenant.role, TENANT_ROLE);
+        assert_eq!(tenant.setting, "all");
+
+        Tenant::reset(&mut conn).await?;
+        let tenant = sqlx::query!(r#"SELECT current_user AS "role!""#)
+            .fetch_one(&mut *conn)
+            .await?;
+        assert_ne!(tenant.role, TENANT_ROLE);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn row_level_security_applies_to_the_table_owner(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+
+        let unforced = sqlx::query_scalar!(
+            r#"
+            SELECT relname AS "relname!" FROM pg_class
+            WHERE relrowsecurity AND NOT relforcerowsecurity
+            "#
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+        assert_eq!(unforced, Vec::<String>::new());
+
+        Ok(())
+    }
+}
@@ -1,9 +1,12 @@
+use axum::{extract::FromRequestParts, http::Request};
 use chrono::{NaiveDate, Utc};
 use http_body_util::BodyExt;
+use sqlx::PgConnection;
 use uuid::Uuid;
 
 use crate::{
-    ElectoralDistrict, TokenValue,
+    AppState, Context, CurrentUser, DbConnection, ElectoralDistrict, Locale, Role, TokenValue,
+    auth::{Identity, repository as auth_repository},
     candidate_lists::CandidateList,
     persons::{AddressForm, Gender, Person, PersonForm},
 };
@@ -18,6 +21,66 @@ pub async fn response_body_string(response: axum::response::Response) -> String
     String::from_utf8(bytes.to_vec()).expect("utf-8 body")
 }
 
+pub fn sample_user(roles: Vec<Role>) -> CurrentUser {
+    CurrentUser {
+        id: Uuid::new_v4(),
+        session_id: Uuid::new_v4(),
+        name: "Evert Klaas Smit".to_string(),
+        email: None,
+        political_group_id: None,
+        roles,
+    }
+}
+
+/// Store a user with the given political group and roles, returning its id.
+pub async fn create_user(
+    conn: &mut PgConnection,
+    name: &str,
+    political_group_id: Option<Uuid>,
+    roles: &[Role],
+) -> Result<Uuid, sqlx::Error> {
+    let id = auth_repository::upsert_user(
+        conn,
+        &Identity {
+            issuer: "http://idp.test".to_string(),
+            subject: name.to_string(),
+            name: name.to_string(),
+            email: None,
+            id_token: String::new(),
+        },
+    )
+    .await?;
+    if let Some(group) = political_group_id {
+        auth_repository::set_user_political_group(conn, &id, &group).await?;
+    }
+    for role in roles {
+        auth_repository::add_user_role(conn, &id, *role).await?;
+    }
+
+    Ok(id)
+}
+
+/// Connection as the extractor hands it to a handler, scoped to the tenant of the user, or to no
+/// tenant at all when nobody is signed in.
+pub async fn db_connection(state: &AppState, user: Option<CurrentUser>) -> DbConnection {
+    let (mut parts, ()) = Request::new(()).into_parts();
+    if let Some(user) = user {
+        parts.extensions.insert(user);
+    }
+
+    DbConnection::from_request_parts(&mut parts, state)
+        .await
+        .expect("database connection")
+}
+
+/// Context of a signed in user with the given role.
+pub fn context_with_role(role: Role) -> Context {
+    Context {
+        user: Some(sample_user(vec![role])),
+        ..Context::new(Locale::En)
+    }
+}
+
 pub fn sample_candidate_list(id: Uuid) -> CandidateList {
     CandidateList {
         id,
@@ -70,6 +133,7 @@ pub fn sample_person_form(csrf_token: &TokenValue) -> PersonForm {
         initials: "H.A.H.A.".to_string(),
         date_of_birth: "01-02-1990".to_string(),
         bsn: "".to_string(),
+        version: String::new(),
         csrf_token: csrf_token.clone(),
     }
 }
@@ -86,6 +150,7 @@ pub fn sample_address_form(csrf_token: &TokenValue) -> AddressForm {
         address_line_1: "".to_string(),
         address_line_2: "".to_string(),
         is_dutch: "true".to_string(),
+        version: String::new(),
         csrf_token: csrf_token.clone(),
     }
 }
@@ -14,10 +14,14 @@ pub type AppResponse<T> = Result<T, AppError>;
 pub enum AppError {
     // Request level errors
     Unauthorized,
+    /// Signed in, but the role acted in does not allow the request.
+    Forbidden,
+    AuthenticationFailed(String),
     InternalServerError,
     #[default]
     GenericNotFound,
     NotFound(String),
+    BadRequest(String),
     DatabaseError(sqlx::Error),
     TemplateError(askama::Error),
     MultipartFormError(MultipartError),
@@ -30,24 +34,31 @@ pub enum AppError {
     MissingEnvVar(&'static str),
     ConfigLoadError(String),
     ServerError(std::io::Error),
+    IdentityProviderError(String),
+    UpstreamError(String),
 }
 
 impl Display for AppError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             AppError::Unauthorized => write!(f, "Unauthorized"),
+            AppError::Forbidden => write!(f, "Forbidden"),
+            AppError::AuthenticationFailed(err) => write!(f, "Authentication failed: {err}"),
             AppError::InternalServerError => write!(f, "Internal server error"),
             AppError::DatabaseError(err) => write!(f, "Database error: {err}"),
             AppError::TemplateError(err) => write!(f, "Template error: {err}"),
             AppError::MissingEnvVar(var) => write!(f, "Missing environment variable: {var}"),
             AppError::ConfigLoadError(err) => write!(f, "Configuration load error: {err}"),
             AppError::ServerError(err) => write!(f, "Server error: {err}"),
+            AppError::IdentityProviderError(err) => write!(f, "Identity provider error: {err}"),
+            AppError::UpstreamError(err) => write!(f, "Upstream service error: {err}"),
             AppError::MultipartFormError(err) => write!(f, "Multipart form error: {err}"),
             AppError::FormRejection(err) => write!(f, "Form error: {err}"),
             AppError::PathRejection(err) => write!(f, "Path error: {err}"),
             AppError::ValidationError(errors) => write!(f, "Validation error: {errors:?}"),
             AppError::JsonRejection(err) => write!(f, "JSON error: {err}"),
             AppError::NotFound(msg) => write!(f, "{msg}"),
+            AppError::BadRequest(msg) => write!(f, "{msg}"),
             AppError::GenericNotFound => write!(f, "Page not found"),
         }
     }
@@ -155,6 +166,7 @@ mod tests {
 
         let errors = vec![
             AppError::Unauthorized,
+            AppError::Forbidden,
             AppError::InternalServerError,
             AppError::GenericNotFound,
             AppError::NotFound("missing".to_string()),
@@ -165,6 +177,7 @@ mod tests {
             AppError::MissingEnvVar("DATABASE_URL"),
             AppError::ConfigLoadError("bad".to_string()),
             AppError::ServerError(std::io::Error::other("oh nooo")),
+            AppError::UpstreamError("timed out".to_string()),
         ];
 
         for error in errors {
@@ -14,9 +14,11 @@ use crate::{AppError, Context, HtmlTemplate, filters, t};
 #[derive(Serialize)]
 enum ErrorResponseVariant {
     Unauthorized,
+    Forbidden,
     BadRequest,
     InternalServerError,
     NotFound,
+    BadGateway,
 }
 
 impl ErrorResponseVariant {
@@ -25,16 +27,20 @@ impl ErrorResponseVariant {
             ErrorResponseVariant::NotFound => StatusCode::NOT_FOUND,
             ErrorResponseVariant::BadRequest => StatusCode::BAD_REQUEST,
             ErrorResponseVariant::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorResponseVariant::Forbidden => StatusCode::FORBIDDEN,
             ErrorResponseVariant::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorResponseVariant::BadGateway => StatusCode::BAD_GATEWAY,
         }
     }
 
     fn title(&self) -> &'static str {
         match self {
             ErrorResponseVariant::Unauthorized => "Unauthorized",
+            ErrorResponseVariant::Forbidden => "Forbidden",
             ErrorResponseVariant::BadRequest => "Bad request",
             ErrorResponseVariant::InternalServerError => "Internal server error",
             ErrorResponseVariant::NotFound => "Not found",
+            ErrorResponseVariant::BadGateway => "Bad gateway",
         }
     }
 }
@@ -120,6 +126,18 @@ impl ErrorResponse {
                 error: ErrorResponseVariant::Unauthorized,
                 message: "You are not authorized to perform this action.".to_string(),
             },
+            AppError::Forbidden => ErrorResponse {
+                error: ErrorResponseVariant::Forbidden,
+                message: "You do not have permission to perform this action.".to_string(),
+            },
+            AppError::AuthenticationFailed(_) => ErrorResponse {
+                error: ErrorResponseVariant::Unauthorized,
+                message: "Signing in failed, please try again.".to_string(),
+            },
+            AppError::BadRequest(msg) => ErrorResponse {
+                error: ErrorResponseVariant::BadRequest,
+                message: msg.to_string(),
+            },
             AppError::MultipartFormError(e) => ErrorResponse {
                 error: ErrorResponseVariant::BadRequest,
                 message: format!("Bad request: {e}"),
@@ -140,12 +158,18 @@ impl ErrorResponse {
                 error: ErrorResponseVariant::BadRequest,
                 message: format!("Bad request: {e}"),
             },
+            AppError::UpstreamError(_) => ErrorResponse {
+                error: ErrorResponseVariant::BadGateway,
+                message: "An external service is not available, please try again later."
+                    .to_string(),
+            },
             AppError::InternalServerError
             | AppError::MissingEnvVar(_)
             | AppError::ConfigLoadError(_)
             | AppError::DatabaseError(_)
             | AppError::TemplateError(_)
-            | AppError::ServerError(_) => ErrorResponse {
+            | AppError::ServerError(_)
+            | AppError::IdentityProviderError(_) => ErrorResponse {
                 error: ErrorResponseVariant::InternalServerError,
                 message: "An internal server error occurred.".to_string(),
             },
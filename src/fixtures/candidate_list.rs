@@ -5,7 +5,7 @@ use crate::{
     AppError, Config,
     candidate_lists::{self, CandidateList},
     pagination::SortDirection,
-    persons::{self, Person, PersonSort},
+    persons::{self, Person, PersonFilter, PersonSort},
 };
 
 const FIXTURE_CANDIDATE_LIST_SIZE: usize = 55;
@@ -25,6 +25,7 @@ pub async fn load(conn: &mut PgConnection) -> Result<(), AppError> {
 
     let persons = persons::repository::list_persons(
         conn,
+        &PersonFilter::default(),
         total_persons,
         0,
         &PersonSort::LastName,
@@ -56,6 +57,7 @@ mod tests {
     use sqlx::PgPool;
 
     use super::*;
+    use crate::candidate_lists::CandidateListSort;
 
     #[sqlx::test]
     async fn test_load(pool: PgPool) {
@@ -65,9 +67,15 @@ mod tests {
         let mut conn = pool.acquire().await.unwrap();
         load(&mut conn).await.unwrap();
 
-        let lists = candidate_lists::repository::list_candidate_list_with_count(&mut conn)
-            .await
-            .unwrap();
+        let lists = candidate_lists::repository::list_candidate_list_summaries(
+            &mut conn,
+            None,
+            0,
+            &CandidateListSort::CreatedAt,
+            &SortDirection::Asc,
+        )
+        .await
+        .unwrap();
 
         assert_eq!(lists.len(), 1);
         assert_eq!(lists[0].person_count, FIXTURE_CANDIDATE_LIST_SIZE as i64);
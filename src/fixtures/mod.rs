@@ -1,14 +1,27 @@
 use sqlx::PgConnection;
 
-use crate::{AppError, AppState};
+use crate::{AppError, AppState, Tenant, auth};
 
 mod candidate_list;
 mod persons;
+#[cfg(feature = "dev-features")]
+mod users;
+
+/// Political group all fixtures belong to.
+const POLITICAL_GROUP_NAME: &str = "Lijst Smit";
 
 pub async fn load(state: &AppState) -> Result<(), AppError> {
     let mut conn = state.pool().acquire().await?;
 
     clear_database(&mut conn).await?;
+    let political_group_id =
+        auth::repository::create_political_group(&mut conn, POLITICAL_GROUP_NAME).await?;
+    #[cfg(feature = "dev-features")]
+    users::load(&mut conn, &political_group_id).await?;
+
+    Tenant::PoliticalGroup(political_group_id)
+        .scope(&mut conn)
+        .await?;
     persons::load(&mut conn).await?;
     candidate_list::load(&mut conn).await?;
 
@@ -18,7 +31,7 @@ pub async fn load(state: &AppState) -> Result<(), AppError> {
 async fn clear_database(conn: &mut PgConnection) -> Result<(), AppError> {
     sqlx::query!(
         "
-        TRUNCATE TABLE candidate_lists_persons, candidate_lists, persons
+        TRUNCATE TABLE candidate_lists_persons, candidate_lists, persons, users, political_groups
         RESTART IDENTITY CASCADE
         "
     )
@@ -83,7 +83,10 @@ pub async fn load(conn: &mut PgConnection) -> Result<(), AppError> {
 mod tests {
     use sqlx::PgPool;
 
-    use crate::{pagination::SortDirection, persons::PersonSort};
+    use crate::{
+        pagination::SortDirection,
+        persons::{PersonFilter, PersonSort},
+    };
 
     use super::*;
 
@@ -93,6 +96,7 @@ mod tests {
         load(&mut conn).await.unwrap();
         let persons = crate::persons::repository::list_persons(
             &mut conn,
+            &PersonFilter::default(),
             50,
             0,
             &PersonSort::LastName,
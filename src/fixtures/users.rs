@@ -0,0 +1,25 @@
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::{AppError, Config, MockProvider, Role, auth, auth::Identity};
+
+/// The user the mock identity provider signs in, so it can be used straight away.
+pub async fn load(conn: &mut PgConnection, political_group_id: &Uuid) -> Result<(), AppError> {
+    let config = Config::from_env()?;
+
+    let user_id = auth::repository::upsert_user(
+        conn,
+        &Identity {
+            issuer: config.oidc.issuer_url.trim_end_matches('/').to_string(),
+            subject: MockProvider::USER_SUBJECT.to_string(),
+            name: MockProvider::USER_NAME.to_string(),
+            email: Some(MockProvider::USER_EMAIL.to_string()),
+            id_token: String::new(),
+        },
+    )
+    .await?;
+    auth::repository::add_user_role(conn, &user_id, Role::AuthorisedAgent).await?;
+    auth::repository::set_user_political_group(conn, &user_id, political_group_id).await?;
+
+    Ok(())
+}
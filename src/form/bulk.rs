@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validate::Validate;
+
+use crate::{TokenValue, form::WithCsrfToken};
+
+/// Form with the ids of the selected rows of a table.
+#[derive(Default, Serialize, Deserialize, Clone, Debug, Validate)]
+#[validate(target = "Vec<Uuid>", build = "BulkForm::build_selection")]
+#[serde(default)]
+pub struct BulkForm {
+    pub ids: Vec<Uuid>,
+    #[validate(csrf)]
+    pub csrf_token: TokenValue,
+}
+
+impl BulkForm {
+    fn build_selection(validated: BulkFormValidated, _: Option<&Vec<Uuid>>) -> Vec<Uuid> {
+        validated.ids
+    }
+}
+
+impl WithCsrfToken for BulkForm {
+    fn with_csrf_token(self, csrf_token: crate::form::CsrfToken) -> Self {
+        BulkForm {
+            csrf_token: csrf_token.value,
+            ..self
+        }
+    }
+}
+
+/// Result of a bulk operation for a single item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkItem {
+    pub label: String,
+    pub succeeded: bool,
+    pub message: String,
+}
+
+/// Per-item results of a bulk operation, in the order the items were submitted.
+#[derive(Debug, Clone, Default)]
+pub struct BulkSummary {
+    pub items: Vec<BulkItem>,
+}
+
+impl BulkSummary {
+    pub fn succeeded(&mut self, label: String, message: String) {
+        self.items.push(BulkItem {
+            label,
+            succeeded: true,
+            message,
+        });
+    }
+
+    pub fn skipped(&mut self, label: String, message: String) {
+        self.items.push(BulkItem {
+            label,
+            succeeded: false,
+            message,
+        });
+    }
+
+    pub fn succeeded_count(&self) -> usize {
+        self.items.iter().filter(|item| item.succeeded).count()
+    }
+
+    pub fn skipped_count(&self) -> usize {
+        self.items.len() - self.succeeded_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CsrfTokens, form::Validate};
+
+    #[test]
+    fn validates_selection_with_csrf_token() {
+        let tokens = CsrfTokens::default();
+        let ids = vec![Uuid::new_v4(), Uuid::new_v4()];
+        let form = BulkForm {
+            ids: ids.clone(),
+            csrf_token: tokens.issue().value,
+        };
+        assert_eq!(form.validate(None, &tokens).unwrap(), ids);
+
+        let form = BulkForm {
+            ids,
+            csrf_token: TokenValue("invalid".to_string()),
+        };
+        assert!(form.validate(None, &tokens).is_err());
+    }
+
+    #[test]
+    fn counts_results() {
+        let mut summary = BulkSummary::default();
+        summary.succeeded("Jansen".to_string(), "Added".to_string());
+        summary.skipped("Bakker".to_string(), "Already on list".to_string());
+        summary.succeeded("Visser".to_string(), "Added".to_string());
+
+        assert_eq!(summary.succeeded_count(), 2);
+        assert_eq!(summary.skipped_count(), 1);
+    }
+}
@@ -0,0 +1,65 @@
+use chrono::{DateTime, SecondsFormat};
+use sqlx::types::chrono::Utc;
+
+use crate::{constants::DEFAULT_DATE_TIME_FORMAT, persons::FieldChange};
+
+/// Version token of a record, carried through forms to detect stale writes.
+///
+/// Uses microsecond precision, matching what Postgres stores for `updated_at`.
+pub fn version_of(updated_at: &DateTime<Utc>) -> String {
+    updated_at.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+/// The `updated_at` a version token was made from, `None` for a token that is not a version.
+pub fn parse_version(version: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(version)
+        .ok()
+        .map(|version| version.with_timezone(&Utc))
+}
+
+/// An update based on an outdated version of a record, which was changed in the meantime.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    /// Version of the stored record; submitting it again reapplies the change.
+    pub version: String,
+    pub updated_at: DateTime<Utc>,
+    /// Fields where the stored record differs from the submitted change, with the stored
+    /// value as `old` and the submitted value as `new`.
+    pub fields: Vec<FieldChange>,
+}
+
+impl Conflict {
+    pub fn new(updated_at: DateTime<Utc>, fields: Vec<FieldChange>) -> Self {
+        Conflict {
+            version: version_of(&updated_at),
+            updated_at,
+            fields,
+        }
+    }
+
+    pub fn updated(&self) -> String {
+        self.updated_at.format(DEFAULT_DATE_TIME_FORMAT).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Timelike};
+
+    #[test]
+    fn version_round_trips_with_microseconds() {
+        let updated_at = Utc
+            .with_ymd_and_hms(2026, 3, 18, 9, 30, 0)
+            .single()
+            .unwrap()
+            .with_nanosecond(123_456_000)
+            .unwrap();
+
+        let version = version_of(&updated_at);
+
+        assert_eq!(version, "2026-03-18T09:30:00.123456Z");
+        assert_eq!(parse_version(&version), Some(updated_at));
+        assert_eq!(parse_version("stale"), None);
+    }
+}
@@ -1,9 +1,13 @@
+mod bulk;
+mod conflict;
 mod csrf;
 mod empty_form;
 mod form_data;
 mod validation_error;
 mod validators;
 
+pub use bulk::{BulkForm, BulkSummary};
+pub use conflict::{Conflict, parse_version, version_of};
 pub use csrf::{CsrfToken, CsrfTokens, TokenValue, WithCsrfToken};
 pub use empty_form::EmptyForm;
 pub use form_data::FormData;
@@ -18,3 +22,199 @@ where
 {
     fn validate(&self, current: Option<&T>, csrf_tokens: &CsrfTokens) -> Result<T, FormData<Self>>;
 }
+
+/// The validated fields of a form deriving `Validate`, to validate it as part of another form.
+pub trait ValidatedFields {
+    type Validated;
+}
+
+/// Validation of forms whose rules depend on the application, e.g. on the electoral districts
+/// of the election, given as `context`.
+pub trait ValidateWith<T, C: ?Sized>
+where
+    Self: Sized,
+{
+    fn validate_with(
+        &self,
+        context: &C,
+        current: Option<&T>,
+        csrf_tokens: &CsrfTokens,
+    ) -> Result<T, FormData<Self>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use std::str::FromStr;
+    use validate::Validate;
+
+    use super::*;
+
+    /// A range of pages, of which the last page may not come before the first.
+    #[derive(Default, Serialize, Deserialize, Clone, Debug, Validate)]
+    #[validate(
+        target = "(u32, Option<u32>)",
+        build = "PageRangeForm::build",
+        with = "PageRangeForm::check_order"
+    )]
+    struct PageRangeForm {
+        #[validate(parse = "u32")]
+        first: String,
+        #[validate(parse = "u32", optional, required_if(single = "false"))]
+        last: String,
+        single: String,
+        #[validate(csrf)]
+        csrf_token: TokenValue,
+    }
+
+    impl WithCsrfToken for PageRangeForm {
+        fn with_csrf_token(self, csrf_token: CsrfToken) -> Self {
+            PageRangeForm {
+                csrf_token: csrf_token.value,
+                ..self
+            }
+        }
+    }
+
+    impl PageRangeForm {
+        fn build(
+            validated: PageRangeFormValidated,
+            _: Option<&(u32, Option<u32>)>,
+        ) -> (u32, Option<u32>) {
+            (validated.first, validated.last)
+        }
+
+        fn check_order(&self) -> FieldErrors {
+            match (self.first.parse::<u32>(), self.last.parse::<u32>()) {
+                (Ok(first), Ok(last)) if last < first => {
+                    vec![("last".to_string(), ValidationError::InvalidValue)]
+                }
+                _ => Vec::new(),
+            }
+        }
+    }
+
+    /// Labels of a document, taken from the labels in use, and the number of copies to print.
+    #[derive(Default, Serialize, Deserialize, Clone, Debug, Validate)]
+    #[validate(
+        target = "(Vec<String>, u32)",
+        build = "LabelsForm::build",
+        context = "[String]"
+    )]
+    struct LabelsForm {
+        #[validate(max_items = 2, each = "validate_length(2, 16)", allowed = "context")]
+        labels: Vec<String>,
+        #[validate(parse = "u32", allowed = "[1, 2, 5]")]
+        copies: String,
+        #[validate(csrf)]
+        csrf_token: TokenValue,
+    }
+
+    impl WithCsrfToken for LabelsForm {
+        fn with_csrf_token(self, csrf_token: CsrfToken) -> Self {
+            LabelsForm {
+                csrf_token: csrf_token.value,
+                ..self
+            }
+        }
+    }
+
+    impl LabelsForm {
+        fn build(
+            validated: LabelsFormValidated,
+            _: Option<&(Vec<String>, u32)>,
+        ) -> (Vec<String>, u32) {
+            (validated.labels, validated.copies)
+        }
+    }
+
+    fn validate_labels(labels: &[&str], copies: &str) -> Result<(Vec<String>, u32), FieldErrors> {
+        let csrf_tokens = CsrfTokens::default();
+        let form = LabelsForm {
+            labels: labels.iter().map(|label| label.to_string()).collect(),
+            copies: copies.to_string(),
+            csrf_token: csrf_tokens.issue().value,
+        };
+        let in_use = vec!["draft".to_string(), "final".to_string(), "x".to_string()];
+
+        form.validate_with(in_use.as_slice(), None, &csrf_tokens)
+            .map_err(|form| form.errors().clone())
+    }
+
+    fn validate_range(
+        first: &str,
+        last: &str,
+        single: &str,
+    ) -> Result<(u32, Option<u32>), FieldErrors> {
+        let csrf_tokens = CsrfTokens::default();
+        let form = PageRangeForm {
+            first: first.to_string(),
+            last: last.to_string(),
+            single: single.to_string(),
+            csrf_token: csrf_tokens.issue().value,
+        };
+
+        form.validate(None, &csrf_tokens)
+            .map_err(|form| form.errors().clone())
+    }
+
+    #[test]
+    fn reports_struct_level_errors_on_their_field() {
+        assert_eq!(validate_range("2", "5", "false"), Ok((2, Some(5))));
+        assert_eq!(
+            validate_range("5", "2", "false"),
+            Err(vec![("last".to_string(), ValidationError::InvalidValue)])
+        );
+        // the order is only checked once both pages are valid
+        assert_eq!(
+            validate_range("", "2", "true"),
+            Err(vec![(
+                "first".to_string(),
+                ValidationError::ValueShouldNotBeEmpty
+            )])
+        );
+    }
+
+    #[test]
+    fn validates_collections_and_allowed_values() {
+        assert_eq!(
+            validate_labels(&["draft", "final"], "2"),
+            Ok((vec!["draft".to_string(), "final".to_string()], 2))
+        );
+        assert_eq!(validate_labels(&[], "1"), Ok((Vec::new(), 1)));
+        assert_eq!(
+            validate_labels(&["draft", "final", "draft"], "1"),
+            Err(vec![(
+                "labels".to_string(),
+                ValidationError::TooManyItems(2)
+            )])
+        );
+        // only the first invalid item is reported
+        assert_eq!(
+            validate_labels(&["x", "y"], "1"),
+            Err(vec![(
+                "labels".to_string(),
+                ValidationError::ValueTooShort(1, 2)
+            )])
+        );
+        assert_eq!(
+            validate_labels(&["final", "old"], "3"),
+            Err(vec![
+                ("labels".to_string(), ValidationError::InvalidValue),
+                ("copies".to_string(), ValidationError::InvalidValue)
+            ])
+        );
+    }
+
+    #[test]
+    fn requires_values_conditionally() {
+        assert_eq!(validate_range("2", "", "true"), Ok((2, None)));
+        assert_eq!(
+            validate_range("2", " ", "false"),
+            Err(vec![(
+                "last".to_string(),
+                ValidationError::ValueShouldNotBeEmpty
+            )])
+        );
+    }
+}
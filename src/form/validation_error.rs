@@ -3,6 +3,9 @@ use crate::{Locale, t};
 type ActualLength = usize;
 type MaxLength = usize;
 type MinLength = usize;
+type MaxNumber = u32;
+type MinItems = usize;
+type MaxItems = usize;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum ValidationError {
@@ -12,7 +15,15 @@ pub enum ValidationError {
     InvalidCsrfToken,
     ValueTooLong(ActualLength, MaxLength),
     ValueTooShort(ActualLength, MinLength),
+    TooFewItems(MinItems),
+    TooManyItems(MaxItems),
     InvalidChecksum,
+    InvalidPostalCode,
+    InvalidHouseNumber(MaxNumber),
+    InvalidHouseNumberAddition,
+    UnknownAddress,
+    /// The value differs from the registered one, given here.
+    AddressMismatch(String),
 }
 
 impl std::fmt::Display for ValidationError {
@@ -35,8 +46,21 @@ impl ValidationError {
             ValidationError::ValueTooShort(actual, min) => {
                 t!("validation.value_too_short", locale, actual, min)
             }
+            ValidationError::TooFewItems(min) => t!("validation.too_few_items", locale, min),
+            ValidationError::TooManyItems(max) => t!("validation.too_many_items", locale, max),
             ValidationError::InvalidCsrfToken => t!("validation.invalid_csrf_token", locale),
             ValidationError::InvalidChecksum => t!("validation.invalid_bsn", locale),
+            ValidationError::InvalidPostalCode => t!("validation.invalid_postal_code", locale),
+            ValidationError::InvalidHouseNumber(max) => {
+                t!("validation.invalid_house_number", locale, max)
+            }
+            ValidationError::InvalidHouseNumberAddition => {
+                t!("validation.invalid_house_number_addition", locale)
+            }
+            ValidationError::UnknownAddress => t!("validation.unknown_address", locale),
+            ValidationError::AddressMismatch(registered) => {
+                t!("validation.address_mismatch", locale, registered)
+            }
         }
         .to_string()
     }
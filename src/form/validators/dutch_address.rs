@@ -0,0 +1,134 @@
+use crate::form::ValidationError;
+
+/// Highest house number used in the Netherlands.
+const MAX_HOUSE_NUMBER: u32 = 99999;
+
+/// Validates a Dutch postal code, normalised to four digits, a space and two capitals, e.g.
+/// "1234 AB".
+pub fn validate_postal_code() -> impl Fn(&str) -> Result<String, ValidationError> {
+    |value: &str| {
+        let compact = value
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_ascii_uppercase();
+
+        if compact.is_empty() {
+            return Err(ValidationError::ValueShouldNotBeEmpty);
+        }
+
+        let (digits, letters) = compact.split_at_checked(4).unwrap_or((&compact, ""));
+        let valid = digits.len() == 4
+            && digits.chars().all(|c| c.is_ascii_digit())
+            && !digits.starts_with('0')
+            && letters.len() == 2
+            && letters.chars().all(|c| c.is_ascii_uppercase());
+
+        if !valid {
+            return Err(ValidationError::InvalidPostalCode);
+        }
+
+        Ok(format!("{digits} {letters}"))
+    }
+}
+
+/// Validates a house number, a whole number from 1 up to 99999, dropping leading zeros.
+pub fn validate_house_number() -> impl Fn(&str) -> Result<String, ValidationError> {
+    |value: &str| {
+        let trimmed_value = value.trim();
+
+        if trimmed_value.is_empty() {
+            return Err(ValidationError::ValueShouldNotBeEmpty);
+        }
+
+        match trimmed_value.parse::<u32>() {
+            Ok(number)
+                if (1..=MAX_HOUSE_NUMBER).contains(&number)
+                    && trimmed_value.chars().all(|c| c.is_ascii_digit()) =>
+            {
+                Ok(number.to_string())
+            }
+            _ => Err(ValidationError::InvalidHouseNumber(MAX_HOUSE_NUMBER)),
+        }
+    }
+}
+
+/// Validates a house number addition (huisletter and toevoeging): letters, digits, spaces and
+/// hyphens.
+pub fn validate_house_number_addition() -> impl Fn(&str) -> Result<String, ValidationError> {
+    |value: &str| {
+        let trimmed_value = value.trim();
+
+        if trimmed_value.is_empty() {
+            return Err(ValidationError::ValueShouldNotBeEmpty);
+        }
+
+        if !trimmed_value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == ' ' || c == '-')
+        {
+            return Err(ValidationError::InvalidHouseNumberAddition);
+        }
+
+        Ok(trimmed_value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalises_postal_codes() {
+        for value in ["1234 AB", "1234ab", " 1234  aB ", "1234\tAB"] {
+            assert_eq!(validate_postal_code()(value).unwrap(), "1234 AB");
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_postal_codes() {
+        for value in [
+            "XYZ", "0123 AB", "123 AB", "12345 AB", "1234 A", "1234 ABC", "1234 Ä1",
+        ] {
+            assert_eq!(
+                validate_postal_code()(value).unwrap_err(),
+                ValidationError::InvalidPostalCode,
+                "{value}"
+            );
+        }
+        assert_eq!(
+            validate_postal_code()("  ").unwrap_err(),
+            ValidationError::ValueShouldNotBeEmpty
+        );
+    }
+
+    #[test]
+    fn validates_house_numbers() {
+        assert_eq!(validate_house_number()(" 10 ").unwrap(), "10");
+        assert_eq!(validate_house_number()("010").unwrap(), "10");
+        assert_eq!(validate_house_number()("99999").unwrap(), "99999");
+
+        for value in ["0", "000", "100000", "10a", "-1", "+1", "1.5"] {
+            assert_eq!(
+                validate_house_number()(value).unwrap_err(),
+                ValidationError::InvalidHouseNumber(MAX_HOUSE_NUMBER),
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn validates_house_number_additions() {
+        for value in ["A", "bis", "2-hoog", "A 3"] {
+            assert_eq!(validate_house_number_addition()(value).unwrap(), value);
+        }
+
+        for value in ["A/B", "#1", "é"] {
+            assert_eq!(
+                validate_house_number_addition()(value).unwrap_err(),
+                ValidationError::InvalidHouseNumberAddition,
+                "{value}"
+            );
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use crate::form::ValidationError;
+
+/// Validates the shape of an email address; whether it exists only shows when mail bounces.
+pub fn validate_email() -> impl Fn(&str) -> Result<String, ValidationError> {
+    move |value: &str| {
+        let trimmed_value = value.trim();
+
+        if trimmed_value.is_empty() {
+            return Err(ValidationError::ValueShouldNotBeEmpty);
+        }
+
+        let valid = trimmed_value.len() <= 254
+            && !trimmed_value.chars().any(char::is_whitespace)
+            && trimmed_value
+                .split_once('@')
+                .is_some_and(|(local, domain)| {
+                    !local.is_empty()
+                        && !domain.contains('@')
+                        && domain.contains('.')
+                        && !domain.starts_with('.')
+                        && !domain.ends_with('.')
+                });
+
+        if !valid {
+            return Err(ValidationError::InvalidEmail);
+        }
+
+        Ok(trimmed_value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_email_addresses() {
+        assert_eq!(
+            validate_email()(" info@kiesraad.nl ").unwrap(),
+            "info@kiesraad.nl"
+        );
+        assert!(validate_email()("j.de.vries+eks@partij.example.org").is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_email_addresses() {
+        for value in [
+            "info",
+            "info@",
+            "@kiesraad.nl",
+            "info@kiesraad",
+            "info@@kiesraad.nl",
+            "info@kiesraad.nl.",
+            "in fo@kiesraad.nl",
+        ] {
+            assert_eq!(
+                validate_email()(value),
+                Err(ValidationError::InvalidEmail),
+                "{value}"
+            );
+        }
+
+        assert_eq!(
+            validate_email()(" "),
+            Err(ValidationError::ValueShouldNotBeEmpty)
+        );
+    }
+}
@@ -1,9 +1,15 @@
+mod dutch_address;
 mod eleven;
+mod email;
 mod initials;
 mod length;
 mod teletex;
 
+pub use dutch_address::{
+    validate_house_number, validate_house_number_addition, validate_postal_code,
+};
 pub use eleven::validate_eleven_check;
+pub use email::validate_email;
 pub use initials::validate_initials;
 pub use length::validate_length;
 pub use teletex::validate_teletex_chars;
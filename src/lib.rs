@@ -1,27 +1,38 @@
 /// Generic modules
+mod auth;
+mod bag;
 mod common;
 mod error;
 mod form;
 mod pages;
 mod pagination;
+mod population_register;
 
 /// Application specific modules
 mod candidate_lists;
 mod persons;
+mod recently_deleted;
+mod users;
 
 #[cfg(feature = "fixtures")]
 pub mod fixtures;
 
+#[cfg(feature = "dev-features")]
+pub use auth::{AuthenticationLevel, MockProvider, MockSamlProvider};
+pub use auth::{Permission, Role};
+
 pub use common::{
-    config::Config,
+    config::{Config, OidcConfig, SamlConfig},
     constants,
     context::Context,
+    current_user::CurrentUser,
     election::{ElectionConfig, ElectoralDistrict},
     filters, locale,
     locale::Locale,
     logging, router, server,
     state::{AppState, DbConnection},
     templates::HtmlTemplate,
+    tenant::Tenant,
     translate,
 };
 pub use error::{AppError, AppResponse, ErrorResponse, render_error_pages};
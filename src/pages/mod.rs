@@ -1,7 +1,7 @@
 use askama::Template;
 use axum::{extract::OriginalUri, http::StatusCode, response::IntoResponse};
 
-use crate::{AppError, Context, HtmlTemplate, filters, t};
+use crate::{AppError, Context, HtmlTemplate, filters, form::BulkSummary, t};
 
 #[derive(Template)]
 #[template(path = "index.html")]
@@ -11,6 +11,15 @@ pub async fn index(context: Context) -> impl IntoResponse {
     HtmlTemplate(IndexTemplate {}, context)
 }
 
+/// Result page of a bulk operation, listing what happened to every selected item.
+#[derive(Template)]
+#[template(path = "bulk_summary.html")]
+pub struct BulkSummaryTemplate {
+    pub title: String,
+    pub back_path: String,
+    pub summary: BulkSummary,
+}
+
 #[derive(Template)]
 #[template(path = "not_found.html")]
 pub struct NotFoundTemplate {
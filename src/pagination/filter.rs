@@ -0,0 +1,48 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Deserializer, de::Error};
+
+/// Deserialize an optional filter value, treating an empty string as "no filter".
+///
+/// HTML forms submit empty selects and inputs as `name=`, which would otherwise fail to parse.
+pub fn empty_as_none<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(value) if !value.trim().is_empty() => T::from_str(value.trim())
+            .map(Some)
+            .map_err(D::Error::custom),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct DummyFilter {
+        #[serde(default, deserialize_with = "empty_as_none")]
+        count: Option<u32>,
+    }
+
+    #[test]
+    fn treats_empty_values_as_none() {
+        let filter: DummyFilter = serde_urlencoded::from_str("count=").unwrap();
+        assert_eq!(filter, DummyFilter { count: None });
+
+        let filter: DummyFilter = serde_urlencoded::from_str("").unwrap();
+        assert_eq!(filter, DummyFilter { count: None });
+    }
+
+    #[test]
+    fn parses_non_empty_values() {
+        let filter: DummyFilter = serde_urlencoded::from_str("count=+3+").unwrap();
+        assert_eq!(filter, DummyFilter { count: Some(3) });
+
+        assert!(serde_urlencoded::from_str::<DummyFilter>("count=abc").is_err());
+    }
+}
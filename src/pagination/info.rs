@@ -1,7 +1,7 @@
-use serde::Serialize;
+use serde::{Serialize, de::DeserializeOwned};
 
 use crate::pagination::{
-    PageLink, Pagination, SortDirection, links::build_links, params::MAX_PER_PAGE,
+    KeysetRow, PageLink, Pagination, SortDirection, links::build_links, params::MAX_PER_PAGE,
 };
 
 /// Pagination metadata consumed by templates and components.
@@ -19,6 +19,12 @@ pub struct PaginationInfo<S> {
 
     pub sort: S,
     pub order: SortDirection,
+    /// Serialized filter parameters, appended to every generated link so filters survive paging.
+    pub filter: String,
+    /// Keyset mode: encoded cursor the previous link pages back from.
+    pub prev_cursor: Option<String>,
+    /// Keyset mode: encoded cursor the next link pages forward from.
+    pub next_cursor: Option<String>,
 }
 
 impl<S> PaginationInfo<S>
@@ -35,15 +41,57 @@ where
         ((self.page - 1) as i64) * self.per_page as i64
     }
 
+    /// Keep the given filter parameters in all generated links.
+    pub fn with_filter<F: Serialize>(self, filter: &F) -> Self {
+        Self {
+            filter: serde_urlencoded::to_string(filter).unwrap_or_default(),
+            ..self
+        }
+    }
+
+    /// Let the previous and next links page by keyset from the first and last of the given rows,
+    /// so paging on from a numbered page does not need an `OFFSET`.
+    pub fn with_cursors<T: KeysetRow<S>>(self, rows: &[T]) -> Self
+    where
+        S: DeserializeOwned,
+    {
+        let cursor = |row: Option<&T>| row.map(|row| row.cursor(&self.sort).encode());
+
+        Self {
+            prev_cursor: cursor(rows.first()).filter(|_| self.has_prev),
+            next_cursor: cursor(rows.last()).filter(|_| self.has_next),
+            ..self
+        }
+    }
+
     /// Generate a URL query string for the given page and per-page values.
     pub fn url(&self, page: u32, per_page: u32) -> String {
-        Pagination {
-            page,
-            per_page,
-            sort: self.sort,
-            order: self.order,
-        }
-        .as_query()
+        self.append_filter(
+            Pagination {
+                page,
+                per_page,
+                sort: self.sort,
+                order: self.order,
+                after: None,
+                before: None,
+            }
+            .as_query(),
+        )
+    }
+
+    /// Generate a URL query string for a keyset page adjacent to the given cursor.
+    fn cursor_url(&self, after: Option<&String>, before: Option<&String>) -> String {
+        self.append_filter(
+            Pagination {
+                page: 1,
+                per_page: self.per_page,
+                sort: self.sort,
+                order: self.order,
+                after: after.cloned(),
+                before: before.cloned(),
+            }
+            .as_query(),
+        )
     }
 
     pub fn sort(&self) -> &S {
@@ -55,11 +103,17 @@ where
     }
 
     pub fn next(&self) -> String {
-        self.url(self.page + 1, self.per_page)
+        match &self.next_cursor {
+            Some(cursor) => self.cursor_url(Some(cursor), None),
+            None => self.url(self.page + 1, self.per_page),
+        }
     }
 
     pub fn prev(&self) -> String {
-        self.url(self.page.saturating_sub(1), self.per_page)
+        match &self.prev_cursor {
+            Some(cursor) => self.cursor_url(None, Some(cursor)),
+            None => self.url(self.page.saturating_sub(1), self.per_page),
+        }
     }
 
     pub fn goto(&self, page: &u32) -> String {
@@ -67,17 +121,29 @@ where
     }
 
     pub fn sort_link(&self, sort: S) -> String {
-        Pagination {
-            page: 1,
-            per_page: self.per_page,
-            sort,
-            order: if sort == self.sort {
-                self.order.reverse()
-            } else {
-                self.order
-            },
+        self.append_filter(
+            Pagination {
+                page: 1,
+                per_page: self.per_page,
+                sort,
+                order: if sort == self.sort {
+                    self.order.reverse()
+                } else {
+                    self.order
+                },
+                after: None,
+                before: None,
+            }
+            .as_query(),
+        )
+    }
+
+    fn append_filter(&self, query: String) -> String {
+        match (self.filter.is_empty(), query.as_str()) {
+            (true, _) => query,
+            (false, "?") => format!("?{}", self.filter),
+            (false, _) => format!("{query}&{}", self.filter),
         }
-        .as_query()
     }
 
     pub fn dir_icon(&self, sort: S) -> &'static str {
@@ -117,6 +183,9 @@ where
         links: build_links(page, total_pages),
         sort: pagination.sort,
         order: pagination.order,
+        filter: String::new(),
+        prev_cursor: None,
+        next_cursor: None,
     }
 }
 
@@ -139,6 +208,7 @@ mod tests {
             per_page: 1_000,
             sort: DummySort::Name,
             order: SortDirection::Desc,
+            ..Default::default()
         };
 
         let info = pagination.set_total(1_200);
@@ -160,6 +230,7 @@ mod tests {
             per_page: 10,
             sort: DummySort::Name,
             order: SortDirection::Desc,
+            ..Default::default()
         };
         let info = pagination.set_total(50);
 
@@ -177,4 +248,28 @@ mod tests {
         assert_eq!(info.dir_icon(DummySort::Name), "▼");
         assert_eq!(info.dir_icon(DummySort::Age), "");
     }
+
+    #[test]
+    fn keeps_filter_in_links() {
+        #[derive(Serialize)]
+        struct DummyFilter {
+            locality: &'static str,
+        }
+
+        let pagination: Pagination<DummySort> = Pagination {
+            page: 2,
+            ..Default::default()
+        };
+        let info = pagination
+            .set_total(5_000)
+            .with_filter(&DummyFilter { locality: "Juinen" });
+
+        assert_eq!(info.next(), "?page=3&locality=Juinen");
+        assert_eq!(info.prev(), "?locality=Juinen");
+        assert_eq!(
+            info.sort_link(DummySort::Name),
+            "?order=desc&locality=Juinen"
+        );
+        assert_eq!(info.sort_link(DummySort::Age), "?sort=age&locality=Juinen");
+    }
 }
@@ -0,0 +1,262 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use uuid::Uuid;
+
+use crate::pagination::{Pagination, PaginationInfo, SortDirection, params::MAX_PER_PAGE};
+
+/// Position of a row within a sorted listing, used as an opaque token in keyset page links.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Cursor<S> {
+    /// Sort column the cursor was issued for.
+    #[serde(rename = "s")]
+    pub sort: S,
+    /// Text representation of the sort column value, `None` for SQL `NULL`.
+    #[serde(rename = "k")]
+    pub key: Option<String>,
+    /// Row id, breaking ties between equal sort keys.
+    #[serde(rename = "i")]
+    pub id: Uuid,
+}
+
+impl<S> Cursor<S>
+where
+    S: Serialize + DeserializeOwned,
+{
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    /// Decode a cursor from a query parameter, returning `None` for malformed input.
+    pub fn decode(value: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(value).ok()?;
+
+        serde_json::from_slice(&bytes).ok()
+    }
+}
+
+/// Rows that can be paginated by keyset for sort enum `S`.
+pub trait KeysetRow<S> {
+    /// Cursor pointing at this row. The key must parse back into the value of the sort column.
+    fn cursor(&self, sort: &S) -> Cursor<S>;
+}
+
+/// Keyset pagination request, derived from [`Pagination`] via [`Pagination::keyset`].
+///
+/// Queries fetch one row more than a page (see [`Keyset::limit`]) in [`Keyset::query_direction`],
+/// starting after [`Keyset::cursor`]; [`Keyset::finish`] trims the rows and builds the links.
+#[derive(Clone, Debug)]
+pub struct Keyset<S> {
+    pub sort: S,
+    pub order: SortDirection,
+    pub per_page: u32,
+    pub cursor: Option<Cursor<S>>,
+    /// Paging towards the start of the listing (`before` cursor).
+    pub backwards: bool,
+}
+
+impl<S> Keyset<S>
+where
+    S: Serialize + DeserializeOwned + Copy + PartialEq + Default,
+{
+    /// SQL `LIMIT`, including one extra row to detect whether more rows follow.
+    pub fn limit(&self) -> i64 {
+        self.per_page as i64 + 1
+    }
+
+    /// Direction to query in; reversed when paging backwards so the nearest rows come first.
+    pub fn query_direction(&self) -> SortDirection {
+        if self.backwards {
+            self.order.reverse()
+        } else {
+            self.order
+        }
+    }
+
+    pub fn cursor_key(&self) -> Option<&str> {
+        self.cursor.as_ref().and_then(|c| c.key.as_deref())
+    }
+
+    pub fn cursor_id(&self) -> Option<Uuid> {
+        self.cursor.as_ref().map(|c| c.id)
+    }
+
+    /// Trim the fetched rows to a single page in display order and compute the page links.
+    pub fn finish<T: KeysetRow<S>>(self, mut rows: Vec<T>) -> (Vec<T>, PaginationInfo<S>) {
+        let has_more = rows.len() > self.per_page as usize;
+        rows.truncate(self.per_page as usize);
+        if self.backwards {
+            rows.reverse();
+        }
+
+        let (has_prev, has_next) = if self.backwards {
+            (has_more, true)
+        } else {
+            (self.cursor.is_some(), has_more)
+        };
+
+        // Without rows, page relative to the requested cursor itself.
+        let first = rows.first().map(|row| row.cursor(&self.sort));
+        let last = rows.last().map(|row| row.cursor(&self.sort));
+        let prev_cursor = first.or_else(|| self.cursor.clone());
+        let next_cursor = last.or(self.cursor);
+
+        let info = PaginationInfo {
+            page: 1,
+            per_page: self.per_page,
+            has_prev,
+            has_next,
+            total_pages: 0,
+            links: Vec::new(),
+            sort: self.sort,
+            order: self.order,
+            filter: String::new(),
+            prev_cursor: prev_cursor.filter(|_| has_prev).map(|c| c.encode()),
+            next_cursor: next_cursor.filter(|_| has_next).map(|c| c.encode()),
+        };
+
+        (rows, info)
+    }
+}
+
+pub fn to_keyset<S>(pagination: Pagination<S>) -> Keyset<S>
+where
+    S: Serialize + DeserializeOwned + Copy + PartialEq + Default,
+{
+    let (encoded, backwards) = match (pagination.after, pagination.before) {
+        (Some(after), _) => (Some(after), false),
+        (None, Some(before)) => (Some(before), true),
+        (None, None) => (None, false),
+    };
+
+    let cursor = encoded
+        .as_deref()
+        .and_then(Cursor::<S>::decode)
+        .filter(|cursor| cursor.sort == pagination.sort);
+
+    Keyset {
+        sort: pagination.sort,
+        order: pagination.order,
+        per_page: pagination.per_page.clamp(1, MAX_PER_PAGE),
+        backwards: backwards && cursor.is_some(),
+        cursor,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Copy, Clone, Deserialize, Serialize, Default, PartialEq)]
+    #[serde(rename_all = "snake_case")]
+    enum DummySort {
+        #[default]
+        Name,
+        Age,
+    }
+
+    struct Row(u128);
+
+    impl KeysetRow<DummySort> for Row {
+        fn cursor(&self, sort: &DummySort) -> Cursor<DummySort> {
+            Cursor {
+                sort: *sort,
+                key: Some(format!("{:03}", self.0)),
+                id: Uuid::from_u128(self.0),
+            }
+        }
+    }
+
+    fn rows(ids: impl IntoIterator<Item = u128>) -> Vec<Row> {
+        ids.into_iter().map(Row).collect()
+    }
+
+    fn keyset(after: Option<String>, before: Option<String>) -> Keyset<DummySort> {
+        Pagination {
+            per_page: 2,
+            after,
+            before,
+            ..Default::default()
+        }
+        .keyset()
+    }
+
+    #[test]
+    fn round_trips_cursor() {
+        let cursor = Row(7).cursor(&DummySort::Age);
+        let encoded = cursor.encode();
+
+        assert!(!encoded.contains('='));
+        assert_eq!(Cursor::decode(&encoded), Some(cursor));
+        assert_eq!(Cursor::<DummySort>::decode("not a cursor"), None);
+    }
+
+    #[test]
+    fn ignores_cursor_for_other_sort() {
+        let encoded = Row(1).cursor(&DummySort::Age).encode();
+        let keyset = keyset(None, Some(encoded));
+
+        assert!(keyset.cursor.is_none());
+        assert!(!keyset.backwards);
+    }
+
+    #[test]
+    fn first_page_links_forward_only() {
+        let keyset = keyset(None, None);
+        assert_eq!(keyset.limit(), 3);
+        assert_eq!(keyset.query_direction(), SortDirection::Asc);
+
+        let (rows, info) = keyset.finish(rows([1, 2, 3]));
+
+        assert_eq!(rows.iter().map(|r| r.0).collect::<Vec<_>>(), vec![1, 2]);
+        assert!(!info.has_prev);
+        assert!(info.has_next);
+        assert!(info.links.is_empty());
+        assert_eq!(
+            info.next(),
+            format!(
+                "?per_page=2&after={}",
+                Row(2).cursor(&DummySort::Name).encode()
+            )
+        );
+    }
+
+    #[test]
+    fn pages_backwards_in_reverse_direction() {
+        let before = Row(5).cursor(&DummySort::Name).encode();
+        let keyset = keyset(None, Some(before));
+        assert!(keyset.backwards);
+        assert_eq!(keyset.query_direction(), SortDirection::Desc);
+
+        // Rows arrive nearest-first when paging backwards.
+        let (rows, info) = keyset.finish(rows([4, 3, 2]));
+
+        assert_eq!(rows.iter().map(|r| r.0).collect::<Vec<_>>(), vec![3, 4]);
+        assert!(info.has_prev);
+        assert!(info.has_next);
+        assert_eq!(
+            info.prev(),
+            format!(
+                "?per_page=2&before={}",
+                Row(3).cursor(&DummySort::Name).encode()
+            )
+        );
+        assert_eq!(
+            info.next(),
+            format!(
+                "?per_page=2&after={}",
+                Row(4).cursor(&DummySort::Name).encode()
+            )
+        );
+    }
+
+    #[test]
+    fn last_page_has_no_next_link() {
+        let after = Row(2).cursor(&DummySort::Name).encode();
+        let (rows, info) = keyset(Some(after), None).finish(rows([3]));
+
+        assert_eq!(rows.len(), 1);
+        assert!(info.has_prev);
+        assert!(!info.has_next);
+        assert!(info.next_cursor.is_none());
+    }
+}
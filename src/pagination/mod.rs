@@ -1,7 +1,13 @@
+mod filter;
 mod info;
+mod keyset;
 mod links;
 mod params;
+mod sort;
 
+pub use filter::empty_as_none;
 pub use info::PaginationInfo;
+pub use keyset::{Cursor, Keyset, KeysetRow};
 pub use links::PageLink;
 pub use params::{Pagination, SortDirection};
+pub use sort::{KeysetColumn, SortColumn, order_by};
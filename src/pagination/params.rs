@@ -5,7 +5,7 @@ use axum::{
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use strum::AsRefStr;
 
-use crate::pagination::{self, PaginationInfo};
+use crate::pagination::{self, Keyset, PaginationInfo};
 
 /// Maximum permitted page size to avoid expensive queries.
 pub const MAX_PER_PAGE: u32 = 500;
@@ -48,6 +48,14 @@ pub struct Pagination<S: Default + PartialEq> {
     #[serde(default)]
     #[serde(skip_serializing_if = "is_default")]
     pub order: SortDirection,
+    /// Keyset mode: encoded cursor of the row after which the requested page starts.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+    /// Keyset mode: encoded cursor of the row before which the requested page ends.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<String>,
 }
 
 /// Default page when the user omits or zeroes the parameter.
@@ -82,6 +90,8 @@ where
             per_page: default_per_page(),
             sort: S::default(),
             order: SortDirection::default(),
+            after: None,
+            before: None,
         }
     }
 }
@@ -112,6 +122,22 @@ where
         pagination::info::to_info(self, total_items)
     }
 
+    /// Whether to page by keyset rather than offset: requests following a previous or next link
+    /// carry an `after` or `before` cursor (see [`PaginationInfo::with_cursors`]), numbered page
+    /// links an offset.
+    pub fn is_keyset(&self) -> bool {
+        self.after.is_some() || self.before.is_some()
+    }
+
+    /// Switch to keyset pagination, decoding the `after` or `before` cursor from the request.
+    /// Cursors issued for a different sort column are ignored, so the listing restarts at the top.
+    pub fn keyset(self) -> Keyset<S>
+    where
+        S: DeserializeOwned,
+    {
+        pagination::keyset::to_keyset(self)
+    }
+
     pub fn as_query(&self) -> String {
         match serde_urlencoded::to_string(self) {
             Ok(query) if !query.is_empty() => format!("?{}", query),
@@ -144,6 +170,22 @@ mod tests {
         assert_eq!(pagination.as_query(), "?");
     }
 
+    #[test]
+    fn pages_by_keyset_for_cursors_only() {
+        let pagination: Pagination<DummySort> = Pagination {
+            per_page: 10,
+            ..Default::default()
+        };
+        assert!(!pagination.is_keyset());
+
+        let pagination: Pagination<DummySort> = Pagination {
+            per_page: 10,
+            before: Some("abc".to_string()),
+            ..Default::default()
+        };
+        assert!(pagination.is_keyset());
+    }
+
     #[test]
     fn serializes_all_fields_in_query_string() {
         let pagination = Pagination {
@@ -151,11 +193,13 @@ mod tests {
             per_page: 15,
             sort: DummySort::Age,
             order: SortDirection::Desc,
+            after: Some("abc".to_string()),
+            before: None,
         };
 
         assert_eq!(
             pagination.as_query(),
-            "?page=2&per_page=15&sort=age&order=desc"
+            "?page=2&per_page=15&sort=age&order=desc&after=abc"
         );
     }
 }
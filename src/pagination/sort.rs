@@ -0,0 +1,61 @@
+use crate::pagination::SortDirection;
+
+/// Sort enums whose variants map onto SQL columns.
+pub trait SortColumn: Copy + PartialEq + Default {
+    /// SQL to sort by this column in the given direction, including a tiebreaker to keep the order
+    /// stable between pages, e.g. `"last_name DESC, id DESC"`. It is spliced into the query text,
+    /// so it must be a fixed, trusted string and never derived from user input.
+    fn order_by(&self, direction: SortDirection) -> &'static str;
+}
+
+/// Sort columns that can be paged by keyset, see [`Keyset`](crate::pagination::Keyset).
+pub trait KeysetColumn: SortColumn {
+    /// SQL condition selecting the rows following the cursor in the given direction, with the
+    /// cursor id bound to `$2` and its key, as text, to `$3`. Like [`SortColumn::order_by`] it
+    /// must be a fixed string.
+    fn after_cursor(&self, direction: SortDirection) -> &'static str;
+
+    /// Whether the cursor key is a value of this column, so it can be cast in
+    /// [`KeysetColumn::after_cursor`].
+    fn is_valid_key(&self, key: &str) -> bool;
+}
+
+/// Build an `ORDER BY` clause for a sort column and direction.
+pub fn order_by<S: SortColumn>(sort: &S, direction: &SortDirection) -> String {
+    format!("ORDER BY {}", sort.order_by(*direction))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Copy, Clone, PartialEq, Default)]
+    enum DummySort {
+        #[default]
+        Name,
+        Age,
+    }
+
+    impl SortColumn for DummySort {
+        fn order_by(&self, direction: SortDirection) -> &'static str {
+            match (self, direction) {
+                (DummySort::Name, SortDirection::Asc) => "name ASC, id ASC",
+                (DummySort::Name, SortDirection::Desc) => "name DESC, id DESC",
+                (DummySort::Age, SortDirection::Asc) => "age ASC, id ASC",
+                (DummySort::Age, SortDirection::Desc) => "age DESC, id DESC",
+            }
+        }
+    }
+
+    #[test]
+    fn builds_order_by_clause() {
+        assert_eq!(
+            order_by(&DummySort::Name, &SortDirection::Asc),
+            "ORDER BY name ASC, id ASC"
+        );
+        assert_eq!(
+            order_by(&DummySort::Age, &SortDirection::Desc),
+            "ORDER BY age DESC, id DESC"
+        );
+    }
+}
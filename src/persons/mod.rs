@@ -2,5 +2,5 @@ mod pages;
 pub mod repository;
 mod structs;
 
-pub use pages::router;
+pub use pages::{check_address, person_not_found, router, save_person};
 pub use structs::*;
@@ -1,17 +1,18 @@
 use askama::Template;
 use axum::{
     extract::State,
+    http::StatusCode,
     response::{IntoResponse, Redirect, Response},
 };
 use axum_extra::extract::Form;
 
 use crate::{
     AppError, AppResponse, AppState, Context, CsrfTokens, DbConnection, HtmlTemplate, filters,
-    form::{FormData, Validate},
+    form::{Conflict, FormData, Validate},
     pagination::{Pagination, SortDirection},
     persons::{
         self, AddressForm, Person, PersonSort,
-        pages::{EditPersonAddressPath, person_not_found},
+        pages::{EditPersonAddressPath, check_address, person_not_found, save_person},
     },
     t,
 };
@@ -21,6 +22,7 @@ use crate::{
 struct PersonAddressUpdateTemplate {
     person: Person,
     form: FormData<AddressForm>,
+    conflict: Option<Conflict>,
 }
 
 pub async fn edit_person_address(
@@ -37,6 +39,7 @@ pub async fn edit_person_address(
         PersonAddressUpdateTemplate {
             form: FormData::new_with_data(AddressForm::from(person.clone()), &csrf_tokens),
             person,
+            conflict: None,
         },
         context,
     ))
@@ -58,13 +61,42 @@ pub async fn update_person_address(
             PersonAddressUpdateTemplate {
                 person,
                 form: form_data,
+                conflict: None,
             },
             context,
         )
         .into_response()),
-        Ok(mut person) => {
-            person.normalize_address();
-            persons::repository::update_person(&mut conn, &person).await?;
+        Ok(mut updated) => {
+            let errors = check_address(app_state.bag(), &mut updated).await;
+            if !errors.is_empty() {
+                return Ok(HtmlTemplate(
+                    PersonAddressUpdateTemplate {
+                        person,
+                        form: FormData::new_with_errors(form.0, app_state.csrf_tokens(), errors),
+                        conflict: None,
+                    },
+                    context,
+                )
+                .into_response());
+            }
+
+            if let Some(conflict) =
+                save_person(&mut conn, &updated, &form.version, context.locale).await?
+            {
+                let form = AddressForm {
+                    version: conflict.version.clone(),
+                    ..form.0
+                };
+                let page = HtmlTemplate(
+                    PersonAddressUpdateTemplate {
+                        person,
+                        form: FormData::new_with_data(form, app_state.csrf_tokens()),
+                        conflict: Some(conflict),
+                    },
+                    context,
+                );
+                return Ok((StatusCode::CONFLICT, page).into_response());
+            }
 
             // Redirect to the persons list after updating, sorted by updated, so the updated person is visible at the top
             let pagination = Pagination {
@@ -126,11 +158,12 @@ mod tests {
         let person = sample_person(id);
 
         let mut conn = pool.acquire().await?;
-        persons::repository::create_person(&mut conn, &person).await?;
+        let person = persons::repository::create_person(&mut conn, &person).await?;
 
         let app_state = AppState::new_for_tests(pool.clone());
         let csrf_token = app_state.csrf_tokens().issue().value;
-        let form = sample_address_form(&csrf_token);
+        let mut form = sample_address_form(&csrf_token);
+        form.version = person.version();
 
         let response = update_person_address(
             EditPersonAddressPath { id },
@@ -194,7 +227,7 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::OK);
         let body = response_body_string(response).await;
-        assert!(body.contains("The value is too short"));
+        assert!(body.contains("Enter a postal code of four digits and two letters"));
 
         Ok(())
     }
@@ -205,7 +238,7 @@ mod tests {
         let person = sample_person(id);
 
         let mut conn = pool.acquire().await?;
-        persons::repository::create_person(&mut conn, &person).await?;
+        let person = persons::repository::create_person(&mut conn, &person).await?;
 
         let app_state = AppState::new_for_tests(pool.clone());
 
@@ -226,6 +259,7 @@ mod tests {
                 address_line_1: "Stationsstraat 10A".to_string(),
                 address_line_2: "1234AB Juinen".to_string(),
                 is_dutch: "true".to_string(),
+                version: person.version(),
                 csrf_token: app_state.csrf_tokens().issue().value,
             }),
         )
@@ -265,6 +299,7 @@ mod tests {
                 address_line_1: "Stationsstraat 10A".to_string(),
                 address_line_2: "1234AB Juinen".to_string(),
                 is_dutch: "false".to_string(),
+                version: updated.version(),
                 csrf_token: app_state.csrf_tokens().issue().value,
             }),
         )
@@ -292,4 +327,52 @@ mod tests {
 
         Ok(())
     }
+
+    #[sqlx::test]
+    async fn update_person_address_checks_the_bag(pool: PgPool) -> Result<(), sqlx::Error> {
+        let id = Uuid::new_v4();
+        let mut conn = pool.acquire().await?;
+        let person = persons::repository::create_person(&mut conn, &sample_person(id)).await?;
+
+        let (url, _) = crate::bag::tests::start_bag_service().await;
+        let app_state = AppState::new_for_tests(pool.clone()).with_bag_service(&url);
+
+        let mut form = sample_address_form(&app_state.csrf_tokens().issue().value);
+        form.house_number = "11".to_string();
+        form.version = person.version();
+        let response = update_person_address(
+            EditPersonAddressPath { id },
+            Context::new(Locale::En),
+            State(app_state.clone()),
+            DbConnection(pool.acquire().await?),
+            Form(form),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        assert!(body.contains("No address exists with this postal code and house number."));
+
+        let mut form = sample_address_form(&app_state.csrf_tokens().issue().value);
+        form.street_name = String::new();
+        form.locality = "Juinen".to_string();
+        form.version = person.version();
+        let response = update_person_address(
+            EditPersonAddressPath { id },
+            Context::new(Locale::En),
+            State(app_state),
+            DbConnection(pool.acquire().await?),
+            Form(form),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+        let updated = persons::repository::get_person(&mut conn, &id)
+            .await?
+            .expect("updated person");
+        assert_eq!(updated.street_name, Some("Stationsstraat".to_string()));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,168 @@
+use axum::{
+    extract::State,
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::extract::Form;
+use sqlx::Connection;
+
+use crate::{
+    AppError, AppState, Context, CsrfTokens, DbConnection, HtmlTemplate, candidate_lists,
+    form::{BulkForm, BulkSummary, Validate},
+    pages::BulkSummaryTemplate,
+    persons::{self, Person, pages::BulkDeletePersonsPath},
+    t,
+};
+
+/// Delete the selected persons in one transaction. Persons that are still on a candidate
+/// list are skipped, like the single delete refuses them.
+pub async fn bulk_delete_persons(
+    _: BulkDeletePersonsPath,
+    context: Context,
+    State(app_state): State<AppState>,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+    Form(form): Form<BulkForm>,
+) -> Result<Response, AppError> {
+    let Ok(mut ids) = form.validate(None, &csrf_tokens) else {
+        // csrf token is invalid => back to the list
+        return Ok(Redirect::to(&Person::list_path()).into_response());
+    };
+    let mut seen = std::collections::HashSet::new();
+    ids.retain(|id| seen.insert(*id));
+
+    let election = app_state.config().election;
+    let locale = context.locale;
+    let mut summary = BulkSummary::default();
+    let mut tx = conn.begin().await?;
+
+    for id in ids {
+        let Some(person) = persons::repository::get_person(&mut tx, &id).await? else {
+            summary.skipped(id.to_string(), t!("bulk.not_found", &locale).to_string());
+            continue;
+        };
+
+        if persons::repository::remove_person(&mut tx, &id).await? {
+            summary.succeeded(
+                person.display_name(),
+                t!("bulk.deleted", &locale).to_string(),
+            );
+        } else {
+            let memberships =
+                candidate_lists::repository::list_memberships_for_person(&mut tx, &id).await?;
+            let lists = memberships
+                .iter()
+                .map(|m| m.list.display_districts(&election, &locale))
+                .collect::<Vec<_>>()
+                .join("; ");
+            summary.skipped(
+                person.display_name(),
+                t!("bulk.on_candidate_lists", &locale, lists),
+            );
+        }
+    }
+
+    tx.commit().await?;
+
+    Ok(HtmlTemplate(
+        BulkSummaryTemplate {
+            title: t!("bulk.delete_title", &locale).to_string(),
+            back_path: Person::list_path(),
+            summary,
+        },
+        context,
+    )
+    .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        Locale, TokenValue,
+        test_utils::{response_body_string, sample_candidate_list, sample_person_with_last_name},
+    };
+
+    #[sqlx::test]
+    async fn bulk_delete_skips_persons_on_lists(pool: PgPool) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let list = sample_candidate_list(Uuid::new_v4());
+        candidate_lists::repository::create_candidate_list(&mut conn, &list).await?;
+        let jansen = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        let bakker = sample_person_with_last_name(Uuid::new_v4(), "Bakker");
+        persons::repository::create_person(&mut conn, &jansen).await?;
+        persons::repository::create_person(&mut conn, &bakker).await?;
+        candidate_lists::repository::update_candidate_list_order(&mut conn, &list.id, &[bakker.id])
+            .await?;
+
+        let csrf_tokens = CsrfTokens::default();
+        let unknown = Uuid::new_v4();
+        let form = BulkForm {
+            ids: vec![jansen.id, bakker.id, unknown, jansen.id],
+            csrf_token: csrf_tokens.issue().value,
+        };
+
+        let response = bulk_delete_persons(
+            BulkDeletePersonsPath {},
+            Context::new(Locale::En),
+            State(AppState::new_for_tests(pool.clone())),
+            csrf_tokens,
+            DbConnection(pool.acquire().await?),
+            Form(form),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        assert!(body.contains("1 processed, 2 skipped."));
+        assert!(body.contains("Still on a candidate list"));
+        assert!(body.contains("Person not found"));
+
+        assert!(
+            persons::repository::get_person(&mut conn, &jansen.id)
+                .await?
+                .is_none()
+        );
+        assert!(
+            persons::repository::get_person(&mut conn, &bakker.id)
+                .await?
+                .is_some()
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn bulk_delete_with_invalid_csrf_redirects(pool: PgPool) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let person = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        persons::repository::create_person(&mut conn, &person).await?;
+
+        let response = bulk_delete_persons(
+            BulkDeletePersonsPath {},
+            Context::new(Locale::En),
+            State(AppState::new_for_tests(pool.clone())),
+            CsrfTokens::default(),
+            DbConnection(pool.acquire().await?),
+            Form(BulkForm {
+                ids: vec![person.id],
+                csrf_token: TokenValue("invalid".to_string()),
+            }),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert!(
+            persons::repository::get_person(&mut conn, &person.id)
+                .await?
+                .is_some()
+        );
+
+        Ok(())
+    }
+}
@@ -1,15 +1,115 @@
-use axum::response::{IntoResponse, Redirect, Response};
+use askama::Template;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::extract::Form;
 
 use crate::{
-    AppError, DbConnection,
-    persons::{self, Person, pages::DeletePersonPath},
+    AppError, AppState, Context, CsrfTokens, DbConnection, ElectionConfig, HtmlTemplate, Locale,
+    candidate_lists::{self, CandidateListMembership},
+    filters,
+    form::{EmptyForm, FormData, Validate},
+    persons::{
+        self, Person,
+        pages::{DeletePersonPath, RemoveAndDeletePersonPath, person_not_found},
+    },
+    t,
 };
 
+#[derive(Template)]
+#[template(path = "persons/delete.html")]
+struct PersonDeleteTemplate {
+    person: Person,
+    /// Candidate lists the person is on, which block a plain delete.
+    memberships: Vec<CandidateListMembership>,
+    form: FormData<EmptyForm>,
+    election: ElectionConfig,
+    locale: Locale,
+}
+
+async fn render_delete_page(
+    conn: &mut sqlx::PgConnection,
+    person: Person,
+    context: Context,
+    app_state: &AppState,
+    csrf_tokens: &CsrfTokens,
+) -> Result<HtmlTemplate<PersonDeleteTemplate>, AppError> {
+    let memberships =
+        candidate_lists::repository::list_memberships_for_person(conn, &person.id).await?;
+
+    Ok(HtmlTemplate(
+        PersonDeleteTemplate {
+            person,
+            memberships,
+            form: FormData::new_with_data(EmptyForm::default(), csrf_tokens),
+            election: app_state.config().election,
+            locale: context.locale,
+        },
+        context,
+    ))
+}
+
+pub async fn delete_person_form(
+    DeletePersonPath { id }: DeletePersonPath,
+    context: Context,
+    State(app_state): State<AppState>,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+) -> Result<Response, AppError> {
+    let person = persons::repository::get_person(&mut conn, &id)
+        .await?
+        .ok_or(person_not_found(id, context.locale))?;
+
+    Ok(
+        render_delete_page(&mut conn, person, context, &app_state, &csrf_tokens)
+            .await?
+            .into_response(),
+    )
+}
+
 pub async fn delete_person(
     DeletePersonPath { id }: DeletePersonPath,
+    context: Context,
+    State(app_state): State<AppState>,
+    csrf_tokens: CsrfTokens,
     DbConnection(mut conn): DbConnection,
+    form: Form<EmptyForm>,
 ) -> Result<Response, AppError> {
-    persons::repository::remove_person(&mut conn, &id).await?;
+    let person = persons::repository::get_person(&mut conn, &id)
+        .await?
+        .ok_or(person_not_found(id, context.locale))?;
+
+    if form.validate(None, &csrf_tokens).is_err() {
+        return Ok(Redirect::to(&person.delete_path()).into_response());
+    }
+
+    // The person is still on a candidate list: explain which lists block the deletion
+    if !persons::repository::remove_person(&mut conn, &id).await? {
+        let page = render_delete_page(&mut conn, person, context, &app_state, &csrf_tokens).await?;
+        return Ok((StatusCode::CONFLICT, page).into_response());
+    }
+
+    Ok(Redirect::to(&Person::list_path()).into_response())
+}
+
+pub async fn remove_and_delete_person(
+    RemoveAndDeletePersonPath { id }: RemoveAndDeletePersonPath,
+    context: Context,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+    form: Form<EmptyForm>,
+) -> Result<Response, AppError> {
+    let person = persons::repository::get_person(&mut conn, &id)
+        .await?
+        .ok_or(person_not_found(id, context.locale))?;
+
+    if form.validate(None, &csrf_tokens).is_err() {
+        return Ok(Redirect::to(&person.delete_path()).into_response());
+    }
+
+    persons::repository::remove_person_from_lists_and_delete(&mut conn, &id).await?;
 
     Ok(Redirect::to(&Person::list_path()).into_response())
 }
@@ -17,10 +117,21 @@ pub async fn delete_person(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use axum::http::header;
     use sqlx::PgPool;
     use uuid::Uuid;
 
-    use crate::{DbConnection, persons, test_utils::sample_person};
+    use crate::{
+        DbConnection, Locale, persons,
+        test_utils::{
+            response_body_string, sample_candidate_list, sample_person,
+            sample_person_with_last_name,
+        },
+    };
+
+    fn csrf_form(csrf_tokens: &CsrfTokens) -> Form<EmptyForm> {
+        Form(EmptyForm::from(csrf_tokens.issue().value))
+    }
 
     #[sqlx::test]
     async fn delete_person_removes_and_redirects(pool: PgPool) -> Result<(), sqlx::Error> {
@@ -30,14 +141,22 @@ mod tests {
         let mut conn = pool.acquire().await?;
         persons::repository::create_person(&mut conn, &person).await?;
 
-        let response = delete_person(DeletePersonPath { id }, DbConnection(pool.acquire().await?))
-            .await
-            .unwrap();
+        let csrf_tokens = CsrfTokens::default();
+        let response = delete_person(
+            DeletePersonPath { id },
+            Context::new(Locale::En),
+            State(AppState::new_for_tests(pool.clone())),
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+            csrf_form(&csrf_tokens),
+        )
+        .await
+        .unwrap();
 
-        assert_eq!(response.status(), axum::http::StatusCode::SEE_OTHER);
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
         let location = response
             .headers()
-            .get(axum::http::header::LOCATION)
+            .get(header::LOCATION)
             .expect("location header")
             .to_str()
             .expect("location header value");
@@ -49,4 +168,97 @@ mod tests {
 
         Ok(())
     }
+
+    #[sqlx::test]
+    async fn delete_person_on_list_shows_blocking_lists(pool: PgPool) -> Result<(), sqlx::Error> {
+        let list_id = Uuid::new_v4();
+        let person = sample_person(Uuid::new_v4());
+
+        let mut conn = pool.acquire().await?;
+        candidate_lists::repository::create_candidate_list(
+            &mut conn,
+            &sample_candidate_list(list_id),
+        )
+        .await?;
+        persons::repository::create_person(&mut conn, &person).await?;
+        candidate_lists::repository::update_candidate_list_order(&mut conn, &list_id, &[person.id])
+            .await?;
+
+        let csrf_tokens = CsrfTokens::default();
+        let response = delete_person(
+            DeletePersonPath { id: person.id },
+            Context::new(Locale::En),
+            State(AppState::new_for_tests(pool.clone())),
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+            csrf_form(&csrf_tokens),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body = response_body_string(response).await;
+        assert!(body.contains(&format!("/candidate-lists/{list_id}")));
+
+        let found = persons::repository::get_person(&mut conn, &person.id).await?;
+        assert!(found.is_some());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn remove_and_delete_person_closes_position_gaps(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let list_id = Uuid::new_v4();
+        let first = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        let removed = sample_person_with_last_name(Uuid::new_v4(), "Bakker");
+        let last = sample_person_with_last_name(Uuid::new_v4(), "de Vries");
+
+        let mut conn = pool.acquire().await?;
+        candidate_lists::repository::create_candidate_list(
+            &mut conn,
+            &sample_candidate_list(list_id),
+        )
+        .await?;
+        for person in [&first, &removed, &last] {
+            persons::repository::create_person(&mut conn, person).await?;
+        }
+        candidate_lists::repository::update_candidate_list_order(
+            &mut conn,
+            &list_id,
+            &[first.id, removed.id, last.id],
+        )
+        .await?;
+
+        let csrf_tokens = CsrfTokens::default();
+        let response = remove_and_delete_person(
+            RemoveAndDeletePersonPath { id: removed.id },
+            Context::new(Locale::En),
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+            csrf_form(&csrf_tokens),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert!(
+            persons::repository::get_person(&mut conn, &removed.id)
+                .await?
+                .is_none()
+        );
+
+        let full_list = candidate_lists::repository::get_full_candidate_list(&mut conn, &list_id)
+            .await?
+            .expect("candidate list");
+        let positions = full_list
+            .candidates
+            .iter()
+            .map(|c| (c.person.id, c.position))
+            .collect::<Vec<_>>();
+        assert_eq!(positions, vec![(first.id, 1), (last.id, 2)]);
+
+        Ok(())
+    }
 }
@@ -1,10 +1,16 @@
 use askama::Template;
-use axum::response::IntoResponse;
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+};
 
 use crate::{
-    AppError, Context, DbConnection, HtmlTemplate, filters,
+    AppError, AppState, Context, CsrfTokens, DbConnection, ElectionConfig, HtmlTemplate, Locale,
+    candidate_lists::{self, CandidateList},
+    filters,
+    form::{BulkForm, FormData},
     pagination::{Pagination, PaginationInfo},
-    persons::{self, Person, PersonSort, pages::PersonsPath},
+    persons::{self, Gender, ListStatus, Person, PersonFilter, PersonSort, pages::PersonsPath},
     t,
 };
 
@@ -13,30 +19,60 @@ use crate::{
 struct PersonListTemplate {
     persons: Vec<Person>,
     pagination: PaginationInfo<PersonSort>,
+    filter: PersonFilter,
+    localities: Vec<String>,
+    candidate_lists: Vec<CandidateList>,
+    /// Selection of persons to delete at once.
+    form: FormData<BulkForm>,
+    election: ElectionConfig,
+    locale: Locale,
 }
 
 pub async fn list_persons(
     _: PersonsPath,
     context: Context,
+    State(app_state): State<AppState>,
     pagination: Pagination<PersonSort>,
+    Query(filter): Query<PersonFilter>,
+    csrf_tokens: CsrfTokens,
     DbConnection(mut conn): DbConnection,
 ) -> Result<impl IntoResponse, AppError> {
-    let total_items = persons::repository::count_persons(&mut conn).await?.max(0) as u64;
-    let pagination = pagination.set_total(total_items);
-
-    let persons = persons::repository::list_persons(
-        &mut conn,
-        pagination.limit(),
-        pagination.offset(),
-        pagination.sort(),
-        pagination.direction(),
-    )
-    .await?;
+    let (persons, pagination) = if pagination.is_keyset() {
+        let keyset = pagination.keyset();
+        let rows = persons::repository::list_persons_keyset(&mut conn, &filter, &keyset).await?;
+        keyset.finish(rows)
+    } else {
+        let total_items = persons::repository::count_filtered_persons(&mut conn, &filter)
+            .await?
+            .max(0) as u64;
+        let pagination = pagination.set_total(total_items);
+        let persons = persons::repository::list_persons(
+            &mut conn,
+            &filter,
+            pagination.limit(),
+            pagination.offset(),
+            pagination.sort(),
+            pagination.direction(),
+        )
+        .await?;
+        let pagination = pagination.with_cursors(&persons);
+        (persons, pagination)
+    };
+    let pagination = pagination.with_filter(&filter);
+
+    let localities = persons::repository::list_localities(&mut conn).await?;
+    let candidate_lists = candidate_lists::repository::list_candidate_list(&mut conn).await?;
 
     Ok(HtmlTemplate(
         PersonListTemplate {
             persons,
             pagination,
+            filter,
+            localities,
+            candidate_lists,
+            form: FormData::new(&csrf_tokens),
+            election: app_state.config().election,
+            locale: context.locale,
         },
         context,
     ))
@@ -51,9 +87,9 @@ mod tests {
 
     use crate::{
         Context, DbConnection, Locale,
-        pagination::Pagination,
+        pagination::{KeysetRow, Pagination},
         persons,
-        test_utils::{response_body_string, sample_person},
+        test_utils::{response_body_string, sample_person, sample_person_with_last_name},
     };
 
     #[sqlx::test]
@@ -67,7 +103,140 @@ mod tests {
         let response = list_persons(
             PersonsPath {},
             Context::new(Locale::En),
+            State(AppState::new_for_tests(pool.clone())),
             Pagination::default(),
+            Query(PersonFilter::default()),
+            CsrfTokens::default(),
+            DbConnection(pool.acquire().await?),
+        )
+        .await
+        .unwrap()
+        .into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        assert!(body.contains("Jansen"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn list_persons_applies_filter(pool: PgPool) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let mut jansen = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        jansen.locality = Some("Juinen".to_string());
+        let mut bakker = sample_person_with_last_name(Uuid::new_v4(), "Bakker");
+        bakker.locality = Some("Heerenveen".to_string());
+        persons::repository::create_person(&mut conn, &jansen).await?;
+        persons::repository::create_person(&mut conn, &bakker).await?;
+
+        let filter = PersonFilter {
+            locality: Some("Heerenveen".to_string()),
+            ..Default::default()
+        };
+
+        let response = list_persons(
+            PersonsPath {},
+            Context::new(Locale::En),
+            State(AppState::new_for_tests(pool.clone())),
+            Pagination::default(),
+            Query(filter),
+            CsrfTokens::default(),
+            DbConnection(pool.acquire().await?),
+        )
+        .await
+        .unwrap()
+        .into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        assert!(body.contains("Bakker"));
+        assert!(!body.contains("Jansen"));
+
+        Ok(())
+    }
+
+    /// Query of the link with the given `rel` in a rendered page.
+    fn link_query(body: &str, rel: &str) -> String {
+        let (href, _) = body
+            .split(&format!(r#"" rel="{rel}""#))
+            .next()
+            .and_then(|before| before.rsplit_once(r#"href=""#))
+            .map(|(_, href)| (href, ()))
+            .unwrap();
+        href.trim_start_matches('?')
+            .replace("&amp;", "&")
+            .replace("&#38;", "&")
+    }
+
+    #[sqlx::test]
+    async fn list_persons_links_to_keyset_pages(pool: PgPool) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        for last_name in ["Bakker", "Jansen", "Visser"] {
+            let person = sample_person_with_last_name(Uuid::new_v4(), last_name);
+            persons::repository::create_person(&mut conn, &person).await?;
+        }
+
+        let mut pagination = Pagination {
+            per_page: 1,
+            ..Default::default()
+        };
+        for (shown, hidden) in [
+            ("Bakker", "Jansen"),
+            ("Jansen", "Visser"),
+            ("Visser", "Bakker"),
+        ] {
+            let response = list_persons(
+                PersonsPath {},
+                Context::new(Locale::En),
+                State(AppState::new_for_tests(pool.clone())),
+                pagination,
+                Query(PersonFilter::default()),
+                CsrfTokens::default(),
+                DbConnection(pool.acquire().await?),
+            )
+            .await
+            .unwrap()
+            .into_response();
+
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = response_body_string(response).await;
+            assert!(body.contains(shown), "{shown}");
+            assert!(!body.contains(hidden), "{hidden}");
+
+            if shown == "Visser" {
+                assert!(!body.contains(r#"rel="next""#));
+                assert!(link_query(&body, "prev").contains("before="));
+                break;
+            }
+            let next = link_query(&body, "next");
+            assert!(next.contains("after="), "{next}");
+            assert!(!next.split('&').any(|param| param.starts_with("page=")));
+            pagination = serde_urlencoded::from_str(&next).unwrap();
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn list_persons_pages_by_keyset_after_a_cursor(pool: PgPool) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let bakker = sample_person_with_last_name(Uuid::new_v4(), "Bakker");
+        let jansen = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        persons::repository::create_person(&mut conn, &bakker).await?;
+        persons::repository::create_person(&mut conn, &jansen).await?;
+
+        let response = list_persons(
+            PersonsPath {},
+            Context::new(Locale::En),
+            State(AppState::new_for_tests(pool.clone())),
+            Pagination {
+                per_page: 1,
+                after: Some(bakker.cursor(&PersonSort::LastName).encode()),
+                ..Default::default()
+            },
+            Query(PersonFilter::default()),
+            CsrfTokens::default(),
             DbConnection(pool.acquire().await?),
         )
         .await
@@ -77,6 +246,9 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
         let body = response_body_string(response).await;
         assert!(body.contains("Jansen"));
+        assert!(!body.contains("Bakker"));
+        // keyset pages link back by cursor instead of by page number
+        assert!(body.contains("before="));
 
         Ok(())
     }
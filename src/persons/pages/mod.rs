@@ -1,20 +1,27 @@
-use axum::Router;
+use axum::{Router, middleware};
 use axum_extra::routing::{RouterExt, TypedPath};
 use serde::Deserialize;
+use sqlx::PgConnection;
 use uuid::Uuid;
 
 use crate::{
-    AppError, AppState, Locale,
+    AppError, AppState, Locale, Permission,
+    auth::require_permission,
+    bag::BagClient,
+    form::{Conflict, FieldErrors, ValidationError},
     pagination::Pagination,
-    persons::{Person, PersonSort},
+    persons::{self, Person, PersonSort},
     t,
 };
 
 mod address;
+mod bulk_delete;
 mod create;
 mod delete;
 mod list;
+mod population_register;
 mod update;
+mod view;
 
 #[derive(TypedPath, Deserialize)]
 #[typed_path("/persons", rejection(AppError))]
@@ -24,6 +31,16 @@ pub struct PersonsPath;
 #[typed_path("/persons/new", rejection(AppError))]
 pub struct PersonsNewPath;
 
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/persons/bulk-delete", rejection(AppError))]
+pub struct BulkDeletePersonsPath;
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/persons/{id}", rejection(AppError))]
+pub struct ViewPersonPath {
+    pub id: Uuid,
+}
+
 #[derive(TypedPath, Deserialize)]
 #[typed_path("/persons/{id}/edit", rejection(AppError))]
 pub struct EditPersonPath {
@@ -36,12 +53,24 @@ pub struct DeletePersonPath {
     pub id: Uuid,
 }
 
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/persons/{id}/delete-from-lists", rejection(AppError))]
+pub struct RemoveAndDeletePersonPath {
+    pub id: Uuid,
+}
+
 #[derive(TypedPath, Deserialize)]
 #[typed_path("/persons/{id}/address", rejection(AppError))]
 pub struct EditPersonAddressPath {
     pub id: Uuid,
 }
 
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/persons/{id}/brp", rejection(AppError))]
+pub struct PersonRegisterPath {
+    pub id: Uuid,
+}
+
 impl Person {
     pub fn list_path() -> String {
         PersonsPath {}.to_uri().to_string()
@@ -55,27 +84,132 @@ impl Person {
         PersonsNewPath {}.to_uri().to_string()
     }
 
+    pub fn bulk_delete_path() -> String {
+        BulkDeletePersonsPath {}.to_uri().to_string()
+    }
+
+    pub fn view_path(&self) -> String {
+        ViewPersonPath { id: self.id }.to_uri().to_string()
+    }
+
     pub fn edit_path(&self) -> String {
         EditPersonPath { id: self.id }.to_uri().to_string()
     }
 
+    pub fn delete_path(&self) -> String {
+        DeletePersonPath { id: self.id }.to_uri().to_string()
+    }
+
+    pub fn remove_and_delete_path(&self) -> String {
+        RemoveAndDeletePersonPath { id: self.id }
+            .to_uri()
+            .to_string()
+    }
+
     pub fn edit_address_path(&self) -> String {
         EditPersonAddressPath { id: self.id }.to_uri().to_string()
     }
+
+    pub fn register_path(&self) -> String {
+        PersonRegisterPath { id: self.id }.to_uri().to_string()
+    }
 }
 
 pub fn router() -> Router<AppState> {
-    Router::new()
+    let view = Router::new()
         .typed_get(list::list_persons)
+        .typed_get(view::view_person)
+        .route_layer(middleware::from_fn_with_state(
+            Permission::ViewPersons,
+            require_permission,
+        ));
+
+    let edit = Router::new()
         .typed_post(create::create_person)
         .typed_get(create::new_person_form)
         .typed_get(update::edit_person_form)
         .typed_post(update::update_person)
         .typed_get(address::edit_person_address)
         .typed_post(address::update_person_address)
+        .typed_get(population_register::compare_person_with_register)
+        .typed_post(population_register::adopt_registered_details)
+        .typed_get(delete::delete_person_form)
         .typed_post(delete::delete_person)
+        .typed_post(delete::remove_and_delete_person)
+        .typed_post(bulk_delete::bulk_delete_persons)
+        .route_layer(middleware::from_fn_with_state(
+            Permission::EditPersons,
+            require_permission,
+        ));
+
+    view.merge(edit)
 }
 
 pub fn person_not_found(id: Uuid, locale: Locale) -> AppError {
     AppError::NotFound(t!("person.not_found", &locale, id))
 }
+
+/// Save a person edited in a form that was loaded at `version`. When someone else changed
+/// the person in the meantime, nothing is saved and the conflict is returned instead.
+pub async fn save_person(
+    conn: &mut PgConnection,
+    person: &Person,
+    version: &str,
+    locale: Locale,
+) -> Result<Option<Conflict>, AppError> {
+    if version == person.version()
+        && persons::repository::update_person(conn, person)
+            .await?
+            .is_some()
+    {
+        return Ok(None);
+    }
+
+    let current = persons::repository::get_person(conn, &person.id)
+        .await?
+        .ok_or(person_not_found(person.id, locale))?;
+
+    Ok(Some(Conflict::new(
+        current.updated_at,
+        person.changes_since(&current, locale),
+    )))
+}
+
+/// Check a Dutch address against the BAG: the postal code and house number must exist, an empty
+/// street name or locality is filled in and a given one must match. Addresses are not checked
+/// while the BAG service is not available.
+pub async fn check_address(bag: &BagClient, person: &mut Person) -> FieldErrors {
+    let (Some(postal_code), Some(house_number)) = (&person.postal_code, &person.house_number)
+    else {
+        return Vec::new();
+    };
+    if person.is_dutch == Some(false) {
+        return Vec::new();
+    }
+
+    let address = match bag.lookup(postal_code, house_number).await {
+        Ok(Some(address)) => address,
+        Ok(None) => return vec![("postal_code".to_string(), ValidationError::UnknownAddress)],
+        Err(err) => {
+            tracing::warn!(?err, "Address not checked against the BAG");
+            return Vec::new();
+        }
+    };
+
+    let mut errors = Vec::new();
+    for (field, value, registered) in [
+        ("street_name", &mut person.street_name, address.street_name),
+        ("locality", &mut person.locality, address.locality),
+    ] {
+        match value {
+            None => *value = Some(registered),
+            Some(value) if value.trim().eq_ignore_ascii_case(&registered) => {}
+            Some(_) => errors.push((
+                field.to_string(),
+                ValidationError::AddressMismatch(registered),
+            )),
+        }
+    }
+
+    errors
+}
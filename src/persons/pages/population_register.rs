@@ -0,0 +1,222 @@
+use askama::Template;
+use axum::{
+    extract::State,
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::extract::Form;
+
+use crate::{
+    AppError, AppState, Context, CsrfTokens, DbConnection, HtmlTemplate, filters,
+    form::{FormData, Validate},
+    persons::{
+        self, FieldChange, Person, RegisterAdoptionForm,
+        pages::{PersonRegisterPath, person_not_found, save_person},
+    },
+    population_register::RegisteredPerson,
+    t,
+};
+
+#[derive(Template)]
+#[template(path = "persons/register.html")]
+struct PersonRegisterTemplate {
+    person: Person,
+    /// Details registered for the BSN of the person, if any.
+    registered: Option<RegisteredPerson>,
+    changes: Vec<FieldChange>,
+    form: FormData<RegisterAdoptionForm>,
+}
+
+/// Look up the person by BSN in the population register.
+async fn find_registered(
+    app_state: &AppState,
+    person: &Person,
+) -> Result<Option<RegisteredPerson>, AppError> {
+    match &person.bsn {
+        Some(bsn) => app_state.population_register().find_by_bsn(bsn).await,
+        None => Ok(None),
+    }
+}
+
+/// Compare a person field by field with their details in the population register (BRP).
+pub async fn compare_person_with_register(
+    PersonRegisterPath { id }: PersonRegisterPath,
+    context: Context,
+    State(app_state): State<AppState>,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+) -> Result<impl IntoResponse, AppError> {
+    let person = persons::repository::get_person(&mut conn, &id)
+        .await?
+        .ok_or(person_not_found(id, context.locale))?;
+
+    let registered = find_registered(&app_state, &person).await?;
+    let changes = registered
+        .as_ref()
+        .map(|registered| {
+            registered
+                .adopt_into(&person)
+                .changes_since(&person, context.locale)
+        })
+        .unwrap_or_default();
+    let form = RegisterAdoptionForm {
+        version: person.version(),
+        ..Default::default()
+    };
+
+    Ok(HtmlTemplate(
+        PersonRegisterTemplate {
+            person,
+            registered,
+            changes,
+            form: FormData::new_with_data(form, &csrf_tokens),
+        },
+        context,
+    ))
+}
+
+/// Replace the details of a person by those in the population register. When the person was
+/// changed since the comparison, the updated comparison is shown again.
+pub async fn adopt_registered_details(
+    PersonRegisterPath { id }: PersonRegisterPath,
+    context: Context,
+    State(app_state): State<AppState>,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+    form: Form<RegisterAdoptionForm>,
+) -> Result<Response, AppError> {
+    let path = PersonRegisterPath { id }.to_string();
+    if form.validate(None, &csrf_tokens).is_err() {
+        return Ok(Redirect::to(&path).into_response());
+    }
+
+    let person = persons::repository::get_person(&mut conn, &id)
+        .await?
+        .ok_or(person_not_found(id, context.locale))?;
+    let registered = find_registered(&app_state, &person)
+        .await?
+        .ok_or(AppError::NotFound(t!(
+            "person.register.not_registered",
+            &context.locale
+        )))?;
+
+    let updated = registered.adopt_into(&person);
+    if save_person(&mut conn, &updated, &form.version, context.locale)
+        .await?
+        .is_some()
+    {
+        return Ok(Redirect::to(&path).into_response());
+    }
+
+    Ok(Redirect::to(&person.view_path()).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{StatusCode, header};
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use crate::{
+        Locale,
+        test_utils::{response_body_string, sample_person},
+    };
+
+    #[sqlx::test]
+    async fn adopts_registered_details(pool: PgPool) -> Result<(), sqlx::Error> {
+        let person = Person {
+            bsn: Some("999990019".to_string()),
+            ..sample_person(Uuid::new_v4())
+        };
+        let mut conn = pool.acquire().await?;
+        let person = persons::repository::create_person(&mut conn, &person).await?;
+
+        let state = AppState::new_for_tests(pool.clone());
+        let response = compare_person_with_register(
+            PersonRegisterPath { id: person.id },
+            Context::new(Locale::En),
+            State(state.clone()),
+            CsrfTokens::default(),
+            DbConnection(pool.acquire().await?),
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        assert!(body.contains("Stationsstraat"));
+        assert!(body.contains("Dorpsstraat"));
+
+        let csrf_tokens = CsrfTokens::default();
+        let response = adopt_registered_details(
+            PersonRegisterPath { id: person.id },
+            Context::new(Locale::En),
+            State(state),
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+            Form(RegisterAdoptionForm {
+                version: person.version(),
+                csrf_token: csrf_tokens.issue().value,
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(
+            response.headers().get(header::LOCATION).unwrap(),
+            person.view_path().as_str()
+        );
+
+        let updated = persons::repository::get_person(&mut conn, &person.id)
+            .await?
+            .expect("updated person");
+        assert_eq!(updated.street_name, Some("Dorpsstraat".to_string()));
+        assert_eq!(updated.house_number, Some("12".to_string()));
+        assert_eq!(updated.house_number_addition, None);
+        assert_eq!(updated.last_name, "Jansen");
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn persons_without_registration_are_not_compared(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let person = Person {
+            bsn: Some("999990007".to_string()),
+            ..sample_person(Uuid::new_v4())
+        };
+        let mut conn = pool.acquire().await?;
+        let person = persons::repository::create_person(&mut conn, &person).await?;
+
+        let response = compare_person_with_register(
+            PersonRegisterPath { id: person.id },
+            Context::new(Locale::En),
+            State(AppState::new_for_tests(pool.clone())),
+            CsrfTokens::default(),
+            DbConnection(pool.acquire().await?),
+        )
+        .await
+        .unwrap()
+        .into_response();
+        let body = response_body_string(response).await;
+        assert!(body.contains("No person is registered with this BSN."));
+
+        let csrf_tokens = CsrfTokens::default();
+        let result = adopt_registered_details(
+            PersonRegisterPath { id: person.id },
+            Context::new(Locale::En),
+            State(AppState::new_for_tests(pool.clone())),
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+            Form(RegisterAdoptionForm {
+                version: person.version(),
+                csrf_token: csrf_tokens.issue().value,
+            }),
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        Ok(())
+    }
+}
@@ -1,16 +1,17 @@
 use askama::Template;
 use axum::{
     extract::State,
+    http::StatusCode,
     response::{IntoResponse, Redirect, Response},
 };
 use axum_extra::extract::Form;
 
 use crate::{
     AppError, AppResponse, AppState, Context, CsrfTokens, DbConnection, HtmlTemplate, filters,
-    form::{FormData, Validate},
+    form::{Conflict, FormData, Validate},
     persons::{
         self, Person, PersonForm,
-        pages::{EditPersonPath, person_not_found},
+        pages::{EditPersonPath, person_not_found, save_person},
     },
     t,
 };
@@ -20,6 +21,7 @@ use crate::{
 struct PersonUpdateTemplate {
     person: Person,
     form: FormData<PersonForm>,
+    conflict: Option<Conflict>,
 }
 
 pub async fn edit_person_form(
@@ -36,6 +38,7 @@ pub async fn edit_person_form(
         PersonUpdateTemplate {
             form: FormData::new_with_data(PersonForm::from(person.clone()), &csrf_tokens),
             person,
+            conflict: None,
         },
         context,
     ))
@@ -57,15 +60,33 @@ pub async fn update_person(
             PersonUpdateTemplate {
                 person,
                 form: form_data,
+                conflict: None,
             },
             context,
         )
         .into_response()),
-        Ok(person) => {
-            persons::repository::update_person(&mut conn, &person).await?;
+        Ok(updated) => {
+            if let Some(conflict) =
+                save_person(&mut conn, &updated, &form.version, context.locale).await?
+            {
+                // Show the form again with the user's changes, based on the stored version
+                let form = PersonForm {
+                    version: conflict.version.clone(),
+                    ..form.0
+                };
+                let page = HtmlTemplate(
+                    PersonUpdateTemplate {
+                        person,
+                        form: FormData::new_with_data(form, app_state.csrf_tokens()),
+                        conflict: Some(conflict),
+                    },
+                    context,
+                );
+                return Ok((StatusCode::CONFLICT, page).into_response());
+            }
 
             // Redirect to the address edit page
-            Ok(Redirect::to(&person.edit_address_path()).into_response())
+            Ok(Redirect::to(&updated.edit_address_path()).into_response())
         }
     }
 }
@@ -118,12 +139,13 @@ mod tests {
         let person = sample_person(id);
 
         let mut conn = pool.acquire().await?;
-        persons::repository::create_person(&mut conn, &person).await?;
+        let person = persons::repository::create_person(&mut conn, &person).await?;
 
         let app_state = AppState::new_for_tests(pool.clone());
         let csrf_token = app_state.csrf_tokens().issue().value;
         let mut form = sample_person_form(&csrf_token);
         form.last_name = "Updated".to_string();
+        form.version = person.version();
 
         let response = update_person(
             EditPersonPath { id },
@@ -182,4 +204,54 @@ mod tests {
 
         Ok(())
     }
+
+    #[sqlx::test]
+    async fn update_person_with_stale_version_shows_conflict(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let id = Uuid::new_v4();
+        let person = sample_person(id);
+
+        let mut conn = pool.acquire().await?;
+        let loaded = persons::repository::create_person(&mut conn, &person).await?;
+
+        // someone else renames the person after our form was loaded
+        let renamed = Person {
+            last_name: "Bakker".to_string(),
+            ..loaded.clone()
+        };
+        let renamed = persons::repository::update_person(&mut conn, &renamed)
+            .await?
+            .expect("saved");
+
+        let app_state = AppState::new_for_tests(pool.clone());
+        let csrf_token = app_state.csrf_tokens().issue().value;
+        let mut form = sample_person_form(&csrf_token);
+        form.last_name = "Visser".to_string();
+        form.version = loaded.version();
+
+        let response = update_person(
+            EditPersonPath { id },
+            Context::new(Locale::En),
+            State(app_state),
+            DbConnection(pool.acquire().await?),
+            Form(form),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body = response_body_string(response).await;
+        assert!(body.contains("Changed by someone else"));
+        assert!(body.contains("Bakker"));
+        assert!(body.contains("Visser"));
+        assert!(body.contains(&format!(r#"name="version" value="{}""#, renamed.version())));
+
+        let stored = persons::repository::get_person(&mut conn, &id)
+            .await?
+            .expect("person");
+        assert_eq!(stored.last_name, "Bakker");
+
+        Ok(())
+    }
 }
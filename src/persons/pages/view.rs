@@ -0,0 +1,117 @@
+use askama::Template;
+use axum::{extract::State, response::IntoResponse};
+
+use crate::{
+    AppError, AppState, Context, DbConnection, ElectionConfig, HtmlTemplate, Locale,
+    candidate_lists::{self, CandidateListMembership},
+    filters,
+    persons::{
+        self, Person, PersonChange,
+        pages::{ViewPersonPath, person_not_found},
+    },
+    t,
+};
+
+#[derive(Template)]
+#[template(path = "persons/view.html")]
+struct PersonViewTemplate {
+    person: Person,
+    memberships: Vec<CandidateListMembership>,
+    history: Vec<PersonChange>,
+    election: ElectionConfig,
+    locale: Locale,
+}
+
+pub async fn view_person(
+    ViewPersonPath { id }: ViewPersonPath,
+    context: Context,
+    State(app_state): State<AppState>,
+    DbConnection(mut conn): DbConnection,
+) -> Result<impl IntoResponse, AppError> {
+    let person = persons::repository::get_person(&mut conn, &id)
+        .await?
+        .ok_or(person_not_found(id, context.locale))?;
+
+    let memberships =
+        candidate_lists::repository::list_memberships_for_person(&mut conn, &id).await?;
+    let versions = persons::repository::list_person_versions(&mut conn, &id).await?;
+    let history = person.history(&versions, context.locale);
+
+    Ok(HtmlTemplate(
+        PersonViewTemplate {
+            person,
+            memberships,
+            history,
+            election: app_state.config().election,
+            locale: context.locale,
+        },
+        context,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, response::IntoResponse};
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use crate::{
+        AppState, Context, DbConnection, Locale, candidate_lists, persons,
+        test_utils::{response_body_string, sample_candidate_list, sample_person},
+    };
+
+    #[sqlx::test]
+    async fn view_person_shows_memberships_and_history(pool: PgPool) -> Result<(), sqlx::Error> {
+        let list_id = Uuid::new_v4();
+        let person = sample_person(Uuid::new_v4());
+
+        let mut conn = pool.acquire().await?;
+        candidate_lists::repository::create_candidate_list(
+            &mut conn,
+            &sample_candidate_list(list_id),
+        )
+        .await?;
+        let mut person = persons::repository::create_person(&mut conn, &person).await?;
+        candidate_lists::repository::update_candidate_list_order(&mut conn, &list_id, &[person.id])
+            .await?;
+        person.last_name = "Bakker".to_string();
+        persons::repository::update_person(&mut conn, &person)
+            .await?
+            .expect("saved");
+
+        let response = view_person(
+            ViewPersonPath { id: person.id },
+            Context::new(Locale::En),
+            State(AppState::new_for_tests(pool.clone())),
+            DbConnection(pool.acquire().await?),
+        )
+        .await
+        .unwrap()
+        .into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        assert!(body.contains("Bakker"));
+        assert!(body.contains(&format!("/candidate-lists/{list_id}")));
+        assert!(body.contains("Utrecht"));
+        assert!(body.contains("Jansen"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn view_person_not_found(pool: PgPool) -> Result<(), sqlx::Error> {
+        let result = view_person(
+            ViewPersonPath { id: Uuid::new_v4() },
+            Context::new(Locale::En),
+            State(AppState::new_for_tests(pool.clone())),
+            DbConnection(pool.acquire().await?),
+        )
+        .await;
+
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        Ok(())
+    }
+}
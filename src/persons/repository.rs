@@ -1,9 +1,11 @@
-use sqlx::PgConnection;
+use chrono::{DateTime, Utc};
+use sqlx::{Connection, PgConnection, types::Json};
 use uuid::Uuid;
 
 use crate::{
-    pagination::SortDirection,
-    persons::{Gender, Person, PersonSort},
+    candidate_lists,
+    pagination::{Cursor, Keyset, KeysetColumn, SortDirection, order_by},
+    persons::{DeletedPerson, Gender, Person, PersonFilter, PersonSort, PersonVersion},
 };
 
 pub async fn count_persons(conn: &mut PgConnection) -> Result<i64, sqlx::Error> {
@@ -11,6 +13,7 @@ pub async fn count_persons(conn: &mut PgConnection) -> Result<i64, sqlx::Error>
         r#"
         SELECT COUNT(*) as "count!"
         FROM persons
+        WHERE deleted_at IS NULL
         "#
     )
     .fetch_one(conn)
@@ -48,7 +51,7 @@ pub async fn list_persons_not_on_candidate_list(
             created_at,
             updated_at
         FROM persons
-        WHERE id NOT IN (
+        WHERE deleted_at IS NULL AND id NOT IN (
             SELECT person_id
             FROM candidate_lists_persons
             WHERE candidate_list_id = $1
@@ -63,19 +66,129 @@ pub async fn list_persons_not_on_candidate_list(
     Ok(persons)
 }
 
+pub async fn count_filtered_persons(
+    conn: &mut PgConnection,
+    filter: &PersonFilter,
+) -> Result<i64, sqlx::Error> {
+    let record = sqlx::query!(
+        r#"
+        SELECT COUNT(*) as "count!"
+        FROM persons
+        WHERE deleted_at IS NULL
+            AND ($1::gender IS NULL OR gender = $1)
+            AND ($2::text IS NULL OR locality = $2)
+            AND ($3::text IS NULL OR (
+                EXISTS(
+                    SELECT 1 FROM candidate_lists_persons clp
+                    JOIN candidate_lists cl ON cl.id = clp.candidate_list_id
+                    WHERE clp.person_id = persons.id AND cl.deleted_at IS NULL
+                )
+                = ($3 = 'on_any_list')
+            ))
+            AND ($4::uuid IS NULL OR EXISTS(
+                SELECT 1 FROM candidate_lists_persons clp
+                WHERE clp.person_id = persons.id AND clp.candidate_list_id = $4
+            ))
+            AND (NOT $5::bool OR bsn IS NULL)
+            AND (NOT $6::bool OR date_of_birth IS NULL)
+            AND (NOT $7::bool OR CASE
+                WHEN is_dutch IS FALSE THEN address_line_1 IS NULL
+                ELSE postal_code IS NULL OR house_number IS NULL
+                    OR street_name IS NULL OR locality IS NULL
+            END)
+        "#,
+        filter.gender as Option<Gender>,
+        filter.locality,
+        filter.list_status.as_ref().map(|s| s.as_ref()),
+        filter.candidate_list,
+        filter.missing_bsn,
+        filter.missing_date_of_birth,
+        filter.missing_address,
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(record.count)
+}
+
+/// All distinct localities in use, for populating the locality filter.
+pub async fn list_localities(conn: &mut PgConnection) -> Result<Vec<String>, sqlx::Error> {
+    let records = sqlx::query!(
+        r#"
+        SELECT DISTINCT locality as "locality!"
+        FROM persons
+        WHERE locality IS NOT NULL AND deleted_at IS NULL
+        ORDER BY locality
+        "#
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(records.into_iter().map(|r| r.locality).collect())
+}
+
+/// One page of persons matching the filter.
 pub async fn list_persons(
     conn: &mut PgConnection,
+    filter: &PersonFilter,
     limit: i64,
     offset: i64,
     sort_field: &PersonSort,
     sort_direction: &SortDirection,
 ) -> Result<Vec<Person>, sqlx::Error> {
-    let persons = sqlx::query_as!(
-        Person,
+    sorted_persons(
+        conn,
+        filter,
+        *sort_field,
+        *sort_direction,
+        limit,
+        offset,
+        None,
+    )
+    .await
+}
+
+/// Keyset-paginated variant of [`list_persons`], fetching the rows following the keyset cursor.
+pub async fn list_persons_keyset(
+    conn: &mut PgConnection,
+    filter: &PersonFilter,
+    keyset: &Keyset<PersonSort>,
+) -> Result<Vec<Person>, sqlx::Error> {
+    sorted_persons(
+        conn,
+        filter,
+        keyset.sort,
+        keyset.query_direction(),
+        keyset.limit(),
+        0,
+        keyset.cursor.as_ref(),
+    )
+    .await
+}
+
+/// Query persons matching the filter in the given order, following the keyset cursor if any. A
+/// cursor key that is not a value of the sort column, e.g. from an edited link, restarts the
+/// listing at the top.
+async fn sorted_persons(
+    conn: &mut PgConnection,
+    filter: &PersonFilter,
+    sort: PersonSort,
+    direction: SortDirection,
+    limit: i64,
+    offset: i64,
+    cursor: Option<&Cursor<PersonSort>>,
+) -> Result<Vec<Person>, sqlx::Error> {
+    let cursor = cursor.filter(|cursor| {
+        cursor
+            .key
+            .as_deref()
+            .is_none_or(|key| sort.is_valid_key(key))
+    });
+    let query = format!(
         r#"
         SELECT
             id,
-            gender as "gender?: Gender",
+            gender,
             last_name,
             last_name_prefix,
             first_name,
@@ -95,34 +208,51 @@ pub async fn list_persons(
             created_at,
             updated_at
         FROM persons
-        ORDER BY
-            CASE WHEN $3 = 'last_name' AND $4 = 'asc' THEN last_name END ASC,
-            CASE WHEN $3 = 'last_name' AND $4 = 'desc' THEN last_name END DESC,
-            CASE WHEN $3 = 'first_name' AND $4 = 'asc' THEN first_name END ASC,
-            CASE WHEN $3 = 'first_name' AND $4 = 'desc' THEN first_name END DESC,
-            CASE WHEN $3 = 'initials' AND $4 = 'asc' THEN initials END ASC,
-            CASE WHEN $3 = 'initials' AND $4 = 'desc' THEN initials END DESC,
-            CASE WHEN $3 = 'gender' AND $4 = 'asc' THEN gender END ASC,
-            CASE WHEN $3 = 'gender' AND $4 = 'desc' THEN gender END DESC,
-            CASE WHEN $3 = 'locality' AND $4 = 'asc' THEN locality END ASC,
-            CASE WHEN $3 = 'locality' AND $4 = 'desc' THEN locality END DESC,
-            CASE WHEN $3 = 'created_at' AND $4 = 'asc' THEN created_at END ASC,
-            CASE WHEN $3 = 'created_at' AND $4 = 'desc' THEN created_at END DESC,
-            CASE WHEN $3 = 'updated_at' AND $4 = 'asc' THEN updated_at END ASC,
-            CASE WHEN $3 = 'updated_at' AND $4 = 'desc' THEN updated_at END DESC,
-            id DESC
+        WHERE deleted_at IS NULL
+            AND ($4::gender IS NULL OR gender = $4)
+            AND ($5::text IS NULL OR locality = $5)
+            AND ($6::text IS NULL OR (
+                EXISTS(
+                    SELECT 1 FROM candidate_lists_persons clp
+                    JOIN candidate_lists cl ON cl.id = clp.candidate_list_id
+                    WHERE clp.person_id = persons.id AND cl.deleted_at IS NULL
+                )
+                = ($6 = 'on_any_list')
+            ))
+            AND ($7::uuid IS NULL OR EXISTS(
+                SELECT 1 FROM candidate_lists_persons clp
+                WHERE clp.person_id = persons.id AND clp.candidate_list_id = $7
+            ))
+            AND (NOT $8::bool OR bsn IS NULL)
+            AND (NOT $9::bool OR date_of_birth IS NULL)
+            AND (NOT $10::bool OR CASE
+                WHEN is_dutch IS FALSE THEN address_line_1 IS NULL
+                ELSE postal_code IS NULL OR house_number IS NULL
+                    OR street_name IS NULL OR locality IS NULL
+            END)
+            AND ($2::uuid IS NULL OR {})
+        {}
         LIMIT $1
-        OFFSET $2
+        OFFSET $11
         "#,
-        limit,
-        offset,
-        sort_field.as_ref(),
-        sort_direction.as_ref(),
-    )
-    .fetch_all(conn)
-    .await?;
+        sort.after_cursor(direction),
+        order_by(&sort, &direction),
+    );
 
-    Ok(persons)
+    sqlx::query_as::<_, Person>(&query)
+        .bind(limit)
+        .bind(cursor.map(|cursor| cursor.id))
+        .bind(cursor.and_then(|cursor| cursor.key.as_deref()))
+        .bind(filter.gender)
+        .bind(&filter.locality)
+        .bind(filter.list_status.as_ref().map(|s| s.as_ref()))
+        .bind(filter.candidate_list)
+        .bind(filter.missing_bsn)
+        .bind(filter.missing_date_of_birth)
+        .bind(filter.missing_address)
+        .bind(offset)
+        .fetch_all(conn)
+        .await
 }
 
 pub async fn get_person(
@@ -154,7 +284,7 @@ pub async fn get_person(
             created_at,
             updated_at
         FROM persons
-        WHERE id = $1
+        WHERE id = $1 AND deleted_at IS NULL
         "#,
         person_id,
     )
@@ -164,6 +294,32 @@ pub async fn get_person(
     Ok(person)
 }
 
+/// Previous versions of a person, oldest first.
+pub async fn list_person_versions(
+    conn: &mut PgConnection,
+    person_id: &Uuid,
+) -> Result<Vec<PersonVersion>, sqlx::Error> {
+    let records = sqlx::query!(
+        r#"
+        SELECT changed_at, previous as "previous: Json<Person>"
+        FROM persons_history
+        WHERE person_id = $1
+        ORDER BY changed_at ASC, id ASC
+        "#,
+        person_id,
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(records
+        .into_iter()
+        .map(|record| PersonVersion {
+            changed_at: record.changed_at,
+            previous: record.previous.0,
+        })
+        .collect())
+}
+
 pub async fn create_person(
     conn: &mut PgConnection,
     new_person: &Person,
@@ -241,11 +397,13 @@ pub async fn create_person(
     .await
 }
 
+/// Update a person, provided `updated_person.updated_at` still matches the stored version.
+/// Returns `None` when the person was changed or deleted in the meantime.
 pub async fn update_person(
     conn: &mut PgConnection,
     updated_person: &Person,
-) -> Result<Person, sqlx::Error> {
-    let person = sqlx::query_as!(
+) -> Result<Option<Person>, sqlx::Error> {
+    sqlx::query_as!(
         Person,
         r#"
         UPDATE persons
@@ -268,7 +426,7 @@ pub async fn update_person(
             address_line_1 = $16,
             address_line_2 = $17,
             updated_at = NOW()
-        WHERE id = $18
+        WHERE id = $18 AND updated_at = $19 AND deleted_at IS NULL
         RETURNING
             id,
             gender as "gender?: Gender",
@@ -309,25 +467,151 @@ pub async fn update_person(
         updated_person.address_line_1,
         updated_person.address_line_2,
         updated_person.id,
+        updated_person.updated_at,
     )
-    .fetch_one(conn)
+    .fetch_optional(conn)
+    .await
+}
+
+/// Soft delete a person; they can be restored until purged, see [`restore_person`]. Returns
+/// `false` when the person is still on a candidate list or was already deleted.
+pub async fn remove_person(conn: &mut PgConnection, person_id: &Uuid) -> Result<bool, sqlx::Error> {
+    let removed = sqlx::query!(
+        r#"
+        UPDATE persons
+        SET deleted_at = NOW()
+        WHERE id = $1 AND deleted_at IS NULL AND NOT EXISTS (
+            SELECT 1
+            FROM candidate_lists_persons clp
+            JOIN candidate_lists cl ON cl.id = clp.candidate_list_id
+            WHERE clp.person_id = $1 AND cl.deleted_at IS NULL
+        )
+        "#,
+        person_id,
+    )
+    .execute(conn)
     .await?;
 
-    Ok(person)
+    Ok(removed.rows_affected() > 0)
 }
 
-pub async fn remove_person(conn: &mut PgConnection, person_id: &Uuid) -> Result<(), sqlx::Error> {
-    sqlx::query!(
+/// Undo [`remove_person`]. Returns `false` when there is no deleted person with this id.
+pub async fn restore_person(
+    conn: &mut PgConnection,
+    person_id: &Uuid,
+) -> Result<bool, sqlx::Error> {
+    let restored = sqlx::query!(
         r#"
-        DELETE FROM persons
-        WHERE id = $1
+        UPDATE persons
+        SET deleted_at = NULL
+        WHERE id = $1 AND deleted_at IS NOT NULL
         "#,
         person_id,
     )
     .execute(conn)
     .await?;
 
-    Ok(())
+    Ok(restored.rows_affected() > 0)
+}
+
+/// Soft deleted persons, most recently deleted first.
+pub async fn list_deleted_persons(
+    conn: &mut PgConnection,
+) -> Result<Vec<DeletedPerson>, sqlx::Error> {
+    let records = sqlx::query!(
+        r#"
+        SELECT
+            id,
+            gender as "gender?: Gender",
+            last_name,
+            last_name_prefix,
+            first_name,
+            initials,
+            date_of_birth,
+            bsn,
+            locality,
+            postal_code,
+            house_number,
+            house_number_addition,
+            street_name,
+            is_dutch,
+            custom_country,
+            custom_region,
+            address_line_1,
+            address_line_2,
+            created_at,
+            updated_at,
+            deleted_at as "deleted_at!"
+        FROM persons
+        WHERE deleted_at IS NOT NULL
+        ORDER BY deleted_at DESC, id ASC
+        "#,
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(records
+        .into_iter()
+        .map(|row| DeletedPerson {
+            person: Person {
+                id: row.id,
+                gender: row.gender,
+                last_name: row.last_name,
+                last_name_prefix: row.last_name_prefix,
+                first_name: row.first_name,
+                initials: row.initials,
+                date_of_birth: row.date_of_birth,
+                bsn: row.bsn,
+                locality: row.locality,
+                postal_code: row.postal_code,
+                house_number: row.house_number,
+                house_number_addition: row.house_number_addition,
+                street_name: row.street_name,
+                is_dutch: row.is_dutch,
+                custom_country: row.custom_country,
+                custom_region: row.custom_region,
+                address_line_1: row.address_line_1,
+                address_line_2: row.address_line_2,
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+            },
+            deleted_at: row.deleted_at,
+        })
+        .collect())
+}
+
+/// Permanently delete persons that were soft deleted before `deleted_before`. Persons still
+/// on a (deleted) candidate list are kept, so restoring that list can bring them back.
+/// Returns the number of purged persons.
+pub async fn purge_deleted_persons(
+    conn: &mut PgConnection,
+    deleted_before: DateTime<Utc>,
+) -> Result<u64, sqlx::Error> {
+    let purged = sqlx::query!(
+        r#"
+        DELETE FROM persons
+        WHERE deleted_at < $1
+            AND NOT EXISTS(SELECT 1 FROM candidate_lists_persons clp WHERE clp.person_id = persons.id)
+        "#,
+        deleted_before,
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(purged.rows_affected())
+}
+
+/// Remove a person from all candidate lists and delete them, in a single transaction.
+pub async fn remove_person_from_lists_and_delete(
+    conn: &mut PgConnection,
+    person_id: &Uuid,
+) -> Result<(), sqlx::Error> {
+    let mut tx = conn.begin().await?;
+
+    candidate_lists::repository::remove_person_from_all_lists(&mut tx, person_id).await?;
+    remove_person(&mut tx, person_id).await?;
+
+    tx.commit().await
 }
 
 #[cfg(test)]
@@ -336,9 +620,9 @@ mod tests {
     use sqlx::PgPool;
 
     use crate::{
-        candidate_lists,
-        pagination::SortDirection,
-        persons::PersonSort,
+        Tenant, auth, candidate_lists,
+        pagination::{KeysetRow, Pagination, SortDirection},
+        persons::{ListStatus, PersonSort},
         test_utils::{sample_candidate_list, sample_person, sample_person_with_last_name},
     };
 
@@ -374,27 +658,248 @@ mod tests {
         let total = count_persons(&mut conn).await?;
         assert_eq!(total, 2);
 
-        let persons =
-            list_persons(&mut conn, 10, 0, &PersonSort::LastName, &SortDirection::Asc).await?;
+        let persons = list_persons(
+            &mut conn,
+            &PersonFilter::default(),
+            10,
+            0,
+            &PersonSort::LastName,
+            &SortDirection::Asc,
+        )
+        .await?;
         assert_eq!(persons.len(), 2);
 
         Ok(())
     }
 
+    #[sqlx::test]
+    async fn list_and_count_filtered_persons(pool: PgPool) -> Result<(), sqlx::Error> {
+        let list_id = Uuid::new_v4();
+        let mut on_list = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        on_list.gender = Some(Gender::Female);
+        on_list.bsn = Some("123456782".to_string());
+        let mut not_on_list = sample_person_with_last_name(Uuid::new_v4(), "Bakker");
+        not_on_list.gender = Some(Gender::Male);
+
+        let mut conn = pool.acquire().await?;
+        candidate_lists::repository::create_candidate_list(
+            &mut conn,
+            &sample_candidate_list(list_id),
+        )
+        .await?;
+        create_person(&mut conn, &on_list).await?;
+        create_person(&mut conn, &not_on_list).await?;
+        candidate_lists::repository::update_candidate_list_order(
+            &mut conn,
+            &list_id,
+            &[on_list.id],
+        )
+        .await?;
+
+        let filters = [
+            (
+                PersonFilter {
+                    gender: Some(Gender::Male),
+                    ..Default::default()
+                },
+                not_on_list.id,
+            ),
+            (
+                PersonFilter {
+                    list_status: Some(ListStatus::OnAnyList),
+                    ..Default::default()
+                },
+                on_list.id,
+            ),
+            (
+                PersonFilter {
+                    list_status: Some(ListStatus::OnNoList),
+                    ..Default::default()
+                },
+                not_on_list.id,
+            ),
+            (
+                PersonFilter {
+                    candidate_list: Some(list_id),
+                    ..Default::default()
+                },
+                on_list.id,
+            ),
+            (
+                PersonFilter {
+                    missing_bsn: true,
+                    ..Default::default()
+                },
+                not_on_list.id,
+            ),
+        ];
+
+        for (filter, expected) in filters {
+            assert_eq!(count_filtered_persons(&mut conn, &filter).await?, 1);
+            let persons = list_persons(
+                &mut conn,
+                &filter,
+                10,
+                0,
+                &PersonSort::LastName,
+                &SortDirection::Asc,
+            )
+            .await?;
+            assert_eq!(
+                persons.iter().map(|p| p.id).collect::<Vec<_>>(),
+                vec![expected]
+            );
+        }
+
+        let filter = PersonFilter {
+            missing_address: true,
+            ..Default::default()
+        };
+        assert_eq!(count_filtered_persons(&mut conn, &filter).await?, 0);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn keyset_pages_cover_all_persons_for_every_sort(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        for (i, last_name) in ["Jansen", "Bakker", "Jansen", "de Vries", "Bakker"]
+            .into_iter()
+            .enumerate()
+        {
+            let mut person = sample_person_with_last_name(Uuid::new_v4(), last_name);
+            // mix in NULLs and ties for the nullable sort columns
+            if i % 2 == 0 {
+                person.first_name = None;
+                person.gender = None;
+                person.locality = None;
+            }
+            create_person(&mut conn, &person).await?;
+        }
+
+        let sorts = [
+            PersonSort::LastName,
+            PersonSort::FirstName,
+            PersonSort::Initials,
+            PersonSort::Gender,
+            PersonSort::Locality,
+            PersonSort::CreatedAt,
+            PersonSort::UpdatedAt,
+        ];
+        let filter = PersonFilter::default();
+
+        for sort in sorts {
+            for order in [SortDirection::Asc, SortDirection::Desc] {
+                let all = list_persons_keyset(
+                    &mut conn,
+                    &filter,
+                    &Pagination {
+                        sort,
+                        order,
+                        ..Default::default()
+                    }
+                    .keyset(),
+                )
+                .await?;
+                let all_ids = all.iter().map(|p| p.id).collect::<Vec<_>>();
+                assert_eq!(all_ids.len(), 5);
+
+                // page numbers list persons in the same order
+                let offset = list_persons(&mut conn, &filter, 2, 2, &sort, &order).await?;
+                assert_eq!(
+                    offset.iter().map(|p| p.id).collect::<Vec<_>>(),
+                    all_ids[2..4],
+                    "{sort:?} {order:?}"
+                );
+
+                let mut pages = Vec::new();
+                let mut after = None;
+                loop {
+                    let keyset = Pagination {
+                        per_page: 2,
+                        sort,
+                        order,
+                        after,
+                        ..Default::default()
+                    }
+                    .keyset();
+                    let rows = list_persons_keyset(&mut conn, &filter, &keyset).await?;
+                    let (rows, info) = keyset.finish(rows);
+                    pages.push(rows.iter().map(|p| p.id).collect::<Vec<_>>());
+                    if !info.has_next {
+                        break;
+                    }
+                    after = info.next_cursor;
+                }
+                assert_eq!(pages.concat(), all_ids, "{sort:?} {order:?}");
+
+                // Paging back from the last page returns the page before it.
+                let last = all.last().expect("person").cursor(&sort);
+                let keyset = Pagination {
+                    per_page: 2,
+                    sort,
+                    order,
+                    before: Some(last.encode()),
+                    ..Default::default()
+                }
+                .keyset();
+                let rows = list_persons_keyset(&mut conn, &filter, &keyset).await?;
+                let (rows, _) = keyset.finish(rows);
+                assert_eq!(
+                    rows.iter().map(|p| p.id).collect::<Vec<_>>(),
+                    all_ids[2..4],
+                    "{sort:?} {order:?}"
+                );
+            }
+        }
+
+        // a key that is not a timestamp restarts at the top instead of failing the query
+        let cursor = Cursor {
+            sort: PersonSort::CreatedAt,
+            key: Some("yesterday".to_string()),
+            id: Uuid::new_v4(),
+        };
+        let keyset = Pagination {
+            sort: PersonSort::CreatedAt,
+            after: Some(cursor.encode()),
+            ..Default::default()
+        }
+        .keyset();
+        assert_eq!(
+            list_persons_keyset(&mut conn, &filter, &keyset)
+                .await?
+                .len(),
+            5
+        );
+
+        Ok(())
+    }
+
     #[sqlx::test]
     async fn update_person_overwrites_fields(pool: PgPool) -> Result<(), sqlx::Error> {
         let id = Uuid::new_v4();
-        let mut person = sample_person(id);
+        let person = sample_person(id);
 
         let mut conn = pool.acquire().await?;
-        create_person(&mut conn, &person).await?;
+        let mut person = create_person(&mut conn, &person).await?;
 
         person.last_name = "Updated".to_string();
-        update_person(&mut conn, &person).await?;
+        let saved = update_person(&mut conn, &person).await?.expect("saved");
 
         let updated = get_person(&mut conn, &id).await?.expect("person");
         assert_eq!(updated.last_name, "Updated");
 
+        // a second update based on the original version is rejected
+        person.last_name = "Stale".to_string();
+        assert!(update_person(&mut conn, &person).await?.is_none());
+        assert_eq!(
+            get_person(&mut conn, &id).await?.expect("person").last_name,
+            "Updated"
+        );
+        assert_eq!(saved.updated_at, updated.updated_at);
+
         Ok(())
     }
 
@@ -405,7 +910,8 @@ mod tests {
 
         let mut conn = pool.acquire().await?;
         create_person(&mut conn, &person).await?;
-        remove_person(&mut conn, &id).await?;
+        assert!(remove_person(&mut conn, &id).await?);
+        assert!(!remove_person(&mut conn, &id).await?);
 
         let missing = get_person(&mut conn, &id).await?;
         assert!(missing.is_none());
@@ -413,6 +919,27 @@ mod tests {
         Ok(())
     }
 
+    #[sqlx::test]
+    async fn remove_person_keeps_candidates(pool: PgPool) -> Result<(), sqlx::Error> {
+        let list_id = Uuid::new_v4();
+        let person = sample_person(Uuid::new_v4());
+
+        let mut conn = pool.acquire().await?;
+        candidate_lists::repository::create_candidate_list(
+            &mut conn,
+            &sample_candidate_list(list_id),
+        )
+        .await?;
+        create_person(&mut conn, &person).await?;
+        candidate_lists::repository::update_candidate_list_order(&mut conn, &list_id, &[person.id])
+            .await?;
+
+        assert!(!remove_person(&mut conn, &person.id).await?);
+        assert!(get_person(&mut conn, &person.id).await?.is_some());
+
+        Ok(())
+    }
+
     #[sqlx::test]
     async fn excludes_persons_on_candidate_list(pool: PgPool) -> Result<(), sqlx::Error> {
         let list_id = Uuid::new_v4();
@@ -437,4 +964,81 @@ mod tests {
 
         Ok(())
     }
+
+    #[sqlx::test]
+    async fn persons_are_isolated_between_political_groups(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let group_a = auth::repository::create_political_group(&mut conn, "Lijst A").await?;
+        let group_b = auth::repository::create_political_group(&mut conn, "Lijst B").await?;
+
+        Tenant::PoliticalGroup(group_a).scope(&mut conn).await?;
+        let person = create_person(&mut conn, &sample_person(Uuid::new_v4())).await?;
+        let updated = Person {
+            last_name: "Bakker".to_string(),
+            ..person.clone()
+        };
+        update_person(&mut conn, &updated).await?.expect("person");
+        assert_eq!(count_persons(&mut conn).await?, 1);
+
+        Tenant::PoliticalGroup(group_b).scope(&mut conn).await?;
+        let filter = PersonFilter::default();
+        assert_eq!(count_persons(&mut conn).await?, 0);
+        assert_eq!(count_filtered_persons(&mut conn, &filter).await?, 0);
+        assert!(list_localities(&mut conn).await?.is_empty());
+        let persons = list_persons(
+            &mut conn,
+            &filter,
+            10,
+            0,
+            &PersonSort::LastName,
+            &SortDirection::Asc,
+        )
+        .await?;
+        assert!(persons.is_empty());
+        assert!(get_person(&mut conn, &person.id).await?.is_none());
+        assert!(
+            list_person_versions(&mut conn, &person.id)
+                .await?
+                .is_empty()
+        );
+
+        // changes to persons of another group do not find them
+        let hijacked = Person {
+            last_name: "Smit".to_string(),
+            ..person.clone()
+        };
+        assert!(update_person(&mut conn, &hijacked).await?.is_none());
+        remove_person(&mut conn, &person.id).await?;
+
+        // nor can persons be created for another group
+        let foreign = sqlx::query!(
+            "INSERT INTO persons (id, last_name, initials, political_group_id) VALUES ($1, 'Smit', 'S.', $2)",
+            Uuid::new_v4(),
+            group_a,
+        )
+        .execute(&mut *conn)
+        .await;
+        assert!(foreign.is_err());
+
+        Tenant::None.scope(&mut conn).await?;
+        assert_eq!(count_persons(&mut conn).await?, 0);
+        assert!(
+            create_person(&mut conn, &sample_person(Uuid::new_v4()))
+                .await
+                .is_err()
+        );
+
+        Tenant::AllPoliticalGroups.scope(&mut conn).await?;
+        let loaded = get_person(&mut conn, &person.id).await?.expect("person");
+        assert_eq!(loaded.last_name, "Bakker");
+        assert_eq!(list_person_versions(&mut conn, &person.id).await?.len(), 1);
+        assert!(update_person(&mut conn, &hijacked).await?.is_none());
+
+        Tenant::reset(&mut conn).await?;
+        assert!(list_deleted_persons(&mut conn).await?.is_empty());
+
+        Ok(())
+    }
 }
@@ -6,33 +6,68 @@ use crate::{CsrfToken, form::*, persons::Person};
 
 #[derive(Default, Serialize, Deserialize, Clone, Debug, Validate)]
 #[validate(target = "Person", build = "AddressForm::build_address")]
+#[serde(default)]
 pub struct AddressForm {
-    #[validate(with = "validate_length(2, 255)", optional)]
+    #[validate(
+        with = "validate_length(2, 255)",
+        optional,
+        when(is_dutch = "true"),
+        required_if(is_dutch = "true")
+    )]
     pub locality: String,
-    #[validate(with = "validate_length(2, 16)", optional)]
+    #[validate(
+        with = "validate_postal_code()",
+        optional,
+        when(is_dutch = "true"),
+        required_if(is_dutch = "true")
+    )]
     pub postal_code: String,
-    #[validate(with = "validate_length(1, 16)", optional)]
+    #[validate(
+        with = "validate_house_number()",
+        optional,
+        when(is_dutch = "true"),
+        required_if(is_dutch = "true")
+    )]
     pub house_number: String,
-    #[validate(with = "validate_length(1, 16)", optional)]
+    #[validate(
+        with = "validate_length(1, 16)",
+        with = "validate_house_number_addition()",
+        optional,
+        when(is_dutch = "true")
+    )]
     pub house_number_addition: String,
-    #[validate(with = "validate_length(2, 255)", optional)]
+    #[validate(with = "validate_length(2, 255)", optional, when(is_dutch = "true"))]
     pub street_name: String,
-    #[validate(with = "validate_length(2, 255)", optional)]
+    #[validate(
+        with = "validate_length(2, 255)",
+        optional,
+        when(is_dutch = "false"),
+        required_unless(is_dutch = "true")
+    )]
     pub custom_country: String,
-    #[validate(with = "validate_length(2, 255)", optional)]
+    #[validate(with = "validate_length(2, 255)", optional, when(is_dutch = "false"))]
     pub custom_region: String,
-    #[validate(with = "validate_length(2, 255)", optional)]
+    #[validate(
+        with = "validate_length(2, 255)",
+        optional,
+        when(is_dutch = "false"),
+        required_unless(is_dutch = "true")
+    )]
     pub address_line_1: String,
-    #[validate(with = "validate_length(2, 255)", optional)]
+    #[validate(with = "validate_length(2, 255)", optional, when(is_dutch = "false"))]
     pub address_line_2: String,
     #[validate(parse = "bool")]
     pub is_dutch: String,
+    /// Version of the person the form was loaded from.
+    #[validate(skip)]
+    pub version: String,
     #[validate(csrf)]
     pub csrf_token: TokenValue,
 }
 
 impl From<Person> for AddressForm {
     fn from(person: Person) -> Self {
+        let version = person.version();
         AddressForm {
             locality: person.locality.unwrap_or_default(),
             postal_code: person.postal_code.unwrap_or_default(),
@@ -48,6 +83,7 @@ impl From<Person> for AddressForm {
                 .as_ref()
                 .map(bool::to_string)
                 .unwrap_or("true".to_owned()),
+            version,
             csrf_token: Default::default(),
         }
     }
@@ -63,19 +99,26 @@ impl WithCsrfToken for AddressForm {
 }
 
 impl AddressForm {
-    fn build_address(validated: AddressFormValidated, current: Option<&Person>) -> Person {
+    /// A person has either a Dutch or an international address; the fields of the other kind of
+    /// address, hidden in the form, are cleared.
+    pub(super) fn build_address(
+        validated: AddressFormValidated,
+        current: Option<&Person>,
+    ) -> Person {
         if let Some(current_person) = current {
+            let dutch = validated.is_dutch;
+            let international = !validated.is_dutch;
             Person {
-                locality: validated.locality,
-                postal_code: validated.postal_code,
-                house_number: validated.house_number,
-                house_number_addition: validated.house_number_addition,
-                street_name: validated.street_name,
+                locality: validated.locality.filter(|_| dutch),
+                postal_code: validated.postal_code.filter(|_| dutch),
+                house_number: validated.house_number.filter(|_| dutch),
+                house_number_addition: validated.house_number_addition.filter(|_| dutch),
+                street_name: validated.street_name.filter(|_| dutch),
                 is_dutch: Some(validated.is_dutch),
-                custom_country: validated.custom_country,
-                custom_region: validated.custom_region,
-                address_line_1: validated.address_line_1,
-                address_line_2: validated.address_line_2,
+                custom_country: validated.custom_country.filter(|_| international),
+                custom_region: validated.custom_region.filter(|_| international),
+                address_line_1: validated.address_line_1.filter(|_| international),
+                address_line_2: validated.address_line_2.filter(|_| international),
                 ..current_person.clone()
             }
         } else {
@@ -83,3 +126,140 @@ impl AddressForm {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    use crate::{
+        CsrfTokens,
+        test_utils::{sample_address_form, sample_person},
+    };
+
+    #[test]
+    fn validates_dutch_addresses_only() {
+        let csrf_tokens = CsrfTokens::default();
+        let person = sample_person(Uuid::new_v4());
+
+        let form = AddressForm {
+            postal_code: "1234ab".to_string(),
+            house_number: "010".to_string(),
+            ..sample_address_form(&csrf_tokens.issue().value)
+        };
+        let updated = form.validate(Some(&person), &csrf_tokens).unwrap();
+        assert_eq!(updated.postal_code, Some("1234 AB".to_string()));
+        assert_eq!(updated.house_number, Some("10".to_string()));
+
+        let form = AddressForm {
+            postal_code: "XYZ".to_string(),
+            house_number: "10a".to_string(),
+            house_number_addition: "A/B".to_string(),
+            ..sample_address_form(&csrf_tokens.issue().value)
+        };
+        let errors = form
+            .validate(Some(&person), &csrf_tokens)
+            .unwrap_err()
+            .errors()
+            .clone();
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    "postal_code".to_string(),
+                    ValidationError::InvalidPostalCode
+                ),
+                (
+                    "house_number".to_string(),
+                    ValidationError::InvalidHouseNumber(99999)
+                ),
+                (
+                    "house_number_addition".to_string(),
+                    ValidationError::InvalidHouseNumberAddition
+                ),
+            ]
+        );
+
+        let form = AddressForm {
+            is_dutch: "false".to_string(),
+            postal_code: "SW1A 1AA".to_string(),
+            address_line_1: "10 Downing Street".to_string(),
+            custom_country: "United Kingdom".to_string(),
+            ..sample_address_form(&csrf_tokens.issue().value)
+        };
+        assert!(form.validate(Some(&person), &csrf_tokens).is_ok());
+    }
+
+    #[test]
+    fn requires_the_fields_of_the_kind_of_address() {
+        let csrf_tokens = CsrfTokens::default();
+        let person = sample_person(Uuid::new_v4());
+
+        let form = AddressForm {
+            postal_code: String::new(),
+            locality: " ".to_string(),
+            ..sample_address_form(&csrf_tokens.issue().value)
+        };
+        let errors = form
+            .validate(Some(&person), &csrf_tokens)
+            .unwrap_err()
+            .errors()
+            .clone();
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    "locality".to_string(),
+                    ValidationError::ValueShouldNotBeEmpty
+                ),
+                (
+                    "postal_code".to_string(),
+                    ValidationError::ValueShouldNotBeEmpty
+                ),
+            ]
+        );
+
+        let form = AddressForm {
+            is_dutch: "false".to_string(),
+            ..sample_address_form(&csrf_tokens.issue().value)
+        };
+        let errors = form
+            .validate(Some(&person), &csrf_tokens)
+            .unwrap_err()
+            .errors()
+            .clone();
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    "custom_country".to_string(),
+                    ValidationError::ValueShouldNotBeEmpty
+                ),
+                (
+                    "address_line_1".to_string(),
+                    ValidationError::ValueShouldNotBeEmpty
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_only_the_kind_of_address_chosen() {
+        let csrf_tokens = CsrfTokens::default();
+        let person = sample_person(Uuid::new_v4());
+
+        let form = AddressForm {
+            is_dutch: "false".to_string(),
+            custom_country: "België".to_string(),
+            address_line_1: "Grote Markt 1".to_string(),
+            // left behind in the hidden Dutch address fields
+            postal_code: "XYZ".to_string(),
+            ..sample_address_form(&csrf_tokens.issue().value)
+        };
+        let updated = form.validate(Some(&person), &csrf_tokens).unwrap();
+        assert_eq!(updated.is_dutch, Some(false));
+        assert_eq!(updated.custom_country, Some("België".to_string()));
+        assert_eq!(updated.postal_code, None);
+        assert_eq!(updated.locality, None);
+    }
+}
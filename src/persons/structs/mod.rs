@@ -1,11 +1,17 @@
 mod address_form;
 mod gender;
 mod person;
+mod person_filter;
 mod person_form;
+mod person_history;
 mod person_sort;
+mod register_adoption_form;
 
 pub use address_form::AddressForm;
 pub use gender::Gender;
-pub use person::Person;
+pub use person::{DeletedPerson, Person};
+pub use person_filter::{ListStatus, PersonFilter};
 pub use person_form::PersonForm;
+pub use person_history::{FieldChange, PersonChange, PersonVersion};
 pub use person_sort::PersonSort;
+pub use register_adoption_form::RegisterAdoptionForm;
@@ -1,11 +1,16 @@
 use chrono::{DateTime, NaiveDate};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use sqlx::types::chrono::Utc;
 use uuid::Uuid;
 
-use crate::{constants::DEFAULT_DATE_TIME_FORMAT, persons::Gender, t};
+use crate::{
+    constants::{DEFAULT_DATE_FORMAT, DEFAULT_DATE_TIME_FORMAT},
+    form::version_of,
+    persons::Gender,
+    t,
+};
 
-#[derive(Default, Debug, Serialize, Clone, sqlx::FromRow)]
+#[derive(Default, Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
 pub struct Person {
     pub id: Uuid,
     pub last_name: String,
@@ -29,6 +34,19 @@ pub struct Person {
     pub updated_at: DateTime<Utc>,
 }
 
+/// A soft deleted person, waiting to be restored or purged.
+#[derive(Debug, Clone)]
+pub struct DeletedPerson {
+    pub person: Person,
+    pub deleted_at: DateTime<Utc>,
+}
+
+impl DeletedPerson {
+    pub fn deleted(&self) -> String {
+        self.deleted_at.format(DEFAULT_DATE_TIME_FORMAT).to_string()
+    }
+}
+
 impl Person {
     /// Returns e.g. "van Dijk"
     pub fn last_name_with_prefix(&self) -> String {
@@ -64,6 +82,17 @@ impl Person {
         self.updated_at.format(DEFAULT_DATE_TIME_FORMAT).to_string()
     }
 
+    /// Version token used to reject updates based on an outdated copy of this person.
+    pub fn version(&self) -> String {
+        version_of(&self.updated_at)
+    }
+
+    pub fn date_of_birth_display(&self) -> String {
+        self.date_of_birth
+            .map(|d| d.format(DEFAULT_DATE_FORMAT).to_string())
+            .unwrap_or_default()
+    }
+
     pub fn first_name_display(&self) -> String {
         self.first_name.clone().unwrap_or_default()
     }
@@ -77,22 +106,4 @@ impl Person {
             })
             .unwrap_or(&["", ""])
     }
-
-    /// Make sure a person has either a Dutch address or an international address, but not both
-    pub fn normalize_address(&mut self) {
-        if self.is_dutch.is_none_or(|d| d) {
-            // remove international address
-            self.address_line_1 = None;
-            self.address_line_2 = None;
-            self.custom_region = None;
-            self.custom_country = None;
-        } else {
-            // remove Dutch address
-            self.postal_code = None;
-            self.house_number = None;
-            self.house_number_addition = None;
-            self.street_name = None;
-            self.locality = None;
-        }
-    }
 }
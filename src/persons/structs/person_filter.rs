@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+use strum::{AsRefStr, EnumString};
+use uuid::Uuid;
+
+use crate::{pagination::empty_as_none, persons::Gender};
+
+/// Whether a person appears on any candidate list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AsRefStr, EnumString, Serialize, Deserialize)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ListStatus {
+    OnAnyList,
+    OnNoList,
+}
+
+/// Filter query parameters for the persons overview.
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersonFilter {
+    #[serde(deserialize_with = "empty_as_none")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gender: Option<Gender>,
+    #[serde(deserialize_with = "empty_as_none")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locality: Option<String>,
+    #[serde(deserialize_with = "empty_as_none")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub list_status: Option<ListStatus>,
+    /// Only show persons on this specific candidate list.
+    #[serde(deserialize_with = "empty_as_none")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub candidate_list: Option<Uuid>,
+    #[serde(skip_serializing_if = "is_false")]
+    pub missing_bsn: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    pub missing_date_of_birth: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    pub missing_address: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+impl PersonFilter {
+    /// Whether any filter is set.
+    pub fn is_active(&self) -> bool {
+        *self != Self::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_query_string() {
+        let filter = PersonFilter {
+            gender: Some(Gender::Female),
+            locality: Some("Juinen".to_string()),
+            list_status: Some(ListStatus::OnNoList),
+            missing_bsn: true,
+            ..Default::default()
+        };
+
+        let query = serde_urlencoded::to_string(&filter).unwrap();
+        assert_eq!(
+            query,
+            "gender=female&locality=Juinen&list_status=on_no_list&missing_bsn=true"
+        );
+
+        let parsed: PersonFilter = serde_urlencoded::from_str(&query).unwrap();
+        assert_eq!(parsed, filter);
+        assert!(parsed.is_active());
+    }
+
+    #[test]
+    fn empty_form_fields_are_ignored() {
+        let parsed: PersonFilter =
+            serde_urlencoded::from_str("gender=&locality=&list_status=&candidate_list=").unwrap();
+
+        assert_eq!(parsed, PersonFilter::default());
+        assert!(!parsed.is_active());
+    }
+}
@@ -40,12 +40,16 @@ pub struct PersonForm {
     pub date_of_birth: String,
     #[validate(with = "validate_eleven_check()", optional)]
     pub bsn: String,
+    /// Version of the person the form was loaded from, empty when creating a person.
+    #[validate(skip)]
+    pub version: String,
     #[validate(csrf)]
     pub csrf_token: TokenValue,
 }
 
 impl From<Person> for PersonForm {
     fn from(person: Person) -> Self {
+        let version = person.version();
         PersonForm {
             gender: person.gender.map(|g| g.to_string()).unwrap_or_default(),
             last_name: person.last_name,
@@ -57,6 +61,7 @@ impl From<Person> for PersonForm {
                 .map(|d| d.format(DEFAULT_DATE_FORMAT).to_string())
                 .unwrap_or_default(),
             bsn: person.bsn.unwrap_or_default(),
+            version,
             csrf_token: Default::default(),
         }
     }
@@ -113,15 +118,54 @@ impl PersonForm {
 
 #[cfg(test)]
 mod tests {
+    use axum::{
+        body::Body,
+        extract::FromRequest,
+        http::{Request, header::CONTENT_TYPE},
+    };
+    use axum_extra::extract::Form;
     use chrono::TimeZone;
+    use std::collections::HashSet;
 
     use super::*;
     use crate::{
         CsrfTokens,
         form::{Validate, ValidationError},
+        persons::AddressForm,
+        test_utils::{sample_address_form, sample_person_form},
     };
     use chrono::NaiveDate;
 
+    /// A new person with their address, validated as one form.
+    #[derive(Default, Serialize, Deserialize, Clone, Debug, Validate)]
+    #[validate(target = "Person", build = "PersonWithAddressForm::build")]
+    struct PersonWithAddressForm {
+        #[validate(nested)]
+        #[serde(flatten)]
+        person: PersonForm,
+        #[validate(nested)]
+        #[serde(flatten)]
+        address: AddressForm,
+        #[validate(csrf)]
+        csrf_token: TokenValue,
+    }
+
+    impl WithCsrfToken for PersonWithAddressForm {
+        fn with_csrf_token(self, csrf_token: CsrfToken) -> Self {
+            PersonWithAddressForm {
+                csrf_token: csrf_token.value,
+                ..self
+            }
+        }
+    }
+
+    impl PersonWithAddressForm {
+        fn build(validated: PersonWithAddressFormValidated, _: Option<&Person>) -> Person {
+            let person = PersonForm::build_person(validated.person, None);
+            AddressForm::build_address(validated.address, Some(&person))
+        }
+    }
+
     fn base_person() -> Person {
         let timestamp = chrono::Utc
             .with_ymd_and_hms(2024, 5, 6, 7, 8, 9)
@@ -164,6 +208,7 @@ mod tests {
             initials: "E.D.".to_string(),
             date_of_birth: "01-02-2020".to_string(),
             bsn: "".to_string(),
+            version: String::new(),
             csrf_token: tokens.issue().value,
         };
 
@@ -198,6 +243,7 @@ mod tests {
             initials: "jd".to_string(),
             date_of_birth: "2020/01/01".to_string(),
             bsn: "".to_string(),
+            version: String::new(),
             csrf_token: tokens.issue().value,
         };
 
@@ -228,6 +274,83 @@ mod tests {
         );
     }
 
+    #[test]
+    fn validates_person_and_address_as_one_form() {
+        let tokens = CsrfTokens::default();
+        let form = PersonWithAddressForm {
+            person: sample_person_form(&TokenValue::default()),
+            address: sample_address_form(&TokenValue::default()),
+            csrf_token: tokens.issue().value,
+        };
+
+        let person = form.validate(None, &tokens).unwrap();
+        assert_eq!(person.last_name, "Jansen");
+        assert_eq!(person.postal_code, Some("1234 AB".to_string()));
+        assert_eq!(person.street_name, Some("Stationsstraat".to_string()));
+
+        // errors of both sub-forms are reported under their own field names
+        let form = PersonWithAddressForm {
+            person: PersonForm {
+                initials: "jd".to_string(),
+                ..sample_person_form(&TokenValue::default())
+            },
+            address: AddressForm {
+                postal_code: String::new(),
+                ..sample_address_form(&TokenValue::default())
+            },
+            csrf_token: tokens.issue().value,
+        };
+        let Err(data) = form.validate(None, &tokens) else {
+            panic!("expected validation errors");
+        };
+        assert_eq!(
+            data.errors(),
+            &vec![
+                ("initials".to_string(), ValidationError::InvalidValue),
+                (
+                    "postal_code".to_string(),
+                    ValidationError::ValueShouldNotBeEmpty
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn nested_forms_are_posted_as_one_form() {
+        let tokens = CsrfTokens::default();
+        let form = PersonWithAddressForm {
+            person: sample_person_form(&TokenValue::default()),
+            address: sample_address_form(&TokenValue::default()),
+            csrf_token: tokens.issue().value,
+        };
+
+        // the fields of both sub-forms are posted under their own names, every name once like a
+        // browser does; the token of the outer form comes last
+        let serialized = serde_urlencoded::to_string(&form).unwrap();
+        let mut names = HashSet::new();
+        let mut pairs = serialized
+            .rsplit('&')
+            .filter(|pair| names.insert(pair.split('=').next()))
+            .collect::<Vec<_>>();
+        pairs.reverse();
+        let body = pairs.join("&");
+        assert!(body.contains("last_name=Jansen"));
+        assert!(body.contains("postal_code=1234"));
+
+        let request = Request::builder()
+            .method("POST")
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from(body))
+            .unwrap();
+        let Form(posted) = Form::<PersonWithAddressForm>::from_request(request, &())
+            .await
+            .unwrap();
+
+        let person = posted.validate(None, &tokens).unwrap();
+        assert_eq!(person.last_name, "Jansen");
+        assert_eq!(person.postal_code, Some("1234 AB".to_string()));
+    }
+
     #[test]
     fn display_helpers_behave_correctly() {
         let mut person = base_person();
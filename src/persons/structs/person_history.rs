@@ -0,0 +1,237 @@
+use chrono::DateTime;
+use sqlx::types::chrono::Utc;
+
+use crate::{
+    Locale,
+    constants::DEFAULT_DATE_TIME_FORMAT,
+    persons::{Gender, Person},
+    t,
+};
+
+/// Version of a person as it was before an update, recorded by the `persons_history` trigger.
+#[derive(Debug, Clone)]
+pub struct PersonVersion {
+    pub changed_at: DateTime<Utc>,
+    pub previous: Person,
+}
+
+/// A single field that changed between two versions of a person.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    /// Translation of the field label.
+    pub field: &'static [&'static str],
+    pub old: String,
+    pub new: String,
+}
+
+/// All field changes made to a person at one moment.
+#[derive(Debug, Clone)]
+pub struct PersonChange {
+    pub changed_at: DateTime<Utc>,
+    pub fields: Vec<FieldChange>,
+}
+
+impl PersonChange {
+    pub fn changed(&self) -> String {
+        self.changed_at.format(DEFAULT_DATE_TIME_FORMAT).to_string()
+    }
+}
+
+fn display_gender(gender: Option<Gender>, locale: Locale) -> String {
+    match gender {
+        Some(Gender::Male) => t!("gender.male", locale),
+        Some(Gender::Female) => t!("gender.female", locale),
+        Some(Gender::X) => t!("gender.x", locale),
+        None => String::new(),
+    }
+}
+
+fn display_is_dutch(is_dutch: Option<bool>, locale: Locale) -> String {
+    match is_dutch {
+        Some(true) => t!("person.fields.netherlands", locale),
+        Some(false) => t!("person.fields.elsewhere", locale),
+        None => String::new(),
+    }
+}
+
+impl Person {
+    /// Fields that differ between `previous` and this version, with display values.
+    pub fn changes_since(&self, previous: &Person, locale: Locale) -> Vec<FieldChange> {
+        let text = |value: &Option<String>| value.clone().unwrap_or_default();
+
+        let fields: [(&'static [&'static str], String, String); 17] = [
+            (
+                t!("person.fields.initials"),
+                previous.initials.clone(),
+                self.initials.clone(),
+            ),
+            (
+                t!("person.fields.last_name_prefix"),
+                text(&previous.last_name_prefix),
+                text(&self.last_name_prefix),
+            ),
+            (
+                t!("person.fields.last_name"),
+                previous.last_name.clone(),
+                self.last_name.clone(),
+            ),
+            (
+                t!("person.fields.first_name"),
+                text(&previous.first_name),
+                text(&self.first_name),
+            ),
+            (
+                t!("person.fields.gender"),
+                display_gender(previous.gender, locale),
+                display_gender(self.gender, locale),
+            ),
+            (
+                t!("person.fields.date_of_birth"),
+                previous.date_of_birth_display(),
+                self.date_of_birth_display(),
+            ),
+            (
+                t!("person.fields.bsn"),
+                text(&previous.bsn),
+                text(&self.bsn),
+            ),
+            (
+                t!("person.fields.country"),
+                display_is_dutch(previous.is_dutch, locale),
+                display_is_dutch(self.is_dutch, locale),
+            ),
+            (
+                t!("person.fields.postal_code"),
+                text(&previous.postal_code),
+                text(&self.postal_code),
+            ),
+            (
+                t!("person.fields.house_number"),
+                text(&previous.house_number),
+                text(&self.house_number),
+            ),
+            (
+                t!("person.fields.house_number_addition"),
+                text(&previous.house_number_addition),
+                text(&self.house_number_addition),
+            ),
+            (
+                t!("person.fields.street_name"),
+                text(&previous.street_name),
+                text(&self.street_name),
+            ),
+            (
+                t!("person.fields.locality"),
+                text(&previous.locality),
+                text(&self.locality),
+            ),
+            (
+                t!("person.fields.address_line_1"),
+                text(&previous.address_line_1),
+                text(&self.address_line_1),
+            ),
+            (
+                t!("person.fields.address_line_2"),
+                text(&previous.address_line_2),
+                text(&self.address_line_2),
+            ),
+            (
+                t!("person.fields.custom_region"),
+                text(&previous.custom_region),
+                text(&self.custom_region),
+            ),
+            (
+                t!("person.fields.custom_country"),
+                text(&previous.custom_country),
+                text(&self.custom_country),
+            ),
+        ];
+
+        fields
+            .into_iter()
+            .filter(|(_, old, new)| old != new)
+            .map(|(field, old, new)| FieldChange { field, old, new })
+            .collect()
+    }
+
+    /// Pair each recorded version (oldest first) with the version that replaced it, newest first.
+    pub fn history(&self, versions: &[PersonVersion], locale: Locale) -> Vec<PersonChange> {
+        let newer = versions
+            .iter()
+            .skip(1)
+            .map(|version| &version.previous)
+            .chain(std::iter::once(self));
+
+        let mut changes = versions
+            .iter()
+            .zip(newer)
+            .map(|(version, newer)| PersonChange {
+                changed_at: version.changed_at,
+                fields: newer.changes_since(&version.previous, locale),
+            })
+            .filter(|change| !change.fields.is_empty())
+            .collect::<Vec<_>>();
+        changes.reverse();
+
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    use crate::test_utils::sample_person;
+
+    #[test]
+    fn lists_changed_fields_newest_first() {
+        let first = sample_person(Uuid::new_v4());
+        let second = Person {
+            last_name: "Bakker".to_string(),
+            ..first.clone()
+        };
+        let current = Person {
+            gender: Some(Gender::X),
+            locality: None,
+            ..second.clone()
+        };
+        let versions = [
+            PersonVersion {
+                changed_at: Utc::now(),
+                previous: first,
+            },
+            PersonVersion {
+                changed_at: Utc::now(),
+                previous: second,
+            },
+        ];
+
+        let history = current.history(&versions, Locale::En);
+
+        assert_eq!(history.len(), 2);
+        assert_eq!(
+            history[0].fields,
+            vec![
+                FieldChange {
+                    field: t!("person.fields.gender"),
+                    old: "Female".to_string(),
+                    new: "X".to_string(),
+                },
+                FieldChange {
+                    field: t!("person.fields.locality"),
+                    old: "Juinen".to_string(),
+                    new: String::new(),
+                },
+            ]
+        );
+        assert_eq!(
+            history[1].fields,
+            vec![FieldChange {
+                field: t!("person.fields.last_name"),
+                old: "Jansen".to_string(),
+                new: "Bakker".to_string(),
+            }]
+        );
+    }
+}
@@ -1,6 +1,12 @@
+use chrono::{DateTime, SecondsFormat, Utc};
 use serde::{Deserialize, Serialize};
 use strum::AsRefStr;
 
+use crate::{
+    pagination::{Cursor, KeysetColumn, KeysetRow, SortColumn, SortDirection},
+    persons::{Gender, Person},
+};
+
 #[derive(Default, Clone, Copy, Debug, PartialEq, Eq, AsRefStr, Serialize, Deserialize)]
 #[strum(serialize_all = "snake_case")]
 #[serde(rename_all = "snake_case")]
@@ -14,3 +20,111 @@ pub enum PersonSort {
     CreatedAt,
     UpdatedAt,
 }
+
+impl SortColumn for PersonSort {
+    /// Matches the keyset indexes on `(column, id)`, which keep NULL values last.
+    fn order_by(&self, direction: SortDirection) -> &'static str {
+        match (self, direction) {
+            (PersonSort::LastName, SortDirection::Asc) => "last_name ASC, id ASC",
+            (PersonSort::LastName, SortDirection::Desc) => "last_name DESC, id DESC",
+            (PersonSort::FirstName, SortDirection::Asc) => "first_name ASC, id ASC",
+            (PersonSort::FirstName, SortDirection::Desc) => "first_name DESC, id DESC",
+            (PersonSort::Initials, SortDirection::Asc) => "initials ASC, id ASC",
+            (PersonSort::Initials, SortDirection::Desc) => "initials DESC, id DESC",
+            (PersonSort::Gender, SortDirection::Asc) => "gender ASC, id ASC",
+            (PersonSort::Gender, SortDirection::Desc) => "gender DESC, id DESC",
+            (PersonSort::Locality, SortDirection::Asc) => "locality ASC, id ASC",
+            (PersonSort::Locality, SortDirection::Desc) => "locality DESC, id DESC",
+            (PersonSort::CreatedAt, SortDirection::Asc) => "created_at ASC, id ASC",
+            (PersonSort::CreatedAt, SortDirection::Desc) => "created_at DESC, id DESC",
+            (PersonSort::UpdatedAt, SortDirection::Asc) => "updated_at ASC, id ASC",
+            (PersonSort::UpdatedAt, SortDirection::Desc) => "updated_at DESC, id DESC",
+        }
+    }
+}
+
+impl KeysetColumn for PersonSort {
+    /// Compares `(column, id)` with the cursor as a row, so the keyset index of the column can be
+    /// used; NULL sort keys come last in ascending and first in descending order.
+    fn after_cursor(&self, direction: SortDirection) -> &'static str {
+        match (self, direction) {
+            (PersonSort::LastName, SortDirection::Asc) => "(last_name, id) > ($3::text, $2)",
+            (PersonSort::LastName, SortDirection::Desc) => "(last_name, id) < ($3::text, $2)",
+            (PersonSort::FirstName, SortDirection::Asc) => {
+                "(first_name, id) > ($3::text, $2)
+                    OR first_name IS NULL AND ($3 IS NOT NULL OR id > $2)"
+            }
+            (PersonSort::FirstName, SortDirection::Desc) => {
+                "(first_name, id) < ($3::text, $2)
+                    OR $3 IS NULL AND (first_name IS NOT NULL OR id < $2)"
+            }
+            (PersonSort::Initials, SortDirection::Asc) => "(initials, id) > ($3::text, $2)",
+            (PersonSort::Initials, SortDirection::Desc) => "(initials, id) < ($3::text, $2)",
+            (PersonSort::Gender, SortDirection::Asc) => {
+                "(gender, id) > ($3::gender, $2)
+                    OR gender IS NULL AND ($3 IS NOT NULL OR id > $2)"
+            }
+            (PersonSort::Gender, SortDirection::Desc) => {
+                "(gender, id) < ($3::gender, $2)
+                    OR $3 IS NULL AND (gender IS NOT NULL OR id < $2)"
+            }
+            (PersonSort::Locality, SortDirection::Asc) => {
+                "(locality, id) > ($3::text, $2)
+                    OR locality IS NULL AND ($3 IS NOT NULL OR id > $2)"
+            }
+            (PersonSort::Locality, SortDirection::Desc) => {
+                "(locality, id) < ($3::text, $2)
+                    OR $3 IS NULL AND (locality IS NOT NULL OR id < $2)"
+            }
+            (PersonSort::CreatedAt, SortDirection::Asc) => {
+                "(created_at, id) > ($3::timestamptz, $2)"
+            }
+            (PersonSort::CreatedAt, SortDirection::Desc) => {
+                "(created_at, id) < ($3::timestamptz, $2)"
+            }
+            (PersonSort::UpdatedAt, SortDirection::Asc) => {
+                "(updated_at, id) > ($3::timestamptz, $2)"
+            }
+            (PersonSort::UpdatedAt, SortDirection::Desc) => {
+                "(updated_at, id) < ($3::timestamptz, $2)"
+            }
+        }
+    }
+
+    fn is_valid_key(&self, key: &str) -> bool {
+        match self {
+            PersonSort::Gender => key.parse::<Gender>().is_ok(),
+            PersonSort::CreatedAt | PersonSort::UpdatedAt => key.parse::<DateTime<Utc>>().is_ok(),
+            PersonSort::LastName
+            | PersonSort::FirstName
+            | PersonSort::Initials
+            | PersonSort::Locality => true,
+        }
+    }
+}
+
+impl KeysetRow<PersonSort> for Person {
+    /// Keys are parsed back into the column type by [`KeysetColumn::after_cursor`],
+    /// timestamps keep the microseconds Postgres stores.
+    fn cursor(&self, sort: &PersonSort) -> Cursor<PersonSort> {
+        let key = match sort {
+            PersonSort::LastName => Some(self.last_name.clone()),
+            PersonSort::FirstName => self.first_name.clone(),
+            PersonSort::Initials => Some(self.initials.clone()),
+            PersonSort::Gender => self.gender.map(|gender| gender.to_string()),
+            PersonSort::Locality => self.locality.clone(),
+            PersonSort::CreatedAt => {
+                Some(self.created_at.to_rfc3339_opts(SecondsFormat::Micros, true))
+            }
+            PersonSort::UpdatedAt => {
+                Some(self.updated_at.to_rfc3339_opts(SecondsFormat::Micros, true))
+            }
+        };
+
+        Cursor {
+            sort: *sort,
+            key,
+            id: self.id,
+        }
+    }
+}
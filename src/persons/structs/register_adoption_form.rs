@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+use validate::Validate;
+
+use crate::{CsrfToken, TokenValue, form::WithCsrfToken};
+
+/// Adopt the details of a person as registered in the population register.
+#[derive(Default, Serialize, Deserialize, Clone, Debug, Validate)]
+#[validate(target = "()", build = "RegisterAdoptionForm::post_validate")]
+#[serde(default)]
+pub struct RegisterAdoptionForm {
+    /// Version of the person the comparison was made with.
+    #[validate(skip)]
+    pub version: String,
+    #[validate(csrf)]
+    pub csrf_token: TokenValue,
+}
+
+impl RegisterAdoptionForm {
+    fn post_validate(_: RegisterAdoptionFormValidated, _: Option<&()>) {
+        // do nothing, the registered details are looked up again when adopting them
+    }
+}
+
+impl WithCsrfToken for RegisterAdoptionForm {
+    fn with_csrf_token(self, csrf_token: CsrfToken) -> Self {
+        RegisterAdoptionForm {
+            csrf_token: csrf_token.value,
+            ..self
+        }
+    }
+}
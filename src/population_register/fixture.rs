@@ -0,0 +1,133 @@
+use std::{collections::HashMap, str::FromStr};
+
+use chrono::NaiveDate;
+use csv::{ReaderBuilder, Trim};
+use serde::Deserialize;
+
+use crate::{
+    AppError,
+    constants::DEFAULT_DATE_FORMAT,
+    persons::Gender,
+    population_register::{PopulationRegister, RegisterLookup, RegisteredPerson},
+};
+
+const BRP_CSV: &str = include_str!("brp.csv");
+
+#[derive(Debug, Deserialize)]
+struct RegisterRecord {
+    bsn: String,
+    gender: Option<String>,
+    last_name: String,
+    last_name_prefix: Option<String>,
+    first_name: Option<String>,
+    initials: String,
+    date_of_birth: String,
+    street_name: String,
+    house_number: String,
+    house_number_addition: Option<String>,
+    postal_code: String,
+    locality: String,
+}
+
+impl TryFrom<RegisterRecord> for RegisteredPerson {
+    type Error = AppError;
+
+    fn try_from(record: RegisterRecord) -> Result<Self, AppError> {
+        let invalid = |field: &str, value: &str| {
+            AppError::ConfigLoadError(format!(
+                "invalid population register fixture: {field} {value} of {}",
+                record.bsn
+            ))
+        };
+        let gender = record
+            .gender
+            .as_deref()
+            .map(|gender| Gender::from_str(gender).map_err(|_| invalid("gender", gender)))
+            .transpose()?;
+        let date_of_birth = NaiveDate::parse_from_str(&record.date_of_birth, DEFAULT_DATE_FORMAT)
+            .map_err(|_| invalid("date of birth", &record.date_of_birth))?;
+
+        Ok(RegisteredPerson {
+            bsn: record.bsn,
+            gender,
+            last_name: record.last_name,
+            last_name_prefix: record.last_name_prefix,
+            first_name: record.first_name,
+            initials: record.initials,
+            date_of_birth: Some(date_of_birth),
+            street_name: record.street_name,
+            house_number: record.house_number,
+            house_number_addition: record.house_number_addition,
+            postal_code: record.postal_code,
+            locality: record.locality,
+        })
+    }
+}
+
+/// Stand-in for the BRP, answering from a local fixture file until the BRP is connected.
+pub struct FixturePopulationRegister {
+    persons: HashMap<String, RegisteredPerson>,
+}
+
+impl FixturePopulationRegister {
+    pub fn new() -> Result<Self, AppError> {
+        Self::from_csv(BRP_CSV)
+    }
+
+    /// Read registered persons from CSV with the columns of `brp.csv`.
+    pub fn from_csv(csv: &str) -> Result<Self, AppError> {
+        let persons = ReaderBuilder::new()
+            .trim(Trim::All)
+            .from_reader(csv.as_bytes())
+            .deserialize::<RegisterRecord>()
+            .map(|record| {
+                let person = RegisteredPerson::try_from(record.map_err(|err| {
+                    AppError::ConfigLoadError(format!("invalid population register fixture: {err}"))
+                })?)?;
+                Ok((person.bsn.clone(), person))
+            })
+            .collect::<Result<_, AppError>>()?;
+
+        Ok(Self { persons })
+    }
+}
+
+impl PopulationRegister for FixturePopulationRegister {
+    fn find_by_bsn<'a>(&'a self, bsn: &'a str) -> RegisterLookup<'a> {
+        Box::pin(async move { Ok(self.persons.get(bsn).cloned()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn finds_registered_persons_by_bsn() {
+        let register = FixturePopulationRegister::new().unwrap();
+
+        let person = register.find_by_bsn("999990020").await.unwrap().unwrap();
+        assert_eq!(person.last_name_prefix, Some("van".to_string()));
+        assert_eq!(person.last_name, "Dijk");
+        assert_eq!(person.gender, Some(Gender::Male));
+        assert_eq!(person.date_of_birth, NaiveDate::from_ymd_opt(1985, 3, 14));
+        assert_eq!(person.house_number_addition, None);
+
+        assert_eq!(register.find_by_bsn("999990007").await.unwrap(), None);
+    }
+
+    #[test]
+    fn rejects_invalid_dates_and_genders() {
+        let header = BRP_CSV.lines().next().unwrap();
+        for row in [
+            "999990019,female,Jansen,,Henk,H.,1990-02-01,Dorpsstraat,12,,1234 AB,Juinen",
+            "999990019,f,Jansen,,Henk,H.,01-02-1990,Dorpsstraat,12,,1234 AB,Juinen",
+        ] {
+            let result = FixturePopulationRegister::from_csv(&format!("{header}\n{row}"));
+            assert!(matches!(result, Err(AppError::ConfigLoadError(_))), "{row}");
+        }
+
+        let row = "999990019,,Jansen,,Henk,H.,01-02-1990,Dorpsstraat,12,,1234 AB,Juinen";
+        assert!(FixturePopulationRegister::from_csv(&format!("{header}\n{row}")).is_ok());
+    }
+}
@@ -0,0 +1,92 @@
+//! Lookup of persons in the population register (Basisregistratie Personen, BRP), to check the
+//! personal details of candidates.
+
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use chrono::NaiveDate;
+use strum::{Display, EnumString};
+
+use crate::{
+    AppError,
+    persons::{Gender, Person},
+};
+
+#[cfg(any(test, feature = "dev-features"))]
+mod fixture;
+
+#[cfg(any(test, feature = "dev-features"))]
+pub use fixture::FixturePopulationRegister;
+
+/// Future returned by a lookup in a population register.
+pub type RegisterLookup<'a> =
+    Pin<Box<dyn Future<Output = Result<Option<RegisteredPerson>, AppError>> + Send + 'a>>;
+
+/// A source of registered personal details, e.g. the BRP or a local stand-in.
+pub trait PopulationRegister: Send + Sync {
+    /// Find the person registered with the given BSN.
+    fn find_by_bsn<'a>(&'a self, bsn: &'a str) -> RegisterLookup<'a>;
+}
+
+/// Implementation of the population register to look up persons in, configured with
+/// `POPULATION_REGISTER`. Until the BRP is connected, only development builds have one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum PopulationRegisterKind {
+    /// The local stand-in with fictional persons, see [`FixturePopulationRegister`].
+    #[cfg(any(test, feature = "dev-features"))]
+    Fixture,
+}
+
+impl PopulationRegisterKind {
+    /// Set up the configured population register.
+    pub fn connect(self) -> Result<Arc<dyn PopulationRegister>, AppError> {
+        match self {
+            #[cfg(any(test, feature = "dev-features"))]
+            PopulationRegisterKind::Fixture => Ok(Arc::new(FixturePopulationRegister::new()?)),
+        }
+    }
+}
+
+/// Personal details of a person as registered in the population register.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegisteredPerson {
+    pub bsn: String,
+    pub last_name: String,
+    pub last_name_prefix: Option<String>,
+    pub initials: String,
+    pub first_name: Option<String>,
+    pub gender: Option<Gender>,
+    pub date_of_birth: Option<NaiveDate>,
+    pub street_name: String,
+    pub house_number: String,
+    pub house_number_addition: Option<String>,
+    pub postal_code: String,
+    pub locality: String,
+}
+
+impl RegisteredPerson {
+    /// The given person with all registered details adopted; residents of the Netherlands have a
+    /// Dutch address only.
+    pub fn adopt_into(&self, person: &Person) -> Person {
+        Person {
+            bsn: Some(self.bsn.clone()),
+            last_name: self.last_name.clone(),
+            last_name_prefix: self.last_name_prefix.clone(),
+            initials: self.initials.clone(),
+            first_name: self.first_name.clone(),
+            gender: self.gender,
+            date_of_birth: self.date_of_birth,
+            street_name: Some(self.street_name.clone()),
+            house_number: Some(self.house_number.clone()),
+            house_number_addition: self.house_number_addition.clone(),
+            postal_code: Some(self.postal_code.clone()),
+            locality: Some(self.locality.clone()),
+            is_dutch: Some(true),
+            custom_country: None,
+            custom_region: None,
+            address_line_1: None,
+            address_line_2: None,
+            ..person.clone()
+        }
+    }
+}
@@ -0,0 +1,5 @@
+mod pages;
+mod purge;
+
+pub use pages::{recently_deleted_path, router};
+pub use purge::spawn_purge_task;
@@ -0,0 +1,95 @@
+use askama::Template;
+use axum::{extract::State, response::IntoResponse};
+
+use crate::{
+    AppError, AppState, Context, CsrfTokens, DbConnection, ElectionConfig, HtmlTemplate, Locale,
+    candidate_lists::{self, DeletedCandidateList},
+    filters,
+    form::{EmptyForm, FormData},
+    persons::{self, DeletedPerson},
+    recently_deleted::pages::RecentlyDeletedPath,
+    t,
+};
+
+#[derive(Template)]
+#[template(path = "recently_deleted/list.html")]
+struct RecentlyDeletedTemplate {
+    candidate_lists: Vec<DeletedCandidateList>,
+    persons: Vec<DeletedPerson>,
+    retention_days: i64,
+    form: FormData<EmptyForm>,
+    election: ElectionConfig,
+    locale: Locale,
+}
+
+pub async fn list_recently_deleted(
+    _: RecentlyDeletedPath,
+    context: Context,
+    State(app_state): State<AppState>,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+) -> Result<impl IntoResponse, AppError> {
+    let candidate_lists =
+        candidate_lists::repository::list_deleted_candidate_lists(&mut conn).await?;
+    let persons = persons::repository::list_deleted_persons(&mut conn).await?;
+
+    Ok(HtmlTemplate(
+        RecentlyDeletedTemplate {
+            candidate_lists,
+            persons,
+            retention_days: app_state.config().deleted_retention_days,
+            form: FormData::new_with_data(EmptyForm::default(), &csrf_tokens),
+            election: app_state.config().election,
+            locale: context.locale,
+        },
+        context,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use crate::test_utils::{
+        response_body_string, sample_candidate_list, sample_person_with_last_name,
+    };
+
+    #[sqlx::test]
+    async fn lists_deleted_candidate_lists_and_persons(pool: PgPool) -> Result<(), sqlx::Error> {
+        let list = sample_candidate_list(Uuid::new_v4());
+        let deleted = sample_person_with_last_name(Uuid::new_v4(), "Bakker");
+        let kept = sample_person_with_last_name(Uuid::new_v4(), "de Vries");
+
+        let mut conn = pool.acquire().await?;
+        candidate_lists::repository::create_candidate_list(&mut conn, &list).await?;
+        candidate_lists::repository::remove_candidate_list(&mut conn, list.id).await?;
+        persons::repository::create_person(&mut conn, &deleted).await?;
+        persons::repository::create_person(&mut conn, &kept).await?;
+        persons::repository::remove_person(&mut conn, &deleted.id).await?;
+
+        let response = list_recently_deleted(
+            RecentlyDeletedPath {},
+            Context::new(Locale::En),
+            State(AppState::new_for_tests(pool.clone())),
+            CsrfTokens::default(),
+            DbConnection(pool.acquire().await?),
+        )
+        .await
+        .unwrap()
+        .into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        assert!(body.contains(&format!(
+            "/recently-deleted/candidate-lists/{}/restore",
+            list.id
+        )));
+        assert!(body.contains("Bakker"));
+        assert!(!body.contains("de Vries"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+use axum::{Router, middleware};
+use axum_extra::routing::{RouterExt, TypedPath};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    AppError, AppState, Permission, auth::require_permission,
+    candidate_lists::DeletedCandidateList, persons::DeletedPerson,
+};
+
+mod list;
+mod restore;
+
+#[derive(TypedPath)]
+#[typed_path("/recently-deleted", rejection(AppError))]
+pub struct RecentlyDeletedPath;
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/recently-deleted/candidate-lists/{id}/restore", rejection(AppError))]
+pub struct RestoreCandidateListPath {
+    pub id: Uuid,
+}
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/recently-deleted/persons/{id}/restore", rejection(AppError))]
+pub struct RestorePersonPath {
+    pub id: Uuid,
+}
+
+pub fn recently_deleted_path() -> String {
+    RecentlyDeletedPath {}.to_string()
+}
+
+impl DeletedCandidateList {
+    pub fn restore_path(&self) -> String {
+        RestoreCandidateListPath { id: self.list.id }.to_string()
+    }
+}
+
+impl DeletedPerson {
+    pub fn restore_path(&self) -> String {
+        RestorePersonPath { id: self.person.id }.to_string()
+    }
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .typed_get(list::list_recently_deleted)
+        .typed_post(restore::restore_candidate_list)
+        .typed_post(restore::restore_person)
+        .route_layer(middleware::from_fn_with_state(
+            Permission::RestoreDeleted,
+            require_permission,
+        ))
+}
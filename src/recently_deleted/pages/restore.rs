@@ -0,0 +1,197 @@
+use axum::response::{IntoResponse, Redirect, Response};
+use axum_extra::extract::Form;
+use sqlx::Connection;
+
+use crate::{
+    AppError, Context, CsrfTokens, DbConnection,
+    candidate_lists::{self, candidate_list_not_found},
+    form::{EmptyForm, Validate},
+    persons::{self, person_not_found},
+    recently_deleted::pages::{RestoreCandidateListPath, RestorePersonPath, recently_deleted_path},
+    t,
+};
+
+pub async fn restore_candidate_list(
+    RestoreCandidateListPath { id }: RestoreCandidateListPath,
+    context: Context,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+    form: Form<EmptyForm>,
+) -> Result<Response, AppError> {
+    if form.validate(None, &csrf_tokens).is_err() {
+        return Ok(Redirect::to(&recently_deleted_path()).into_response());
+    }
+
+    let mut tx = conn.begin().await?;
+    if !candidate_lists::repository::restore_candidate_list(&mut tx, &id).await? {
+        return Err(candidate_list_not_found(id, context.locale));
+    }
+    candidate_lists::repository::create_snapshot(
+        &mut tx,
+        &id,
+        Some(&t!(
+            "candidate_list.snapshots.automatic.restored",
+            &context.locale
+        )),
+    )
+    .await?;
+    tx.commit().await?;
+
+    let candidate_list = candidate_lists::repository::get_candidate_list(&mut conn, &id)
+        .await?
+        .ok_or(candidate_list_not_found(id, context.locale))?;
+
+    Ok(Redirect::to(&candidate_list.view_path()).into_response())
+}
+
+pub async fn restore_person(
+    RestorePersonPath { id }: RestorePersonPath,
+    context: Context,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+    form: Form<EmptyForm>,
+) -> Result<Response, AppError> {
+    if form.validate(None, &csrf_tokens).is_err() {
+        return Ok(Redirect::to(&recently_deleted_path()).into_response());
+    }
+
+    if !persons::repository::restore_person(&mut conn, &id).await? {
+        return Err(person_not_found(id, context.locale));
+    }
+
+    let person = persons::repository::get_person(&mut conn, &id)
+        .await?
+        .ok_or(person_not_found(id, context.locale))?;
+
+    Ok(Redirect::to(&person.view_path()).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{StatusCode, header};
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use crate::{
+        Locale,
+        test_utils::{sample_candidate_list, sample_person_with_last_name},
+    };
+
+    fn csrf_form(csrf_tokens: &CsrfTokens) -> Form<EmptyForm> {
+        Form(EmptyForm::from(csrf_tokens.issue().value))
+    }
+
+    #[sqlx::test]
+    async fn restore_candidate_list_brings_back_candidates_and_positions(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let list = sample_candidate_list(Uuid::new_v4());
+        let first = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        let second = sample_person_with_last_name(Uuid::new_v4(), "Bakker");
+
+        let mut conn = pool.acquire().await?;
+        candidate_lists::repository::create_candidate_list(&mut conn, &list).await?;
+        persons::repository::create_person(&mut conn, &first).await?;
+        persons::repository::create_person(&mut conn, &second).await?;
+        candidate_lists::repository::update_candidate_list_order(
+            &mut conn,
+            &list.id,
+            &[second.id, first.id],
+        )
+        .await?;
+
+        candidate_lists::repository::remove_candidate_list(&mut conn, list.id).await?;
+        // a candidate of the deleted list was deleted as well
+        persons::repository::remove_person(&mut conn, &first.id).await?;
+        assert!(
+            candidate_lists::repository::get_full_candidate_list(&mut conn, &list.id)
+                .await?
+                .is_none()
+        );
+
+        let csrf_tokens = CsrfTokens::default();
+        let response = restore_candidate_list(
+            RestoreCandidateListPath { id: list.id },
+            Context::new(Locale::En),
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+            csrf_form(&csrf_tokens),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        let location = response
+            .headers()
+            .get(header::LOCATION)
+            .expect("location header")
+            .to_str()
+            .expect("location header value");
+        assert_eq!(location, list.view_path());
+
+        let restored = candidate_lists::repository::get_full_candidate_list(&mut conn, &list.id)
+            .await?
+            .expect("candidate list");
+        let positions = restored
+            .candidates
+            .iter()
+            .map(|c| (c.person.id, c.position))
+            .collect::<Vec<_>>();
+        assert_eq!(positions, vec![(second.id, 1), (first.id, 2)]);
+
+        // restoring the list is recorded as a snapshot
+        let snapshots = candidate_lists::repository::list_snapshots(&mut conn, &list.id).await?;
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].label.as_deref(), Some("List restored"));
+        assert_eq!(snapshots[0].full_list.candidates.len(), 2);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn restore_person_redirects_to_person(pool: PgPool) -> Result<(), sqlx::Error> {
+        let person = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+
+        let mut conn = pool.acquire().await?;
+        persons::repository::create_person(&mut conn, &person).await?;
+        persons::repository::remove_person(&mut conn, &person.id).await?;
+
+        let csrf_tokens = CsrfTokens::default();
+        let response = restore_person(
+            RestorePersonPath { id: person.id },
+            Context::new(Locale::En),
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+            csrf_form(&csrf_tokens),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert!(
+            persons::repository::get_person(&mut conn, &person.id)
+                .await?
+                .is_some()
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn restore_unknown_person_is_not_found(pool: PgPool) -> Result<(), sqlx::Error> {
+        let csrf_tokens = CsrfTokens::default();
+        let result = restore_person(
+            RestorePersonPath { id: Uuid::new_v4() },
+            Context::new(Locale::En),
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+            csrf_form(&csrf_tokens),
+        )
+        .await;
+
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use sqlx::{Connection, PgConnection};
+
+use crate::{AppState, Tenant, candidate_lists, persons};
+
+/// How often the purge task looks for expired soft deleted rows.
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Permanently delete candidate lists and persons that were soft deleted more than
+/// `retention_days` ago. Returns the number of purged lists and persons.
+pub async fn purge_expired(
+    conn: &mut PgConnection,
+    retention_days: i64,
+) -> Result<(u64, u64), sqlx::Error> {
+    let deleted_before = Utc::now() - chrono::Duration::days(retention_days);
+    let mut tx = conn.begin().await?;
+
+    // lists first, so candidates that only remained on a purged list are purged as well
+    let lists =
+        candidate_lists::repository::purge_deleted_candidate_lists(&mut tx, deleted_before).await?;
+    let persons = persons::repository::purge_deleted_persons(&mut tx, deleted_before).await?;
+
+    tx.commit().await?;
+
+    Ok((lists, persons))
+}
+
+/// Run [`purge_expired`] periodically in the background, using the configured retention period.
+pub fn spawn_purge_task(state: AppState) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let result = async {
+                let mut conn = state.pool().acquire().await?;
+                Tenant::Maintenance.scope(&mut conn).await?;
+                purge_expired(&mut conn, state.config().deleted_retention_days).await
+            }
+            .await;
+
+            match result {
+                Ok((0, 0)) => {}
+                Ok((lists, persons)) => {
+                    tracing::info!("Purged {lists} deleted candidate lists and {persons} persons")
+                }
+                Err(err) => tracing::error!(?err, "Failed to purge deleted rows"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use crate::test_utils::{sample_candidate_list, sample_person_with_last_name};
+
+    #[sqlx::test]
+    async fn purges_only_rows_deleted_before_retention(pool: PgPool) -> Result<(), sqlx::Error> {
+        let expired_list = sample_candidate_list(Uuid::new_v4());
+        let recent_list = sample_candidate_list(Uuid::new_v4());
+        let on_expired_list = sample_person_with_last_name(Uuid::new_v4(), "Jansen");
+        let on_recent_list = sample_person_with_last_name(Uuid::new_v4(), "Bakker");
+        let expired_person = sample_person_with_last_name(Uuid::new_v4(), "de Vries");
+
+        let mut conn = pool.acquire().await?;
+        for person in [&on_expired_list, &on_recent_list, &expired_person] {
+            persons::repository::create_person(&mut conn, person).await?;
+        }
+        for (list, person) in [
+            (&expired_list, &on_expired_list),
+            (&recent_list, &on_recent_list),
+        ] {
+            candidate_lists::repository::create_candidate_list(&mut conn, list).await?;
+            candidate_lists::repository::update_candidate_list_order(
+                &mut conn,
+                &list.id,
+                &[person.id],
+            )
+            .await?;
+            candidate_lists::repository::remove_candidate_list(&mut conn, list.id).await?;
+        }
+        for person in [&on_expired_list, &on_recent_list, &expired_person] {
+            persons::repository::remove_person(&mut conn, &person.id).await?;
+        }
+
+        // backdate everything except the recent list
+        sqlx::query!(
+            "UPDATE candidate_lists SET deleted_at = NOW() - INTERVAL '31 days' WHERE id = $1",
+            expired_list.id
+        )
+        .execute(&mut *conn)
+        .await?;
+        sqlx::query!("UPDATE persons SET deleted_at = NOW() - INTERVAL '31 days'")
+            .execute(&mut *conn)
+            .await?;
+
+        // rows of every political group are purged
+        Tenant::Maintenance.scope(&mut conn).await?;
+        let purged = purge_expired(&mut conn, 30).await?;
+        assert_eq!(purged, (1, 2));
+
+        let lists = candidate_lists::repository::list_deleted_candidate_lists(&mut conn).await?;
+        assert_eq!(
+            lists.iter().map(|l| l.list.id).collect::<Vec<_>>(),
+            vec![recent_list.id]
+        );
+        // the person on the recently deleted list is kept, so the list can still be restored
+        let persons = persons::repository::list_deleted_persons(&mut conn).await?;
+        assert_eq!(
+            persons.iter().map(|p| p.person.id).collect::<Vec<_>>(),
+            vec![on_recent_list.id]
+        );
+
+        Ok(())
+    }
+}
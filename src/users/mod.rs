@@ -0,0 +1,6 @@
+mod pages;
+pub mod repository;
+mod structs;
+
+pub use pages::{router, users_path};
+pub use structs::*;
@@ -0,0 +1,258 @@
+use askama::Template;
+use axum::response::{IntoResponse, Redirect, Response};
+use axum_extra::extract::Form;
+use sqlx::{Connection, PgConnection};
+
+use crate::{
+    AppError, Context, CsrfTokens, CurrentUser, DbConnection, HtmlTemplate,
+    auth::{repository as auth_repository, select_role_path},
+    common::current_user::hash_token,
+    filters,
+    form::{EmptyForm, FormData, Validate},
+    t,
+    users::{self, Invitation, pages::InvitationPath},
+};
+
+#[derive(Template)]
+#[template(path = "users/invitation.html")]
+struct InvitationTemplate {
+    invitation: Invitation,
+    path: String,
+    form: FormData<EmptyForm>,
+    /// Users can join a single political group or the central electoral committee only.
+    can_accept: bool,
+}
+
+async fn load_invitation(
+    conn: &mut PgConnection,
+    token: &str,
+    context: &Context,
+) -> Result<Invitation, AppError> {
+    users::repository::get_pending_invitation(conn, &hash_token(token))
+        .await?
+        .ok_or(AppError::NotFound(t!(
+            "invitation.not_found",
+            &context.locale
+        )))
+}
+
+/// Whether the user fits the political group of the invitation: members of a group can only
+/// be invited for that group, users without any role can join any group.
+async fn can_accept(
+    conn: &mut PgConnection,
+    user: &CurrentUser,
+    invitation: &Invitation,
+) -> Result<bool, AppError> {
+    if user.political_group_id == invitation.political_group_id {
+        return Ok(true);
+    }
+
+    Ok(user.political_group_id.is_none()
+        && auth_repository::list_user_roles(conn, &user.id)
+            .await?
+            .is_empty())
+}
+
+pub async fn view_invitation(
+    InvitationPath { token }: InvitationPath,
+    context: Context,
+    user: CurrentUser,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+) -> Result<impl IntoResponse, AppError> {
+    let invitation = load_invitation(&mut conn, &token, &context).await?;
+    let can_accept = can_accept(&mut conn, &user, &invitation).await?;
+
+    Ok(HtmlTemplate(
+        InvitationTemplate {
+            invitation,
+            path: InvitationPath { token }.to_string(),
+            form: FormData::new(&csrf_tokens),
+            can_accept,
+        },
+        context,
+    ))
+}
+
+/// Give the signed in user the role of the invitation, after which the link can no longer be
+/// used.
+pub async fn accept_invitation(
+    InvitationPath { token }: InvitationPath,
+    context: Context,
+    user: CurrentUser,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+    form: Form<EmptyForm>,
+) -> Result<Response, AppError> {
+    if form.validate(None, &csrf_tokens).is_err() {
+        return Ok(Redirect::to(&InvitationPath { token }.to_string()).into_response());
+    }
+
+    let invitation = load_invitation(&mut conn, &token, &context).await?;
+    if !can_accept(&mut conn, &user, &invitation).await? {
+        return Err(AppError::Unauthorized);
+    }
+
+    let mut tx = conn.begin().await?;
+    if !users::repository::accept_invitation(&mut tx, &invitation.id, &user.id).await? {
+        return Err(AppError::NotFound(t!(
+            "invitation.not_found",
+            &context.locale
+        )));
+    }
+    if let (None, Some(group)) = (user.political_group_id, invitation.political_group_id) {
+        auth_repository::set_user_political_group(&mut tx, &user.id, &group).await?;
+    }
+    auth_repository::add_user_role(&mut tx, &user.id, invitation.role).await?;
+    let roles = auth_repository::list_user_roles(&mut tx, &user.id).await?;
+    tx.commit().await?;
+
+    if roles.len() > 1 {
+        return Ok(Redirect::to(&select_role_path("/")).into_response());
+    }
+
+    Ok(Redirect::to("/").into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{StatusCode, header};
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+
+    use crate::{
+        Locale, Role,
+        auth::repository::create_political_group,
+        common::current_user::random_token,
+        test_utils::{create_user, response_body_string, sample_user},
+        users::NewInvitation,
+    };
+
+    async fn invite(
+        conn: &mut PgConnection,
+        political_group_id: Option<uuid::Uuid>,
+        role: Role,
+    ) -> Result<String, sqlx::Error> {
+        let token = random_token();
+        let invited_by = create_user(conn, "Bakker", political_group_id, &[]).await?;
+        users::repository::create_invitation(
+            conn,
+            &NewInvitation {
+                name: "Jansen".to_string(),
+                email: None,
+                political_group_id,
+                role,
+            },
+            &hash_token(&token),
+            &invited_by,
+            Utc::now() + Duration::days(1),
+        )
+        .await?;
+
+        Ok(token)
+    }
+
+    #[sqlx::test]
+    async fn accepting_joins_the_political_group_once(pool: PgPool) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let group = create_political_group(&mut conn, "Lijst Smit").await?;
+        let token = invite(&mut conn, Some(group), Role::ListSubmitter).await?;
+        let id = create_user(&mut conn, "Jansen", None, &[]).await?;
+        let user = CurrentUser {
+            id,
+            ..sample_user(vec![])
+        };
+
+        let csrf_tokens = CsrfTokens::default();
+        let response = view_invitation(
+            InvitationPath {
+                token: token.clone(),
+            },
+            Context::new(Locale::En),
+            user.clone(),
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+        )
+        .await
+        .unwrap()
+        .into_response();
+        let body = response_body_string(response).await;
+        assert!(body.contains("Lijst Smit"));
+        assert!(body.contains("List submitter"));
+
+        let response = accept_invitation(
+            InvitationPath {
+                token: token.clone(),
+            },
+            Context::new(Locale::En),
+            user.clone(),
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+            Form(EmptyForm::from(csrf_tokens.issue().value)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(response.headers().get(header::LOCATION).unwrap(), "/");
+
+        let account = users::repository::get_user(&mut conn, &id, Some(group))
+            .await?
+            .expect("member of the political group");
+        assert_eq!(account.roles, vec![Role::ListSubmitter]);
+
+        // the link can only be used once
+        let result = accept_invitation(
+            InvitationPath { token },
+            Context::new(Locale::En),
+            user,
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+            Form(EmptyForm::from(csrf_tokens.issue().value)),
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn members_of_other_political_groups_can_not_accept(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let group = create_political_group(&mut conn, "Lijst Smit").await?;
+        let other_group = create_political_group(&mut conn, "Lijst Jansen").await?;
+        let token = invite(&mut conn, Some(group), Role::AuthorisedAgent).await?;
+        let id = create_user(
+            &mut conn,
+            "de Vries",
+            Some(other_group),
+            &[Role::ListSubmitter],
+        )
+        .await?;
+        let user = CurrentUser {
+            id,
+            political_group_id: Some(other_group),
+            ..sample_user(vec![Role::ListSubmitter])
+        };
+
+        let csrf_tokens = CsrfTokens::default();
+        let result = accept_invitation(
+            InvitationPath { token },
+            Context::new(Locale::En),
+            user,
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+            Form(EmptyForm::from(csrf_tokens.issue().value)),
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::Unauthorized)));
+        assert_eq!(
+            auth_repository::list_user_roles(&mut conn, &id).await?,
+            vec![Role::ListSubmitter]
+        );
+
+        Ok(())
+    }
+}
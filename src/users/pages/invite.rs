@@ -0,0 +1,276 @@
+use askama::Template;
+use axum::{
+    extract::State,
+    response::{IntoResponse, Response},
+};
+use axum_extra::extract::Form;
+use chrono::{Duration, Utc};
+
+use crate::{
+    AppError, AppState, Context, CsrfTokens, CurrentUser, DbConnection, HtmlTemplate, Role,
+    common::current_user::{hash_token, random_token},
+    filters,
+    form::{FieldErrors, FormData, Validate, ValidationError},
+    t,
+    users::{
+        self, INVITATION_TTL_DAYS, InvitationForm, NewInvitation, PoliticalGroup, UserScope,
+        pages::{InvitationPath, InviteUserPath},
+    },
+};
+
+#[derive(Template)]
+#[template(path = "users/invite.html")]
+struct InviteTemplate {
+    form: FormData<InvitationForm>,
+    /// Political groups to choose from, only for administrators managing all users.
+    political_groups: Vec<PoliticalGroup>,
+    roles: &'static [Role],
+}
+
+#[derive(Template)]
+#[template(path = "users/invited.html")]
+struct InvitedTemplate {
+    invitation: NewInvitation,
+    link: String,
+    ttl_days: i64,
+}
+
+/// Roles the administrator can invite users for.
+fn invitable_roles(scope: UserScope) -> &'static [Role] {
+    match scope {
+        UserScope::PoliticalGroup(_) => Role::assignable(true),
+        UserScope::All => &[
+            Role::AuthorisedAgent,
+            Role::ListSubmitter,
+            Role::CsbMember,
+            Role::KiesraadAdmin,
+        ],
+    }
+}
+
+async fn political_groups(
+    conn: &mut sqlx::PgConnection,
+    scope: UserScope,
+) -> Result<Vec<PoliticalGroup>, AppError> {
+    Ok(match scope {
+        UserScope::PoliticalGroup(_) => Vec::new(),
+        UserScope::All => users::repository::list_political_groups(conn).await?,
+    })
+}
+
+/// Errors in the combination of fields: a role has to fit the political group, or the lack
+/// of one.
+fn check_invitation(
+    invitation: &NewInvitation,
+    political_groups: &[PoliticalGroup],
+    scope: UserScope,
+) -> FieldErrors {
+    let mut errors = FieldErrors::new();
+
+    if let (UserScope::All, Some(id)) = (scope, invitation.political_group_id)
+        && !political_groups.iter().any(|group| group.id == id)
+    {
+        errors.push(("political_group".to_string(), ValidationError::InvalidValue));
+    }
+
+    if !Role::assignable(invitation.political_group_id.is_some()).contains(&invitation.role) {
+        errors.push(("role".to_string(), ValidationError::InvalidValue));
+    }
+
+    errors
+}
+
+pub async fn new_invitation_form(
+    _: InviteUserPath,
+    context: Context,
+    scope: UserScope,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(HtmlTemplate(
+        InviteTemplate {
+            form: FormData::new(&csrf_tokens),
+            political_groups: political_groups(&mut conn, scope).await?,
+            roles: invitable_roles(scope),
+        },
+        context,
+    ))
+}
+
+/// Create an invitation and show its one-time link, to be handed to the invited user.
+pub async fn create_invitation(
+    _: InviteUserPath,
+    context: Context,
+    State(app_state): State<AppState>,
+    current_user: CurrentUser,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+    Form(form): Form<InvitationForm>,
+) -> Result<Response, AppError> {
+    let scope = UserScope::of(&current_user).ok_or(AppError::Forbidden)?;
+    let political_groups = political_groups(&mut conn, scope).await?;
+    let roles = invitable_roles(scope);
+
+    let mut invitation = match form.validate(None, &csrf_tokens) {
+        Ok(invitation) => invitation,
+        Err(form) => {
+            return Ok(HtmlTemplate(
+                InviteTemplate {
+                    form,
+                    political_groups,
+                    roles,
+                },
+                context,
+            )
+            .into_response());
+        }
+    };
+
+    // gemachtigden invite users for their own political group only
+    if let UserScope::PoliticalGroup(id) = scope {
+        invitation.political_group_id = Some(id);
+    }
+
+    let errors = check_invitation(&invitation, &political_groups, scope);
+    if !errors.is_empty() {
+        return Ok(HtmlTemplate(
+            InviteTemplate {
+                form: FormData::new_with_errors(form, &csrf_tokens, errors),
+                political_groups,
+                roles,
+            },
+            context,
+        )
+        .into_response());
+    }
+
+    let token = random_token();
+    users::repository::create_invitation(
+        &mut conn,
+        &invitation,
+        &hash_token(&token),
+        &current_user.id,
+        Utc::now() + Duration::days(INVITATION_TTL_DAYS),
+    )
+    .await?;
+
+    Ok(HtmlTemplate(
+        InvitedTemplate {
+            invitation,
+            link: format!(
+                "{}{}",
+                app_state.config().public_url,
+                InvitationPath { token }
+            ),
+            ttl_days: INVITATION_TTL_DAYS,
+        },
+        context,
+    )
+    .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use crate::{
+        Locale,
+        auth::repository::create_political_group,
+        test_utils::{create_user, response_body_string, sample_user},
+    };
+
+    fn invitation_form(
+        csrf_tokens: &CsrfTokens,
+        political_group: &str,
+        role: &str,
+    ) -> InvitationForm {
+        InvitationForm {
+            name: "Jansen".to_string(),
+            email: String::new(),
+            political_group: political_group.to_string(),
+            role: role.to_string(),
+            csrf_token: csrf_tokens.issue().value,
+        }
+    }
+
+    #[sqlx::test]
+    async fn invites_users_for_own_political_group(pool: PgPool) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let group = create_political_group(&mut conn, "Lijst Smit").await?;
+        let id = create_user(&mut conn, "Bakker", Some(group), &[Role::AuthorisedAgent]).await?;
+        let current_user = CurrentUser {
+            id,
+            political_group_id: Some(group),
+            ..sample_user(vec![Role::AuthorisedAgent])
+        };
+
+        let csrf_tokens = CsrfTokens::default();
+        let response = create_invitation(
+            InviteUserPath {},
+            Context::new(Locale::En),
+            State(AppState::new_for_tests(pool.clone())),
+            current_user,
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+            // the political group in the form is ignored
+            Form(invitation_form(
+                &csrf_tokens,
+                &Uuid::new_v4().to_string(),
+                "list_submitter",
+            )),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        let (_, token) = body.split_once("/invitations/").unwrap();
+        let token = &token[..48];
+
+        let invitation = users::repository::get_pending_invitation(&mut conn, &hash_token(token))
+            .await?
+            .unwrap();
+        assert_eq!(invitation.political_group_id, Some(group));
+        assert_eq!(invitation.role, Role::ListSubmitter);
+        assert_eq!(invitation.invited_by_name.as_deref(), Some("Bakker"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn roles_have_to_fit_the_political_group(pool: PgPool) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let id = create_user(&mut conn, "Bakker", None, &[Role::KiesraadAdmin]).await?;
+        let current_user = CurrentUser {
+            id,
+            ..sample_user(vec![Role::KiesraadAdmin])
+        };
+
+        let csrf_tokens = CsrfTokens::default();
+        let response = create_invitation(
+            InviteUserPath {},
+            Context::new(Locale::En),
+            State(AppState::new_for_tests(pool.clone())),
+            current_user,
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+            // a list submitter of the central electoral committee
+            Form(invitation_form(&csrf_tokens, "", "list_submitter")),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        assert!(!body.contains("/invitations/"));
+        assert!(body.contains("The provided value is not valid."));
+
+        let pending = users::repository::list_pending_invitations(&mut conn, None).await?;
+        assert!(pending.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+use askama::Template;
+use axum::response::IntoResponse;
+
+use crate::{
+    AppError, Context, DbConnection, HtmlTemplate, filters,
+    pagination::{Pagination, PaginationInfo},
+    t,
+    users::{self, Invitation, User, UserScope, UserSort, pages::UsersPath},
+};
+
+#[derive(Template)]
+#[template(path = "users/list.html")]
+struct UserListTemplate {
+    users: Vec<User>,
+    pagination: PaginationInfo<UserSort>,
+    invitations: Vec<Invitation>,
+}
+
+pub async fn list_users(
+    _: UsersPath,
+    context: Context,
+    scope: UserScope,
+    pagination: Pagination<UserSort>,
+    DbConnection(mut conn): DbConnection,
+) -> Result<impl IntoResponse, AppError> {
+    let political_group_id = scope.political_group_id();
+    let total_items = users::repository::count_users(&mut conn, political_group_id)
+        .await?
+        .max(0) as u64;
+    let pagination = pagination.set_total(total_items);
+
+    let users = users::repository::list_users(
+        &mut conn,
+        political_group_id,
+        pagination.limit(),
+        pagination.offset(),
+        pagination.sort(),
+        pagination.direction(),
+    )
+    .await?;
+    let invitations =
+        users::repository::list_pending_invitations(&mut conn, political_group_id).await?;
+
+    Ok(HtmlTemplate(
+        UserListTemplate {
+            users,
+            pagination,
+            invitations,
+        },
+        context,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use sqlx::PgPool;
+
+    use crate::{
+        Locale, Role,
+        auth::repository::create_political_group,
+        test_utils::{create_user, response_body_string},
+    };
+
+    #[sqlx::test]
+    async fn lists_users_of_own_political_group(pool: PgPool) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let group = create_political_group(&mut conn, "Lijst Smit").await?;
+        let other_group = create_political_group(&mut conn, "Lijst Jansen").await?;
+        create_user(&mut conn, "Bakker", Some(group), &[Role::ListSubmitter]).await?;
+        create_user(
+            &mut conn,
+            "de Vries",
+            Some(other_group),
+            &[Role::ListSubmitter],
+        )
+        .await?;
+
+        let response = list_users(
+            UsersPath {},
+            Context::new(Locale::En),
+            UserScope::PoliticalGroup(group),
+            Pagination::default(),
+            DbConnection(pool.acquire().await?),
+        )
+        .await
+        .unwrap()
+        .into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body_string(response).await;
+        assert!(body.contains("Bakker"));
+        assert!(body.contains("List submitter"));
+        assert!(!body.contains("de Vries"));
+
+        Ok(())
+    }
+}
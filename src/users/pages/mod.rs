@@ -0,0 +1,175 @@
+use axum::{Router, middleware};
+use axum_extra::routing::{RouterExt, TypedPath};
+use serde::Deserialize;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::{
+    AppError, AppState, CurrentUser, Locale, Permission,
+    auth::require_permission,
+    t,
+    users::{self, User, UserScope, UserSession},
+};
+
+mod invitation;
+mod invite;
+mod list;
+mod sessions;
+mod view;
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/users", rejection(AppError))]
+pub struct UsersPath;
+
+#[derive(TypedPath)]
+#[typed_path("/users/invite", rejection(AppError))]
+pub struct InviteUserPath;
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/users/{id}", rejection(AppError))]
+pub struct ViewUserPath {
+    pub id: Uuid,
+}
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/users/{id}/roles/add", rejection(AppError))]
+pub struct AddUserRolePath {
+    pub id: Uuid,
+}
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/users/{id}/roles/remove", rejection(AppError))]
+pub struct RemoveUserRolePath {
+    pub id: Uuid,
+}
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/users/{id}/deactivate", rejection(AppError))]
+pub struct DeactivateUserPath {
+    pub id: Uuid,
+}
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/users/{id}/activate", rejection(AppError))]
+pub struct ActivateUserPath {
+    pub id: Uuid,
+}
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/users/{id}/sessions/end", rejection(AppError))]
+pub struct EndUserSessionsPath {
+    pub id: Uuid,
+}
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/users/{id}/sessions/{session_id}/end", rejection(AppError))]
+pub struct EndUserSessionPath {
+    pub id: Uuid,
+    pub session_id: Uuid,
+}
+
+/// The one-time link of an invitation, opened by the invited user.
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/invitations/{token}", rejection(AppError))]
+pub struct InvitationPath {
+    pub token: String,
+}
+
+pub fn users_path() -> String {
+    UsersPath {}.to_string()
+}
+
+impl User {
+    pub fn invite_path() -> String {
+        InviteUserPath {}.to_string()
+    }
+
+    pub fn view_path(&self) -> String {
+        ViewUserPath { id: self.id }.to_string()
+    }
+
+    pub fn add_role_path(&self) -> String {
+        AddUserRolePath { id: self.id }.to_string()
+    }
+
+    pub fn remove_role_path(&self) -> String {
+        RemoveUserRolePath { id: self.id }.to_string()
+    }
+
+    pub fn deactivate_path(&self) -> String {
+        DeactivateUserPath { id: self.id }.to_string()
+    }
+
+    pub fn activate_path(&self) -> String {
+        ActivateUserPath { id: self.id }.to_string()
+    }
+
+    pub fn end_sessions_path(&self) -> String {
+        EndUserSessionsPath { id: self.id }.to_string()
+    }
+
+    pub fn end_session_path(&self, session: &UserSession) -> String {
+        EndUserSessionPath {
+            id: self.id,
+            session_id: session.id,
+        }
+        .to_string()
+    }
+}
+
+pub fn router() -> Router<AppState> {
+    let manage = Router::new()
+        .typed_get(list::list_users)
+        .typed_get(invite::new_invitation_form)
+        .typed_post(invite::create_invitation)
+        .typed_get(view::view_user)
+        .typed_post(view::add_user_role)
+        .typed_post(view::remove_user_role)
+        .typed_post(view::deactivate_user)
+        .typed_post(view::activate_user)
+        .typed_post(sessions::end_user_sessions)
+        .typed_post(sessions::end_user_session)
+        .route_layer(middleware::from_fn_with_state(
+            Permission::ManageUsers,
+            require_permission,
+        ));
+
+    // invited users can not have any permissions yet
+    let invitation = Router::new()
+        .typed_get(invitation::view_invitation)
+        .typed_post(invitation::accept_invitation);
+
+    manage.merge(invitation)
+}
+
+pub fn user_not_found(id: Uuid, locale: Locale) -> AppError {
+    AppError::NotFound(t!("user.not_found", &locale, id))
+}
+
+/// Load a user managed by the administrator, or fail as if the user does not exist.
+async fn load_user(
+    conn: &mut PgConnection,
+    id: Uuid,
+    scope: UserScope,
+    locale: Locale,
+) -> Result<User, AppError> {
+    users::repository::get_user(conn, &id, scope.political_group_id())
+        .await?
+        .ok_or(user_not_found(id, locale))
+}
+
+/// Like `load_user`, for changes administrators can not make to their own account, so they
+/// can not lock themselves out.
+async fn load_other_user(
+    conn: &mut PgConnection,
+    id: Uuid,
+    current_user: &CurrentUser,
+    scope: UserScope,
+    locale: Locale,
+) -> Result<User, AppError> {
+    if id == current_user.id {
+        return Err(AppError::Unauthorized);
+    }
+
+    load_user(conn, id, scope, locale).await
+}
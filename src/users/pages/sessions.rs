@@ -0,0 +1,116 @@
+use axum::response::{IntoResponse, Redirect, Response};
+use axum_extra::extract::Form;
+
+use crate::{
+    AppError, Context, CsrfTokens, DbConnection,
+    form::{EmptyForm, Validate},
+    t,
+    users::{
+        self, UserScope,
+        pages::{EndUserSessionPath, EndUserSessionsPath, load_user},
+    },
+};
+
+/// Sign a user out on every device.
+pub async fn end_user_sessions(
+    EndUserSessionsPath { id }: EndUserSessionsPath,
+    context: Context,
+    scope: UserScope,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+    form: Form<EmptyForm>,
+) -> Result<Response, AppError> {
+    let account = load_user(&mut conn, id, scope, context.locale).await?;
+
+    if form.validate(None, &csrf_tokens).is_ok() {
+        users::repository::delete_user_sessions(&mut conn, &account.id).await?;
+    }
+
+    Ok(Redirect::to(&account.view_path()).into_response())
+}
+
+pub async fn end_user_session(
+    EndUserSessionPath { id, session_id }: EndUserSessionPath,
+    context: Context,
+    scope: UserScope,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+    form: Form<EmptyForm>,
+) -> Result<Response, AppError> {
+    let account = load_user(&mut conn, id, scope, context.locale).await?;
+
+    if form.validate(None, &csrf_tokens).is_err() {
+        return Ok(Redirect::to(&account.view_path()).into_response());
+    }
+
+    if !users::repository::delete_user_session(&mut conn, &account.id, &session_id).await? {
+        return Err(AppError::NotFound(t!(
+            "user.session_not_found",
+            &context.locale,
+            session_id
+        )));
+    }
+
+    Ok(Redirect::to(&account.view_path()).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use crate::{Locale, Role, auth::repository as auth_repository, test_utils::create_user};
+
+    #[sqlx::test]
+    async fn ends_a_single_session(pool: PgPool) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let id = create_user(&mut conn, "Bakker", None, &[Role::CsbMember]).await?;
+        let expires_at = Utc::now() + Duration::hours(1);
+        let first =
+            auth_repository::create_session(&mut conn, &id, &[1; 32], None, expires_at).await?;
+        let second =
+            auth_repository::create_session(&mut conn, &id, &[2; 32], None, expires_at).await?;
+
+        let csrf_tokens = CsrfTokens::default();
+        let response = end_user_session(
+            EndUserSessionPath {
+                id,
+                session_id: first,
+            },
+            Context::new(Locale::En),
+            UserScope::All,
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+            Form(EmptyForm::from(csrf_tokens.issue().value)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+        let sessions = users::repository::list_user_sessions(&mut conn, &id).await?;
+        assert_eq!(
+            sessions.iter().map(|s| s.id).collect::<Vec<_>>(),
+            vec![second]
+        );
+
+        // sessions of other users can not be ended through this user
+        let result = end_user_session(
+            EndUserSessionPath {
+                id,
+                session_id: Uuid::new_v4(),
+            },
+            Context::new(Locale::En),
+            UserScope::All,
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+            Form(EmptyForm::from(csrf_tokens.issue().value)),
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        Ok(())
+    }
+}
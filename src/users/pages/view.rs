@@ -0,0 +1,309 @@
+use askama::Template;
+use axum::response::{IntoResponse, Redirect, Response};
+use axum_extra::extract::Form;
+
+use crate::{
+    AppError, Context, CsrfTokens, CurrentUser, DbConnection, HtmlTemplate, Role,
+    auth::{RoleForm, repository as auth_repository},
+    filters,
+    form::{EmptyForm, FormData, Validate},
+    t,
+    users::{
+        self, User, UserScope, UserSession,
+        pages::{
+            ActivateUserPath, AddUserRolePath, DeactivateUserPath, RemoveUserRolePath,
+            ViewUserPath, load_other_user, load_user,
+        },
+    },
+};
+
+#[derive(Template)]
+#[template(path = "users/view.html")]
+struct UserTemplate {
+    account: User,
+    sessions: Vec<UserSession>,
+    form: FormData<EmptyForm>,
+    /// Administrators can not change their own roles or deactivate themselves.
+    is_self: bool,
+}
+
+pub async fn view_user(
+    ViewUserPath { id }: ViewUserPath,
+    context: Context,
+    current_user: CurrentUser,
+    scope: UserScope,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+) -> Result<impl IntoResponse, AppError> {
+    let account = load_user(&mut conn, id, scope, context.locale).await?;
+    let sessions = users::repository::list_user_sessions(&mut conn, &account.id).await?;
+
+    Ok(HtmlTemplate(
+        UserTemplate {
+            is_self: account.id == current_user.id,
+            account,
+            sessions,
+            form: FormData::new(&csrf_tokens),
+        },
+        context,
+    ))
+}
+
+/// Validate a role form for a user, refusing roles that do not fit the user.
+fn assignable_role(
+    account: &User,
+    form: &RoleForm,
+    csrf_tokens: &CsrfTokens,
+) -> Result<Option<Role>, AppError> {
+    let Ok(role) = form.validate(None, csrf_tokens) else {
+        return Ok(None);
+    };
+
+    if !account.assignable_roles().contains(&role) {
+        return Err(AppError::BadRequest(format!(
+            "role {role} can not be assigned to user {}",
+            account.id
+        )));
+    }
+
+    Ok(Some(role))
+}
+
+pub async fn add_user_role(
+    AddUserRolePath { id }: AddUserRolePath,
+    context: Context,
+    current_user: CurrentUser,
+    scope: UserScope,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+    Form(form): Form<RoleForm>,
+) -> Result<Response, AppError> {
+    let account = load_other_user(&mut conn, id, &current_user, scope, context.locale).await?;
+
+    if let Some(role) = assignable_role(&account, &form, &csrf_tokens)? {
+        auth_repository::add_user_role(&mut conn, &account.id, role).await?;
+    }
+
+    Ok(Redirect::to(&account.view_path()).into_response())
+}
+
+pub async fn remove_user_role(
+    RemoveUserRolePath { id }: RemoveUserRolePath,
+    context: Context,
+    current_user: CurrentUser,
+    scope: UserScope,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+    Form(form): Form<RoleForm>,
+) -> Result<Response, AppError> {
+    let account = load_other_user(&mut conn, id, &current_user, scope, context.locale).await?;
+
+    if let Some(role) = assignable_role(&account, &form, &csrf_tokens)? {
+        auth_repository::remove_user_role(&mut conn, &account.id, role).await?;
+    }
+
+    Ok(Redirect::to(&account.view_path()).into_response())
+}
+
+/// Deactivate a user, which also signs them out everywhere.
+pub async fn deactivate_user(
+    DeactivateUserPath { id }: DeactivateUserPath,
+    context: Context,
+    current_user: CurrentUser,
+    scope: UserScope,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+    form: Form<EmptyForm>,
+) -> Result<Response, AppError> {
+    let account = load_other_user(&mut conn, id, &current_user, scope, context.locale).await?;
+
+    if form.validate(None, &csrf_tokens).is_ok() {
+        users::repository::deactivate_user(&mut conn, &account.id).await?;
+    }
+
+    Ok(Redirect::to(&account.view_path()).into_response())
+}
+
+pub async fn activate_user(
+    ActivateUserPath { id }: ActivateUserPath,
+    context: Context,
+    current_user: CurrentUser,
+    scope: UserScope,
+    csrf_tokens: CsrfTokens,
+    DbConnection(mut conn): DbConnection,
+    form: Form<EmptyForm>,
+) -> Result<Response, AppError> {
+    let account = load_other_user(&mut conn, id, &current_user, scope, context.locale).await?;
+
+    if form.validate(None, &csrf_tokens).is_ok() {
+        users::repository::activate_user(&mut conn, &account.id).await?;
+    }
+
+    Ok(Redirect::to(&account.view_path()).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use crate::{
+        Locale,
+        auth::repository::create_political_group,
+        test_utils::{create_user, response_body_string, sample_user},
+    };
+
+    fn role_form(csrf_tokens: &CsrfTokens, role: &str) -> Form<RoleForm> {
+        Form(RoleForm {
+            role: role.to_string(),
+            csrf_token: csrf_tokens.issue().value,
+        })
+    }
+
+    #[sqlx::test]
+    async fn assigns_roles_fitting_the_user(pool: PgPool) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let group = create_political_group(&mut conn, "Lijst Smit").await?;
+        let id = create_user(&mut conn, "Bakker", Some(group), &[Role::ListSubmitter]).await?;
+        let admin = sample_user(vec![Role::KiesraadAdmin]);
+
+        let csrf_tokens = CsrfTokens::default();
+        let response = add_user_role(
+            AddUserRolePath { id },
+            Context::new(Locale::En),
+            admin.clone(),
+            UserScope::All,
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+            role_form(&csrf_tokens, "authorised_agent"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+        let result = add_user_role(
+            AddUserRolePath { id },
+            Context::new(Locale::En),
+            admin,
+            UserScope::All,
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+            role_form(&csrf_tokens, "kiesraad_admin"),
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+
+        assert_eq!(
+            auth_repository::list_user_roles(&mut conn, &id).await?,
+            vec![Role::AuthorisedAgent, Role::ListSubmitter]
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn users_of_other_political_groups_are_not_found(
+        pool: PgPool,
+    ) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let group = create_political_group(&mut conn, "Lijst Smit").await?;
+        let other_group = create_political_group(&mut conn, "Lijst Jansen").await?;
+        let id = create_user(
+            &mut conn,
+            "de Vries",
+            Some(other_group),
+            &[Role::ListSubmitter],
+        )
+        .await?;
+        let agent = CurrentUser {
+            political_group_id: Some(group),
+            ..sample_user(vec![Role::AuthorisedAgent])
+        };
+
+        let csrf_tokens = CsrfTokens::default();
+        let result = deactivate_user(
+            DeactivateUserPath { id },
+            Context::new(Locale::En),
+            agent,
+            UserScope::PoliticalGroup(group),
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+            Form(EmptyForm::from(csrf_tokens.issue().value)),
+        )
+        .await;
+
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+        assert!(!auth_repository::is_user_deactivated(&mut conn, &id).await?);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn deactivation_signs_the_user_out(pool: PgPool) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let group = create_political_group(&mut conn, "Lijst Smit").await?;
+        let id = create_user(&mut conn, "Bakker", Some(group), &[Role::ListSubmitter]).await?;
+        auth_repository::create_session(
+            &mut conn,
+            &id,
+            &[1; 32],
+            None,
+            chrono::Utc::now() + chrono::Duration::hours(1),
+        )
+        .await?;
+        let agent = CurrentUser {
+            political_group_id: Some(group),
+            ..sample_user(vec![Role::AuthorisedAgent])
+        };
+
+        let csrf_tokens = CsrfTokens::default();
+        let response = deactivate_user(
+            DeactivateUserPath { id },
+            Context::new(Locale::En),
+            agent.clone(),
+            UserScope::PoliticalGroup(group),
+            csrf_tokens.clone(),
+            DbConnection(pool.acquire().await?),
+            Form(EmptyForm::from(csrf_tokens.issue().value)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert!(auth_repository::is_user_deactivated(&mut conn, &id).await?);
+        assert!(
+            users::repository::list_user_sessions(&mut conn, &id)
+                .await?
+                .is_empty()
+        );
+
+        let response = view_user(
+            ViewUserPath { id },
+            Context::new(Locale::En),
+            agent,
+            UserScope::PoliticalGroup(group),
+            csrf_tokens,
+            DbConnection(pool.acquire().await?),
+        )
+        .await
+        .unwrap()
+        .into_response();
+        let body = response_body_string(response).await;
+        assert!(body.contains("Deactivated"));
+
+        // an unknown user is not found
+        let result = view_user(
+            ViewUserPath { id: Uuid::new_v4() },
+            Context::new(Locale::En),
+            sample_user(vec![Role::KiesraadAdmin]),
+            UserScope::All,
+            CsrfTokens::default(),
+            DbConnection(pool.acquire().await?),
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,411 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::{
+    Role,
+    pagination::{SortDirection, order_by},
+    users::{Invitation, NewInvitation, PoliticalGroup, User, UserSession, UserSort},
+};
+
+/// Number of users, of a single political group or of all when `None`.
+pub async fn count_users(
+    conn: &mut PgConnection,
+    political_group_id: Option<Uuid>,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM users
+        WHERE $1::uuid IS NULL OR political_group_id = $1
+        "#,
+        political_group_id,
+    )
+    .fetch_one(conn)
+    .await
+}
+
+/// One page of users, of a single political group or of all when `None`.
+pub async fn list_users(
+    conn: &mut PgConnection,
+    political_group_id: Option<Uuid>,
+    limit: i64,
+    offset: i64,
+    sort_field: &UserSort,
+    sort_direction: &SortDirection,
+) -> Result<Vec<User>, sqlx::Error> {
+    let query = format!(
+        r#"
+        SELECT
+            u.id,
+            u.name,
+            u.email,
+            u.political_group_id,
+            g.name AS political_group_name,
+            ARRAY(
+                SELECT r.role FROM user_roles r WHERE r.user_id = u.id ORDER BY r.role
+            ) AS roles,
+            u.created_at,
+            u.last_login_at,
+            u.deactivated_at,
+            (
+                SELECT COUNT(*) FROM sessions s WHERE s.user_id = u.id AND s.expires_at > NOW()
+            ) AS session_count
+        FROM users u
+        LEFT JOIN political_groups g ON g.id = u.political_group_id
+        WHERE $1::uuid IS NULL OR u.political_group_id = $1
+        {}
+        LIMIT $2
+        OFFSET $3
+        "#,
+        order_by(sort_field, sort_direction),
+    );
+
+    sqlx::query_as::<_, User>(&query)
+        .bind(political_group_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(conn)
+        .await
+}
+
+/// Get a user, provided they belong to the given political group, if any.
+pub async fn get_user(
+    conn: &mut PgConnection,
+    id: &Uuid,
+    political_group_id: Option<Uuid>,
+) -> Result<Option<User>, sqlx::Error> {
+    sqlx::query_as!(
+        User,
+        r#"
+        SELECT
+            u.id,
+            u.name,
+            u.email,
+            u.political_group_id,
+            g.name AS "political_group_name?",
+            ARRAY(
+                SELECT r.role FROM user_roles r WHERE r.user_id = u.id ORDER BY r.role
+            ) AS "roles!: Vec<Role>",
+            u.created_at,
+            u.last_login_at,
+            u.deactivated_at,
+            (
+                SELECT COUNT(*) FROM sessions s WHERE s.user_id = u.id AND s.expires_at > NOW()
+            ) AS "session_count!"
+        FROM users u
+        LEFT JOIN political_groups g ON g.id = u.political_group_id
+        WHERE u.id = $1 AND ($2::uuid IS NULL OR u.political_group_id = $2)
+        "#,
+        id,
+        political_group_id,
+    )
+    .fetch_optional(conn)
+    .await
+}
+
+pub async fn list_political_groups(
+    conn: &mut PgConnection,
+) -> Result<Vec<PoliticalGroup>, sqlx::Error> {
+    sqlx::query_as!(
+        PoliticalGroup,
+        "SELECT id, name FROM political_groups ORDER BY name, id"
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Deactivate a user and end all of their sessions.
+pub async fn deactivate_user(conn: &mut PgConnection, id: &Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE users SET deactivated_at = NOW() WHERE id = $1 AND deactivated_at IS NULL",
+        id,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    delete_user_sessions(conn, id).await?;
+
+    Ok(())
+}
+
+pub async fn activate_user(conn: &mut PgConnection, id: &Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query!("UPDATE users SET deactivated_at = NULL WHERE id = $1", id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn list_user_sessions(
+    conn: &mut PgConnection,
+    user_id: &Uuid,
+) -> Result<Vec<UserSession>, sqlx::Error> {
+    sqlx::query_as!(
+        UserSession,
+        r#"
+        SELECT id, role AS "role: Role", created_at, expires_at
+        FROM sessions
+        WHERE user_id = $1 AND expires_at > NOW()
+        ORDER BY created_at DESC
+        "#,
+        user_id,
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Sign a user out of a single session, returning whether it existed.
+pub async fn delete_user_session(
+    conn: &mut PgConnection,
+    user_id: &Uuid,
+    session_id: &Uuid,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        "DELETE FROM sessions WHERE id = $1 AND user_id = $2",
+        session_id,
+        user_id,
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Sign a user out everywhere, returning the number of sessions that were ended.
+pub async fn delete_user_sessions(
+    conn: &mut PgConnection,
+    user_id: &Uuid,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!("DELETE FROM sessions WHERE user_id = $1", user_id)
+        .execute(conn)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn create_invitation(
+    conn: &mut PgConnection,
+    invitation: &NewInvitation,
+    token_hash: &[u8],
+    invited_by: &Uuid,
+    expires_at: DateTime<Utc>,
+) -> Result<Uuid, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        INSERT INTO user_invitations
+            (token_hash, name, email, political_group_id, role, invited_by, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id
+        "#,
+        token_hash,
+        invitation.name,
+        invitation.email,
+        invitation.political_group_id,
+        invitation.role as Role,
+        invited_by,
+        expires_at,
+    )
+    .fetch_one(conn)
+    .await
+}
+
+/// Invitations that were neither accepted nor expired, of a single political group or of all
+/// when `None`.
+pub async fn list_pending_invitations(
+    conn: &mut PgConnection,
+    political_group_id: Option<Uuid>,
+) -> Result<Vec<Invitation>, sqlx::Error> {
+    sqlx::query_as!(
+        Invitation,
+        r#"
+        SELECT
+            i.id,
+            i.name,
+            i.email,
+            i.political_group_id,
+            g.name AS "political_group_name?",
+            i.role AS "role: Role",
+            u.name AS "invited_by_name?",
+            i.created_at,
+            i.expires_at
+        FROM user_invitations i
+        LEFT JOIN political_groups g ON g.id = i.political_group_id
+        LEFT JOIN users u ON u.id = i.invited_by
+        WHERE i.accepted_at IS NULL AND i.expires_at > NOW()
+            AND ($1::uuid IS NULL OR i.political_group_id = $1)
+        ORDER BY i.created_at DESC
+        "#,
+        political_group_id,
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Get the invitation with the given token, while it can still be accepted.
+pub async fn get_pending_invitation(
+    conn: &mut PgConnection,
+    token_hash: &[u8],
+) -> Result<Option<Invitation>, sqlx::Error> {
+    sqlx::query_as!(
+        Invitation,
+        r#"
+        SELECT
+            i.id,
+            i.name,
+            i.email,
+            i.political_group_id,
+            g.name AS "political_group_name?",
+            i.role AS "role: Role",
+            u.name AS "invited_by_name?",
+            i.created_at,
+            i.expires_at
+        FROM user_invitations i
+        LEFT JOIN political_groups g ON g.id = i.political_group_id
+        LEFT JOIN users u ON u.id = i.invited_by
+        WHERE i.token_hash = $1 AND i.accepted_at IS NULL AND i.expires_at > NOW()
+        "#,
+        token_hash,
+    )
+    .fetch_optional(conn)
+    .await
+}
+
+/// Mark an invitation as accepted by the given user; every invitation can only be accepted
+/// once. Returns whether it was still pending.
+pub async fn accept_invitation(
+    conn: &mut PgConnection,
+    id: &Uuid,
+    user_id: &Uuid,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE user_invitations
+        SET accepted_at = NOW(), accepted_by = $2
+        WHERE id = $1 AND accepted_at IS NULL AND expires_at > NOW()
+        "#,
+        id,
+        user_id,
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::{auth::repository as auth_repository, test_utils::create_user};
+
+    #[sqlx::test]
+    async fn lists_users_of_a_political_group(pool: PgPool) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let group = auth_repository::create_political_group(&mut conn, "Lijst Smit").await?;
+        let agent = create_user(&mut conn, "Bakker", Some(group), &[Role::AuthorisedAgent]).await?;
+        create_user(&mut conn, "Jansen", None, &[Role::CsbMember]).await?;
+
+        assert_eq!(count_users(&mut conn, Some(group)).await?, 1);
+        assert_eq!(count_users(&mut conn, None).await?, 2);
+
+        let users = list_users(
+            &mut conn,
+            None,
+            10,
+            0,
+            &UserSort::Name,
+            &SortDirection::Desc,
+        )
+        .await?;
+        let names: Vec<&str> = users.iter().map(|user| user.name.as_str()).collect();
+        assert_eq!(names, ["Jansen", "Bakker"]);
+        assert_eq!(users[1].roles, [Role::AuthorisedAgent]);
+        assert_eq!(users[1].political_group_name.as_deref(), Some("Lijst Smit"));
+
+        let user = get_user(&mut conn, &agent, Some(group)).await?.unwrap();
+        assert_eq!(user.name, "Bakker");
+        assert!(
+            get_user(&mut conn, &agent, Some(Uuid::new_v4()))
+                .await?
+                .is_none()
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn deactivating_ends_all_sessions(pool: PgPool) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let id = create_user(&mut conn, "Bakker", None, &[Role::CsbMember]).await?;
+        let expires_at = Utc::now() + Duration::hours(1);
+        let session =
+            auth_repository::create_session(&mut conn, &id, b"first", None, expires_at).await?;
+        auth_repository::create_session(&mut conn, &id, b"second", None, expires_at).await?;
+
+        assert_eq!(list_user_sessions(&mut conn, &id).await?.len(), 2);
+        assert!(delete_user_session(&mut conn, &id, &session).await?);
+        assert!(!delete_user_session(&mut conn, &id, &session).await?);
+        assert_eq!(list_user_sessions(&mut conn, &id).await?.len(), 1);
+
+        deactivate_user(&mut conn, &id).await?;
+        assert!(list_user_sessions(&mut conn, &id).await?.is_empty());
+        assert!(auth_repository::is_user_deactivated(&mut conn, &id).await?);
+
+        activate_user(&mut conn, &id).await?;
+        assert!(!auth_repository::is_user_deactivated(&mut conn, &id).await?);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn invitations_can_be_accepted_once(pool: PgPool) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let group = auth_repository::create_political_group(&mut conn, "Lijst Smit").await?;
+        let agent = create_user(&mut conn, "Bakker", Some(group), &[Role::AuthorisedAgent]).await?;
+        let invitee = create_user(&mut conn, "Jansen", None, &[]).await?;
+
+        let invitation = NewInvitation {
+            name: "Jansen".to_string(),
+            email: None,
+            political_group_id: Some(group),
+            role: Role::ListSubmitter,
+        };
+        let expires_at = Utc::now() + Duration::days(1);
+        let id = create_invitation(&mut conn, &invitation, b"token", &agent, expires_at).await?;
+        create_invitation(
+            &mut conn,
+            &invitation,
+            b"expired",
+            &agent,
+            Utc::now() - Duration::days(1),
+        )
+        .await?;
+
+        let pending = list_pending_invitations(&mut conn, Some(group)).await?;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].invited_by_name.as_deref(), Some("Bakker"));
+        assert!(
+            list_pending_invitations(&mut conn, Some(Uuid::new_v4()))
+                .await?
+                .is_empty()
+        );
+
+        assert!(
+            get_pending_invitation(&mut conn, b"expired")
+                .await?
+                .is_none()
+        );
+        let pending = get_pending_invitation(&mut conn, b"token").await?.unwrap();
+        assert_eq!(pending.id, id);
+        assert_eq!(pending.role, Role::ListSubmitter);
+
+        assert!(accept_invitation(&mut conn, &id, &invitee).await?);
+        assert!(!accept_invitation(&mut conn, &id, &invitee).await?);
+        assert!(get_pending_invitation(&mut conn, b"token").await?.is_none());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use uuid::Uuid;
+use validate::Validate;
+
+use crate::{Role, constants::DEFAULT_DATE_TIME_FORMAT, form::*};
+
+/// Number of days an invitation link can be used.
+pub const INVITATION_TTL_DAYS: i64 = 7;
+
+/// An invitation that was not accepted yet.
+#[derive(Debug, Clone)]
+pub struct Invitation {
+    pub id: Uuid,
+    pub name: String,
+    pub email: Option<String>,
+    /// Political group the invited user joins, none for the central electoral committee.
+    pub political_group_id: Option<Uuid>,
+    pub political_group_name: Option<String>,
+    pub role: Role,
+    pub invited_by_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl Invitation {
+    pub fn created(&self) -> String {
+        self.created_at.format(DEFAULT_DATE_TIME_FORMAT).to_string()
+    }
+
+    pub fn expires(&self) -> String {
+        self.expires_at.format(DEFAULT_DATE_TIME_FORMAT).to_string()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewInvitation {
+    pub name: String,
+    pub email: Option<String>,
+    pub political_group_id: Option<Uuid>,
+    pub role: Role,
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Debug, Validate)]
+#[validate(target = "NewInvitation", build = "InvitationForm::build")]
+#[serde(default)]
+pub struct InvitationForm {
+    #[validate(with = "validate_length(2, 255)")]
+    pub name: String,
+    #[validate(with = "validate_email()", optional)]
+    pub email: String,
+    /// Empty for the central electoral committee.
+    #[validate(parse = "Uuid", optional)]
+    pub political_group: String,
+    #[validate(parse = "Role")]
+    pub role: String,
+    #[validate(csrf)]
+    pub csrf_token: TokenValue,
+}
+
+impl WithCsrfToken for InvitationForm {
+    fn with_csrf_token(self, csrf_token: CsrfToken) -> Self {
+        InvitationForm {
+            csrf_token: csrf_token.value,
+            ..self
+        }
+    }
+}
+
+impl InvitationForm {
+    fn build(
+        validated: InvitationFormValidated,
+        _current: Option<&NewInvitation>,
+    ) -> NewInvitation {
+        NewInvitation {
+            name: validated.name,
+            email: validated.email,
+            political_group_id: validated.political_group,
+            role: validated.role,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_invitation_form() {
+        let csrf_tokens = CsrfTokens::default();
+        let group = Uuid::new_v4();
+        let form = InvitationForm {
+            name: "Evert Klaas Smit".to_string(),
+            email: "eks@example.com".to_string(),
+            political_group: group.to_string(),
+            role: "list_submitter".to_string(),
+            csrf_token: csrf_tokens.issue().value,
+        };
+
+        assert_eq!(
+            form.validate(None, &csrf_tokens).unwrap(),
+            NewInvitation {
+                name: "Evert Klaas Smit".to_string(),
+                email: Some("eks@example.com".to_string()),
+                political_group_id: Some(group),
+                role: Role::ListSubmitter,
+            }
+        );
+    }
+
+    #[test]
+    fn reports_invalid_fields() {
+        let csrf_tokens = CsrfTokens::default();
+        let form = InvitationForm {
+            name: "E".to_string(),
+            email: "eks".to_string(),
+            political_group: String::new(),
+            role: "chair".to_string(),
+            csrf_token: csrf_tokens.issue().value,
+        };
+
+        let errors = form.validate(None, &csrf_tokens).unwrap_err();
+        let fields: Vec<&str> = errors
+            .errors()
+            .iter()
+            .map(|(field, _)| field.as_str())
+            .collect();
+        assert_eq!(fields, ["name", "email", "role"]);
+    }
+}
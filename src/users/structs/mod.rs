@@ -0,0 +1,9 @@
+mod invitation;
+mod user;
+mod user_scope;
+mod user_sort;
+
+pub use invitation::{INVITATION_TTL_DAYS, Invitation, InvitationForm, NewInvitation};
+pub use user::{PoliticalGroup, User, UserSession};
+pub use user_scope::UserScope;
+pub use user_sort::UserSort;
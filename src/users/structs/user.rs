@@ -0,0 +1,68 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{Role, constants::DEFAULT_DATE_TIME_FORMAT};
+
+/// A user as shown to the administrators managing them.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct User {
+    pub id: Uuid,
+    pub name: String,
+    pub email: Option<String>,
+    pub political_group_id: Option<Uuid>,
+    pub political_group_name: Option<String>,
+    pub roles: Vec<Role>,
+    pub created_at: DateTime<Utc>,
+    pub last_login_at: DateTime<Utc>,
+    pub deactivated_at: Option<DateTime<Utc>>,
+    /// Number of sessions that have not expired yet.
+    pub session_count: i64,
+}
+
+impl User {
+    pub fn is_active(&self) -> bool {
+        self.deactivated_at.is_none()
+    }
+
+    /// Roles that fit the user: those of a political group for its members, those of the
+    /// central electoral committee for everyone else.
+    pub fn assignable_roles(&self) -> &'static [Role] {
+        Role::assignable(self.political_group_id.is_some())
+    }
+
+    pub fn created(&self) -> String {
+        self.created_at.format(DEFAULT_DATE_TIME_FORMAT).to_string()
+    }
+
+    pub fn last_login(&self) -> String {
+        self.last_login_at
+            .format(DEFAULT_DATE_TIME_FORMAT)
+            .to_string()
+    }
+}
+
+/// A session of a user that has not expired yet.
+#[derive(Debug, Clone)]
+pub struct UserSession {
+    pub id: Uuid,
+    /// Role chosen for the session, by users holding more than one.
+    pub role: Option<Role>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl UserSession {
+    pub fn created(&self) -> String {
+        self.created_at.format(DEFAULT_DATE_TIME_FORMAT).to_string()
+    }
+
+    pub fn expires(&self) -> String {
+        self.expires_at.format(DEFAULT_DATE_TIME_FORMAT).to_string()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoliticalGroup {
+    pub id: Uuid,
+    pub name: String,
+}
@@ -0,0 +1,89 @@
+use axum::{extract::FromRequestParts, http::request::Parts};
+use uuid::Uuid;
+
+use crate::{AppError, CurrentUser, Permission, Role};
+
+/// Users an administrator manages: the members of their own political group for gemachtigden,
+/// all users for administrators of the Electoral Council.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserScope {
+    PoliticalGroup(Uuid),
+    All,
+}
+
+impl UserScope {
+    pub fn of(user: &CurrentUser) -> Option<Self> {
+        if !user.can(Permission::ManageUsers) {
+            return None;
+        }
+
+        match user.political_group_id {
+            Some(id) if user.roles.contains(&Role::AuthorisedAgent) => {
+                Some(UserScope::PoliticalGroup(id))
+            }
+            None if user.roles.contains(&Role::KiesraadAdmin) => Some(UserScope::All),
+            _ => None,
+        }
+    }
+
+    /// The political group to limit queries to, `None` for all users.
+    pub fn political_group_id(&self) -> Option<Uuid> {
+        match self {
+            UserScope::PoliticalGroup(id) => Some(*id),
+            UserScope::All => None,
+        }
+    }
+}
+
+impl<S> FromRequestParts<S> for UserScope
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        let user = parts
+            .extensions
+            .get::<CurrentUser>()
+            .ok_or(AppError::Unauthorized)?;
+
+        UserScope::of(user).ok_or(AppError::Forbidden)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::sample_user;
+
+    #[test]
+    fn administrators_manage_their_own_political_group_or_everyone() {
+        let group = Uuid::new_v4();
+        let agent = CurrentUser {
+            political_group_id: Some(group),
+            ..sample_user(vec![Role::AuthorisedAgent])
+        };
+        assert_eq!(
+            UserScope::of(&agent),
+            Some(UserScope::PoliticalGroup(group))
+        );
+
+        let submitter = CurrentUser {
+            political_group_id: Some(group),
+            ..sample_user(vec![Role::ListSubmitter])
+        };
+        assert_eq!(UserScope::of(&submitter), None);
+
+        assert_eq!(
+            UserScope::of(&sample_user(vec![Role::KiesraadAdmin])),
+            Some(UserScope::All)
+        );
+        assert_eq!(UserScope::of(&sample_user(vec![Role::CsbMember])), None);
+
+        // a gemachtigde without political group manages nobody
+        assert_eq!(
+            UserScope::of(&sample_user(vec![Role::AuthorisedAgent])),
+            None
+        );
+    }
+}
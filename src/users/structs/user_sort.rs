@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+use strum::AsRefStr;
+
+use crate::pagination::{SortColumn, SortDirection};
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, AsRefStr, Serialize, Deserialize)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum UserSort {
+    #[default]
+    Name,
+    PoliticalGroup,
+    LastLoginAt,
+    CreatedAt,
+}
+
+impl SortColumn for UserSort {
+    fn order_by(&self, direction: SortDirection) -> &'static str {
+        match (self, direction) {
+            (UserSort::Name, SortDirection::Asc) => "u.name ASC, u.id ASC",
+            (UserSort::Name, SortDirection::Desc) => "u.name DESC, u.id ASC",
+            (UserSort::PoliticalGroup, SortDirection::Asc) => "g.name ASC, u.id ASC",
+            (UserSort::PoliticalGroup, SortDirection::Desc) => "g.name DESC, u.id ASC",
+            (UserSort::LastLoginAt, SortDirection::Asc) => "u.last_login_at ASC, u.id ASC",
+            (UserSort::LastLoginAt, SortDirection::Desc) => "u.last_login_at DESC, u.id ASC",
+            (UserSort::CreatedAt, SortDirection::Asc) => "u.created_at ASC, u.id ASC",
+            (UserSort::CreatedAt, SortDirection::Desc) => "u.created_at DESC, u.id ASC",
+        }
+    }
+}
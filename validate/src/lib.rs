@@ -1,17 +1,39 @@
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
-use syn::{Data, DeriveInput, Expr, Fields, LitStr, Path, Type, parse_macro_input};
+use syn::{Data, DeriveInput, Expr, Fields, LitInt, LitStr, Path, Type, parse_macro_input};
 
 /// Derive `Validate` implementations with field annotations.
 ///
 /// Supported annotations:
 /// - `#[validate(target = "Type", build = "path::to::builder")]` on the struct.
+/// - `#[validate(with = "path::to::check")]` on the struct (repeatable), where `check` is a
+///   `fn(&Self) -> FieldErrors` for rules spanning multiple fields. It runs after the field
+///   validators, its errors are reported with the field errors.
+/// - `#[validate(context = "Type")]` on the struct to validate with a `&Type` from the
+///   application, available as `context` in validator expressions. The form then implements
+///   `ValidateWith` instead of `Validate`.
 /// - `#[validate(with = "expr")]` on fields (repeatable to apply multiple validators in order),
 ///   where `expr` evaluates to `Fn(&str) -> Result<String, ValidationError>`.
 /// - `#[validate(parse = "Type")]` to parse via `Type::from_str`.
 /// - `#[validate(parse_with = "path", format = "...", ty = "Type")]` to parse via a custom function.
 /// - `#[validate(optional)]` to treat empty strings as `None`.
+/// - `#[validate(when(field = "value"))]` to only apply the `with` validators when another field
+///   of the form has the given value; otherwise the value is taken as is.
+/// - `#[validate(required_if(field = "value"))]` and `#[validate(required_unless(field = "value"))]`
+///   to require a value when another field of the form has, or has not, the given value.
+/// - `#[validate(min_items = 1, max_items = 5)]` on `Vec<T>` fields to bound the number of items.
+/// - `#[validate(each = "expr")]` on `Vec<T>` fields (repeatable), where `expr` evaluates to
+///   `Fn(&T) -> Result<T, ValidationError>` and is applied to every item.
+/// - `#[validate(allowed = "expr")]` on `Vec<T>` fields, on typed fields like enums and with
+///   `parse`, where `expr` evaluates to a collection of the values (or items) that are allowed.
+/// - `#[validate(nested)]` on a field holding another form deriving `Validate`, to validate
+///   both as one form. Errors of the sub-form are reported under its own field names, its CSRF
+///   token is ignored. The sub-form can not have a `context`. As the errors are, its fields must
+///   be posted under their own names: mark the field `#[serde(flatten)]`, and let the sub-form
+///   deserialize without the fields the outer form takes, like the CSRF token, e.g. with
+///   `#[serde(default)]`.
 /// - `#[validate(csrf)]` to validate CSRF tokens.
+/// - `#[validate(skip)]` to keep a field on the form only, leaving it out of the validated value.
 #[proc_macro_derive(Validate, attributes(validate))]
 pub fn derive_validate(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
@@ -24,14 +46,67 @@ pub fn derive_validate(input: TokenStream) -> TokenStream {
 struct StructOptions {
     target: Type,
     build: Path,
+    checks: Vec<Path>,
+    context: Option<Type>,
+}
+
+/// Condition on the value of another field of the form, e.g. `is_dutch = "true"`.
+struct Condition {
+    field: syn::Ident,
+    value: LitStr,
+}
+
+impl Condition {
+    fn parse(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Self> {
+        let mut condition = None;
+        meta.parse_nested_meta(|nested| {
+            let field = nested
+                .path
+                .get_ident()
+                .ok_or_else(|| nested.error("expected a field name"))?
+                .clone();
+            let value: LitStr = nested.value()?.parse()?;
+            condition = Some(Condition { field, value });
+            Ok(())
+        })?;
+
+        condition.ok_or_else(|| meta.error("expected a condition like (field = \"value\")"))
+    }
+
+    /// Expression that is true when the condition holds.
+    fn holds(&self) -> proc_macro2::TokenStream {
+        let Condition { field, value } = self;
+        quote!(self.#field.trim() == #value)
+    }
+}
+
+/// A value that is required when `condition` holds, or when it does not hold if `negate` is set.
+struct Requirement {
+    condition: Condition,
+    negate: bool,
 }
 
 #[derive(Default)]
 struct FieldOptions {
     optional: bool,
     csrf: bool,
+    skip: bool,
     with_validators: Vec<Expr>,
     validator: Option<Validator>,
+    /// Only validate when the condition holds.
+    when: Option<Condition>,
+    required: Vec<Requirement>,
+    min_items: Option<LitInt>,
+    max_items: Option<LitInt>,
+    each_validators: Vec<Expr>,
+    allowed: Option<Expr>,
+    nested: bool,
+}
+
+impl FieldOptions {
+    fn is_collection(&self) -> bool {
+        self.min_items.is_some() || self.max_items.is_some() || !self.each_validators.is_empty()
+    }
 }
 
 enum Validator {
@@ -51,6 +126,7 @@ fn expand_validate(input: &DeriveInput) -> syn::Result<TokenStream> {
     let validated_name = format_ident!("{}Validated", struct_name);
     let target = struct_options.target;
     let build_fn = struct_options.build;
+    let checks = struct_options.checks;
 
     let fields = match &input.data {
         Data::Struct(data) => match &data.fields {
@@ -73,6 +149,7 @@ fn expand_validate(input: &DeriveInput) -> syn::Result<TokenStream> {
     let mut validated_fields = Vec::new();
     let mut field_inits = Vec::new();
     let mut field_blocks = Vec::new();
+    let mut csrf_blocks = Vec::new();
 
     for field in fields {
         let ident = field
@@ -83,7 +160,7 @@ fn expand_validate(input: &DeriveInput) -> syn::Result<TokenStream> {
         let opts = parse_field_options(field)?;
 
         if opts.csrf {
-            field_blocks.push(quote! {
+            csrf_blocks.push(quote! {
                 if !csrf_tokens.consume(&self.#ident) {
                     errors.push((
                         #field_name.to_string(),
@@ -94,6 +171,10 @@ fn expand_validate(input: &DeriveInput) -> syn::Result<TokenStream> {
             continue;
         }
 
+        if opts.skip {
+            continue;
+        }
+
         let (value_type, value_expr, validated) =
             build_field_validation(ident, &field_name, &field.ty, &opts)?;
 
@@ -112,36 +193,73 @@ fn expand_validate(input: &DeriveInput) -> syn::Result<TokenStream> {
         }
     }
 
+    let (context_param, context_arg) = match &struct_options.context {
+        Some(context) => (quote!(context: &#context,), quote!(context,)),
+        None => (quote!(), quote!()),
+    };
+    let (validate_trait, validate_fn) = match &struct_options.context {
+        Some(context) => (
+            quote!(crate::form::ValidateWith<#target, #context>),
+            quote!(validate_with),
+        ),
+        None => (quote!(crate::form::Validate<#target>), quote!(validate)),
+    };
+
     let tokens = quote! {
         #[derive(Debug, Clone)]
-        struct #validated_name {
+        pub struct #validated_name {
             #(#validated_fields,)*
         }
 
-        impl crate::form::Validate<#target> for #struct_name {
-            fn validate(
+        impl crate::form::ValidatedFields for #struct_name {
+            type Validated = #validated_name;
+        }
+
+        impl #struct_name {
+            /// Validate the fields of the form, leaving out its CSRF token.
+            pub(crate) fn validate_fields(
                 &self,
-                current: Option<&#target>,
-                csrf_tokens: &crate::form::CsrfTokens,
-            ) -> Result<#target, crate::form::FormData<Self>> {
-                let mut errors: crate::form::FieldErrors = Vec::new();
+                #context_param
+                errors: &mut crate::form::FieldErrors,
+            ) -> Option<<Self as crate::form::ValidatedFields>::Validated> {
+                let errors_before = errors.len();
 
                 #(#field_blocks)*
+                #(errors.extend(#checks(self));)*
 
-                if !errors.is_empty() {
-                    tracing::debug!("Validation errors: {errors:?}");
-                    return Err(crate::form::FormData::new_with_errors(
-                        self.clone(),
-                        csrf_tokens,
-                        errors,
-                    ));
+                if errors.len() > errors_before {
+                    return None;
                 }
 
-                let validated = #validated_name {
+                Some(#validated_name {
                     #(#field_inits,)*
-                };
+                })
+            }
+        }
 
-                Ok(#build_fn(validated, current))
+        impl #validate_trait for #struct_name {
+            fn #validate_fn(
+                &self,
+                #context_param
+                current: Option<&#target>,
+                csrf_tokens: &crate::form::CsrfTokens,
+            ) -> Result<#target, crate::form::FormData<Self>> {
+                let mut errors: crate::form::FieldErrors = Vec::new();
+
+                let validated = self.validate_fields(#context_arg &mut errors);
+                #(#csrf_blocks)*
+
+                match validated {
+                    Some(validated) if errors.is_empty() => Ok(#build_fn(validated, current)),
+                    _ => {
+                        tracing::debug!("Validation errors: {errors:?}");
+                        Err(crate::form::FormData::new_with_errors(
+                            self.clone(),
+                            csrf_tokens,
+                            errors,
+                        ))
+                    }
+                }
             }
         }
     };
@@ -152,6 +270,8 @@ fn expand_validate(input: &DeriveInput) -> syn::Result<TokenStream> {
 fn parse_struct_options(input: &DeriveInput) -> syn::Result<StructOptions> {
     let mut target = None;
     let mut build = None;
+    let mut checks = Vec::new();
+    let mut context = None;
 
     for attr in &input.attrs {
         if !attr.path().is_ident("validate") {
@@ -169,6 +289,16 @@ fn parse_struct_options(input: &DeriveInput) -> syn::Result<StructOptions> {
                 build = Some(lit.parse::<Path>()?);
                 return Ok(());
             }
+            if meta.path.is_ident("with") {
+                let lit: LitStr = meta.value()?.parse()?;
+                checks.push(lit.parse::<Path>()?);
+                return Ok(());
+            }
+            if meta.path.is_ident("context") {
+                let lit: LitStr = meta.value()?.parse()?;
+                context = Some(lit.parse::<Type>()?);
+                return Ok(());
+            }
 
             Err(meta.error("unsupported validate attribute on struct"))
         })?;
@@ -181,7 +311,12 @@ fn parse_struct_options(input: &DeriveInput) -> syn::Result<StructOptions> {
         syn::Error::new_spanned(input, "missing #[validate(build = \"path\")] on struct")
     })?;
 
-    Ok(StructOptions { target, build })
+    Ok(StructOptions {
+        target,
+        build,
+        checks,
+        context,
+    })
 }
 
 fn parse_field_options(field: &syn::Field) -> syn::Result<FieldOptions> {
@@ -201,6 +336,43 @@ fn parse_field_options(field: &syn::Field) -> syn::Result<FieldOptions> {
                 opts.csrf = true;
                 return Ok(());
             }
+            if meta.path.is_ident("skip") {
+                opts.skip = true;
+                return Ok(());
+            }
+            if meta.path.is_ident("when") {
+                opts.when = Some(Condition::parse(&meta)?);
+                return Ok(());
+            }
+            if meta.path.is_ident("required_if") || meta.path.is_ident("required_unless") {
+                opts.required.push(Requirement {
+                    negate: meta.path.is_ident("required_unless"),
+                    condition: Condition::parse(&meta)?,
+                });
+                return Ok(());
+            }
+            if meta.path.is_ident("nested") {
+                opts.nested = true;
+                return Ok(());
+            }
+            if meta.path.is_ident("min_items") {
+                opts.min_items = Some(meta.value()?.parse()?);
+                return Ok(());
+            }
+            if meta.path.is_ident("max_items") {
+                opts.max_items = Some(meta.value()?.parse()?);
+                return Ok(());
+            }
+            if meta.path.is_ident("each") {
+                let lit: LitStr = meta.value()?.parse()?;
+                opts.each_validators.push(lit.parse::<Expr>()?);
+                return Ok(());
+            }
+            if meta.path.is_ident("allowed") {
+                let lit: LitStr = meta.value()?.parse()?;
+                opts.allowed = Some(lit.parse::<Expr>()?);
+                return Ok(());
+            }
             if meta.path.is_ident("with") {
                 if opts.validator.is_some() {
                     return Err(meta.error("with cannot be combined with parse or parse_with"));
@@ -254,6 +426,58 @@ fn parse_field_options(field: &syn::Field) -> syn::Result<FieldOptions> {
         })?;
     }
 
+    if !opts.required.is_empty() && opts.with_validators.is_empty() && opts.validator.is_none() {
+        return Err(syn::Error::new_spanned(
+            field,
+            "required_if and required_unless require a validator",
+        ));
+    }
+
+    if opts.when.is_some() && opts.with_validators.is_empty() {
+        return Err(syn::Error::new_spanned(
+            field,
+            "when requires with validators",
+        ));
+    }
+
+    let has_validators = !opts.with_validators.is_empty() || opts.validator.is_some();
+
+    if opts.nested
+        && (has_validators
+            || opts.optional
+            || opts.when.is_some()
+            || !opts.required.is_empty()
+            || opts.is_collection()
+            || opts.allowed.is_some())
+    {
+        return Err(syn::Error::new_spanned(
+            field,
+            "nested cannot be combined with other validate attributes",
+        ));
+    }
+
+    if (opts.is_collection() || opts.allowed.is_some()) && vec_item_type(&field.ty).is_some() {
+        if has_validators || opts.optional || opts.when.is_some() || !opts.required.is_empty() {
+            return Err(syn::Error::new_spanned(
+                field,
+                "Vec fields only support min_items, max_items, each and allowed",
+            ));
+        }
+    } else if opts.is_collection() {
+        return Err(syn::Error::new_spanned(
+            field,
+            "min_items, max_items and each require a Vec field",
+        ));
+    } else if opts.allowed.is_some()
+        && (!opts.with_validators.is_empty()
+            || matches!(opts.validator, Some(Validator::ParseWith { .. })))
+    {
+        return Err(syn::Error::new_spanned(
+            field,
+            "allowed requires a Vec field, a typed field or parse",
+        ));
+    }
+
     if let Some(Validator::ParseWith { ty, .. }) = &opts.validator
         && ty.is_none()
     {
@@ -272,6 +496,45 @@ fn build_field_validation(
     field_ty: &Type,
     opts: &FieldOptions,
 ) -> syn::Result<(Type, proc_macro2::TokenStream, bool)> {
+    if opts.nested {
+        return Ok((
+            syn::parse_quote!(<#field_ty as crate::form::ValidatedFields>::Validated),
+            quote!(self.#ident.validate_fields(errors)),
+            true,
+        ));
+    }
+
+    if let Some(item_ty) = vec_item_type(field_ty)
+        && (opts.is_collection() || opts.allowed.is_some())
+    {
+        return Ok((
+            syn::parse_quote!(Vec<#item_ty>),
+            build_collection_validation(ident, field_name, opts),
+            true,
+        ));
+    }
+
+    if opts.with_validators.is_empty()
+        && opts.validator.is_none()
+        && let Some(allowed) = &opts.allowed
+    {
+        return Ok((
+            field_ty.clone(),
+            quote!({
+                if (#allowed).contains(&self.#ident) {
+                    Some(self.#ident.clone())
+                } else {
+                    errors.push((
+                        #field_name.to_string(),
+                        crate::form::ValidationError::InvalidValue,
+                    ));
+                    None
+                }
+            }),
+            true,
+        ));
+    }
+
     let base_ty = if !opts.with_validators.is_empty() {
         syn::parse_quote!(String)
     } else {
@@ -306,24 +569,47 @@ fn build_field_validation(
             }
         });
 
-        if opts.optional {
-            quote!({
-                if self.#ident.is_empty() {
-                    Some(None)
-                } else {
-                    let mut value = self.#ident.trim().to_string();
+        let (validated, unvalidated) = if opts.optional {
+            (
+                quote!({
+                    if self.#ident.is_empty() {
+                        Some(None)
+                    } else {
+                        let mut value = self.#ident.trim().to_string();
+                        let mut ok = true;
+                        #(#validators)*
+                        if ok { Some(Some(value)) } else { None }
+                    }
+                }),
+                quote!({
+                    let value = self.#ident.trim();
+                    Some((!value.is_empty()).then(|| value.to_string()))
+                }),
+            )
+        } else {
+            (
+                quote!({
+                    let mut value = self.#ident.clone();
                     let mut ok = true;
                     #(#validators)*
-                    if ok { Some(Some(value)) } else { None }
-                }
-            })
-        } else {
-            quote!({
-                let mut value = self.#ident.clone();
-                let mut ok = true;
-                #(#validators)*
-                if ok { Some(value) } else { None }
-            })
+                    if ok { Some(value) } else { None }
+                }),
+                quote!(Some(self.#ident.clone())),
+            )
+        };
+
+        match &opts.when {
+            Some(condition) => {
+                let holds = condition.holds();
+                quote!({
+                    if #holds {
+                        #validated
+                    } else {
+                        #unvalidated
+                    }
+                })
+            }
+            None => validated,
         }
     } else {
         match &opts.validator {
@@ -422,9 +708,145 @@ fn build_field_validation(
         }
     };
 
+    // a parsed value must also be one of the allowed values
+    let expr = match &opts.allowed {
+        Some(allowed) => {
+            let disallowed = if opts.optional {
+                quote!(Some(Some(value)))
+            } else {
+                quote!(Some(value))
+            };
+            quote!({
+                match #expr {
+                    #disallowed if !(#allowed).contains(&value) => {
+                        errors.push((
+                            #field_name.to_string(),
+                            crate::form::ValidationError::InvalidValue,
+                        ));
+                        None
+                    }
+                    value => value,
+                }
+            })
+        }
+        None => expr,
+    };
+
+    // a missing required value is reported instead of the errors of the validators
+    let expr = opts.required.iter().fold(expr, |expr, requirement| {
+        let holds = requirement.condition.holds();
+        let applies = if requirement.negate {
+            quote!(!(#holds))
+        } else {
+            holds
+        };
+
+        quote!({
+            if #applies && self.#ident.trim().is_empty() {
+                errors.push((
+                    #field_name.to_string(),
+                    crate::form::ValidationError::ValueShouldNotBeEmpty,
+                ));
+                None
+            } else {
+                #expr
+            }
+        })
+    });
+
     Ok((output_ty, expr, true))
 }
 
+/// Validation of the items of a `Vec` field, which stops at the first invalid item.
+fn build_collection_validation(
+    ident: &syn::Ident,
+    field_name: &str,
+    opts: &FieldOptions,
+) -> proc_macro2::TokenStream {
+    let min_items = opts.min_items.iter().map(|min| {
+        quote! {
+            if self.#ident.len() < #min {
+                errors.push((
+                    #field_name.to_string(),
+                    crate::form::ValidationError::TooFewItems(#min),
+                ));
+                ok = false;
+            }
+        }
+    });
+    let max_items = opts.max_items.iter().map(|max| {
+        quote! {
+            if self.#ident.len() > #max {
+                errors.push((
+                    #field_name.to_string(),
+                    crate::form::ValidationError::TooManyItems(#max),
+                ));
+                ok = false;
+            }
+        }
+    });
+    let validators = opts.each_validators.iter().map(|expr| {
+        quote! {
+            if ok {
+                let validator = #expr;
+                match validator(&value) {
+                    Ok(next_value) => value = next_value,
+                    Err(err) => {
+                        errors.push((#field_name.to_string(), err));
+                        ok = false;
+                    }
+                }
+            }
+        }
+    });
+    let allowed = opts.allowed.iter().map(|allowed| {
+        quote! {
+            if ok && !(#allowed).contains(&value) {
+                errors.push((
+                    #field_name.to_string(),
+                    crate::form::ValidationError::InvalidValue,
+                ));
+                ok = false;
+            }
+        }
+    });
+
+    quote!({
+        let mut ok = true;
+        #(#min_items)*
+        #(#max_items)*
+        let mut items = Vec::with_capacity(self.#ident.len());
+        for item in &self.#ident {
+            if !ok {
+                break;
+            }
+            let mut value = item.clone();
+            #(#validators)*
+            #(#allowed)*
+            items.push(value);
+        }
+        if ok { Some(items) } else { None }
+    })
+}
+
+/// The item type of a `Vec<T>` field.
+fn vec_item_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Vec" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        syn::GenericArgument::Type(item_ty) => Some(item_ty),
+        _ => None,
+    }
+}
+
 fn build_field_block(
     ident: &syn::Ident,
     value_expr: &proc_macro2::TokenStream,